@@ -0,0 +1,320 @@
+//! High-level API for embedding GENT in Rust programs
+//!
+//! An `Engine` loads scripts and lets the host call the functions, tools
+//! and agents they declare, with arguments and results as JSON:
+//!
+//! ```no_run
+//! use gent::Engine;
+//! use serde_json::json;
+//!
+//! # async fn example() -> gent::GentResult<()> {
+//! let mut engine = Engine::builder().build();
+//! engine.load_file("agents.gnt").await?;
+//! let summary = engine.call("summarize", vec![json!("some text")]).await?;
+//! let report = engine.run_agent("Researcher", "Find recent papers").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde_json::Value as JsonValue;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+use crate::config::Config;
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::json::{json_to_value, value_to_json};
+use crate::interpreter::native::NativeFunction;
+use crate::interpreter::{Session, Value};
+use crate::logging::{Logger, NullLogger};
+use crate::parser::parse;
+use crate::runtime::{run_agent_turn_with_handoffs, ProviderFactory, Tool, ToolContext};
+
+/// Builder for an `Engine`
+pub struct EngineBuilder {
+    provider_factory: Option<ProviderFactory>,
+    logger: Arc<dyn Logger>,
+    tools: Vec<Box<dyn Tool>>,
+    functions: Vec<Box<dyn NativeFunction>>,
+    script_args: Vec<String>,
+}
+
+impl EngineBuilder {
+    /// Use `provider_factory` for agents instead of the configured providers
+    pub fn provider_factory(mut self, provider_factory: ProviderFactory) -> Self {
+        self.provider_factory = Some(provider_factory);
+        self
+    }
+
+    /// Log through `logger` (nothing is logged by default)
+    pub fn logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Register a tool agents can use
+    pub fn tool(mut self, tool: Box<dyn Tool>) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Register a native function scripts can call
+    pub fn function(mut self, function: Box<dyn NativeFunction>) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    /// Set the arguments scripts get from `args()` in `std:env`
+    pub fn script_args(mut self, args: Vec<String>) -> Self {
+        self.script_args = args;
+        self
+    }
+
+    /// Create the engine
+    pub fn build(self) -> Engine {
+        let provider_factory = self
+            .provider_factory
+            .unwrap_or_else(|| ProviderFactory::new(Config::load()));
+        let mut session = Session::new(provider_factory, self.logger);
+        for tool in self.tools {
+            session.register_tool(tool);
+        }
+        for function in self.functions {
+            session.register_function(function);
+        }
+        session.set_script_args(self.script_args);
+        Engine {
+            session,
+            cancel: CancelHandle::default(),
+        }
+    }
+}
+
+/// Handle for cancelling an engine's calls from another task
+///
+/// Cancelling stops the calls in flight and makes later calls fail with
+/// `GentError::Cancelled` until `reset` is called.
+#[derive(Clone, Default)]
+pub struct CancelHandle {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancelHandle {
+    /// Cancel the engine's calls
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// Check if the engine has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Allow calls again after a cancellation
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+
+    /// Wait until the engine is cancelled
+    async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Loaded GENT scripts and the state they declare
+pub struct Engine {
+    session: Session,
+    cancel: CancelHandle,
+}
+
+impl Engine {
+    /// Start building an engine
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder {
+            provider_factory: None,
+            logger: Arc::new(NullLogger),
+            tools: Vec::new(),
+            functions: Vec::new(),
+            script_args: Vec::new(),
+        }
+    }
+
+    /// Load a script, resolving its imports relative to the file
+    ///
+    /// Top-level statements run as the script is loaded.
+    pub async fn load_file(&mut self, path: impl AsRef<Path>) -> GentResult<()> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| GentError::FileReadError {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let program = parse(&source)?;
+        let cancel = self.cancel.clone();
+        with_cancel(&cancel, self.session.eval_program(&program, Some(path))).await
+    }
+
+    /// Load a script from source
+    pub async fn load_source(&mut self, source: &str) -> GentResult<()> {
+        let program = parse(source)?;
+        let cancel = self.cancel.clone();
+        with_cancel(&cancel, self.session.eval_program(&program, None)).await
+    }
+
+    /// Names of the declared agents, sorted
+    pub fn agents(&self) -> Vec<&str> {
+        self.names(|value| matches!(value, Value::Agent(_)))
+    }
+
+    /// Names of the declared functions, sorted
+    pub fn functions(&self) -> Vec<&str> {
+        self.names(|value| matches!(value, Value::Function(_)))
+    }
+
+    /// Names of the declared tools, sorted
+    pub fn tools(&self) -> Vec<&str> {
+        self.names(|value| matches!(value, Value::Tool(_)))
+    }
+
+    fn names(&self, filter: impl Fn(&Value) -> bool) -> Vec<&str> {
+        self.session
+            .variables()
+            .into_iter()
+            .filter(|(_, value)| filter(value))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Call a function or tool by name
+    ///
+    /// Tools take their arguments in parameter order and return their
+    /// output as a string.
+    pub async fn call(&mut self, name: &str, args: Vec<JsonValue>) -> GentResult<JsonValue> {
+        if let (Some(Value::Tool(declared)), Some(tool)) =
+            (self.session.env().get(name), self.session.tools().get(name))
+        {
+            let required = declared
+                .params
+                .iter()
+                .filter(|param| param.default.is_none())
+                .count();
+            if args.len() < required || args.len() > declared.params.len() {
+                return Err(GentError::WrongArgumentCount {
+                    expected: required.max(args.len()).min(declared.params.len()),
+                    got: args.len(),
+                    span: Span::new(0, 0),
+                });
+            }
+            let named = declared
+                .params
+                .iter()
+                .map(|param| param.name.clone())
+                .zip(args)
+                .collect();
+            let ctx = ToolContext {
+                agent: None,
+                provider_factory: self.session.provider_factory(),
+                tools: self.session.tools(),
+                logger: self.session.logger(),
+                env: Some(self.session.env()),
+            };
+            let execute = async {
+                tool.execute_with_context(JsonValue::Object(named), &ctx)
+                    .await
+                    .map_err(|message| GentError::ToolError {
+                        tool: name.to_string(),
+                        message,
+                    })
+            };
+            return with_cancel(&self.cancel, execute)
+                .await
+                .map(JsonValue::String);
+        }
+
+        let args = args.iter().map(json_to_value).collect();
+        let cancel = self.cancel.clone();
+        let value = with_cancel(&cancel, self.session.call(name, args)).await?;
+        Ok(value_to_json(&value))
+    }
+
+    /// Run an agent with `prompt` as its input
+    ///
+    /// Agents with structured output return the parsed JSON; others return
+    /// the reply as a string.
+    pub async fn run_agent(&self, name: &str, prompt: &str) -> GentResult<JsonValue> {
+        let agent = match self.session.env().get(name) {
+            Some(Value::Agent(agent)) => agent.clone(),
+            _ => {
+                return Err(GentError::UndefinedAgent {
+                    name: name.to_string(),
+                    span: Span::new(0, 0),
+                }
+                .with_suggestion(name, self.agents()))
+            }
+        };
+        let mut history = Vec::new();
+        let run = run_agent_turn_with_handoffs(
+            &agent,
+            Some(prompt.to_string()),
+            &mut history,
+            self.session.provider_factory(),
+            self.session.tools(),
+            self.session.logger(),
+            Some(self.session.env()),
+        );
+        let output = with_cancel(&self.cancel, run)
+            .await
+            .map(|result| result.output)
+            .map_err(|e| match e {
+                GentError::Cancelled => e,
+                e => GentError::AgentContext {
+                    agent: agent.name.clone(),
+                    source: Box::new(e),
+                },
+            })?;
+        if agent.returns_json() {
+            if let Ok(json) = serde_json::from_str(&output) {
+                return Ok(json);
+            }
+        }
+        Ok(JsonValue::String(output))
+    }
+
+    /// Get a handle that cancels this engine's calls
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Get the underlying session
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Get the underlying session mutably, e.g. to evaluate REPL input
+    pub fn session_mut(&mut self) -> &mut Session {
+        &mut self.session
+    }
+}
+
+/// Run `future` unless `cancel` fires first
+async fn with_cancel<T>(
+    cancel: &CancelHandle,
+    future: impl Future<Output = GentResult<T>>,
+) -> GentResult<T> {
+    if cancel.is_cancelled() {
+        return Err(GentError::Cancelled);
+    }
+    tokio::select! {
+        result = future => result,
+        _ = cancel.cancelled() => Err(GentError::Cancelled),
+    }
+}
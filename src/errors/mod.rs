@@ -3,7 +3,9 @@
 use thiserror::Error;
 
 mod reporter;
+mod suggest;
 pub use reporter::ErrorReporter;
+pub use suggest::{did_you_mean, did_you_mean_help};
 
 /// Source location span for error reporting
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -19,11 +21,33 @@ impl Span {
     }
 }
 
+/// A single frame of a GENT call stack, recorded while an error unwinds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Name of the called function, tool, lambda or agent
+    pub function: String,
+    /// Span of the call site
+    pub span: Span,
+    /// File containing the call site (None = the main program)
+    pub file: Option<String>,
+}
+
+impl StackFrame {
+    /// Create a new stack frame
+    pub fn new(function: impl Into<String>, span: Span, file: Option<String>) -> Self {
+        Self {
+            function: function.into(),
+            span,
+            file,
+        }
+    }
+}
+
 /// All possible errors in the GENT language
 #[derive(Debug, Error)]
 pub enum GentError {
     /// Syntax error during parsing
-    #[error("Syntax error at {span:?}: {message}")]
+    #[error("Syntax error: {message}")]
     SyntaxError { message: String, span: Span },
 
     /// Unexpected token during parsing
@@ -66,6 +90,14 @@ pub enum GentError {
         source: std::io::Error,
     },
 
+    /// File write error
+    #[error("Could not write file '{path}': {source}")]
+    FileWriteError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
     /// API request failed
     #[error("API error: {message}")]
     ApiError { message: String },
@@ -148,6 +180,22 @@ pub enum GentError {
         span: Span,
     },
 
+    /// Error returned by a native function registered by the host program
+    #[error("Native function '{function}' failed: {message}")]
+    NativeFunctionError {
+        function: String,
+        message: String,
+        span: Span,
+    },
+
+    /// Argument outside the domain a function accepts (e.g. `sqrt(-1)`)
+    #[error("Value error: {message}")]
+    ValueError { message: String, span: Span },
+
+    /// JSON text could not be parsed, or a value could not be serialized
+    #[error("JSON error: {message}")]
+    JsonError { message: String, span: Span },
+
     /// Output validation failed for structured output
     #[error("Output validation error: {message}\nExpected schema: {expected}\nGot: {got}")]
     OutputValidationError {
@@ -159,6 +207,47 @@ pub enum GentError {
     /// Provider-specific error (e.g., CLI not found, authentication failed)
     #[error("Provider error: {message}")]
     ProviderError { message: String },
+
+    /// Evaluation was cancelled by the embedding program
+    #[error("Evaluation cancelled")]
+    Cancelled,
+
+    /// Error raised by a `throw` statement
+    #[error("{kind}: {message}")]
+    Thrown {
+        kind: String,
+        message: String,
+        span: Span,
+    },
+
+    /// Error raised while running an agent, tagged with the agent's name
+    #[error("{source}")]
+    AgentContext {
+        agent: String,
+        source: Box<GentError>,
+    },
+
+    /// Error with an extra hint for the user (e.g. a "did you mean" suggestion)
+    #[error("{source}")]
+    WithHelp {
+        source: Box<GentError>,
+        help: String,
+    },
+
+    /// Error that unwound through GENT calls; frames are ordered innermost first
+    #[error("{source}")]
+    Traced {
+        source: Box<GentError>,
+        frames: Vec<StackFrame>,
+    },
+
+    /// Error raised in code from `file` (None = the main program), whose
+    /// span points into that file's source
+    #[error("{source}")]
+    InFile {
+        source: Box<GentError>,
+        file: Option<String>,
+    },
 }
 
 impl GentError {
@@ -179,8 +268,12 @@ impl GentError {
             GentError::DivisionByZero { span } => Some(span),
             GentError::WrongArgumentCount { span, .. } => Some(span),
             GentError::ArgumentTypeMismatch { span, .. } => Some(span),
+            GentError::NativeFunctionError { span, .. } => Some(span),
+            GentError::ValueError { span, .. } => Some(span),
+            GentError::JsonError { span, .. } => Some(span),
             GentError::LLMError { .. } => None,
             GentError::FileReadError { .. } => None,
+            GentError::FileWriteError { .. } => None,
             GentError::ApiError { .. } => None,
             GentError::MissingApiKey { .. } => None,
             GentError::UnknownProvider { .. } => None,
@@ -189,6 +282,142 @@ impl GentError {
             GentError::ToolError { .. } => None,
             GentError::OutputValidationError { .. } => None,
             GentError::ProviderError { .. } => None,
+            GentError::Cancelled => None,
+            GentError::Thrown { span, .. } => Some(span),
+            GentError::AgentContext { source, .. } => source.span(),
+            GentError::WithHelp { source, .. } => source.span(),
+            GentError::Traced { source, .. } => source.span(),
+            GentError::InFile { source, .. } => source.span(),
+        }
+    }
+
+    /// Attach a hint that the error reporter prints below the error
+    pub fn with_help(self, help: impl Into<String>) -> GentError {
+        GentError::WithHelp {
+            source: Box::new(self),
+            help: help.into(),
+        }
+    }
+
+    /// Attach a "did you mean" hint if one of `candidates` is close to `name`
+    pub fn with_suggestion<'a>(
+        self,
+        name: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> GentError {
+        match did_you_mean(name, candidates) {
+            Some(suggestion) => self.with_help(did_you_mean_help(suggestion)),
+            None => self,
+        }
+    }
+
+    /// Get the hint attached to this error, if any
+    pub fn help(&self) -> Option<&str> {
+        match self {
+            GentError::WithHelp { help, .. } => Some(help),
+            GentError::AgentContext { source, .. } => source.help(),
+            GentError::Traced { source, .. } => source.help(),
+            GentError::InFile { source, .. } => source.help(),
+            _ => None,
+        }
+    }
+
+    /// Record a call frame on this error as it unwinds through a call site
+    pub fn with_frame(self, frame: StackFrame) -> GentError {
+        match self {
+            GentError::Traced { source, mut frames } => {
+                frames.push(frame);
+                GentError::Traced { source, frames }
+            }
+            other => GentError::Traced {
+                source: Box::new(other),
+                frames: vec![frame],
+            },
+        }
+    }
+
+    /// Get the call frames recorded on this error, innermost first
+    pub fn frames(&self) -> &[StackFrame] {
+        match self {
+            GentError::Traced { frames, .. } => frames,
+            _ => &[],
+        }
+    }
+
+    /// Record that the error was raised in code from `file` (None = the main
+    /// program), unless an inner call already recorded where it came from
+    pub fn in_file(self, file: Option<String>) -> GentError {
+        if self.origin().is_some() {
+            return self;
+        }
+        match self {
+            // Keep the frames outermost so later calls extend the same trace
+            GentError::Traced { source, frames } => GentError::Traced {
+                source: Box::new(source.in_file(file)),
+                frames,
+            },
+            other => GentError::InFile {
+                source: Box::new(other),
+                file,
+            },
+        }
+    }
+
+    /// Get the file the error's span points into (None = the main program)
+    pub fn file(&self) -> Option<&str> {
+        self.origin().flatten()
+    }
+
+    fn origin(&self) -> Option<Option<&str>> {
+        match self {
+            GentError::InFile { file, .. } => Some(file.as_deref()),
+            GentError::AgentContext { source, .. } => source.origin(),
+            GentError::WithHelp { source, .. } => source.origin(),
+            GentError::Traced { source, .. } => source.origin(),
+            _ => None,
+        }
+    }
+
+    /// Get the error kind used by typed `catch (e: Kind)` clauses
+    ///
+    /// This is the variant name for built-in errors and the user-supplied
+    /// kind for errors raised with `throw`.
+    pub fn kind(&self) -> &str {
+        match self {
+            GentError::SyntaxError { .. } => "SyntaxError",
+            GentError::UnexpectedToken { .. } => "UnexpectedToken",
+            GentError::UndefinedAgent { .. } => "UndefinedAgent",
+            GentError::MissingAgentField { .. } => "MissingAgentField",
+            GentError::TypeError { .. } => "TypeError",
+            GentError::LLMError { .. } => "LLMError",
+            GentError::FileReadError { .. } => "FileReadError",
+            GentError::FileWriteError { .. } => "FileWriteError",
+            GentError::ApiError { .. } => "ApiError",
+            GentError::MissingApiKey { .. } => "MissingApiKey",
+            GentError::UnknownProvider { .. } => "UnknownProvider",
+            GentError::MaxStepsExceeded { .. } => "MaxStepsExceeded",
+            GentError::ParallelTimeout { .. } => "ParallelTimeout",
+            GentError::UnknownTool { .. } => "UnknownTool",
+            GentError::ToolError { .. } => "ToolError",
+            GentError::UndefinedVariable { .. } => "UndefinedVariable",
+            GentError::UndefinedProperty { .. } => "UndefinedProperty",
+            GentError::IndexOutOfBounds { .. } => "IndexOutOfBounds",
+            GentError::NotIndexable { .. } => "NotIndexable",
+            GentError::InvalidOperands { .. } => "InvalidOperands",
+            GentError::DivisionByZero { .. } => "DivisionByZero",
+            GentError::WrongArgumentCount { .. } => "WrongArgumentCount",
+            GentError::ArgumentTypeMismatch { .. } => "ArgumentTypeMismatch",
+            GentError::NativeFunctionError { .. } => "NativeFunctionError",
+            GentError::ValueError { .. } => "ValueError",
+            GentError::JsonError { .. } => "JsonError",
+            GentError::OutputValidationError { .. } => "OutputValidationError",
+            GentError::ProviderError { .. } => "ProviderError",
+            GentError::Cancelled => "Cancelled",
+            GentError::Thrown { kind, .. } => kind,
+            GentError::AgentContext { source, .. } => source.kind(),
+            GentError::WithHelp { source, .. } => source.kind(),
+            GentError::Traced { source, .. } => source.kind(),
+            GentError::InFile { source, .. } => source.kind(),
         }
     }
 }
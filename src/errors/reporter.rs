@@ -1,6 +1,6 @@
 //! Error formatting with source context
 
-use super::GentError;
+use super::{GentError, Span, StackFrame};
 
 /// Formats errors with source context
 pub struct ErrorReporter<'a> {
@@ -21,36 +21,13 @@ impl<'a> ErrorReporter<'a> {
 
     /// Calculate line and column from byte offset
     fn line_col(&self, offset: usize) -> (usize, usize) {
-        let mut line = 1;
-        let mut col = 1;
-        for (i, ch) in self.source.char_indices() {
-            if i >= offset {
-                break;
-            }
-            if ch == '\n' {
-                line += 1;
-                col = 1;
-            } else {
-                col += 1;
-            }
-        }
-        (line, col)
-    }
-
-    /// Get the source line containing the given offset
-    fn get_line(&self, offset: usize) -> &str {
-        let start = self.source[..offset]
-            .rfind('\n')
-            .map(|i| i + 1)
-            .unwrap_or(0);
-        let end = self.source[offset..]
-            .find('\n')
-            .map(|i| offset + i)
-            .unwrap_or(self.source.len());
-        &self.source[start..end]
+        line_col(self.source, offset)
     }
 
     /// Format an error with source context
+    ///
+    /// Errors that unwound through GENT calls also get a stack trace. Errors
+    /// without a span (e.g. provider failures) point at the innermost call site.
     pub fn format(&self, error: &GentError) -> String {
         let mut output = String::new();
 
@@ -62,66 +39,193 @@ impl<'a> ErrorReporter<'a> {
             output.push_str(&format!("error: {}\n", error_msg));
         }
 
-        // Source location if available
-        if let Some(span) = error.span() {
-            let (line, col) = self.line_col(span.start);
-            let source_line = self.get_line(span.start);
-            let caret_count = (span.end - span.start).max(1);
+        // Source location if available. Errors raised in an imported file
+        // are shown against that file, or without a snippet if it can't be read.
+        let frames = error.frames();
+        match (error.span(), error.file()) {
+            (Some(span), Some(file)) if file != self.filename => {
+                if let Ok(source) = std::fs::read_to_string(file) {
+                    self.format_location(&mut output, span, &source, file);
+                }
+            }
+            (Some(span), _) => self.format_location(&mut output, span, self.source, self.filename),
+            (None, _) => {
+                if let Some(frame) = frames.iter().find(|f| f.file.is_none()) {
+                    self.format_location(&mut output, &frame.span, self.source, self.filename);
+                }
+            }
+        }
 
-            // Location line
+        // Hint such as a "did you mean" suggestion
+        if let Some(help) = error.help() {
             if self.use_colors {
-                output.push_str(&format!(
-                    "  \x1b[36m-->\x1b[0m {}:\x1b[34m{}:{}\x1b[0m\n",
-                    self.filename, line, col
-                ));
+                output.push_str(&format!("  \x1b[36m= help:\x1b[0m {}\n", help));
             } else {
-                output.push_str(&format!("  --> {}:{}:{}\n", self.filename, line, col));
+                output.push_str(&format!("  = help: {}\n", help));
             }
+        }
 
-            // Gutter and source line
-            let line_num_width = line.to_string().len();
-            output.push_str(&format!("{:width$} |\n", "", width = line_num_width + 1));
+        // Stack trace through GENT frames, innermost first
+        if !frames.is_empty() {
+            output.push_str("stack trace:\n");
+            for frame in frames {
+                let location = self.frame_location(frame);
+                if self.use_colors {
+                    output.push_str(&format!(
+                        "  at {} (\x1b[34m{}\x1b[0m)\n",
+                        frame.function, location
+                    ));
+                } else {
+                    output.push_str(&format!("  at {} ({})\n", frame.function, location));
+                }
+            }
+        }
 
-            if self.use_colors {
-                output.push_str(&format!(
-                    "\x1b[34m{:>width$}\x1b[0m | {}\n",
-                    line,
-                    source_line,
-                    width = line_num_width
-                ));
-            } else {
-                output.push_str(&format!(
-                    "{:>width$} | {}\n",
-                    line,
-                    source_line,
-                    width = line_num_width
-                ));
+        output
+    }
+
+    /// Format a warning at `span` in the main source
+    pub fn format_warning(&self, message: &str, span: &Span) -> String {
+        let mut output = String::new();
+        if self.use_colors {
+            output.push_str(&format!("\x1b[33mwarning:\x1b[0m {}\n", message));
+        } else {
+            output.push_str(&format!("warning: {}\n", message));
+        }
+        self.format_location(&mut output, span, self.source, self.filename);
+        output
+    }
+
+    /// Format several errors, followed by a summary line when there is more than one
+    pub fn format_all(&self, errors: &[GentError]) -> String {
+        let mut output = String::new();
+        for (i, error) in errors.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
             }
+            output.push_str(&self.format(error));
+        }
 
-            // Caret line
-            let padding = col - 1;
-            let carets = "^".repeat(caret_count);
+        if errors.len() > 1 {
+            let summary = format!("aborting due to {} errors", errors.len());
             if self.use_colors {
-                output.push_str(&format!(
-                    "{:width$} | {:padding$}\x1b[31m{}\x1b[0m\n",
-                    "",
-                    "",
-                    carets,
-                    width = line_num_width + 1,
-                    padding = padding
-                ));
+                output.push_str(&format!("\n\x1b[31merror:\x1b[0m {}\n", summary));
             } else {
-                output.push_str(&format!(
-                    "{:width$} | {:padding$}{}\n",
-                    "",
-                    "",
-                    carets,
-                    width = line_num_width + 1,
-                    padding = padding
-                ));
+                output.push_str(&format!("\nerror: {}\n", summary));
             }
         }
 
         output
     }
+
+    /// Format `file:line:col` for a stack frame
+    ///
+    /// Frames in other files are resolved by reading that file; if it cannot
+    /// be read the byte offset is shown instead.
+    fn frame_location(&self, frame: &StackFrame) -> String {
+        match &frame.file {
+            None => {
+                let (line, col) = self.line_col(frame.span.start);
+                format!("{}:{}:{}", self.filename, line, col)
+            }
+            Some(file) => match std::fs::read_to_string(file) {
+                Ok(source) => {
+                    let (line, col) = line_col(&source, frame.span.start);
+                    format!("{}:{}:{}", file, line, col)
+                }
+                Err(_) => format!("{}@{}", file, frame.span.start),
+            },
+        }
+    }
+
+    /// Append the location line, source line and carets for a span in `source`
+    fn format_location(&self, output: &mut String, span: &Span, source: &str, filename: &str) {
+        if !source.is_char_boundary(span.start) {
+            return;
+        }
+        let (line, col) = line_col(source, span.start);
+        let source_line = get_line(source, span.start);
+        let caret_count = (span.end - span.start).max(1);
+
+        // Location line
+        if self.use_colors {
+            output.push_str(&format!(
+                "  \x1b[36m-->\x1b[0m {}:\x1b[34m{}:{}\x1b[0m\n",
+                filename, line, col
+            ));
+        } else {
+            output.push_str(&format!("  --> {}:{}:{}\n", filename, line, col));
+        }
+
+        // Gutter and source line
+        let line_num_width = line.to_string().len();
+        output.push_str(&format!("{:width$} |\n", "", width = line_num_width + 1));
+
+        if self.use_colors {
+            output.push_str(&format!(
+                "\x1b[34m{:>width$}\x1b[0m | {}\n",
+                line,
+                source_line,
+                width = line_num_width
+            ));
+        } else {
+            output.push_str(&format!(
+                "{:>width$} | {}\n",
+                line,
+                source_line,
+                width = line_num_width
+            ));
+        }
+
+        // Caret line
+        let padding = col - 1;
+        let carets = "^".repeat(caret_count);
+        if self.use_colors {
+            output.push_str(&format!(
+                "{:width$} | {:padding$}\x1b[31m{}\x1b[0m\n",
+                "",
+                "",
+                carets,
+                width = line_num_width + 1,
+                padding = padding
+            ));
+        } else {
+            output.push_str(&format!(
+                "{:width$} | {:padding$}{}\n",
+                "",
+                "",
+                carets,
+                width = line_num_width + 1,
+                padding = padding
+            ));
+        }
+    }
+}
+
+/// Calculate line and column from byte offset
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut col = 1;
+    for (i, ch) in source.char_indices() {
+        if i >= offset {
+            break;
+        }
+        if ch == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+/// Get the line of `source` containing the given offset
+fn get_line(source: &str, offset: usize) -> &str {
+    let start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = source[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(source.len());
+    &source[start..end]
 }
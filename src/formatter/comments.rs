@@ -0,0 +1,91 @@
+//! Recovering `//` comments from source text
+//!
+//! The grammar discards comments, so the formatter scans the source for
+//! them separately. The scan tracks string literals (including the
+//! expressions interpolated into them) so that `//` inside a string, such
+//! as a URL, is not mistaken for a comment.
+
+/// A line comment and its byte range in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub start: usize,
+    pub end: usize,
+    /// Comment text including the leading `//`, without trailing whitespace
+    pub text: String,
+}
+
+/// What the scanner is currently inside of
+enum Mode {
+    /// Code, with the depth of `{` opened inside it
+    Code(usize),
+    /// A `"..."` string literal
+    String,
+    /// A `"""..."""` string literal
+    Multiline,
+}
+
+/// Collect every line comment in `source`, in order
+pub fn collect_comments(source: &str) -> Vec<Comment> {
+    let bytes = source.as_bytes();
+    let mut comments = Vec::new();
+    let mut stack = vec![Mode::Code(0)];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        match stack.last_mut() {
+            Some(Mode::Code(depth)) => {
+                if rest.starts_with(b"//") {
+                    let line_end = source[i..]
+                        .find('\n')
+                        .map(|n| i + n)
+                        .unwrap_or(source.len());
+                    let text = source[i..line_end].trim_end();
+                    comments.push(Comment {
+                        start: i,
+                        end: i + text.len(),
+                        text: text.to_string(),
+                    });
+                    i = line_end;
+                    continue;
+                } else if rest.starts_with(b"\"\"\"") {
+                    stack.push(Mode::Multiline);
+                    i += 3;
+                    continue;
+                } else if bytes[i] == b'"' {
+                    stack.push(Mode::String);
+                } else if bytes[i] == b'{' {
+                    *depth += 1;
+                } else if bytes[i] == b'}' {
+                    if *depth > 0 {
+                        *depth -= 1;
+                    } else if stack.len() > 1 {
+                        // End of a string interpolation
+                        stack.pop();
+                    }
+                }
+            }
+            Some(Mode::String) => match bytes[i] {
+                b'\\' => i += 1,
+                b'"' => {
+                    stack.pop();
+                }
+                b'{' => stack.push(Mode::Code(0)),
+                _ => {}
+            },
+            Some(Mode::Multiline) => {
+                if rest.starts_with(b"\"\"\"") {
+                    stack.pop();
+                    i += 3;
+                    continue;
+                } else if bytes[i] == b'{' {
+                    stack.push(Mode::Code(0));
+                }
+            }
+            None => break,
+        }
+        i += 1;
+    }
+
+    comments
+}
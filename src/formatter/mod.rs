@@ -0,0 +1,937 @@
+//! Canonical source formatter for GENT (`gent fmt`)
+//!
+//! Pretty-prints the AST with four-space indentation and one item per line
+//! in declaration bodies and blocks. Comments are dropped by the grammar,
+//! so they are recovered from the source text and re-attached by byte
+//! offset: a comment on its own line stays before the item that follows
+//! it, and a comment after code stays at the end of that line. String
+//! literals are copied verbatim, since whitespace inside multi-line strings
+//! is significant. Formatting already formatted source returns it unchanged.
+
+mod comments;
+
+pub use comments::{collect_comments, Comment};
+
+use pest::Parser;
+
+use crate::errors::{GentResult, Span};
+use crate::lexer::{GentParser, Rule};
+use crate::parser::{
+    parse, AgentDecl, BinaryOp, Block, BlockStmt, DurationUnit, EnumVariant, Expression, FieldType,
+    InterfaceMember, Lambda, LambdaBody, MatchArm, MatchBody, MatchExpr, MatchPattern, OutputType,
+    ParallelDecl, Param, Program, Statement, StructField, TypeName, UnaryOp,
+};
+
+const INDENT: &str = "    ";
+
+/// Lists and argument lists longer than this are split one item per line
+const MAX_WIDTH: usize = 100;
+
+/// Precedence of postfix expressions (calls, member and index access)
+const POSTFIX: u8 = 8;
+
+/// Precedence of prefix `!` and `-`
+const UNARY: u8 = 7;
+
+/// Format GENT source code
+///
+/// Returns the first syntax error if the source does not parse.
+pub fn format_source(source: &str) -> GentResult<String> {
+    let program = parse(source)?;
+    Ok(format_program(source, &program))
+}
+
+/// Format a program parsed from `source`, keeping the comments in `source`
+pub fn format_program(source: &str, program: &Program) -> String {
+    Formatter::new(source).program(program)
+}
+
+/// Check whether source code is already formatted
+pub fn is_formatted(source: &str) -> GentResult<bool> {
+    Ok(format_source(source)? == source)
+}
+
+/// A declaration body item whose position comes from re-parsing the source
+///
+/// The AST keeps `tools`, `knowledge`, `output`, `agents` and `timeout`
+/// apart from the other fields, so their order is recovered from pest.
+struct BodyItem {
+    rule: Rule,
+    start: usize,
+    end: usize,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: Vec<Comment>,
+    /// Index of the first comment not printed yet
+    next_comment: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            comments: collect_comments(source),
+            next_comment: 0,
+        }
+    }
+
+    fn program(&mut self, program: &Program) -> String {
+        self.lines(
+            &program.statements,
+            0,
+            self.source.len(),
+            "",
+            statement_span,
+            |f, statement, indent| f.statement(statement, indent),
+        )
+    }
+
+    // === Comments & layout ===
+
+    /// Whether an unprinted comment starts inside `start..end`
+    fn has_comment_in(&self, start: usize, end: usize) -> bool {
+        self.comments[self.next_comment..]
+            .iter()
+            .any(|c| c.start >= start && c.start < end)
+    }
+
+    /// Take the comment that trails code ending at `offset` on the same line
+    fn trailing_comment(&mut self, offset: usize) -> Option<String> {
+        let comment = self.comments.get(self.next_comment)?;
+        if comment.start < offset {
+            return None;
+        }
+        let between = &self.source[offset..comment.start];
+        if between.chars().all(|c| c == ' ' || c == '\t' || c == ',') {
+            self.next_comment += 1;
+            Some(comment.text.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Print the comments that start before `offset` on their own lines
+    fn comment_lines(&mut self, offset: usize, indent: usize, out: &mut String, first: &mut bool) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.start >= offset {
+                break;
+            }
+            if !*first && self.blank_line_before(comment.start) {
+                out.push('\n');
+            }
+            out.push_str(&INDENT.repeat(indent));
+            out.push_str(&comment.text);
+            out.push('\n');
+            *first = false;
+            self.next_comment += 1;
+        }
+    }
+
+    /// Whether the source has an empty line right before `offset`
+    fn blank_line_before(&self, offset: usize) -> bool {
+        let before = &self.source[..offset];
+        let code = before.trim_end_matches(|c: char| c.is_whitespace());
+        before[code.len()..].matches('\n').count() > 1
+    }
+
+    /// End of the code in `start..end`, before any trailing whitespace and
+    /// comments (pest spans of statements include them)
+    fn code_end(&self, start: usize, mut end: usize) -> usize {
+        loop {
+            end = start + self.source[start..end].trim_end().len();
+            match self
+                .comments
+                .iter()
+                .find(|c| c.start >= start && c.end == end)
+            {
+                Some(comment) => end = comment.start,
+                None => return end,
+            }
+        }
+    }
+
+    /// Skip whitespace and comments from `offset`
+    fn skip_trivia(&self, mut offset: usize) -> usize {
+        loop {
+            let rest = &self.source[offset..];
+            let trimmed = rest.trim_start();
+            offset += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                offset += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return offset;
+            }
+        }
+    }
+
+    /// Offset of the first `delimiter` in `start..end`
+    fn find_in(&self, start: usize, end: usize, delimiter: char) -> usize {
+        self.source[start..end]
+            .find(delimiter)
+            .map(|i| start + i)
+            .unwrap_or(start)
+    }
+
+    /// Print items one per line at `indent`, with the comments around them,
+    /// up to the closing delimiter at `close`
+    ///
+    /// Single blank lines between items are kept; longer runs are collapsed.
+    fn lines<T>(
+        &mut self,
+        items: &[T],
+        indent: usize,
+        close: usize,
+        separator: &str,
+        span: impl Fn(&T) -> (usize, usize),
+        mut render: impl FnMut(&mut Self, &T, usize) -> String,
+    ) -> String {
+        let mut out = String::new();
+        let mut first = true;
+        for (i, item) in items.iter().enumerate() {
+            let (start, end) = span(item);
+            self.comment_lines(start, indent, &mut out, &mut first);
+            if !first && self.blank_line_before(start) {
+                out.push('\n');
+            }
+            let text = render(self, item, indent);
+            out.push_str(&INDENT.repeat(indent));
+            out.push_str(&text);
+            if i + 1 < items.len() {
+                out.push_str(separator);
+            }
+            if let Some(comment) = self.trailing_comment(self.code_end(start, end)) {
+                out.push(' ');
+                out.push_str(&comment);
+            }
+            out.push('\n');
+            first = false;
+        }
+        self.comment_lines(close, indent, &mut out, &mut first);
+        out
+    }
+
+    /// Wrap a body in braces, the opening one being at `open` in the source
+    fn braces(
+        &mut self,
+        open: usize,
+        indent: usize,
+        body: impl FnOnce(&mut Self) -> String,
+    ) -> String {
+        let mut out = String::from("{");
+        if let Some(comment) = self.trailing_comment(open + 1) {
+            out.push(' ');
+            out.push_str(&comment);
+        }
+        let inner = body(self);
+        if inner.is_empty() && out.len() == 1 {
+            return "{}".to_string();
+        }
+        out.push('\n');
+        out.push_str(&inner);
+        out.push_str(&INDENT.repeat(indent));
+        out.push('}');
+        out
+    }
+
+    /// Re-parse a declaration to find the rule and span of each body item
+    fn body_items(&self, rule: Rule, span: &Span) -> Vec<BodyItem> {
+        let Ok(mut pairs) = GentParser::parse(rule, &self.source[span.start..span.end]) else {
+            return Vec::new();
+        };
+        let Some(decl) = pairs.next() else {
+            return Vec::new();
+        };
+        let Some(body) = decl.into_inner().nth(1) else {
+            return Vec::new();
+        };
+        body.into_inner()
+            .filter_map(|item| item.into_inner().next())
+            .map(|item| BodyItem {
+                rule: item.as_rule(),
+                start: span.start + item.as_span().start(),
+                end: span.start + item.as_span().end(),
+            })
+            .collect()
+    }
+
+    // === Declarations ===
+
+    fn statement(&mut self, statement: &Statement, indent: usize) -> String {
+        match statement {
+            Statement::Import(import) => format!(
+                "import {{ {} }} from \"{}\"",
+                import.names.join(", "),
+                import.path
+            ),
+            Statement::AgentDecl(decl) => self.agent(decl, indent),
+            Statement::ToolDecl(decl) => format!(
+                "tool {}{} {}",
+                decl.name,
+                self.signature(&decl.params, &decl.return_type, indent),
+                self.block(&decl.body, indent)
+            ),
+            Statement::FnDecl(decl) => format!(
+                "fn {}{} {}",
+                decl.name,
+                self.signature(&decl.params, &decl.return_type, indent),
+                self.block(&decl.body, indent)
+            ),
+            Statement::StructDecl(decl) => {
+                let mut header = format!("struct {}", decl.name);
+                if !decl.implements.is_empty() {
+                    header.push_str(&format!(" implements {}", decl.implements.join(", ")));
+                }
+                let open = self.find_in(decl.span.start, decl.span.end, '{');
+                let body = self.braces(open, indent, |f| {
+                    f.struct_fields(&decl.fields, indent + 1, decl.span.end - 1)
+                });
+                format!("{} {}", header, body)
+            }
+            Statement::EnumDecl(decl) => {
+                let open = self.find_in(decl.span.start, decl.span.end, '{');
+                let body = self.braces(open, indent, |f| {
+                    f.lines(
+                        &decl.variants,
+                        indent + 1,
+                        decl.span.end - 1,
+                        "",
+                        |variant| (variant.span.start, variant.span.end),
+                        |_, variant, _| enum_variant(variant),
+                    )
+                });
+                format!("enum {} {}", decl.name, body)
+            }
+            Statement::InterfaceDecl(decl) => {
+                let open = self.find_in(decl.span.start, decl.span.end, '{');
+                let body = self.braces(open, indent, |f| {
+                    f.lines(
+                        &decl.members,
+                        indent + 1,
+                        decl.span.end - 1,
+                        "",
+                        |member| match member {
+                            InterfaceMember::Field(field) => (field.span.start, field.span.end),
+                            InterfaceMember::Method(method) => (method.span.start, method.span.end),
+                        },
+                        |f, member, indent| match member {
+                            InterfaceMember::Field(field) => {
+                                format!("{}: {}", field.name, type_name(&field.type_name))
+                            }
+                            InterfaceMember::Method(method) => format!(
+                                "{}{}",
+                                method.name,
+                                f.signature(&method.params, &method.return_type, indent)
+                            ),
+                        },
+                    )
+                });
+                format!("interface {} {}", decl.name, body)
+            }
+            Statement::ParallelDecl(decl) => self.parallel(decl, indent),
+            Statement::LetStmt(stmt) => {
+                format!("let {} = {}", stmt.name, self.expr(&stmt.value, indent))
+            }
+            Statement::TopLevelCall(call) => {
+                let close = call.span.end.saturating_sub(1);
+                format!("{}{}", call.name, self.args(&call.args, close, indent))
+            }
+        }
+    }
+
+    fn agent(&mut self, decl: &AgentDecl, indent: usize) -> String {
+        let mut items = self.body_items(Rule::agent_decl, &decl.span);
+        // A repeated `tools`, `knowledge`, `output` or `input` keeps only its last value
+        let singles = [
+            Rule::tools_field,
+            Rule::knowledge_field,
+            Rule::output_field,
+            Rule::input_field,
+        ];
+        let mut seen = Vec::new();
+        for i in (0..items.len()).rev() {
+            let rule = items[i].rule;
+            if singles.contains(&rule) {
+                if seen.contains(&rule) {
+                    items.remove(i);
+                } else {
+                    seen.push(rule);
+                }
+            }
+        }
+
+        let mut fields = decl.fields.iter();
+        let open = self.find_in(decl.span.start, decl.span.end, '{');
+        let body = self.braces(open, indent, |f| {
+            f.lines(
+                &items,
+                indent + 1,
+                decl.span.end - 1,
+                "",
+                |item| (item.start, item.end),
+                |f, item, indent| match item.rule {
+                    Rule::tools_field => match &decl.tools_expr {
+                        Some(tools) => format!("tools: {}", f.expr(tools, indent)),
+                        None => String::new(),
+                    },
+                    Rule::knowledge_field => match &decl.knowledge_expr {
+                        Some(knowledge) => format!("knowledge: {}", f.expr(knowledge, indent)),
+                        None => String::new(),
+                    },
+                    Rule::output_field => match &decl.output {
+                        Some(OutputType::Named(name)) => format!("output: {}", name),
+                        Some(OutputType::Inline(fields)) => format!(
+                            "output: {}",
+                            f.object_type(fields, item.start, item.end, indent)
+                        ),
+                        None => String::new(),
+                    },
+                    Rule::input_field => match &decl.input {
+                        Some(OutputType::Named(name)) => format!("input: {}", name),
+                        Some(OutputType::Inline(fields)) => format!(
+                            "input: {}",
+                            f.object_type(fields, item.start, item.end, indent)
+                        ),
+                        None => String::new(),
+                    },
+                    _ => match fields.next() {
+                        Some(field) => format!("{}: {}", field.name, f.expr(&field.value, indent)),
+                        None => String::new(),
+                    },
+                },
+            )
+        });
+        format!("agent {} {}", decl.name, body)
+    }
+
+    fn parallel(&mut self, decl: &ParallelDecl, indent: usize) -> String {
+        let mut items = self.body_items(Rule::parallel_decl, &decl.span);
+        // Agents from every `agents` field are printed together at the last one
+        let last_agents = items.iter().rposition(|i| i.rule == Rule::agents_field);
+        let last_timeout = items.iter().rposition(|i| i.rule == Rule::timeout_field);
+        let mut index = 0;
+        items.retain(|_| {
+            index += 1;
+            Some(index - 1) == last_agents || Some(index - 1) == last_timeout
+        });
+
+        let open = self.find_in(decl.span.start, decl.span.end, '{');
+        let body = self.braces(open, indent, |f| {
+            f.lines(
+                &items,
+                indent + 1,
+                decl.span.end - 1,
+                "",
+                |item| (item.start, item.end),
+                |f, item, indent| {
+                    if item.rule == Rule::agents_field {
+                        let start = f.find_in(item.start, item.end, '[');
+                        format!("agents: {}", f.array(&decl.agents, start, item.end, indent))
+                    } else {
+                        let unit = match decl.timeout.unit {
+                            DurationUnit::Milliseconds => "ms",
+                            DurationUnit::Seconds => "s",
+                            DurationUnit::Minutes => "m",
+                        };
+                        format!("timeout: {}{}", decl.timeout.value, unit)
+                    }
+                },
+            )
+        });
+        format!("parallel {} {}", decl.name, body)
+    }
+
+    fn struct_fields(&mut self, fields: &[StructField], indent: usize, close: usize) -> String {
+        self.lines(
+            fields,
+            indent,
+            close,
+            "",
+            |field| (field.span.start, field.span.end),
+            |f, field, indent| {
+                let field_type = f.field_type(&field.field_type, &field.span, indent);
+                format!("{}: {}", field.name, field_type)
+            },
+        )
+    }
+
+    /// An inline object type, kept on one line if it was written on one line
+    fn object_type(
+        &mut self,
+        fields: &[StructField],
+        start: usize,
+        end: usize,
+        indent: usize,
+    ) -> String {
+        let close = self.source[start..end]
+            .rfind('}')
+            .map(|i| start + i)
+            .unwrap_or(end);
+        if !self.source[start..end].contains('\n') && !self.has_comment_in(start, end) {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| {
+                    let field_type = self.field_type(&field.field_type, &field.span, indent);
+                    format!("{}: {}", field.name, field_type)
+                })
+                .collect();
+            let inline = if fields.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            };
+            if fits(indent, &inline) {
+                return inline;
+            }
+        }
+        let open = self.find_in(start, end, '{');
+        self.braces(open, indent, |f| f.struct_fields(fields, indent + 1, close))
+    }
+
+    fn field_type(&mut self, field_type: &FieldType, span: &Span, indent: usize) -> String {
+        match field_type {
+            FieldType::String => "string".to_string(),
+            FieldType::Number => "number".to_string(),
+            FieldType::Boolean => "boolean".to_string(),
+            FieldType::Array(inner) => format!("{}[]", self.field_type(inner, span, indent)),
+            FieldType::Object(fields) => self.object_type(fields, span.start, span.end, indent),
+            FieldType::Named(name) => name.clone(),
+            FieldType::Nullable(inner) => format!("{}?", self.field_type(inner, span, indent)),
+        }
+    }
+
+    // === Statements ===
+
+    fn block(&mut self, block: &Block, indent: usize) -> String {
+        self.braces(block.span.start, indent, |f| {
+            f.lines(
+                &block.statements,
+                indent + 1,
+                block.span.end - 1,
+                "",
+                block_stmt_span,
+                |f, stmt, indent| f.block_stmt(stmt, indent),
+            )
+        })
+    }
+
+    fn block_stmt(&mut self, stmt: &BlockStmt, indent: usize) -> String {
+        match stmt {
+            BlockStmt::Let(stmt) => {
+                format!("let {} = {}", stmt.name, self.expr(&stmt.value, indent))
+            }
+            BlockStmt::Assignment(stmt) => {
+                format!("{} = {}", stmt.name, self.expr(&stmt.value, indent))
+            }
+            BlockStmt::Return(stmt) => match &stmt.value {
+                Some(value) => format!("return {}", self.expr(value, indent)),
+                None => "return".to_string(),
+            },
+            BlockStmt::Throw(stmt) => format!("throw {}", self.expr(&stmt.value, indent)),
+            BlockStmt::If(stmt) => {
+                let mut out = format!(
+                    "if {} {}",
+                    self.expr(&stmt.condition, indent),
+                    self.block(&stmt.then_block, indent)
+                );
+                if let Some(else_block) = &stmt.else_block {
+                    out.push_str(&format!(" else {}", self.block(else_block, indent)));
+                }
+                out
+            }
+            BlockStmt::For(stmt) => format!(
+                "for {} in {} {}",
+                match &stmt.value_variable {
+                    Some(value) => format!("({}, {})", stmt.variable, value),
+                    None => stmt.variable.clone(),
+                },
+                self.expr(&stmt.iterable, indent),
+                self.block(&stmt.body, indent)
+            ),
+            BlockStmt::While(stmt) => format!(
+                "while {} {}",
+                self.expr(&stmt.condition, indent),
+                self.block(&stmt.body, indent)
+            ),
+            BlockStmt::Try(stmt) => {
+                let binding = match &stmt.error_type {
+                    Some(kind) => format!("({}: {})", stmt.error_var, kind),
+                    None => stmt.error_var.clone(),
+                };
+                let mut out = format!(
+                    "try {} catch {} {}",
+                    self.block(&stmt.try_block, indent),
+                    binding,
+                    self.block(&stmt.catch_block, indent)
+                );
+                if let Some(finally_block) = &stmt.finally_block {
+                    out.push_str(&format!(" finally {}", self.block(finally_block, indent)));
+                }
+                out
+            }
+            BlockStmt::Break(_) => "break".to_string(),
+            BlockStmt::Continue(_) => "continue".to_string(),
+            BlockStmt::Expr(expr) => self.expr(expr, indent),
+        }
+    }
+
+    // === Expressions ===
+
+    fn signature(
+        &mut self,
+        params: &[Param],
+        return_type: &Option<TypeName>,
+        indent: usize,
+    ) -> String {
+        let params: Vec<String> = params
+            .iter()
+            .map(|p| {
+                let param = format!("{}: {}", p.name, type_name(&p.type_name));
+                match &p.default {
+                    Some(default) => format!("{} = {}", param, self.expr(default, indent)),
+                    None => param,
+                }
+            })
+            .collect();
+        match return_type {
+            Some(return_type) => format!("({}) -> {}", params.join(", "), type_name(return_type)),
+            None => format!("({})", params.join(", ")),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression, indent: usize) -> String {
+        match expr {
+            Expression::String(_, span) => self.source[span.start..span.end].to_string(),
+            Expression::Number(_, span) => self.source[span.start..span.end]
+                .split_whitespace()
+                .collect(),
+            Expression::Boolean(value, _) => value.to_string(),
+            Expression::Identifier(name, _) => name.clone(),
+            Expression::Null(_) => "null".to_string(),
+            Expression::Array(items, span) => self.array(items, span.start, span.end, indent),
+            Expression::Object(fields, span) => self.object(fields, span, indent),
+            Expression::Binary(op, left, right, _) => {
+                let precedence = binary_precedence(op);
+                let left = self.operand(left, precedence, false, indent);
+                let right = self.operand(right, precedence, true, indent);
+                format!("{} {} {}", left, binary_op(op), right)
+            }
+            Expression::Unary(op, operand, _) => {
+                let op = match op {
+                    UnaryOp::Not => "!",
+                    UnaryOp::Neg => "-",
+                };
+                format!("{}{}", op, self.operand(operand, UNARY, false, indent))
+            }
+            Expression::Call(callee, args, span) => {
+                let callee = self.operand(callee, POSTFIX, false, indent);
+                let close = match args.last() {
+                    Some(last) => self.skip_trivia(last.span().end),
+                    None => span.end,
+                };
+                format!("{}{}", callee, self.args(args, close, indent))
+            }
+            Expression::Member(object, name, _) => {
+                format!("{}.{}", self.operand(object, POSTFIX, false, indent), name)
+            }
+            Expression::Index(object, index, _) => format!(
+                "{}[{}]",
+                self.operand(object, POSTFIX, false, indent),
+                self.expr(index, indent)
+            ),
+            Expression::Range(start, end, _) => {
+                format!("{}..{}", self.expr(start, indent), self.expr(end, indent))
+            }
+            Expression::Lambda(lambda) => self.lambda(lambda, indent),
+            Expression::Match(expr) => self.match_expr(expr, indent),
+        }
+    }
+
+    /// Print an operand, parenthesized if it binds looser than its context
+    fn operand(&mut self, expr: &Expression, context: u8, right: bool, indent: usize) -> String {
+        let precedence = self.precedence(expr);
+        let text = self.expr(expr, indent);
+        if precedence < context || (right && precedence == context) {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+
+    fn precedence(&self, expr: &Expression) -> u8 {
+        match expr {
+            Expression::Binary(op, ..) => binary_precedence(op),
+            Expression::Unary(..) => UNARY,
+            Expression::Number(_, span) if self.source[span.start..].starts_with('-') => UNARY,
+            // A lambda body extends as far as possible, so lambdas always need
+            // parentheses inside other expressions
+            Expression::Lambda(_) => 0,
+            _ => POSTFIX + 1,
+        }
+    }
+
+    /// An argument list, split one per line when it has comments or is too
+    /// long; a trailing multi-line argument such as a block lambda stays
+    /// attached to the call
+    fn args(&mut self, args: &[Expression], close: usize, indent: usize) -> String {
+        let Some(first) = args.first() else {
+            return "()".to_string();
+        };
+        let start = first.span().start;
+        if !self.has_comment_in(start, close) {
+            let parts: Vec<String> = args.iter().map(|arg| self.expr(arg, indent)).collect();
+            let inline = format!("({})", parts.join(", "));
+            let leading_multiline = parts[..parts.len() - 1]
+                .iter()
+                .any(|part| part.contains('\n'));
+            let first_line = inline.lines().next().unwrap_or_default();
+            if !leading_multiline && (args.len() == 1 || fits(indent, first_line)) {
+                return inline;
+            }
+        }
+        let body = self.lines(
+            args,
+            indent + 1,
+            close,
+            ",",
+            |arg| (arg.span().start, arg.span().end),
+            |f, arg, indent| f.expr(arg, indent),
+        );
+        format!("(\n{}{})", body, INDENT.repeat(indent))
+    }
+
+    /// An array literal, kept on one line if it was written on one line
+    fn array(&mut self, items: &[Expression], start: usize, end: usize, indent: usize) -> String {
+        let commented = self.has_comment_in(start, end);
+        if items.is_empty() && !commented {
+            return "[]".to_string();
+        }
+        if !commented && !self.source[start..end].contains('\n') {
+            let parts: Vec<String> = items.iter().map(|item| self.expr(item, indent)).collect();
+            let inline = format!("[{}]", parts.join(", "));
+            if !inline.contains('\n') && fits(indent, &inline) {
+                return inline;
+            }
+        }
+        let body = self.lines(
+            items,
+            indent + 1,
+            end - 1,
+            ",",
+            |item| (item.span().start, item.span().end),
+            |f, item, indent| f.expr(item, indent),
+        );
+        format!("[\n{}{}]", body, INDENT.repeat(indent))
+    }
+
+    /// An object literal, kept on one line if it was written on one line
+    fn object(&mut self, fields: &[(String, Expression)], span: &Span, indent: usize) -> String {
+        let commented = self.has_comment_in(span.start, span.end);
+        if fields.is_empty() && !commented {
+            return "{}".to_string();
+        }
+        if !commented && !self.source[span.start..span.end].contains('\n') {
+            let parts: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{}: {}", object_key(key), self.expr(value, indent)))
+                .collect();
+            let inline = format!("{{ {} }}", parts.join(", "));
+            if !inline.contains('\n') && fits(indent, &inline) {
+                return inline;
+            }
+        }
+        let source = self.source;
+        let body = self.lines(
+            fields,
+            indent + 1,
+            span.end - 1,
+            ",",
+            |(_, value)| (key_start(source, value.span().start), value.span().end),
+            |f, (key, value), indent| format!("{}: {}", object_key(key), f.expr(value, indent)),
+        );
+        format!("{{\n{}{}}}", body, INDENT.repeat(indent))
+    }
+
+    fn lambda(&mut self, lambda: &Lambda, indent: usize) -> String {
+        let params = format!("({})", lambda.params.join(", "));
+        match &lambda.body {
+            LambdaBody::Expression(body) => format!("{} => {}", params, self.expr(body, indent)),
+            LambdaBody::Block(block) => format!("{} => {}", params, self.block(block, indent)),
+        }
+    }
+
+    fn match_expr(&mut self, expr: &MatchExpr, indent: usize) -> String {
+        let subject = self.expr(&expr.subject, indent);
+        let open = self.find_in(expr.subject.span().end, expr.span.end, '{');
+        let arms = self.braces(open, indent, |f| {
+            f.lines(
+                &expr.arms,
+                indent + 1,
+                expr.span.end - 1,
+                "",
+                |arm| (arm.span.start, arm.span.end),
+                |f, arm, indent| f.match_arm(arm, indent),
+            )
+        });
+        format!("match {} {}", subject, arms)
+    }
+
+    fn match_arm(&mut self, arm: &MatchArm, indent: usize) -> String {
+        let pattern = match &arm.pattern {
+            MatchPattern::Wildcard => "_".to_string(),
+            MatchPattern::EnumVariant {
+                enum_name,
+                variant_name,
+                bindings,
+            } if bindings.is_empty() => format!("{}.{}", enum_name, variant_name),
+            MatchPattern::EnumVariant {
+                enum_name,
+                variant_name,
+                bindings,
+            } => format!("{}.{}({})", enum_name, variant_name, bindings.join(", ")),
+        };
+        let body = match &arm.body {
+            MatchBody::Expression(body) => self.expr(body, indent),
+            MatchBody::Block(block) => self.block(block, indent),
+        };
+        format!("{} => {}", pattern, body)
+    }
+}
+
+fn fits(indent: usize, text: &str) -> bool {
+    indent * INDENT.len() + text.chars().count() <= MAX_WIDTH
+}
+
+fn statement_span(statement: &Statement) -> (usize, usize) {
+    let span = match statement {
+        Statement::Import(s) => &s.span,
+        Statement::AgentDecl(s) => &s.span,
+        Statement::ToolDecl(s) => &s.span,
+        Statement::FnDecl(s) => &s.span,
+        Statement::StructDecl(s) => &s.span,
+        Statement::EnumDecl(s) => &s.span,
+        Statement::InterfaceDecl(s) => &s.span,
+        Statement::ParallelDecl(s) => &s.span,
+        Statement::LetStmt(s) => &s.span,
+        Statement::TopLevelCall(s) => &s.span,
+    };
+    (span.start, span.end)
+}
+
+fn block_stmt_span(stmt: &BlockStmt) -> (usize, usize) {
+    let span = match stmt {
+        BlockStmt::Let(s) => &s.span,
+        BlockStmt::Assignment(s) => &s.span,
+        BlockStmt::Return(s) => &s.span,
+        BlockStmt::Throw(s) => &s.span,
+        BlockStmt::If(s) => &s.span,
+        BlockStmt::For(s) => &s.span,
+        BlockStmt::While(s) => &s.span,
+        BlockStmt::Try(s) => &s.span,
+        BlockStmt::Break(span) | BlockStmt::Continue(span) => span,
+        BlockStmt::Expr(expr) => expr.span(),
+    };
+    (span.start, span.end)
+}
+
+fn type_name(ty: &TypeName) -> String {
+    match ty {
+        TypeName::String => "string".to_string(),
+        TypeName::Number => "number".to_string(),
+        TypeName::Boolean => "boolean".to_string(),
+        TypeName::Object => "object".to_string(),
+        TypeName::Array => "array".to_string(),
+        TypeName::Any => "any".to_string(),
+        TypeName::Named(name) => name.clone(),
+        TypeName::ArrayOf(inner) => format!("{}[]", type_name(inner)),
+        TypeName::Nullable(inner) => format!("{}?", type_name(inner)),
+    }
+}
+
+fn enum_variant(variant: &EnumVariant) -> String {
+    if variant.fields.is_empty() {
+        return variant.name.clone();
+    }
+    let fields: Vec<String> = variant
+        .fields
+        .iter()
+        .map(|field| match &field.name {
+            Some(name) => format!("{}: {}", name, field.type_name),
+            None => field.type_name.clone(),
+        })
+        .collect();
+    format!("{}({})", variant.name, fields.join(", "))
+}
+
+fn binary_precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Or => 1,
+        BinaryOp::And => 2,
+        BinaryOp::Eq | BinaryOp::Ne => 3,
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 4,
+        BinaryOp::Add | BinaryOp::Sub => 5,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 6,
+    }
+}
+
+fn binary_op(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+        BinaryOp::And => "&&",
+        BinaryOp::Or => "||",
+    }
+}
+
+/// Start of the key of the object field whose value starts at `value_start`
+fn key_start(source: &str, value_start: usize) -> usize {
+    let before = source[..value_start].trim_end();
+    let before = before.strip_suffix(':').unwrap_or(before).trim_end();
+    if let Some(quoted) = before.strip_suffix('"') {
+        return quoted.rfind('"').unwrap_or(before.len());
+    }
+    let key_len = before
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .count();
+    before.len() - key_len
+}
+
+/// An object key, quoted unless it is a plain identifier
+fn object_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let plain = chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic())
+        .unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        return key.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in key.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '{' => quoted.push_str("\\{"),
+            '}' => quoted.push_str("\\}"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -1,14 +1,26 @@
 //! Array method implementations for GENT
 //!
 //! This module provides built-in methods for array values,
-//! including length, push, pop, indexOf, join, slice, concat.
-//! Also includes higher-order methods: map, filter, reduce, find.
+//! including length, push, pop, indexOf, join, slice, concat, sort,
+//! reverse, includes, flatten, unique, zip, chunk, take, skip, sum, min, max.
+//! Also includes higher-order methods: map, filter, reduce, find, findIndex,
+//! some, every, forEach, flatMap, sortBy, groupBy and sort with a comparator.
 
 use crate::errors::{GentError, GentResult};
 use crate::interpreter::{Environment, Value};
 use crate::parser::ast::LambdaBody;
 use crate::runtime::tools::ToolRegistry;
 use crate::Span;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Names of the methods available on arrays, including higher-order ones
+pub const ARRAY_METHODS: &[&str] = &[
+    "length", "push", "pop", "indexOf", "join", "slice", "concat", "map", "filter", "reduce",
+    "find", "findIndex", "some", "every", "forEach", "flatMap", "sort", "sortBy", "groupBy",
+    "reverse", "includes", "flatten", "unique", "zip", "chunk", "take", "skip", "sum", "min",
+    "max",
+];
 
 /// Call a method on an array value (non-lambda methods only)
 ///
@@ -25,12 +37,23 @@ use crate::Span;
 /// * `join(separator)` - Joins elements into a string
 /// * `slice(start, end)` - Returns a new array with elements from start to end
 /// * `concat(other)` - Returns a new array combining this and other
+/// * `sort()` - Returns a new array of numbers or strings in ascending order
+/// * `reverse()` - Returns a new array in reverse order
+/// * `includes(value)` - Checks if the value is an element
+/// * `flatten(depth)` - Returns a new array with nested arrays spliced in (depth defaults to 1)
+/// * `unique()` - Returns a new array without repeated elements, keeping first occurrences
+/// * `zip(other)` - Returns `[a, b]` pairs, as many as the shorter array has elements
+/// * `chunk(size)` - Splits into arrays of `size` elements (the last may be shorter)
+/// * `take(n)` - Returns the first n elements
+/// * `skip(n)` - Returns all but the first n elements
+/// * `sum()` - Adds up an array of numbers
+/// * `min()` / `max()` - Smallest / largest number or string, or null when empty
 pub fn call_array_method(arr: &mut Vec<Value>, method: &str, args: &[Value]) -> GentResult<Value> {
     match method {
         "length" => Ok(Value::Number(arr.len() as f64)),
 
         "push" => {
-            let value = args.get(0).cloned().ok_or_else(|| GentError::TypeError {
+            let value = args.first().cloned().ok_or_else(|| GentError::TypeError {
                 expected: "argument for push()".to_string(),
                 got: "missing argument".to_string(),
                 span: Span::default(),
@@ -42,7 +65,7 @@ pub fn call_array_method(arr: &mut Vec<Value>, method: &str, args: &[Value]) ->
         "pop" => Ok(arr.pop().unwrap_or(Value::Null)),
 
         "indexOf" => {
-            let target = args.get(0).ok_or_else(|| GentError::TypeError {
+            let target = args.first().ok_or_else(|| GentError::TypeError {
                 expected: "argument for indexOf()".to_string(),
                 got: "missing argument".to_string(),
                 span: Span::default(),
@@ -73,6 +96,125 @@ pub fn call_array_method(arr: &mut Vec<Value>, method: &str, args: &[Value]) ->
             Ok(Value::Array(result))
         }
 
+        "sort" => {
+            let mut result = arr.clone();
+            let mut error = None;
+            result.sort_by(|a, b| {
+                compare_values(a, b, "sort").unwrap_or_else(|e| {
+                    error.get_or_insert(e);
+                    Ordering::Equal
+                })
+            });
+            match error {
+                Some(e) => Err(e),
+                None => Ok(Value::Array(result)),
+            }
+        }
+
+        "reverse" => Ok(Value::Array(arr.iter().rev().cloned().collect())),
+
+        "includes" => {
+            let target = args.first().ok_or_else(|| GentError::TypeError {
+                expected: "argument for includes()".to_string(),
+                got: "missing argument".to_string(),
+                span: Span::default(),
+            })?;
+            Ok(Value::Boolean(arr.contains(target)))
+        }
+
+        "flatten" => {
+            let depth = if args.is_empty() {
+                1
+            } else {
+                get_number_arg(args, 0, "flatten")? as usize
+            };
+            Ok(Value::Array(flatten(arr, depth)))
+        }
+
+        "unique" => {
+            let mut result: Vec<Value> = Vec::new();
+            for item in arr.iter() {
+                if !result.contains(item) {
+                    result.push(item.clone());
+                }
+            }
+            Ok(Value::Array(result))
+        }
+
+        "zip" => {
+            let other = get_array_arg(args, 0, "zip")?;
+            Ok(Value::Array(
+                arr.iter()
+                    .zip(other)
+                    .map(|(a, b)| Value::Array(vec![a.clone(), b]))
+                    .collect(),
+            ))
+        }
+
+        "chunk" => {
+            let size = get_number_arg(args, 0, "chunk")?;
+            if size < 1.0 {
+                return Err(GentError::TypeError {
+                    expected: "chunk size of at least 1".to_string(),
+                    got: Value::Number(size).to_string(),
+                    span: Span::default(),
+                });
+            }
+            Ok(Value::Array(
+                arr.chunks(size as usize)
+                    .map(|chunk| Value::Array(chunk.to_vec()))
+                    .collect(),
+            ))
+        }
+
+        "take" => {
+            let n = (get_number_arg(args, 0, "take")?.max(0.0) as usize).min(arr.len());
+            Ok(Value::Array(arr[..n].to_vec()))
+        }
+
+        "skip" => {
+            let n = (get_number_arg(args, 0, "skip")?.max(0.0) as usize).min(arr.len());
+            Ok(Value::Array(arr[n..].to_vec()))
+        }
+
+        "sum" => {
+            let mut total = 0.0;
+            for item in arr.iter() {
+                match item {
+                    Value::Number(n) => total += n,
+                    other => {
+                        return Err(GentError::TypeError {
+                            expected: "Array of Numbers for sum()".to_string(),
+                            got: other.type_name(),
+                            span: Span::default(),
+                        })
+                    }
+                }
+            }
+            Ok(Value::Number(total))
+        }
+
+        "min" | "max" => {
+            let mut best: Option<&Value> = None;
+            for item in arr.iter() {
+                let better = match best {
+                    None => true,
+                    Some(current) => {
+                        let ordering = compare_values(item, current, method)?;
+                        if method == "min" {
+                            ordering == Ordering::Less
+                        } else {
+                            ordering == Ordering::Greater
+                        }
+                    }
+                };
+                if better {
+                    best = Some(item);
+                }
+            }
+            Ok(best.cloned().unwrap_or(Value::Null))
+        }
+
         _ => Err(GentError::UndefinedProperty {
             property: method.to_string(),
             type_name: "Array".to_string(),
@@ -92,6 +234,31 @@ fn values_equal(a: &Value, b: &Value) -> bool {
     }
 }
 
+/// Order two numbers or two strings, for sorting and min/max
+fn compare_values(a: &Value, b: &Value, method: &str) -> GentResult<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => Ok(x.partial_cmp(y).unwrap_or(Ordering::Equal)),
+        (Value::String(x), Value::String(y)) => Ok(x.cmp(y)),
+        _ => Err(GentError::TypeError {
+            expected: format!("Numbers or Strings of one type for {}()", method),
+            got: format!("{} and {}", a.type_name(), b.type_name()),
+            span: Span::default(),
+        }),
+    }
+}
+
+/// Splice nested arrays into their parent, `depth` levels deep
+fn flatten(arr: &[Value], depth: usize) -> Vec<Value> {
+    let mut result = Vec::new();
+    for item in arr {
+        match item {
+            Value::Array(inner) if depth > 0 => result.extend(flatten(inner, depth - 1)),
+            other => result.push(other.clone()),
+        }
+    }
+    result
+}
+
 /// Helper function to extract a string argument from the argument list
 fn get_string_arg(args: &[Value], index: usize, method: &str) -> GentResult<String> {
     args.get(index)
@@ -153,12 +320,34 @@ fn get_array_arg(args: &[Value], index: usize, method: &str) -> GentResult<Vec<V
 }
 
 // ============================================
-// Higher-order array methods (map, filter, reduce, find)
+// Higher-order array methods (map, filter, reduce, find, ...)
 // ============================================
 
 /// Check if method requires a callback (for dispatch routing)
 pub fn is_callback_method(method: &str) -> bool {
-    matches!(method, "map" | "filter" | "reduce" | "find")
+    matches!(
+        method,
+        "map"
+            | "filter"
+            | "reduce"
+            | "find"
+            | "findIndex"
+            | "some"
+            | "every"
+            | "forEach"
+            | "flatMap"
+            | "sortBy"
+            | "groupBy"
+    )
+}
+
+/// Check if a call goes to `call_array_method_with_callback`
+///
+/// Like `is_callback_method`, but also routes `sort` there when it is given
+/// a comparator.
+pub fn is_callback_call(method: &str, args: &[Value]) -> bool {
+    is_callback_method(method)
+        || (method == "sort" && matches!(args.first(), Some(Value::Lambda(_) | Value::Function(_))))
 }
 
 /// Call a higher-order array method that takes a lambda/function callback
@@ -175,7 +364,8 @@ pub fn call_array_method_with_callback<'a>(
             "map" => {
                 let mut results = Vec::new();
                 for item in arr {
-                    let result = apply_callback(callback, &[item.clone()], env, tools).await?;
+                    let result =
+                        apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
                     results.push(result);
                 }
                 Ok(Value::Array(results))
@@ -184,7 +374,8 @@ pub fn call_array_method_with_callback<'a>(
             "filter" => {
                 let mut results = Vec::new();
                 for item in arr {
-                    let result = apply_callback(callback, &[item.clone()], env, tools).await?;
+                    let result =
+                        apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
                     if result.is_truthy() {
                         results.push(item.clone());
                     }
@@ -228,7 +419,8 @@ pub fn call_array_method_with_callback<'a>(
 
             "find" => {
                 for item in arr {
-                    let result = apply_callback(callback, &[item.clone()], env, tools).await?;
+                    let result =
+                        apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
                     if result.is_truthy() {
                         return Ok(item.clone());
                     }
@@ -236,6 +428,125 @@ pub fn call_array_method_with_callback<'a>(
                 Ok(Value::Null)
             }
 
+            "findIndex" => {
+                for (i, item) in arr.iter().enumerate() {
+                    let result =
+                        apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
+                    if result.is_truthy() {
+                        return Ok(Value::Number(i as f64));
+                    }
+                }
+                Ok(Value::Number(-1.0))
+            }
+
+            "some" | "every" => {
+                let want = method == "some";
+                for item in arr {
+                    let result =
+                        apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
+                    if result.is_truthy() == want {
+                        return Ok(Value::Boolean(want));
+                    }
+                }
+                Ok(Value::Boolean(!want))
+            }
+
+            "forEach" => {
+                for item in arr {
+                    apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
+                }
+                Ok(Value::Null)
+            }
+
+            "flatMap" => {
+                let mut results = Vec::new();
+                for item in arr {
+                    match apply_callback(callback, std::slice::from_ref(item), env, tools).await? {
+                        Value::Array(items) => results.extend(items),
+                        other => results.push(other),
+                    }
+                }
+                Ok(Value::Array(results))
+            }
+
+            "sortBy" => {
+                let mut keyed = Vec::new();
+                for item in arr {
+                    let key =
+                        apply_callback(callback, std::slice::from_ref(item), env, tools).await?;
+                    keyed.push((key, item.clone()));
+                }
+                let mut error = None;
+                keyed.sort_by(|(a, _), (b, _)| {
+                    compare_values(a, b, "sortBy").unwrap_or_else(|e| {
+                        error.get_or_insert(e);
+                        Ordering::Equal
+                    })
+                });
+                match error {
+                    Some(e) => Err(e),
+                    None => Ok(Value::Array(keyed.into_iter().map(|(_, item)| item).collect())),
+                }
+            }
+
+            "sort" => {
+                // Merge sort, as the comparator has to be awaited
+                let mut items = arr.to_vec();
+                let mut width = 1;
+                while width < items.len() {
+                    let mut merged = Vec::with_capacity(items.len());
+                    for pair in items.chunks(width * 2) {
+                        let (mut left, mut right) = if pair.len() > width {
+                            (pair[..width].iter().peekable(), pair[width..].iter().peekable())
+                        } else {
+                            (pair.iter().peekable(), [].iter().peekable())
+                        };
+                        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+                            let args = [(*a).clone(), (*b).clone()];
+                            let ordering = apply_callback(callback, &args, env, tools).await?;
+                            let b_first = match ordering {
+                                Value::Number(n) => n > 0.0,
+                                other => {
+                                    return Err(GentError::TypeError {
+                                        expected: "Number from sort() comparator".to_string(),
+                                        got: other.type_name(),
+                                        span: Span::default(),
+                                    })
+                                }
+                            };
+                            if b_first {
+                                merged.extend(right.next().cloned());
+                            } else {
+                                merged.extend(left.next().cloned());
+                            }
+                        }
+                        merged.extend(left.cloned());
+                        merged.extend(right.cloned());
+                    }
+                    items = merged;
+                    width *= 2;
+                }
+                Ok(Value::Array(items))
+            }
+
+            "groupBy" => {
+                let mut groups: BTreeMap<String, Value> = BTreeMap::new();
+                for item in arr {
+                    let key = match apply_callback(callback, std::slice::from_ref(item), env, tools)
+                        .await?
+                    {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    if let Value::Array(group) =
+                        groups.entry(key).or_insert_with(|| Value::Array(Vec::new()))
+                    {
+                        group.push(item.clone());
+                    }
+                }
+                Ok(Value::Object(groups))
+            }
+
             _ => Err(GentError::UndefinedProperty {
                 property: method.to_string(),
                 type_name: "Array".to_string(),
@@ -264,7 +575,10 @@ fn apply_callback<'a>(
 
                 let result = match &lambda.body {
                     LambdaBody::Expression(expr) => {
-                        crate::interpreter::expr_eval::evaluate_expr(expr, &lambda_env)?
+                        // Async evaluation, so the body may call methods such as `s.length()`
+                        let ctx = crate::interpreter::block_eval::BlockEvalContext::empty();
+                        crate::interpreter::block_eval::evaluate_expr_async(expr, &mut lambda_env, tools, &ctx)
+                            .await?
                     }
                     LambdaBody::Block(block) => {
                         crate::interpreter::block_eval::evaluate_block(block, &mut lambda_env, tools).await?
@@ -276,16 +590,29 @@ fn apply_callback<'a>(
 
             Value::Function(fn_val) => {
                 let mut fn_env = env.clone();
+                fn_env.set_file(fn_val.source_file.clone());
                 fn_env.push_scope();
 
                 for (param, arg) in fn_val.params.iter().zip(args.iter()) {
                     fn_env.define(&param.name, arg.clone());
                 }
 
-                let result = crate::interpreter::block_eval::evaluate_block(&fn_val.body, &mut fn_env, tools).await?;
+                let result = crate::interpreter::block_eval::evaluate_block(&fn_val.body, &mut fn_env, tools)
+                    .await
+                    .map_err(|e| {
+                        if fn_val.source_file.as_deref() != env.file() {
+                            e.in_file(fn_val.source_file.clone())
+                        } else {
+                            e
+                        }
+                    })?;
                 Ok(result)
             }
 
+            Value::StdFunction(function) => {
+                crate::interpreter::stdlib::call_std_function(function, args, &Span::default(), env.std_state()).await
+            }
+
             _ => Err(GentError::TypeError {
                 expected: "function or lambda".to_string(),
                 got: callback.type_name().to_string(),
@@ -3,18 +3,26 @@
 //! This module provides async block evaluation for executing tool bodies
 //! with let bindings, return statements, if/else, and expression statements.
 
-use crate::errors::{GentError, GentResult};
-use crate::interpreter::builtins::{call_builtin, is_builtin};
+use crate::errors::{GentError, GentResult, Span, StackFrame};
+use crate::interpreter::builtins::{call_builtin, is_builtin, BUILTINS};
+use crate::interpreter::conversation_methods::{call_conversation_method, CONVERSATION};
 use crate::interpreter::expr_eval::evaluate_expr;
-use crate::interpreter::array_methods::{call_array_method, call_array_method_with_callback, is_callback_method};
+use crate::interpreter::json::{
+    call_from_json, call_to_json, json_to_value, type_name_arg, value_to_json, FROM_JSON, TO_JSON,
+};
+use crate::interpreter::array_methods::{call_array_method, call_array_method_with_callback, is_callback_call};
+use crate::interpreter::object_methods::call_object_method;
+use crate::interpreter::place::{call_in_place, is_mutating_method, receiver_path, PathKey, PathSegment};
+use crate::interpreter::native::call_native_function;
+use crate::interpreter::stdlib::call_std_function;
 use crate::interpreter::string_methods::call_string_method;
-use crate::interpreter::types::EnumValue;
-use crate::interpreter::{parse_index_options, Environment, Value};
+use crate::interpreter::types::{EnumValue, ErrorValue};
+use crate::interpreter::{parse_index_options, search_results_to_value, Environment, FnValue, Value};
 use crate::logging::{Logger, NullLogger};
-use crate::parser::ast::{Block, BlockStmt, Expression, MatchBody, MatchPattern};
-use crate::runtime::tools::ToolRegistry;
-use crate::runtime::{run_agent_with_tools, ProviderFactory};
-use std::collections::HashMap;
+use crate::parser::ast::{Block, BlockStmt, Expression, MatchBody, MatchPattern, TypeName};
+use crate::runtime::tools::{ToolContext, ToolRegistry};
+use crate::runtime::{run_agent_turn_with_handoffs, Conversation, ProviderFactory};
+use std::sync::Arc;
 
 /// Context for block evaluation that includes optional provider factory for agent execution
 pub struct BlockEvalContext<'a> {
@@ -115,6 +123,30 @@ pub fn evaluate_block_with_provider_factory<'a>(
     })
 }
 
+/// Evaluate a block in the current scope, with provider factory support
+///
+/// Unlike `evaluate_block_with_provider_factory`, no scope is pushed, so the
+/// bindings made by `let` are still defined afterwards. The REPL uses this to
+/// continue each input where the previous one left off.
+pub fn evaluate_block_in_scope<'a>(
+    block: &'a Block,
+    env: &'a mut Environment,
+    tools: &'a ToolRegistry,
+    provider_factory: &'a ProviderFactory,
+    logger: &'a dyn Logger,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = GentResult<Value>> + 'a>> {
+    Box::pin(async move {
+        let ctx = BlockEvalContext::with_provider_factory(provider_factory, logger);
+        let (flow, result) = evaluate_block_internal(block, env, tools, &ctx).await?;
+
+        match flow {
+            ControlFlow::Return(val) => Ok(*val),
+            ControlFlow::Continue => Ok(result),
+            ControlFlow::Break | ControlFlow::LoopContinue => Ok(result),
+        }
+    })
+}
+
 /// Evaluate a block with an existing context
 ///
 /// This is used internally when calling functions to preserve the LLM context.
@@ -155,35 +187,7 @@ fn evaluate_block_internal<'a>(
         for stmt in &block.statements {
             match stmt {
                 BlockStmt::Let(let_stmt) => {
-                    // Check if the value is a mutating array method call (push/pop)
-                    let value = if let Some((arr_var, method_name, args)) = extract_array_method_call(&let_stmt.value) {
-                        if method_name == "push" || method_name == "pop" {
-                            if let Some(Value::Array(arr)) = env.get(&arr_var).cloned() {
-                                let mut arr_mut = arr;
-
-                                // Evaluate arguments
-                                let mut arg_values = Vec::new();
-                                for arg in args {
-                                    let val = evaluate_expr_async(arg, env, tools, ctx).await?;
-                                    arg_values.push(val);
-                                }
-
-                                // Call the method and get result
-                                let result = call_array_method(&mut arr_mut, &method_name, &arg_values)?;
-
-                                // Update the array variable with the mutated array
-                                env.set(&arr_var, Value::Array(arr_mut));
-
-                                result
-                            } else {
-                                evaluate_expr_async(&let_stmt.value, env, tools, ctx).await?
-                            }
-                        } else {
-                            evaluate_expr_async(&let_stmt.value, env, tools, ctx).await?
-                        }
-                    } else {
-                        evaluate_expr_async(&let_stmt.value, env, tools, ctx).await?
-                    };
+                    let value = evaluate_expr_async(&let_stmt.value, env, tools, ctx).await?;
 
                     // Define the variable in the current scope
                     env.define(&let_stmt.name, value);
@@ -198,7 +202,8 @@ fn evaluate_block_internal<'a>(
                         return Err(GentError::SyntaxError {
                             message: format!("Undefined variable: '{}'", assign_stmt.name),
                             span: assign_stmt.span.clone(),
-                        });
+                        }
+                        .with_suggestion(&assign_stmt.name, env.names()));
                     }
                 }
 
@@ -212,6 +217,11 @@ fn evaluate_block_internal<'a>(
                     return Ok((ControlFlow::Return(Box::new(result)), Value::Null));
                 }
 
+                BlockStmt::Throw(throw_stmt) => {
+                    let value = evaluate_expr_async(&throw_stmt.value, env, tools, ctx).await?;
+                    return Err(thrown_error(value, &throw_stmt.span));
+                }
+
                 BlockStmt::If(if_stmt) => {
                     // Evaluate the condition
                     let condition = evaluate_expr_async(&if_stmt.condition, env, tools, ctx).await?;
@@ -246,13 +256,31 @@ fn evaluate_block_internal<'a>(
                     // Evaluate the iterable expression
                     let iterable = evaluate_expr(&for_stmt.iterable, env)?;
 
-                    // Convert iterable to a list of items
-                    let items: Vec<Value> = match iterable {
-                        Value::Array(arr) => arr,
-                        Value::String(s) => s.chars().map(|c| Value::String(c.to_string())).collect(),
+                    // Convert iterable to a list of (item, value) bindings. `for x in obj`
+                    // iterates over keys; `for (k, v) in ...` binds keys or indices and values
+                    let items: Vec<(Value, Value)> = match iterable {
+                        Value::Array(arr) if for_stmt.value_variable.is_some() => arr
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, v)| (Value::Number(i as f64), v))
+                            .collect(),
+                        Value::Array(arr) => arr.into_iter().map(|v| (v, Value::Null)).collect(),
+                        Value::String(s) if for_stmt.value_variable.is_none() => s
+                            .chars()
+                            .map(|c| (Value::String(c.to_string()), Value::Null))
+                            .collect(),
+                        Value::Object(map) => map
+                            .into_iter()
+                            .map(|(k, v)| (Value::String(k), v))
+                            .collect(),
                         other => {
+                            let expected = if for_stmt.value_variable.is_some() {
+                                "Object or Array"
+                            } else {
+                                "Array, String or Object"
+                            };
                             return Err(GentError::TypeError {
-                                expected: "Array or String".to_string(),
+                                expected: expected.to_string(),
                                 got: other.type_name().to_string(),
                                 span: for_stmt.span.clone(),
                             });
@@ -260,9 +288,12 @@ fn evaluate_block_internal<'a>(
                     };
 
                     // Iterate over items
-                    'outer: for item in items {
+                    'outer: for (item, value) in items {
                         env.push_scope();
                         env.define(&for_stmt.variable, item);
+                        if let Some(value_variable) = &for_stmt.value_variable {
+                            env.define(value_variable, value);
+                        }
 
                         // Execute the loop body using internal evaluation
                         let (flow, _) = evaluate_block_internal(&for_stmt.body, env, tools, ctx).await?;
@@ -291,30 +322,6 @@ fn evaluate_block_internal<'a>(
                 }
 
                 BlockStmt::Expr(expr) => {
-                    // Check for mutating array method calls (push/pop) and handle specially
-                    if let Some((var_name, method_name, args)) = extract_array_method_call(expr) {
-                        if method_name == "push" || method_name == "pop" {
-                            // Get the current array value
-                            if let Some(Value::Array(arr)) = env.get(&var_name).cloned() {
-                                let mut arr_mut = arr;
-
-                                // Evaluate arguments
-                                let mut arg_values = Vec::new();
-                                for arg in args {
-                                    let val = evaluate_expr_async(arg, env, tools, ctx).await?;
-                                    arg_values.push(val);
-                                }
-
-                                // Call the method
-                                call_array_method(&mut arr_mut, &method_name, &arg_values)?;
-
-                                // Update the variable with the mutated array
-                                env.set(&var_name, Value::Array(arr_mut));
-                            }
-                            continue;
-                        }
-                    }
-
                     // Evaluate the expression for side effects, discarding the result
                     evaluate_expr_async(expr, env, tools, ctx).await?;
                 }
@@ -380,54 +387,46 @@ fn evaluate_block_internal<'a>(
                 }
 
                 BlockStmt::Try(try_stmt) => {
-                    // Execute try block and capture result
+                    // Execute try block and capture result. Scopes are unwound to
+                    // the saved depth since an error can leave nested scopes open.
+                    let depth = env.depth();
                     env.push_scope();
                     let try_result = evaluate_block_internal(&try_stmt.try_block, env, tools, ctx).await;
-                    env.pop_scope();
-
-                    match try_result {
-                        Ok((flow, _value)) => {
-                            // Try block succeeded
-                            match flow {
-                                ControlFlow::Return(val) => {
-                                    return Ok((ControlFlow::Return(val), Value::Null));
-                                }
-                                ControlFlow::Break => {
-                                    return Ok((ControlFlow::Break, Value::Null));
-                                }
-                                ControlFlow::LoopContinue => {
-                                    return Ok((ControlFlow::LoopContinue, Value::Null));
-                                }
-                                ControlFlow::Continue => {
-                                    // Normal completion, continue with next statement after try/catch
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            // Error occurred, execute catch block with error bound
-                            env.push_scope();
-                            env.define(&try_stmt.error_var, Value::String(e.to_string()));
+                    unwind_scopes(env, depth);
 
+                    // Run the catch block if the error matches the catch filter,
+                    // otherwise keep the error to propagate after `finally`
+                    let outcome = match try_result {
+                        Err(e) if catch_matches(try_stmt.error_type.as_deref(), &e) => {
+                            env.push_scope();
+                            env.define(&try_stmt.error_var, Value::Error(ErrorValue::from_error(&e)));
                             let catch_result =
-                                evaluate_block_internal(&try_stmt.catch_block, env, tools, ctx).await?;
-                            env.pop_scope();
+                                evaluate_block_internal(&try_stmt.catch_block, env, tools, ctx).await;
+                            unwind_scopes(env, depth);
+                            catch_result
+                        }
+                        other => other,
+                    };
 
-                            match catch_result.0 {
-                                ControlFlow::Return(val) => {
-                                    return Ok((ControlFlow::Return(val), Value::Null));
-                                }
-                                ControlFlow::Break => {
-                                    return Ok((ControlFlow::Break, Value::Null));
-                                }
-                                ControlFlow::LoopContinue => {
-                                    return Ok((ControlFlow::LoopContinue, Value::Null));
-                                }
-                                ControlFlow::Continue => {
-                                    // Normal completion, continue with next statement after try/catch
-                                }
-                            }
+                    if let Some(ref finally_block) = try_stmt.finally_block {
+                        env.push_scope();
+                        let finally_result =
+                            evaluate_block_internal(finally_block, env, tools, ctx).await;
+                        unwind_scopes(env, depth);
+                        let (flow, _) = finally_result?;
+
+                        // Control flow in finally overrides the try/catch outcome
+                        if flow != ControlFlow::Continue {
+                            return Ok((flow, Value::Null));
                         }
                     }
+
+                    match outcome?.0 {
+                        ControlFlow::Continue => {
+                            // Normal completion, continue with next statement after try/catch
+                        }
+                        other => return Ok((other, Value::Null)),
+                    }
                 }
             }
         }
@@ -442,7 +441,7 @@ fn evaluate_block_internal<'a>(
 /// The `ctx` parameter provides optional LLM client for agent execution.
 pub fn evaluate_expr_async<'a>(
     expr: &'a Expression,
-    env: &'a Environment,
+    env: &'a mut Environment,
     tools: &'a ToolRegistry,
     ctx: &'a BlockEvalContext<'a>,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = GentResult<Value>> + 'a>> {
@@ -454,7 +453,7 @@ pub fn evaluate_expr_async<'a>(
                 if let Expression::Member(obj_expr, method_name, _) = callee_expr.as_ref() {
                     // First check if this could be an enum constructor call: EnumName.Variant(args)
                     if let Expression::Identifier(name, _) = obj_expr.as_ref() {
-                        if let Some(enum_def) = env.get_enum(name) {
+                        if let Some(enum_def) = env.get_enum(name).cloned() {
                             // Find the variant
                             if let Some(v) = enum_def.variants.iter().find(|v| v.name == *method_name) {
                                 // Evaluate arguments
@@ -490,9 +489,38 @@ pub fn evaluate_expr_async<'a>(
                         }
                     }
 
+                    // In-place methods (push, pop) update the variable they are called on
+                    if is_mutating_method(method_name) {
+                        if let Some((root, segments)) = receiver_path(obj_expr).filter(|(root, _)| env.contains(root)) {
+                            let mut path = Vec::new();
+                            for segment in segments {
+                                path.push(match segment {
+                                    PathSegment::Field(field, span) => PathKey::Field(field.to_string(), span.clone()),
+                                    PathSegment::Index(index, span) => {
+                                        PathKey::Index(evaluate_expr_async(index, env, tools, ctx).await?, span.clone())
+                                    }
+                                });
+                            }
+                            let mut arg_values = Vec::new();
+                            for arg in args {
+                                arg_values.push(evaluate_expr_async(arg, env, tools, ctx).await?);
+                            }
+                            return call_in_place(env, root, &path, method_name, &arg_values, span);
+                        }
+                    }
+
                     // Evaluate the object expression
                     let obj = evaluate_expr_async(obj_expr, env, tools, ctx).await?;
 
+                    // Any value can be serialized with .toJson()
+                    if method_name == TO_JSON {
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            arg_values.push(evaluate_expr_async(arg, env, tools, ctx).await?);
+                        }
+                        return call_to_json(&obj, &arg_values, span);
+                    }
+
                     // If it's a string, dispatch to string methods
                     if let Value::String(s) = &obj {
                         // Evaluate method arguments
@@ -574,8 +602,8 @@ pub fn evaluate_expr_async<'a>(
                             arg_values.push(val);
                         }
 
-                        // Check if this is a callback method (map, filter, reduce, find)
-                        if is_callback_method(method_name) {
+                        // Check if this is a callback method (map, filter, reduce, find, ...)
+                        if is_callback_call(method_name, &arg_values) {
                             let callback = arg_values.first().ok_or_else(|| GentError::TypeError {
                                 expected: "callback function for array method".to_string(),
                                 got: "missing argument".to_string(),
@@ -589,7 +617,9 @@ pub fn evaluate_expr_async<'a>(
                                 extra_args,
                                 env,
                                 tools,
-                            ).await;
+                            )
+                            .await
+                            .map_err(|e| e.with_frame(call_frame(callback_name(callback), span, env)));
                         }
 
                         // Non-callback methods
@@ -603,6 +633,16 @@ pub fn evaluate_expr_async<'a>(
                         return Ok(result);
                     }
 
+                    // If it's an object, dispatch to object methods
+                    if let Value::Object(ref map) = obj {
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            let val = evaluate_expr_async(arg, env, tools, ctx).await?;
+                            arg_values.push(val);
+                        }
+                        return call_object_method(map, method_name, &arg_values);
+                    }
+
                     // Handle Agent method calls (userPrompt, systemPrompt, run)
                     if let Value::Agent(mut agent) = obj {
                         match method_name.as_str() {
@@ -641,14 +681,17 @@ pub fn evaluate_expr_async<'a>(
                             "run" => {
                                 // Execute the agent - requires provider factory
                                 if let Some(provider_factory) = ctx.provider_factory {
-                                    let result = run_agent_with_tools(&agent, None, provider_factory, tools, ctx.logger).await?;
-                                    // If agent has structured output, parse as JSON
-                                    if agent.output_schema.is_some() {
-                                        if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&result) {
-                                            return Ok(json_to_value(&json_val));
+                                    let result = run_agent_turn_with_handoffs(&agent, None, &mut Vec::new(), provider_factory, tools, ctx.logger, Some(env))
+                                        .await
+                                        .map_err(|e| agent_error(e, &agent.name, span, env))?;
+                                    // If the answering agent answers with JSON, parse it
+                                    let mut output = Value::String(result.output.clone());
+                                    if result.agent.returns_json() {
+                                        if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&result.output) {
+                                            output = json_to_value(&json_val);
                                         }
                                     }
-                                    return Ok(Value::String(result));
+                                    return Ok(output);
                                 } else {
                                     return Err(GentError::SyntaxError {
                                         message: "Cannot call .run() on agent in this context (no provider factory available)".to_string(),
@@ -656,6 +699,10 @@ pub fn evaluate_expr_async<'a>(
                                     });
                                 }
                             }
+                            CONVERSATION => {
+                                let conversation = Conversation::new(*agent);
+                                return Ok(Value::Conversation(Arc::new(tokio::sync::Mutex::new(conversation))));
+                            }
                             _ => {
                                 return Err(GentError::SyntaxError {
                                     message: format!("Unknown agent method: {}", method_name),
@@ -722,18 +769,7 @@ pub fn evaluate_expr_async<'a>(
                                 let results = kb.search(&query, limit).await
                                     .map_err(|e| GentError::SyntaxError { message: e, span: span.clone() })?;
 
-                                // Convert to array of objects
-                                let result_values: Vec<Value> = results.iter().map(|r| {
-                                    let mut map = std::collections::HashMap::new();
-                                    map.insert("source".to_string(), Value::String(r.metadata.source.clone()));
-                                    map.insert("score".to_string(), Value::Number(r.score as f64));
-                                    map.insert("content".to_string(), Value::String(r.metadata.content.clone()));
-                                    map.insert("startLine".to_string(), Value::Number(r.metadata.start_line as f64));
-                                    map.insert("endLine".to_string(), Value::Number(r.metadata.end_line as f64));
-                                    Value::Object(map)
-                                }).collect();
-
-                                return Ok(Value::Array(result_values));
+                                return Ok(search_results_to_value(&results));
                             }
                             "isIndexed" => {
                                 let kb = kb.read().await;
@@ -748,9 +784,34 @@ pub fn evaluate_expr_async<'a>(
                         }
                     }
 
+                    // Handle Conversation method calls (send, history, save, load)
+                    if let Value::Conversation(conversation) = obj {
+                        let Some(provider_factory) = ctx.provider_factory else {
+                            return Err(GentError::SyntaxError {
+                                message: format!("Cannot call .{}() on a conversation in this context (no provider factory available)", method_name),
+                                span: span.clone(),
+                            });
+                        };
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            arg_values.push(evaluate_expr_async(arg, env, tools, ctx).await?);
+                        }
+                        return call_conversation_method(
+                            &conversation,
+                            method_name,
+                            &arg_values,
+                            provider_factory,
+                            tools,
+                            ctx.logger,
+                            env,
+                            span,
+                        )
+                        .await;
+                    }
+
                     // For other types, return an error for now
                     return Err(GentError::TypeError {
-                        expected: "String, Array, Agent, or KnowledgeBase".to_string(),
+                        expected: "String, Array, Object, Agent, KnowledgeBase or Conversation".to_string(),
                         got: obj.type_name().to_string(),
                         span: span.clone(),
                     });
@@ -771,15 +832,31 @@ pub fn evaluate_expr_async<'a>(
                 // Evaluate arguments first (needed for both functions and tools)
                 let mut arg_values = Vec::new();
                 for arg in args {
+                    // `fromJson(text, Person)` names a struct or enum, which is not a value
+                    if callable_name == FROM_JSON {
+                        if let Some(name) = type_name_arg(arg, env) {
+                            arg_values.push(name);
+                            continue;
+                        }
+                    }
                     let val = evaluate_expr_async(arg, env, tools, ctx).await?;
                     arg_values.push(val);
                 }
 
+                if callable_name == FROM_JSON && !env.contains(FROM_JSON) {
+                    return call_from_json(&arg_values, env, span);
+                }
+
                 // Check if it's a built-in function
                 if is_builtin(&callable_name) {
                     return call_builtin(&callable_name, &arg_values, span);
                 }
 
+                // Check if it's an imported standard library function
+                if let Some(Value::StdFunction(function)) = env.get(&callable_name) {
+                    return call_std_function(function, &arg_values, span, env.std_state()).await;
+                }
+
                 // Check if it's a function in the environment
                 if let Some(Value::Function(fn_val)) = env.get(&callable_name) {
                     // Clone the function value since we need to borrow env mutably later
@@ -800,6 +877,7 @@ pub fn evaluate_expr_async<'a>(
 
                     // Create a new environment with function scope
                     let mut fn_env = env.clone();
+                    fn_env.set_file(fn_val.source_file.clone());
                     fn_env.push_scope();
 
                     // Bind parameters to arguments
@@ -807,34 +885,85 @@ pub fn evaluate_expr_async<'a>(
                         fn_env.define(&param.name, arg_val.clone());
                     }
 
-                    // Evaluate the function body
-                    let result = evaluate_block_with_ctx(&fn_val.body, &mut fn_env, tools, ctx).await?;
+                    // Evaluate the function body, recording this call if it fails
+                    let result = evaluate_block_with_ctx(&fn_val.body, &mut fn_env, tools, ctx)
+                        .await
+                        .map_err(|e| function_error(e, &fn_val, span, env))?;
                     return Ok(result);
                 }
 
+                // Check if it's a native function registered by the host
+                if let Some(function) = tools.function(&callable_name) {
+                    return call_native_function(function.as_ref(), arg_values, span).await;
+                }
+
                 // Look up the tool in the registry
                 let tool = tools
                     .get(&callable_name)
-                    .ok_or_else(|| GentError::UnknownTool {
-                        name: callable_name.clone(),
-                        span: span.clone(),
+                    .ok_or_else(|| {
+                        GentError::UnknownTool {
+                            name: callable_name.clone(),
+                            span: span.clone(),
+                        }
+                        .with_suggestion(
+                            &callable_name,
+                            BUILTINS
+                                .iter()
+                                .copied()
+                                .chain(tools.function_names())
+                                .chain(tools.names())
+                                .chain(env.names()),
+                        )
                     })?;
 
-                // Convert arguments to JSON for tool execution
-                let json_args = args_to_json(&arg_values);
+                // Convert arguments to JSON for tool execution; user tools
+                // take them by parameter name, and trailing ones may be left out
+                let json_args = match env.get(&callable_name) {
+                    Some(Value::Tool(declared)) if arg_values.len() <= declared.params.len() => {
+                        serde_json::Value::Object(
+                            declared
+                                .params
+                                .iter()
+                                .map(|param| param.name.clone())
+                                .zip(arg_values.iter().map(value_to_json))
+                                .collect(),
+                        )
+                    }
+                    _ => args_to_json(&arg_values),
+                };
 
-                // Execute the tool
-                let result = tool
-                    .execute(json_args)
-                    .await
-                    .map_err(|e| GentError::ToolError {
-                        tool: callable_name.clone(),
-                        message: e,
+                // Execute the tool, with access to agents when they can run
+                let result = match ctx.provider_factory {
+                    Some(provider_factory) => {
+                        let tool_ctx = ToolContext {
+                            agent: None,
+                            provider_factory,
+                            tools,
+                            logger: ctx.logger,
+                            env: Some(env),
+                        };
+                        tool.execute_with_context(json_args, &tool_ctx).await
+                    }
+                    None => tool.execute(json_args).await,
+                };
+                let result = result
+                    .map_err(|e| {
+                        GentError::ToolError {
+                            tool: callable_name.clone(),
+                            message: e,
+                        }
+                        .with_frame(call_frame(&callable_name, span, env))
                     })?;
 
-                // For now, return the result as a string
-                // TODO: Parse JSON results in the future
-                Ok(Value::String(result))
+                // User tools with a structured return type send JSON back
+                let structured = matches!(
+                    env.get(&callable_name),
+                    Some(Value::Tool(declared)) if declared.return_type.as_ref().is_some_and(is_structured)
+                );
+                match serde_json::from_str(&result) {
+                    Ok(json) if structured => Ok(json_to_value(&json)),
+                    _ => Ok(Value::String(result)),
+                }
             }
 
             // Match expression
@@ -843,24 +972,22 @@ pub fn evaluate_expr_async<'a>(
 
                 for arm in &match_expr.arms {
                     if let Some(bindings) = match_pattern(&subject, &arm.pattern) {
-                        // Create new scope with bindings
-                        let mut match_env = env.clone();
-                        match_env.push_scope();
+                        // Create new scope with bindings (in place, so the arm
+                        // body can update variables from the enclosing scope)
+                        let depth = env.depth();
+                        env.push_scope();
                         for (name, value) in bindings {
-                            match_env.define(&name, value);
+                            env.define(&name, value);
                         }
 
                         // Evaluate arm body
                         let result = match &arm.body {
-                            MatchBody::Expression(expr) => {
-                                evaluate_expr_async(expr, &match_env, tools, ctx).await?
-                            }
-                            MatchBody::Block(block) => {
-                                evaluate_block_with_ctx(block, &mut match_env, tools, ctx).await?
-                            }
+                            MatchBody::Expression(expr) => evaluate_expr_async(expr, env, tools, ctx).await,
+                            MatchBody::Block(block) => evaluate_block_with_ctx(block, env, tools, ctx).await,
                         };
+                        unwind_scopes(env, depth);
 
-                        return Ok(result);
+                        return result;
                     }
                 }
 
@@ -895,7 +1022,7 @@ pub fn evaluate_expr_async<'a>(
 
             // Object literals - need async for values that might contain calls
             Expression::Object(fields, _) => {
-                let mut map = std::collections::HashMap::new();
+                let mut map = std::collections::BTreeMap::new();
                 for (key, value_expr) in fields {
                     let value = evaluate_expr_async(value_expr, env, tools, ctx).await?;
                     map.insert(key.clone(), value);
@@ -963,6 +1090,14 @@ pub fn evaluate_expr_async<'a>(
                                 span: span.clone(),
                             })
                     }
+                    Value::Error(ref err) => {
+                        err.get_field(property)
+                            .ok_or_else(|| GentError::UndefinedProperty {
+                                property: property.clone(),
+                                type_name: "Error".to_string(),
+                                span: span.clone(),
+                            })
+                    }
                     _ => Err(GentError::UndefinedProperty {
                         property: property.clone(),
                         type_name: object.type_name().to_string(),
@@ -1026,52 +1161,7 @@ pub fn evaluate_expr_async<'a>(
 
 /// Convert a vector of Values to a JSON value for tool execution
 fn args_to_json(args: &[Value]) -> serde_json::Value {
-    use serde_json::{json, Map, Value as JsonValue};
-
-    fn value_to_json(val: &Value) -> JsonValue {
-        match val {
-            Value::String(s) => JsonValue::String(s.clone()),
-            Value::Number(n) => json!(n),
-            Value::Boolean(b) => JsonValue::Bool(*b),
-            Value::Null => JsonValue::Null,
-            Value::Array(items) => {
-                let json_items: Vec<JsonValue> = items.iter().map(value_to_json).collect();
-                JsonValue::Array(json_items)
-            }
-            Value::Object(map) => {
-                let mut json_map = Map::new();
-                for (k, v) in map {
-                    json_map.insert(k.clone(), value_to_json(v));
-                }
-                JsonValue::Object(json_map)
-            }
-            Value::Agent(_) => JsonValue::String("<agent>".to_string()),
-            Value::Tool(_) => JsonValue::String("<tool>".to_string()),
-            Value::Function(_) => JsonValue::String("<function>".to_string()),
-            Value::Lambda(_) => JsonValue::String("<lambda>".to_string()),
-            Value::Enum(e) => {
-                if e.data.is_empty() {
-                    JsonValue::String(format!("{}.{}", e.enum_name, e.variant))
-                } else {
-                    let mut map = Map::new();
-                    map.insert("enum".to_string(), JsonValue::String(e.enum_name.clone()));
-                    map.insert(
-                        "variant".to_string(),
-                        JsonValue::String(e.variant.clone()),
-                    );
-                    let data: Vec<JsonValue> = e.data.iter().map(value_to_json).collect();
-                    map.insert("data".to_string(), JsonValue::Array(data));
-                    JsonValue::Object(map)
-                }
-            }
-            Value::EnumConstructor(c) => {
-                JsonValue::String(format!("<enum constructor {}.{}>", c.enum_name, c.variant))
-            }
-            Value::Parallel(p) => JsonValue::String(format!("<parallel {}>", p.name)),
-            Value::KnowledgeBase(_) => JsonValue::String("<KnowledgeBase>".to_string()),
-            Value::BuiltinTool(name) => JsonValue::String(format!("<builtin tool {}>", name)),
-        }
-    }
+    use serde_json::Value as JsonValue;
 
     // If there's a single object argument, use it directly
     // Otherwise, wrap arguments in an array
@@ -1085,17 +1175,96 @@ fn args_to_json(args: &[Value]) -> serde_json::Value {
     JsonValue::Array(args.iter().map(value_to_json).collect())
 }
 
-/// Extract variable name, method name, and arguments from a method call expression
-/// Returns Some((var_name, method_name, args)) if this is a method call on an identifier
-fn extract_array_method_call(expr: &Expression) -> Option<(String, String, &Vec<Expression>)> {
-    if let Expression::Call(callee_expr, args, _) = expr {
-        if let Expression::Member(obj_expr, method_name, _) = callee_expr.as_ref() {
-            if let Expression::Identifier(var_name, _) = obj_expr.as_ref() {
-                return Some((var_name.clone(), method_name.clone(), args));
+/// Whether a tool's return type is written as JSON (see `UserToolWrapper`)
+fn is_structured(type_name: &TypeName) -> bool {
+    match type_name {
+        TypeName::Object | TypeName::Array | TypeName::ArrayOf(_) | TypeName::Named(_) => true,
+        TypeName::Nullable(inner) => is_structured(inner),
+        _ => false,
+    }
+}
+
+/// Build the stack frame for a call made at `span` from code in `env`
+pub(crate) fn call_frame(function: &str, span: &Span, env: &Environment) -> StackFrame {
+    StackFrame::new(function, span.clone(), env.file().map(String::from))
+}
+
+/// Tag an error from the body of `function`, called at `span` from code in `env`
+///
+/// The error's span points into the function's file, which is recorded when
+/// it differs from the caller's.
+pub(crate) fn function_error(
+    error: GentError,
+    function: &FnValue,
+    span: &Span,
+    env: &Environment,
+) -> GentError {
+    let error = if function.source_file.as_deref() != env.file() {
+        error.in_file(function.source_file.clone())
+    } else {
+        error
+    };
+    error.with_frame(call_frame(&function.name, span, env))
+}
+
+/// Tag an error from an agent run with the agent's name and call site
+pub(crate) fn agent_error(error: GentError, agent: &str, span: &Span, env: &Environment) -> GentError {
+    GentError::AgentContext {
+        agent: agent.to_string(),
+        source: Box::new(error),
+    }
+    .with_frame(call_frame(&format!("{}.run", agent), span, env))
+}
+
+/// Name shown in stack traces for a callback passed to an array method
+fn callback_name(callback: &Value) -> &str {
+    match callback {
+        Value::Function(fn_val) => &fn_val.name,
+        _ => "<lambda>",
+    }
+}
+
+/// Build the error raised by `throw value`
+///
+/// Strings become errors of kind `Error`, caught errors are rethrown as-is and
+/// objects may supply their own `kind` and `message` fields.
+fn thrown_error(value: Value, span: &Span) -> GentError {
+    match value {
+        Value::Error(err) => err.to_error(span.clone()),
+        Value::Object(ref map) if map.contains_key("message") => {
+            let kind = match map.get("kind") {
+                Some(Value::String(k)) => k.clone(),
+                _ => "Error".to_string(),
+            };
+            GentError::Thrown {
+                kind,
+                message: map["message"].to_string(),
+                span: span.clone(),
             }
         }
+        other => GentError::Thrown {
+            kind: "Error".to_string(),
+            message: other.to_string(),
+            span: span.clone(),
+        },
+    }
+}
+
+/// Check whether a `catch (e: Kind)` filter accepts the error
+///
+/// A missing filter and the `Error` kind both catch everything.
+fn catch_matches(filter: Option<&str>, error: &GentError) -> bool {
+    match filter {
+        None | Some("Error") => true,
+        Some(kind) => error.kind() == kind,
+    }
+}
+
+/// Pop scopes until the environment is back at the given depth
+fn unwind_scopes(env: &mut Environment, depth: usize) {
+    while env.depth() > depth {
+        env.pop_scope();
     }
-    None
 }
 
 /// Match a value against a pattern, returning bindings if successful
@@ -1120,31 +1289,4 @@ fn match_pattern(value: &Value, pattern: &MatchPattern) -> Option<Vec<(String, V
     }
 }
 
-/// Convert a JSON value to a GENT Value
-fn json_to_value(json: &serde_json::Value) -> Value {
-    match json {
-        serde_json::Value::Null => Value::Null,
-        serde_json::Value::Bool(b) => Value::Boolean(*b),
-        serde_json::Value::Number(n) => {
-            if let Some(f) = n.as_f64() {
-                Value::Number(f)
-            } else {
-                Value::Null
-            }
-        }
-        serde_json::Value::String(s) => Value::String(s.clone()),
-        serde_json::Value::Array(arr) => {
-            let items = arr.iter().map(json_to_value).collect();
-            Value::Array(items)
-        }
-        serde_json::Value::Object(obj) => {
-            let mut map = HashMap::new();
-            for (k, v) in obj {
-                map.insert(k.clone(), json_to_value(v));
-            }
-            Value::Object(map)
-        }
-    }
-}
-
 // parse_index_options is now imported from crate::interpreter::parse_index_options
@@ -8,9 +8,12 @@ use crate::errors::{GentError, GentResult};
 use crate::interpreter::Value;
 use crate::Span;
 
+/// Names of the built-in functions
+pub const BUILTINS: &[&str] = &["print", "println"];
+
 /// Check if a function name is a built-in
 pub fn is_builtin(name: &str) -> bool {
-    matches!(name, "print" | "println")
+    BUILTINS.contains(&name)
 }
 
 /// Call a built-in function
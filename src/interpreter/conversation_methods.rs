@@ -0,0 +1,156 @@
+//! Conversation method implementations for GENT
+//!
+//! `Agent.conversation()` starts a conversation. Its methods send messages
+//! and read, save or restore the history; every message, including tool
+//! calls and their results, is sent again with the next one.
+
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::block_eval::call_frame;
+use crate::interpreter::json::json_to_value;
+use crate::interpreter::{Environment, Value};
+use crate::logging::Logger;
+use crate::runtime::{Conversation, ProviderFactory, ToolRegistry};
+
+/// Agent method that starts a conversation
+pub const CONVERSATION: &str = "conversation";
+
+/// Names of the methods available on conversations
+pub const CONVERSATION_METHODS: &[&str] = &["send", "history", "agent", "handoffs", "save", "load"];
+
+/// Call a method on a conversation
+///
+/// # Supported Methods
+/// * `send(text)` - Sends a message and returns the reply (parsed if the
+///   agent has structured output)
+/// * `history()` - Returns the messages so far as objects
+/// * `agent()` - Returns the name of the agent that answered last
+/// * `handoffs()` - Returns the names of the agents handed off to in the
+///   last turn
+/// * `save(path)` - Writes the conversation to a JSON file
+/// * `load(path)` - Replaces the history with one saved by `save`, and
+///   continues with the agent that answered last in it
+#[allow(clippy::too_many_arguments)]
+pub async fn call_conversation_method(
+    conversation: &Arc<Mutex<Conversation>>,
+    method: &str,
+    args: &[Value],
+    provider_factory: &ProviderFactory,
+    tools: &ToolRegistry,
+    logger: &dyn Logger,
+    env: &Environment,
+    span: &Span,
+) -> GentResult<Value> {
+    if method == "send" {
+        let text = match single_arg(args, span)? {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        return send(conversation, text, provider_factory, tools, logger, env, span).await;
+    }
+
+    let mut conversation = conversation.lock().await;
+    match method {
+        "agent" => Ok(Value::String(conversation.agent().name.clone())),
+        "handoffs" => Ok(Value::Array(
+            conversation.handoffs().iter().cloned().map(Value::String).collect(),
+        )),
+        "history" => {
+            let messages = serde_json::to_value(conversation.messages()).map_err(|e| {
+                GentError::JsonError {
+                    message: format!("Cannot serialize history: {}", e),
+                    span: span.clone(),
+                }
+            })?;
+            Ok(json_to_value(&messages))
+        }
+        "save" => {
+            let path = path_arg(args, span)?;
+            conversation.save(Path::new(path))?;
+            Ok(Value::Null)
+        }
+        "load" => {
+            let path = path_arg(args, span)?;
+            conversation.load_with(Path::new(path), |name| match env.get(name) {
+                Some(Value::Agent(agent)) => Ok((**agent).clone()),
+                _ => Err(GentError::UndefinedAgent {
+                    name: name.to_string(),
+                    span: span.clone(),
+                }),
+            })?;
+            Ok(Value::Null)
+        }
+        _ => Err(GentError::UndefinedProperty {
+            property: method.to_string(),
+            type_name: "Conversation".to_string(),
+            span: span.clone(),
+        }
+        .with_suggestion(method, CONVERSATION_METHODS.iter().copied())),
+    }
+}
+
+/// Send a message and return the reply
+///
+/// The turn runs on a copy of the conversation, so the lock is not held
+/// while the agent works and its tools can use the conversation too.
+async fn send(
+    conversation: &Arc<Mutex<Conversation>>,
+    text: String,
+    provider_factory: &ProviderFactory,
+    tools: &ToolRegistry,
+    logger: &dyn Logger,
+    env: &Environment,
+    span: &Span,
+) -> GentResult<Value> {
+    let before = conversation.lock().await.clone();
+    let mut turn = before.clone();
+    let reply = turn
+        .send_with_env(text, provider_factory, tools, logger, Some(env))
+        .await
+        .map_err(|e| {
+            let agent = &turn.agent().name;
+            GentError::AgentContext {
+                agent: agent.clone(),
+                source: Box::new(e),
+            }
+            .with_frame(call_frame(&format!("{}.send", agent), span, env))
+        })?;
+    let returns_json = turn.agent().returns_json();
+    conversation
+        .lock()
+        .await
+        .finish_turn(before.messages(), turn);
+
+    if returns_json {
+        if let Ok(json) = serde_json::from_str(&reply) {
+            return Ok(json_to_value(&json));
+        }
+    }
+    Ok(Value::String(reply))
+}
+
+fn single_arg<'a>(args: &'a [Value], span: &Span) -> GentResult<&'a Value> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err(GentError::WrongArgumentCount {
+            expected: 1,
+            got: args.len(),
+            span: span.clone(),
+        }),
+    }
+}
+
+fn path_arg<'a>(args: &'a [Value], span: &Span) -> GentResult<&'a str> {
+    match single_arg(args, span)? {
+        Value::String(path) => Ok(path),
+        other => Err(GentError::ArgumentTypeMismatch {
+            param: "path".to_string(),
+            expected: "string".to_string(),
+            got: other.type_name(),
+            span: span.clone(),
+        }),
+    }
+}
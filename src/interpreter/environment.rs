@@ -1,7 +1,9 @@
 //! Environment for scoped variable storage
 
+use crate::interpreter::stdlib::StdState;
 use crate::interpreter::types::{EnumDef, InterfaceDef};
 use crate::interpreter::Value;
+use crate::parser::ast::StructField;
 use std::collections::HashMap;
 
 /// Scoped environment for storing variables
@@ -13,6 +15,12 @@ pub struct Environment {
     enums: HashMap<String, EnumDef>,
     /// Interface type definitions
     interfaces: HashMap<String, InterfaceDef>,
+    /// Struct type definitions
+    structs: HashMap<String, Vec<StructField>>,
+    /// File the code being evaluated comes from (None = the main program)
+    file: Option<String>,
+    /// Script arguments and random generator of the standard library
+    std_state: StdState,
 }
 
 impl Environment {
@@ -22,6 +30,9 @@ impl Environment {
             scopes: vec![HashMap::new()],
             enums: HashMap::new(),
             interfaces: HashMap::new(),
+            structs: HashMap::new(),
+            file: None,
+            std_state: StdState::default(),
         }
     }
 
@@ -42,6 +53,14 @@ impl Environment {
         None
     }
 
+    /// Get a mutable reference to a variable from any scope (innermost first)
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
     /// Set an existing variable in the nearest scope where it exists
     pub fn set(&mut self, name: &str, value: Value) -> bool {
         for scope in self.scopes.iter_mut().rev() {
@@ -53,6 +72,11 @@ impl Environment {
         false
     }
 
+    /// Remove a variable from the nearest scope where it exists, returning its value
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.remove(name))
+    }
+
     /// Push a new scope onto the stack
     pub fn push_scope(&mut self) {
         self.scopes.push(HashMap::new());
@@ -70,11 +94,36 @@ impl Environment {
         self.get(name).is_some()
     }
 
+    /// Get the names of all variables in scope
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().flat_map(|scope| scope.keys().map(String::as_str))
+    }
+
     /// Get the current scope depth
     pub fn depth(&self) -> usize {
         self.scopes.len()
     }
 
+    /// Get the file the code being evaluated comes from (None = the main program)
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Set the file the code being evaluated comes from
+    pub fn set_file(&mut self, file: Option<String>) {
+        self.file = file;
+    }
+
+    /// Get the standard library state of this evaluation
+    pub fn std_state(&self) -> &StdState {
+        &self.std_state
+    }
+
+    /// Set the arguments returned by `args()` from `std:env`
+    pub fn set_script_args(&mut self, args: Vec<String>) {
+        self.std_state.set_args(args);
+    }
+
     /// Define an enum type
     pub fn define_enum(&mut self, def: EnumDef) {
         self.enums.insert(def.name.clone(), def);
@@ -94,6 +143,21 @@ impl Environment {
     pub fn get_interface(&self, name: &str) -> Option<&InterfaceDef> {
         self.interfaces.get(name)
     }
+
+    /// Define a struct type
+    pub fn define_struct(&mut self, name: impl Into<String>, fields: Vec<StructField>) {
+        self.structs.insert(name.into(), fields);
+    }
+
+    /// Get the fields of a struct type
+    pub fn get_struct(&self, name: &str) -> Option<&[StructField]> {
+        self.structs.get(name).map(Vec::as_slice)
+    }
+
+    /// All struct type definitions
+    pub fn structs(&self) -> &HashMap<String, Vec<StructField>> {
+        &self.structs
+    }
 }
 
 impl Default for Environment {
@@ -1,17 +1,27 @@
 //! Program evaluation for GENT
 
-use crate::errors::{GentError, GentResult};
-use crate::interpreter::block_eval::evaluate_block_with_provider_factory;
-use crate::interpreter::builtins::{call_builtin, is_builtin};
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::block_eval::{agent_error, evaluate_block_with_provider_factory, function_error};
+use crate::interpreter::builtins::{call_builtin, is_builtin, BUILTINS};
+use crate::interpreter::conversation_methods::{call_conversation_method, CONVERSATION};
 use crate::interpreter::expr_eval::evaluate_expr;
 use crate::interpreter::imports::collect_imports;
+use crate::interpreter::json::{call_from_json, call_to_json, type_name_arg, FROM_JSON, TO_JSON};
+use crate::interpreter::native::call_native_function;
+use crate::interpreter::stdlib::{call_std_function, is_std_import, std_module, STD_MODULES, STD_PREFIX};
+use crate::interpreter::object_methods::call_object_method;
+use crate::interpreter::place::{call_in_place, is_mutating_method, receiver_path, PathKey, PathSegment};
 use crate::interpreter::string_methods::call_string_method;
-use crate::interpreter::{parse_index_options, AgentValue, Environment, FnValue, KnowledgeConfig, OutputSchema, ParallelValue, UserToolValue, Value};
+use crate::interpreter::{parse_index_options, search_results_to_value, AgentValue, Environment, FnValue, KnowledgeConfig, OutputSchema, ParallelValue, UserToolValue, Value};
 use crate::logging::{LogLevel, Logger};
 use crate::parser::{AgentDecl, Expression, Program, Statement, StringPart, StructField, ToolDecl};
-use crate::runtime::{run_agent_with_tools, ProviderFactory, ToolRegistry, UserToolWrapper};
+use crate::runtime::context::ContextStrategy;
+use crate::runtime::memory::DEFAULT_MEMORY_DIR;
+use crate::runtime::tools::type_schema;
+use crate::runtime::handoff::TRANSFER_TOOL;
+use crate::runtime::{run_agent_turn_with_handoffs, Conversation, ProviderFactory, ToolChoice, ToolRegistry, UserToolWrapper};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Evaluate a GENT program
@@ -39,66 +49,8 @@ pub async fn evaluate(
 
     let mut structs: HashMap<String, Vec<StructField>> = HashMap::new();
 
-    // First pass: collect struct declarations
-    for statement in &program.statements {
-        if let Statement::StructDecl(decl) = statement {
-            structs.insert(decl.name.clone(), decl.fields.clone());
-        }
-    }
-
-    // Collect enum definitions
-    for statement in &program.statements {
-        if let Statement::EnumDecl(decl) = statement {
-            let def = crate::interpreter::types::EnumDef {
-                name: decl.name.clone(),
-                variants: decl
-                    .variants
-                    .iter()
-                    .map(|v| crate::interpreter::types::EnumVariantDef {
-                        name: v.name.clone(),
-                        fields: v
-                            .fields
-                            .iter()
-                            .map(|f| crate::interpreter::types::EnumFieldDef {
-                                name: f.name.clone(),
-                                type_name: f.type_name.clone(),
-                            })
-                            .collect(),
-                    })
-                    .collect(),
-            };
-            env.define_enum(def);
-        }
-    }
-
-    // Collect interface definitions
-    for statement in &program.statements {
-        if let Statement::InterfaceDecl(decl) = statement {
-            let def = crate::interpreter::types::InterfaceDef {
-                name: decl.name.clone(),
-                members: decl
-                    .members
-                    .iter()
-                    .map(|m| match m {
-                        crate::parser::ast::InterfaceMember::Field(f) => {
-                            crate::interpreter::types::InterfaceMemberDef::Field {
-                                name: f.name.clone(),
-                                type_name: f.type_name.clone(),
-                            }
-                        }
-                        crate::parser::ast::InterfaceMember::Method(method) => {
-                            crate::interpreter::types::InterfaceMemberDef::Method {
-                                name: method.name.clone(),
-                                params: method.params.clone(),
-                                return_type: method.return_type.clone(),
-                            }
-                        }
-                    })
-                    .collect(),
-            };
-            env.define_interface(def);
-        }
-    }
+    declare_types(program, &mut env, &mut structs);
+    declare_std_imports(program, &mut env)?;
 
     // Second pass: evaluate statements
     for statement in &program.statements {
@@ -114,8 +66,21 @@ pub async fn evaluate_with_output(
     provider_factory: &ProviderFactory,
     tools: &mut ToolRegistry,
     logger: &dyn Logger,
+) -> GentResult<Vec<String>> {
+    evaluate_with_args(program, Vec::new(), provider_factory, tools, logger).await
+}
+
+/// Evaluate a GENT program and capture output, with `script_args` as the
+/// arguments returned by `args()` from `std:env`
+pub async fn evaluate_with_args(
+    program: &Program,
+    script_args: Vec<String>,
+    provider_factory: &ProviderFactory,
+    tools: &mut ToolRegistry,
+    logger: &dyn Logger,
 ) -> GentResult<Vec<String>> {
     let mut env = Environment::new();
+    env.set_script_args(script_args);
 
     // Register built-in tools in environment so they can be referenced by name
     for tool_name in ["web_fetch", "read_file", "write_file", "json_parse"] {
@@ -125,66 +90,8 @@ pub async fn evaluate_with_output(
     let mut outputs = Vec::new();
     let mut structs: HashMap<String, Vec<StructField>> = HashMap::new();
 
-    // First pass: collect struct declarations
-    for statement in &program.statements {
-        if let Statement::StructDecl(decl) = statement {
-            structs.insert(decl.name.clone(), decl.fields.clone());
-        }
-    }
-
-    // Collect enum definitions
-    for statement in &program.statements {
-        if let Statement::EnumDecl(decl) = statement {
-            let def = crate::interpreter::types::EnumDef {
-                name: decl.name.clone(),
-                variants: decl
-                    .variants
-                    .iter()
-                    .map(|v| crate::interpreter::types::EnumVariantDef {
-                        name: v.name.clone(),
-                        fields: v
-                            .fields
-                            .iter()
-                            .map(|f| crate::interpreter::types::EnumFieldDef {
-                                name: f.name.clone(),
-                                type_name: f.type_name.clone(),
-                            })
-                            .collect(),
-                    })
-                    .collect(),
-            };
-            env.define_enum(def);
-        }
-    }
-
-    // Collect interface definitions
-    for statement in &program.statements {
-        if let Statement::InterfaceDecl(decl) = statement {
-            let def = crate::interpreter::types::InterfaceDef {
-                name: decl.name.clone(),
-                members: decl
-                    .members
-                    .iter()
-                    .map(|m| match m {
-                        crate::parser::ast::InterfaceMember::Field(f) => {
-                            crate::interpreter::types::InterfaceMemberDef::Field {
-                                name: f.name.clone(),
-                                type_name: f.type_name.clone(),
-                            }
-                        }
-                        crate::parser::ast::InterfaceMember::Method(method) => {
-                            crate::interpreter::types::InterfaceMemberDef::Method {
-                                name: method.name.clone(),
-                                params: method.params.clone(),
-                                return_type: method.return_type.clone(),
-                            }
-                        }
-                    })
-                    .collect(),
-            };
-            env.define_interface(def);
-        }
-    }
+    declare_types(program, &mut env, &mut structs);
+    declare_std_imports(program, &mut env)?;
 
     // Second pass: evaluate statements
     for statement in &program.statements {
@@ -218,47 +125,34 @@ pub async fn evaluate_with_imports(
 
     // Process imports if source file is provided
     if let Some(file) = source_file {
-        let mut visited = std::collections::HashSet::new();
-        let imports = collect_imports(program, file, &mut visited)?;
-
-        for (names, imported_program) in imports {
-            // Collect structs from imported program
-            for stmt in &imported_program.statements {
-                if let Statement::StructDecl(decl) = stmt {
-                    if names.contains(&decl.name) {
-                        structs.insert(decl.name.clone(), decl.fields.clone());
-                    }
-                }
-            }
+        load_imports(program, file, &mut env, &mut structs, tools)?;
+    }
 
-            // Evaluate imported declarations
-            for stmt in &imported_program.statements {
-                match stmt {
-                    Statement::FnDecl(fn_decl) if names.contains(&fn_decl.name) => {
-                        let fn_value = Value::Function(FnValue {
-                            name: fn_decl.name.clone(),
-                            params: fn_decl.params.clone(),
-                            return_type: fn_decl.return_type.clone(),
-                            body: fn_decl.body.clone(),
-                        });
-                        env.define(&fn_decl.name, fn_value);
-                    }
-                    Statement::AgentDecl(decl) if names.contains(&decl.name) => {
-                        evaluate_agent_decl(decl, &mut env, &structs, tools)?;
-                    }
-                    Statement::ToolDecl(decl) if names.contains(&decl.name) => {
-                        evaluate_tool_decl(decl, &mut env, tools)?;
-                    }
-                    _ => {}
-                }
-            }
-        }
+    declare_types(program, &mut env, &mut structs);
+    declare_std_imports(program, &mut env)?;
+
+    // Second pass: evaluate statements
+    for statement in &program.statements {
+        evaluate_statement(statement, &mut env, provider_factory, tools, logger, &structs).await?;
     }
 
-    // First pass: collect struct declarations from main program
+    Ok(())
+}
+
+/// Declare the structs, enums and interfaces of a program
+///
+/// Runs before any statement is evaluated, so types can be used before
+/// their declaration.
+pub(crate) fn declare_types(
+    program: &Program,
+    env: &mut Environment,
+    structs: &mut HashMap<String, Vec<StructField>>,
+) {
+    // Collect struct declarations
     for statement in &program.statements {
         if let Statement::StructDecl(decl) = statement {
             structs.insert(decl.name.clone(), decl.fields.clone());
+            env.define_struct(&decl.name, decl.fields.clone());
         }
     }
 
@@ -315,16 +209,98 @@ pub async fn evaluate_with_imports(
             env.define_interface(def);
         }
     }
+}
 
-    // Second pass: evaluate statements
+/// Declare the functions imported from standard library modules
+/// (`import { now } from "std:time"`)
+pub(crate) fn declare_std_imports(program: &Program, env: &mut Environment) -> GentResult<()> {
     for statement in &program.statements {
-        evaluate_statement(statement, &mut env, provider_factory, tools, logger, &structs).await?;
+        let Statement::Import(import) = statement else {
+            continue;
+        };
+        if !is_std_import(&import.path) {
+            continue;
+        }
+        let module = std_module(&import.path).ok_or_else(|| {
+            GentError::SyntaxError {
+                message: format!("Unknown standard library module: {}", import.path),
+                span: import.span.clone(),
+            }
+            .with_suggestion(
+                import.path.trim_start_matches(STD_PREFIX),
+                STD_MODULES.iter().map(|m| m.name),
+            )
+        })?;
+        for name in &import.names {
+            let function = module.function(name).ok_or_else(|| {
+                GentError::SyntaxError {
+                    message: format!("'{}' is not exported by {}", name, import.path),
+                    span: import.span.clone(),
+                }
+                .with_suggestion(name, module.functions.iter().map(|f| f.name))
+            })?;
+            env.define(name, Value::StdFunction(function));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the imports of a program loaded from `file` and declare the
+/// imported functions, agents, tools and structs
+pub(crate) fn load_imports(
+    program: &Program,
+    file: &Path,
+    env: &mut Environment,
+    structs: &mut HashMap<String, Vec<StructField>>,
+    tools: &mut ToolRegistry,
+) -> GentResult<()> {
+    let mut visited = std::collections::HashSet::new();
+    let imports = collect_imports(program, file, &mut visited)?;
+
+    for (names, imported_program, import_path) in imports {
+        // Imported functions may call standard library functions of their own
+        declare_std_imports(&imported_program, env)?;
+
+        // Collect structs from imported program
+        for stmt in &imported_program.statements {
+            if let Statement::StructDecl(decl) = stmt {
+                if names.contains(&decl.name) {
+                    structs.insert(decl.name.clone(), decl.fields.clone());
+                    env.define_struct(&decl.name, decl.fields.clone());
+                }
+            }
+        }
+
+        // Evaluate imported declarations
+        for stmt in &imported_program.statements {
+            match stmt {
+                Statement::FnDecl(fn_decl) if names.contains(&fn_decl.name) => {
+                    let fn_value = Value::Function(FnValue {
+                        name: fn_decl.name.clone(),
+                        params: fn_decl.params.clone(),
+                        return_type: fn_decl.return_type.clone(),
+                        body: fn_decl.body.clone(),
+                        source_file: Some(import_path.display().to_string()),
+                    });
+                    env.define(&fn_decl.name, fn_value);
+                }
+                Statement::AgentDecl(decl) if names.contains(&decl.name) => {
+                    evaluate_agent_decl(decl, env, structs, tools)
+                        .map_err(|e| e.in_file(Some(import_path.display().to_string())))?;
+                }
+                Statement::ToolDecl(decl) if names.contains(&decl.name) => {
+                    evaluate_tool_decl(decl, env, tools)
+                        .map_err(|e| e.in_file(Some(import_path.display().to_string())))?;
+                }
+                _ => {}
+            }
+        }
     }
 
     Ok(())
 }
 
-async fn evaluate_statement(
+pub(crate) async fn evaluate_statement(
     statement: &Statement,
     env: &mut Environment,
     provider_factory: &ProviderFactory,
@@ -384,6 +360,7 @@ async fn evaluate_statement(
                 params: decl.params.clone(),
                 return_type: decl.return_type.clone(),
                 body: decl.body.clone(),
+                source_file: None,
             });
             env.define(&decl.name, fn_value);
         }
@@ -410,6 +387,12 @@ async fn evaluate_statement(
                 return Ok(());
             }
 
+            // Check if it's an imported standard library function
+            if let Some(Value::StdFunction(function)) = env.get(&call.name) {
+                call_std_function(function, &arg_values, &call.span, env.std_state()).await?;
+                return Ok(());
+            }
+
             // Check if it's a user-defined function
             if let Some(Value::Function(fn_val)) = env.get(&call.name) {
                 let fn_val = fn_val.clone();
@@ -429,20 +412,33 @@ async fn evaluate_statement(
 
                 // Create function scope and bind parameters
                 let mut fn_env = env.clone();
+                fn_env.set_file(fn_val.source_file.clone());
                 fn_env.push_scope();
                 for (param, arg_val) in fn_val.params.iter().zip(arg_values.iter()) {
                     fn_env.define(&param.name, arg_val.clone());
                 }
 
                 // Evaluate function body with provider factory support for agent calls
-                evaluate_block_with_provider_factory(&fn_val.body, &mut fn_env, tools, provider_factory, logger).await?;
+                evaluate_block_with_provider_factory(&fn_val.body, &mut fn_env, tools, provider_factory, logger)
+                    .await
+                    .map_err(|e| function_error(e, &fn_val, &call.span, env))?;
+                return Ok(());
+            }
+
+            // Check if it's a native function registered by the host
+            if let Some(function) = tools.function(&call.name) {
+                call_native_function(function.as_ref(), arg_values, &call.span).await?;
                 return Ok(());
             }
 
             return Err(GentError::UnknownTool {
                 name: call.name.clone(),
                 span: call.span.clone(),
-            });
+            }
+            .with_suggestion(
+                &call.name,
+                BUILTINS.iter().copied().chain(tools.function_names()).chain(env.names()),
+            ));
         }
     }
     Ok(())
@@ -515,6 +511,7 @@ async fn evaluate_statement_with_output(
                 params: decl.params.clone(),
                 return_type: decl.return_type.clone(),
                 body: decl.body.clone(),
+                source_file: None,
             });
             env.define(&decl.name, fn_value);
             Ok(None)
@@ -549,6 +546,12 @@ async fn evaluate_statement_with_output(
                 return Ok(None);
             }
 
+            // Check if it's an imported standard library function
+            if let Some(Value::StdFunction(function)) = env.get(&call.name) {
+                call_std_function(function, &arg_values, &call.span, env.std_state()).await?;
+                return Ok(None);
+            }
+
             // Check if it's a user-defined function
             if let Some(Value::Function(fn_val)) = env.get(&call.name) {
                 let fn_val = fn_val.clone();
@@ -568,20 +571,33 @@ async fn evaluate_statement_with_output(
 
                 // Create function scope and bind parameters
                 let mut fn_env = env.clone();
+                fn_env.set_file(fn_val.source_file.clone());
                 fn_env.push_scope();
                 for (param, arg_val) in fn_val.params.iter().zip(arg_values.iter()) {
                     fn_env.define(&param.name, arg_val.clone());
                 }
 
                 // Evaluate function body with provider factory support for agent calls
-                evaluate_block_with_provider_factory(&fn_val.body, &mut fn_env, tools, provider_factory, logger).await?;
+                evaluate_block_with_provider_factory(&fn_val.body, &mut fn_env, tools, provider_factory, logger)
+                    .await
+                    .map_err(|e| function_error(e, &fn_val, &call.span, env))?;
                 return Ok(None);
             }
 
-            return Err(GentError::UnknownTool {
+            // Check if it's a native function registered by the host
+            if let Some(function) = tools.function(&call.name) {
+                call_native_function(function.as_ref(), arg_values, &call.span).await?;
+                return Ok(None);
+            }
+
+            Err(GentError::UnknownTool {
                 name: call.name.clone(),
                 span: call.span.clone(),
-            });
+            }
+            .with_suggestion(
+                &call.name,
+                BUILTINS.iter().copied().chain(tools.function_names()).chain(env.names()),
+            ))
         }
     }
 }
@@ -599,6 +615,15 @@ fn evaluate_agent_decl(
     let mut provider: Option<String> = None;
     let mut output_retries: Option<u32> = None;
     let mut dangerously_skip_permissions: bool = false;
+    let mut memory: Option<PathBuf> = None;
+    let mut context_strategy: Option<ContextStrategy> = None;
+    let mut context_limit: Option<usize> = None;
+    let mut handoffs: Vec<String> = Vec::new();
+    let mut max_handoffs: Option<u32> = None;
+    let mut tool_concurrency: Option<usize> = None;
+    let mut tool_timeout_ms: Option<u64> = None;
+    let mut tool_choice: Option<(ToolChoice, Span)> = None;
+    let mut finish_tool: Option<String> = None;
 
     // Extract fields
     for field in &decl.fields {
@@ -714,6 +739,191 @@ fn evaluate_agent_decl(
                     }
                 };
             }
+            "memory" => {
+                let value = evaluate_expr(&field.value, env)?;
+                memory = match value {
+                    Value::Boolean(true) => Some(PathBuf::from(DEFAULT_MEMORY_DIR)),
+                    Value::Boolean(false) => None,
+                    Value::String(dir) => Some(PathBuf::from(dir)),
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "Boolean or String".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                };
+            }
+            "contextStrategy" => {
+                let value = evaluate_expr(&field.value, env)?;
+                context_strategy = Some(match value {
+                    Value::String(s) => ContextStrategy::from_name(&s).ok_or_else(|| {
+                        GentError::TypeError {
+                            expected: "\"truncate\", \"summarize\" or \"drop-tool-results\"".to_string(),
+                            got: format!("\"{}\"", s),
+                            span: field.span.clone(),
+                        }
+                        .with_suggestion(&s, ContextStrategy::NAMES.iter().copied())
+                    })?,
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "String".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                });
+            }
+            "contextLimit" => {
+                let value = evaluate_expr(&field.value, env)?;
+                context_limit = Some(match value {
+                    Value::Number(n) if n >= 1.0 => n as usize,
+                    Value::Number(_) => {
+                        return Err(GentError::TypeError {
+                            expected: "positive number".to_string(),
+                            got: value.to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "Number".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                });
+            }
+            "handoffs" => {
+                // Agents not declared yet are kept by name and looked up
+                // when this one runs, so agents can hand off to each other
+                let agent_name = |value: Value| match value {
+                    Value::Agent(agent) => Ok(agent.name),
+                    other => Err(GentError::TypeError {
+                        expected: "Agent".to_string(),
+                        got: other.type_name().to_string(),
+                        span: field.span.clone(),
+                    }),
+                };
+                match &field.value {
+                    Expression::Array(items, _) => {
+                        for item in items {
+                            match item {
+                                Expression::Identifier(name, _) if !env.contains(name) => {
+                                    handoffs.push(name.clone())
+                                }
+                                _ => handoffs.push(agent_name(evaluate_expr(item, env)?)?),
+                            }
+                        }
+                    }
+                    expr => match evaluate_expr(expr, env)? {
+                        Value::Array(items) => {
+                            for item in items {
+                                handoffs.push(agent_name(item)?);
+                            }
+                        }
+                        value => {
+                            return Err(GentError::TypeError {
+                                expected: "Array of agents".to_string(),
+                                got: value.type_name().to_string(),
+                                span: field.span.clone(),
+                            })
+                        }
+                    },
+                }
+            }
+            "maxHandoffs" => {
+                let value = evaluate_expr(&field.value, env)?;
+                max_handoffs = Some(match value {
+                    Value::Number(n) if n >= 0.0 => n as u32,
+                    Value::Number(_) => {
+                        return Err(GentError::TypeError {
+                            expected: "positive number".to_string(),
+                            got: "negative number".to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "Number".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                });
+            }
+            "toolConcurrency" => {
+                let value = evaluate_expr(&field.value, env)?;
+                tool_concurrency = Some(match value {
+                    Value::Number(n) if n >= 1.0 => n as usize,
+                    Value::Number(_) => {
+                        return Err(GentError::TypeError {
+                            expected: "positive number".to_string(),
+                            got: value.to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "Number".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                });
+            }
+            "toolTimeout" => {
+                // A duration such as `30s`, which evaluates to milliseconds
+                let value = evaluate_expr(&field.value, env)?;
+                tool_timeout_ms = Some(match value {
+                    Value::Number(n) if n >= 1.0 => n as u64,
+                    Value::Number(_) => {
+                        return Err(GentError::TypeError {
+                            expected: "positive duration".to_string(),
+                            got: value.to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "Duration".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                });
+            }
+            "toolChoice" => {
+                // "auto", "required", "none", or a tool the agent must call
+                let value = evaluate_expr(&field.value, env)?;
+                let choice = match value {
+                    Value::String(s) => ToolChoice::parse(&s),
+                    Value::Tool(t) => ToolChoice::Tool(t.name),
+                    Value::BuiltinTool(name) => ToolChoice::Tool(name),
+                    Value::Agent(agent) => ToolChoice::Tool(agent.name),
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "\"auto\", \"required\", \"none\" or a tool".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                };
+                tool_choice = Some((choice, field.span.clone()));
+            }
+            "finishTool" => {
+                let value = evaluate_expr(&field.value, env)?;
+                finish_tool = Some(match value {
+                    Value::Tool(t) => t.name,
+                    _ => {
+                        return Err(GentError::TypeError {
+                            expected: "Tool".to_string(),
+                            got: value.type_name().to_string(),
+                            span: field.span.clone(),
+                        })
+                    }
+                });
+            }
             "userPrompt" => {
                 let value = evaluate_expr(&field.value, env)?;
                 user_prompt = Some(match value {
@@ -743,8 +953,10 @@ fn evaluate_agent_decl(
         span: decl.span.clone(),
     })?;
 
-    // Determine tool names from either tools_expr or legacy `use` syntax
-    let tool_names = if let Some(ref tools_expr) = decl.tools_expr {
+    // Determine tool names from either tools_expr or legacy `use` syntax;
+    // agents in the list are delegated to through tools built for each run
+    let mut sub_agents = Vec::new();
+    let mut tool_names = if let Some(ref tools_expr) = decl.tools_expr {
         // Evaluate the tools expression (should be an array)
         let tools_value = evaluate_expr(tools_expr, env)?;
         match tools_value {
@@ -766,9 +978,10 @@ fn evaluate_agent_decl(
                             tools.register(Box::new(kb_tool));
                             names.push(tool_name);
                         }
+                        Value::Agent(agent) => sub_agents.push(*agent),
                         _ => {
                             return Err(GentError::TypeError {
-                                expected: "Tool, BuiltinTool, String, or KnowledgeBase".to_string(),
+                                expected: "Tool, BuiltinTool, String, KnowledgeBase, or Agent".to_string(),
                                 got: item.type_name().to_string(),
                                 span: decl.span.clone(),
                             })
@@ -790,6 +1003,27 @@ fn evaluate_agent_decl(
         Vec::new()
     };
 
+    // The finish tool is offered even when it is not listed in `tools`
+    if let Some(ref name) = finish_tool {
+        if !tool_names.contains(name) {
+            tool_names.push(name.clone());
+        }
+    }
+
+    // A tool the model is made to call must be one it is offered
+    if let Some((ToolChoice::Tool(ref name), ref span)) = tool_choice {
+        let offered = tool_names.contains(name)
+            || sub_agents.iter().any(|agent| agent.name == *name)
+            || (name == TRANSFER_TOOL && !handoffs.is_empty());
+        if !offered {
+            return Err(GentError::TypeError {
+                expected: "a tool listed in tools".to_string(),
+                got: format!("'{}'", name),
+                span: span.clone(),
+            });
+        }
+    }
+
     // Parse knowledge config if present
     let knowledge_config = if let Some(ref knowledge_expr) = decl.knowledge_expr {
         let knowledge_value = evaluate_expr(knowledge_expr, env)?;
@@ -864,6 +1098,8 @@ fn evaluate_agent_decl(
     // Build agent with all fields
     let mut agent = AgentValue::new(&decl.name, prompt)
         .with_tools(tool_names)
+        .with_sub_agents(sub_agents)
+        .with_handoffs(handoffs)
         .with_model(model);
 
     // Set knowledge config if present
@@ -875,6 +1111,26 @@ fn evaluate_agent_decl(
         agent = agent.with_max_steps(steps);
     }
 
+    if let Some(limit) = max_handoffs {
+        agent = agent.with_max_handoffs(limit);
+    }
+
+    if let Some(limit) = tool_concurrency {
+        agent = agent.with_tool_concurrency(limit);
+    }
+
+    if let Some(timeout_ms) = tool_timeout_ms {
+        agent = agent.with_tool_timeout(timeout_ms);
+    }
+
+    if let Some((choice, _)) = tool_choice {
+        agent = agent.with_tool_choice(choice);
+    }
+
+    if let Some(name) = finish_tool {
+        agent = agent.with_finish_tool(name);
+    }
+
     // Set output_retries if present
     if let Some(retries) = output_retries {
         agent = agent.with_output_retries(retries);
@@ -893,6 +1149,19 @@ fn evaluate_agent_decl(
     // Set dangerously_skip_permissions
     agent.dangerously_skip_permissions = dangerously_skip_permissions;
 
+    // Set memory directory if present
+    if let Some(dir) = memory {
+        agent = agent.with_memory(dir);
+    }
+
+    // Set context window management if present
+    if let Some(strategy) = context_strategy {
+        agent = agent.with_context_strategy(strategy);
+    }
+    if let Some(tokens) = context_limit {
+        agent = agent.with_context_limit(tokens);
+    }
+
     // Convert output type to schema if present
     if let Some(output_type) = &decl.output {
         let schema = OutputSchema::from_output_type(output_type, structs).map_err(|msg| {
@@ -905,7 +1174,19 @@ fn evaluate_agent_decl(
         agent = agent.with_output_schema(schema);
     }
 
-    env.define(&decl.name, Value::Agent(agent));
+    // Convert input type to schema if present
+    if let Some(input_type) = &decl.input {
+        let schema = OutputSchema::from_output_type(input_type, structs).map_err(|msg| {
+            GentError::TypeError {
+                expected: "valid input type".to_string(),
+                got: msg,
+                span: decl.span.clone(),
+            }
+        })?;
+        agent = agent.with_input_schema(schema);
+    }
+
+    env.define(&decl.name, Value::Agent(Box::new(agent)));
 
     Ok(())
 }
@@ -915,8 +1196,23 @@ fn evaluate_tool_decl(
     env: &mut Environment,
     tools: &mut ToolRegistry,
 ) -> GentResult<()> {
+    // Struct and enum types must be known, so they can be described to the model
+    let types = decl
+        .params
+        .iter()
+        .map(|param| (&param.type_name, &param.span))
+        .chain(decl.return_type.iter().map(|ty| (ty, &decl.span)));
+    for (type_name, span) in types {
+        type_schema(type_name, env).map_err(|msg| GentError::TypeError {
+            expected: "valid parameter type".to_string(),
+            got: msg,
+            span: span.clone(),
+        })?;
+    }
+
     let tool_value = UserToolValue {
         name: decl.name.clone(),
+        description: decl.description.clone(),
         params: decl.params.clone(),
         return_type: decl.return_type.clone(),
         body: decl.body.clone(),
@@ -935,7 +1231,7 @@ fn evaluate_tool_decl(
 /// Evaluate an expression with environment access and async agent call support
 fn evaluate_expr_with_env<'a>(
     expr: &'a Expression,
-    env: &'a Environment,
+    env: &'a mut Environment,
     provider_factory: &'a ProviderFactory,
     tools: &'a ToolRegistry,
     logger: &'a dyn Logger,
@@ -960,25 +1256,59 @@ fn evaluate_expr_with_env<'a>(
             Expression::Boolean(b, _) => Ok(Value::Boolean(*b)),
             Expression::Identifier(name, span) => {
                 // Look up variable in environment
-                env.get(name).cloned().ok_or_else(|| GentError::SyntaxError {
-                    message: format!("Undefined variable: {}", name),
-                    span: span.clone(),
+                env.get(name).cloned().ok_or_else(|| {
+                    GentError::SyntaxError {
+                        message: format!("Undefined variable: {}", name),
+                        span: span.clone(),
+                    }
+                    .with_suggestion(name, env.names())
                 })
             }
-            Expression::Null(_) => Ok(Value::String("null".to_string())),
+            Expression::Null(_) => Ok(Value::Null),
             Expression::Call(callee, args, span) => {
                 // Check if this is a method call (callee is Member expression)
                 if let Expression::Member(obj, method, _) = callee.as_ref() {
+                    // In-place methods (push, pop) update the variable they are called on
+                    if is_mutating_method(method) {
+                        if let Some((root, segments)) = receiver_path(obj).filter(|(root, _)| env.contains(root)) {
+                            let mut path = Vec::new();
+                            for segment in segments {
+                                path.push(match segment {
+                                    PathSegment::Field(field, span) => PathKey::Field(field.to_string(), span.clone()),
+                                    PathSegment::Index(index, span) => {
+                                        PathKey::Index(evaluate_expr_with_env(index, env, provider_factory, tools, logger).await?, span.clone())
+                                    }
+                                });
+                            }
+                            let mut arg_values = Vec::new();
+                            for arg in args {
+                                arg_values.push(evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?);
+                            }
+                            return call_in_place(env, root, &path, method, &arg_values, span);
+                        }
+                    }
+
                     // Evaluate the object
                     let obj_value = evaluate_expr_with_env(obj, env, provider_factory, tools, logger).await?;
 
+                    // Any value can be serialized with .toJson()
+                    if method == TO_JSON {
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            arg_values.push(evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?);
+                        }
+                        return call_to_json(&obj_value, &arg_values, span);
+                    }
+
                     match obj_value {
                         Value::Agent(mut agent) => {
                             match method.as_str() {
                                 "run" => {
                                     // Execute the agent
-                                    let result = run_agent_with_tools(&agent, None, provider_factory, tools, logger).await?;
-                                    return Ok(Value::String(result));
+                                    let result = run_agent_turn_with_handoffs(&agent, None, &mut Vec::new(), provider_factory, tools, logger, Some(env))
+                                        .await
+                                        .map_err(|e| agent_error(e, &agent.name, span, env))?;
+                                    return Ok(Value::String(result.output));
                                 }
                                 "userPrompt" => {
                                     // Set user_prompt and return modified agent
@@ -1012,6 +1342,10 @@ fn evaluate_expr_with_env<'a>(
                                     agent.system_prompt = prompt;
                                     return Ok(Value::Agent(agent));
                                 }
+                                CONVERSATION => {
+                                    let conversation = Conversation::new(*agent);
+                                    return Ok(Value::Conversation(Arc::new(tokio::sync::Mutex::new(conversation))));
+                                }
                                 _ => {
                                     return Err(GentError::SyntaxError {
                                         message: format!("Unknown agent method: {}", method),
@@ -1037,8 +1371,8 @@ fn evaluate_expr_with_env<'a>(
                                 arg_values.push(val);
                             }
 
-                            // Check if this is a callback method (map, filter, reduce, find)
-                            if crate::interpreter::array_methods::is_callback_method(method) {
+                            // Check if this is a callback method (map, filter, reduce, find, ...)
+                            if crate::interpreter::array_methods::is_callback_call(method, &arg_values) {
                                 let callback = arg_values.first().ok_or_else(|| GentError::TypeError {
                                     expected: "callback function or lambda".to_string(),
                                     got: "missing argument".to_string(),
@@ -1058,6 +1392,15 @@ fn evaluate_expr_with_env<'a>(
                             // Otherwise, call non-callback array method
                             return crate::interpreter::array_methods::call_array_method(&mut arr, method, &arg_values);
                         }
+                        Value::Object(map) => {
+                            // Object method call - evaluate arguments and dispatch
+                            let mut arg_values = Vec::new();
+                            for arg in args {
+                                let val = evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?;
+                                arg_values.push(val);
+                            }
+                            return call_object_method(&map, method, &arg_values);
+                        }
                         Value::Parallel(parallel) => {
                             // Parallel block method call
                             if method == "run" {
@@ -1130,17 +1473,7 @@ fn evaluate_expr_with_env<'a>(
                                     let results = kb.search(&query, limit).await
                                         .map_err(|e| GentError::SyntaxError { message: e, span: span.clone() })?;
 
-                                    let result_values: Vec<Value> = results.iter().map(|r| {
-                                        let mut map = std::collections::HashMap::new();
-                                        map.insert("source".to_string(), Value::String(r.metadata.source.clone()));
-                                        map.insert("score".to_string(), Value::Number(r.score as f64));
-                                        map.insert("content".to_string(), Value::String(r.metadata.content.clone()));
-                                        map.insert("startLine".to_string(), Value::Number(r.metadata.start_line as f64));
-                                        map.insert("endLine".to_string(), Value::Number(r.metadata.end_line as f64));
-                                        Value::Object(map)
-                                    }).collect();
-
-                                    return Ok(Value::Array(result_values));
+                                    return Ok(search_results_to_value(&results));
                                 }
                                 "isIndexed" => {
                                     let kb = kb.read().await;
@@ -1154,8 +1487,25 @@ fn evaluate_expr_with_env<'a>(
                                 }
                             }
                         }
+                        Value::Conversation(conversation) => {
+                            let mut arg_values = Vec::new();
+                            for arg in args {
+                                arg_values.push(evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?);
+                            }
+                            return call_conversation_method(
+                                &conversation,
+                                method,
+                                &arg_values,
+                                provider_factory,
+                                tools,
+                                logger,
+                                env,
+                                span,
+                            )
+                            .await;
+                        }
                         _ => {
-                            // Not an agent, string, array, or KnowledgeBase - method calls not yet supported
+                            // Not an agent, string, array, object, KnowledgeBase or conversation - method calls not yet supported
                             return Err(GentError::SyntaxError {
                                 message: format!("Method calls on {} not yet implemented", obj_value.type_name()),
                                 span: span.clone(),
@@ -1167,7 +1517,7 @@ fn evaluate_expr_with_env<'a>(
                 // Check if callee is an identifier
                 if let Expression::Identifier(name, _) = callee.as_ref() {
                     // Check if it's an agent (direct call)
-                    if let Some(Value::Agent(agent)) = env.get(name) {
+                    if let Some(Value::Agent(agent)) = env.get(name).cloned() {
                         // This is an agent call - execute it
                         let input = if !args.is_empty() {
                             let arg_value = evaluate_expr_with_env(&args[0], env, provider_factory, tools, logger).await?;
@@ -1178,8 +1528,34 @@ fn evaluate_expr_with_env<'a>(
                         } else {
                             None
                         };
-                        let output = run_agent_with_tools(agent, input, provider_factory, tools, logger).await?;
-                        return Ok(Value::String(output));
+                        let result = run_agent_turn_with_handoffs(&agent, input, &mut Vec::new(), provider_factory, tools, logger, Some(env))
+                            .await
+                            .map_err(|e| agent_error(e, &agent.name, span, env))?;
+                        return Ok(Value::String(result.output));
+                    }
+
+                    if name == FROM_JSON && !env.contains(FROM_JSON) {
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            // `fromJson(text, Person)` names a struct or enum, which is not a value
+                            match type_name_arg(arg, env) {
+                                Some(struct_name) => arg_values.push(struct_name),
+                                None => arg_values.push(
+                                    evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?,
+                                ),
+                            }
+                        }
+                        return call_from_json(&arg_values, env, span);
+                    }
+
+                    // Check if it's an imported standard library function
+                    if let Some(Value::StdFunction(function)) = env.get(name) {
+                        let function = *function;
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            arg_values.push(evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?);
+                        }
+                        return call_std_function(function, &arg_values, span, env.std_state()).await;
                     }
 
                     // Check if it's a function call
@@ -1209,6 +1585,7 @@ fn evaluate_expr_with_env<'a>(
 
                         // Create a new environment with function scope
                         let mut fn_env = env.clone();
+                        fn_env.set_file(fn_val.source_file.clone());
                         fn_env.push_scope();
 
                         // Bind parameters to arguments
@@ -1217,10 +1594,21 @@ fn evaluate_expr_with_env<'a>(
                         }
 
                         // Evaluate the function body with provider factory support for agent calls
-                        let result = crate::interpreter::evaluate_block_with_provider_factory(&fn_val.body, &mut fn_env, tools, provider_factory, logger).await?;
+                        let result = crate::interpreter::evaluate_block_with_provider_factory(&fn_val.body, &mut fn_env, tools, provider_factory, logger)
+                            .await
+                            .map_err(|e| function_error(e, &fn_val, span, env))?;
                         return Ok(result);
                     }
 
+                    // Check if it's a native function registered by the host
+                    if let Some(function) = tools.function(name) {
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            arg_values.push(evaluate_expr_with_env(arg, env, provider_factory, tools, logger).await?);
+                        }
+                        return call_native_function(function.as_ref(), arg_values, span).await;
+                    }
+
                     // Check if it's a KnowledgeBase constructor
                     if name == "KnowledgeBase" {
                         if args.len() != 1 {
@@ -1283,7 +1671,7 @@ fn evaluate_expr_with_env<'a>(
 
             // Object literals - need async evaluation for values
             Expression::Object(fields, _) => {
-                let mut map = std::collections::HashMap::new();
+                let mut map = std::collections::BTreeMap::new();
                 for (key, value_expr) in fields {
                     let value = evaluate_expr_with_env(value_expr, env, provider_factory, tools, logger).await?;
                     map.insert(key.clone(), value);
@@ -1397,7 +1785,7 @@ fn evaluate_expr_with_env<'a>(
 /// Execute a parallel block - runs all agents concurrently
 async fn run_parallel(
     parallel: &ParallelValue,
-    env: &Environment,
+    env: &mut Environment,
     provider_factory: &ProviderFactory,
     tools: &ToolRegistry,
     logger: &dyn Logger,
@@ -1422,9 +1810,14 @@ async fn run_parallel(
     }
 
     // Create futures for all agents
+    let env: &Environment = env;
     let futures: Vec<_> = agent_values
         .iter()
-        .map(|agent| run_agent_with_tools(agent, None, provider_factory, tools, logger))
+        .map(|agent| async move {
+            run_agent_turn_with_handoffs(agent, None, &mut Vec::new(), provider_factory, tools, logger, Some(env))
+                .await
+                .map(|result| result.output)
+        })
         .collect();
 
     // Wait for all with timeout
@@ -6,7 +6,7 @@ use crate::errors::{GentError, GentResult};
 use crate::interpreter::types::{EnumConstructor, EnumValue};
 use crate::interpreter::{Environment, Value};
 use crate::parser::ast::{BinaryOp, Expression, StringPart, UnaryOp};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Evaluate an expression in the given environment
 pub fn evaluate_expr(expr: &Expression, env: &Environment) -> GentResult<Value> {
@@ -34,9 +34,12 @@ pub fn evaluate_expr(expr: &Expression, env: &Environment) -> GentResult<Value>
         Expression::Identifier(name, span) => {
             env.get(name)
                 .cloned()
-                .ok_or_else(|| GentError::UndefinedVariable {
-                    name: name.clone(),
-                    span: span.clone(),
+                .ok_or_else(|| {
+                    GentError::UndefinedVariable {
+                        name: name.clone(),
+                        span: span.clone(),
+                    }
+                    .with_suggestion(name, env.names())
                 })
         }
 
@@ -51,7 +54,7 @@ pub fn evaluate_expr(expr: &Expression, env: &Environment) -> GentResult<Value>
 
         // Object literal
         Expression::Object(fields, _) => {
-            let mut map = HashMap::new();
+            let mut map = BTreeMap::new();
             for (key, value_expr) in fields {
                 let value = evaluate_expr(value_expr, env)?;
                 map.insert(key.clone(), value);
@@ -117,6 +120,14 @@ pub fn evaluate_expr(expr: &Expression, env: &Environment) -> GentResult<Value>
                             span: span.clone(),
                         })
                 }
+                Value::Error(ref err) => {
+                    err.get_field(property)
+                        .ok_or_else(|| GentError::UndefinedProperty {
+                            property: property.clone(),
+                            type_name: "Error".to_string(),
+                            span: span.clone(),
+                        })
+                }
                 _ => Err(GentError::UndefinedProperty {
                     property: property.clone(),
                     type_name: object.type_name().to_string(),
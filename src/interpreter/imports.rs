@@ -4,6 +4,7 @@ use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::stdlib::is_std_import;
 use crate::parser::{parse, Program, Statement};
 
 /// Resolve an import path relative to the current file
@@ -19,15 +20,17 @@ pub fn load_import(path: &Path) -> GentResult<Program> {
         source: e,
     })?;
 
-    parse(&source)
+    parse(&source).map_err(|e| e.in_file(Some(path.display().to_string())))
 }
 
 /// Collect all imports from a program, checking for circular dependencies
+///
+/// Returns the imported names, the parsed program and the path it was loaded from.
 pub fn collect_imports(
     program: &Program,
     current_file: &Path,
     visited: &mut HashSet<PathBuf>,
-) -> GentResult<Vec<(Vec<String>, Program)>> {
+) -> GentResult<Vec<(Vec<String>, Program, PathBuf)>> {
     let canonical = current_file
         .canonicalize()
         .unwrap_or_else(|_| current_file.to_path_buf());
@@ -45,6 +48,10 @@ pub fn collect_imports(
 
     for stmt in &program.statements {
         if let Statement::Import(import_stmt) = stmt {
+            // Standard library modules are built in, not files
+            if is_std_import(&import_stmt.path) {
+                continue;
+            }
             let import_path = resolve_import_path(current_file, &import_stmt.path);
             let imported_program = load_import(&import_path)?;
 
@@ -52,7 +59,7 @@ pub fn collect_imports(
             let nested = collect_imports(&imported_program, &import_path, visited)?;
             imports.extend(nested);
 
-            imports.push((import_stmt.names.clone(), imported_program));
+            imports.push((import_stmt.names.clone(), imported_program, import_path));
         }
     }
 
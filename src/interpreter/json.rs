@@ -0,0 +1,285 @@
+//! Conversion between GENT values and JSON
+//!
+//! Values cross into JSON for tool arguments and results, structured agent
+//! output, `std:json`, native functions and the embedding API; all of them
+//! go through `value_to_json` and `json_to_value`.
+//!
+//! Enum values are written as tagged objects,
+//! `{"enum": "Shape", "variant": "Circle", "data": [2]}` (`data` is left
+//! out for variants without fields). JSON carries no types, so such objects
+//! are only read back as enum values where an enum is expected
+//! (`json_to_enum`); everywhere else objects stay objects. Values with no
+//! JSON form, like agents and functions, are written as descriptive strings.
+
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::BTreeMap;
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::types::{EnumDef, EnumValue, OutputSchema};
+use crate::interpreter::{Environment, Value};
+use crate::parser::ast::{Expression, OutputType};
+use crate::runtime::validate_output;
+
+/// Method that serializes any value: `value.toJson(pretty?)`
+pub const TO_JSON: &str = "toJson";
+
+/// Function that parses JSON, optionally against a struct:
+/// `fromJson(text, Person?)`
+pub const FROM_JSON: &str = "fromJson";
+
+/// Largest integer an f64 holds exactly; whole numbers below it are
+/// written without a fraction (`1`, not `1.0`)
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Convert a value to JSON
+pub fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::String(s) => JsonValue::String(s.clone()),
+        Value::Number(n) if n.fract() == 0.0 && n.abs() < MAX_SAFE_INTEGER => json!(*n as i64),
+        Value::Number(n) => json!(n),
+        Value::Boolean(b) => JsonValue::Bool(*b),
+        Value::Null => JsonValue::Null,
+        Value::Array(items) => JsonValue::Array(items.iter().map(value_to_json).collect()),
+        Value::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), value_to_json(v)))
+                .collect(),
+        ),
+        Value::Enum(e) => {
+            let mut map = Map::new();
+            map.insert("enum".to_string(), JsonValue::String(e.enum_name.clone()));
+            map.insert("variant".to_string(), JsonValue::String(e.variant.clone()));
+            if !e.data.is_empty() {
+                map.insert(
+                    "data".to_string(),
+                    JsonValue::Array(e.data.iter().map(value_to_json).collect()),
+                );
+            }
+            JsonValue::Object(map)
+        }
+        Value::Error(e) => json!({ "kind": e.kind, "message": e.message }),
+        Value::Agent(_) => JsonValue::String("<agent>".to_string()),
+        Value::Tool(_) => JsonValue::String("<tool>".to_string()),
+        Value::Function(_) | Value::StdFunction(_) => JsonValue::String("<function>".to_string()),
+        Value::Lambda(_) => JsonValue::String("<lambda>".to_string()),
+        Value::EnumConstructor(c) => {
+            JsonValue::String(format!("<enum constructor {}.{}>", c.enum_name, c.variant))
+        }
+        Value::Parallel(p) => JsonValue::String(format!("<parallel {}>", p.name)),
+        Value::KnowledgeBase(_) => JsonValue::String("<KnowledgeBase>".to_string()),
+        Value::Conversation(_) => JsonValue::String("<Conversation>".to_string()),
+        Value::BuiltinTool(name) => JsonValue::String(format!("<builtin tool {}>", name)),
+    }
+}
+
+/// Convert JSON to a value
+///
+/// Objects become objects, even when they look like enum values; use
+/// `json_to_enum` where an enum is expected.
+pub fn json_to_value(json: &JsonValue) -> Value {
+    match json {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(b) => Value::Boolean(*b),
+        JsonValue::Number(n) => n.as_f64().map(Value::Number).unwrap_or(Value::Null),
+        JsonValue::String(s) => Value::String(s.clone()),
+        JsonValue::Array(items) => Value::Array(items.iter().map(json_to_value).collect()),
+        JsonValue::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), json_to_value(v)))
+                .collect::<BTreeMap<_, _>>(),
+        ),
+    }
+}
+
+/// Read an enum value written by `value_to_json`, checked against `def`
+///
+/// The object must have string `enum` and `variant` keys, and at most a
+/// `data` array besides. Variant data whose declared type is an enum in
+/// `env` is read as that enum.
+pub fn json_to_enum(json: &JsonValue, def: &EnumDef, env: &Environment) -> Result<EnumValue, String> {
+    let expected = || format!("expected a {} value, got {}", def.name, json);
+    let map = json.as_object().ok_or_else(expected)?;
+    let enum_name = map.get("enum").and_then(JsonValue::as_str);
+    let variant = map.get("variant").and_then(JsonValue::as_str);
+    let data = match map.get("data") {
+        Some(JsonValue::Array(items)) => items.as_slice(),
+        Some(_) => return Err(expected()),
+        None => &[],
+    };
+    let (Some(enum_name), Some(variant)) = (enum_name, variant) else {
+        return Err(expected());
+    };
+    if enum_name != def.name
+        || map
+            .keys()
+            .any(|k| !matches!(k.as_str(), "enum" | "variant" | "data"))
+    {
+        return Err(expected());
+    }
+    let known = def
+        .variants
+        .iter()
+        .find(|v| v.name == variant)
+        .ok_or_else(|| format!("{} has no variant '{}'", def.name, variant))?;
+    if known.fields.len() != data.len() {
+        return Err(format!(
+            "{}.{} takes {} values, got {}",
+            def.name,
+            variant,
+            known.fields.len(),
+            data.len()
+        ));
+    }
+    let data = known
+        .fields
+        .iter()
+        .zip(data)
+        .map(|(field, item)| match env.get_enum(&field.type_name) {
+            Some(inner) => json_to_enum(item, inner, env).map(Value::Enum),
+            None => Ok(json_to_value(item)),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(EnumValue {
+        enum_name: def.name.clone(),
+        variant: variant.to_string(),
+        data,
+    })
+}
+
+/// JSON schema of the tagged objects an enum's values are written as
+fn enum_schema(def: &EnumDef) -> JsonValue {
+    let variants: Vec<&str> = def.variants.iter().map(|v| v.name.as_str()).collect();
+    json!({
+        "type": "object",
+        "properties": {
+            "enum": { "const": def.name },
+            "variant": { "enum": variants },
+            "data": { "type": "array" }
+        },
+        "required": ["enum", "variant"]
+    })
+}
+
+/// Serialize a value as JSON text
+pub fn to_json_string(value: &Value, pretty: bool) -> Result<String, String> {
+    let json = value_to_json(value);
+    if pretty {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
+    }
+    .map_err(|e| format!("Cannot serialize value: {}", e))
+}
+
+/// Call `value.toJson(pretty?)`
+pub(crate) fn call_to_json(value: &Value, args: &[Value], span: &Span) -> GentResult<Value> {
+    if args.len() > 1 {
+        return Err(GentError::WrongArgumentCount {
+            expected: 1,
+            got: args.len(),
+            span: span.clone(),
+        });
+    }
+    let pretty = match args.first() {
+        None => false,
+        Some(Value::Boolean(b)) => *b,
+        Some(other) => {
+            return Err(GentError::ArgumentTypeMismatch {
+                param: "pretty".to_string(),
+                expected: "boolean".to_string(),
+                got: other.type_name(),
+                span: span.clone(),
+            })
+        }
+    };
+    to_json_string(value, pretty)
+        .map(Value::String)
+        .map_err(|message| GentError::JsonError {
+            message,
+            span: span.clone(),
+        })
+}
+
+/// Struct or enum named by a `fromJson` schema argument
+/// (`fromJson(text, Person)`)
+///
+/// Type names are not values, so the argument is passed on as the name.
+pub(crate) fn type_name_arg(expr: &Expression, env: &Environment) -> Option<Value> {
+    match expr {
+        Expression::Identifier(name, _)
+            if !env.contains(name)
+                && (env.get_struct(name).is_some() || env.get_enum(name).is_some()) =>
+        {
+            Some(Value::String(name.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Call `fromJson(text, Type?)`
+///
+/// With a struct, the parsed JSON is validated against its fields; with an
+/// enum, it must be a value of that enum as written by `toJson`.
+pub(crate) fn call_from_json(args: &[Value], env: &Environment, span: &Span) -> GentResult<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(GentError::WrongArgumentCount {
+            expected: if args.is_empty() { 1 } else { 2 },
+            got: args.len(),
+            span: span.clone(),
+        });
+    }
+    let text = match &args[0] {
+        Value::String(s) => s,
+        other => {
+            return Err(GentError::ArgumentTypeMismatch {
+                param: "text".to_string(),
+                expected: "string".to_string(),
+                got: other.type_name(),
+                span: span.clone(),
+            })
+        }
+    };
+    let json: JsonValue = serde_json::from_str(text).map_err(|e| GentError::JsonError {
+        message: format!("Invalid JSON: {}", e),
+        span: span.clone(),
+    })?;
+
+    if let Some(schema) = args.get(1) {
+        let name = match schema {
+            Value::String(name) => name,
+            other => {
+                return Err(GentError::ArgumentTypeMismatch {
+                    param: "schema".to_string(),
+                    expected: "struct name".to_string(),
+                    got: other.type_name(),
+                    span: span.clone(),
+                })
+            }
+        };
+        if let Some(def) = env.get_enum(name) {
+            return json_to_enum(&json, def, env)
+                .map(Value::Enum)
+                .map_err(|message| GentError::OutputValidationError {
+                    message,
+                    expected: enum_schema(def).to_string(),
+                    got: text.clone(),
+                });
+        }
+        let schema =
+            OutputSchema::from_output_type(&OutputType::Named(name.clone()), env.structs())
+                .map_err(|message| {
+                    GentError::JsonError {
+                        message,
+                        span: span.clone(),
+                    }
+                    .with_suggestion(name, env.structs().keys().map(String::as_str))
+                })?;
+        validate_output(&json, &schema).map_err(|message| GentError::OutputValidationError {
+            message,
+            expected: schema.to_json_schema().to_string(),
+            got: text.clone(),
+        })?;
+    }
+
+    Ok(json_to_value(&json))
+}
@@ -1,8 +1,10 @@
 //! Helper functions for KnowledgeBase evaluation
 
 use crate::errors::GentResult;
+use crate::interpreter::json::json_to_value;
 use crate::interpreter::types::Value;
-use crate::runtime::rag::IndexOptions;
+use crate::runtime::rag::{IndexOptions, SearchResult};
+use serde_json::json;
 
 /// Parse IndexOptions from a GENT Value (typically an object)
 pub fn parse_index_options(value: &Value) -> GentResult<IndexOptions> {
@@ -46,3 +48,20 @@ pub fn parse_index_options(value: &Value) -> GentResult<IndexOptions> {
 
     Ok(options)
 }
+
+/// Convert KnowledgeBase search results to an array of objects
+pub fn search_results_to_value(results: &[SearchResult]) -> Value {
+    let results: Vec<_> = results
+        .iter()
+        .map(|r| {
+            json!({
+                "source": r.metadata.source,
+                "score": r.score,
+                "content": r.metadata.content,
+                "startLine": r.metadata.start_line,
+                "endLine": r.metadata.end_line,
+            })
+        })
+        .collect();
+    json_to_value(&serde_json::Value::Array(results))
+}
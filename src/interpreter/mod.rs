@@ -3,21 +3,45 @@
 pub mod array_methods;
 pub mod block_eval;
 pub mod builtins;
+pub mod conversation_methods;
 pub mod environment;
 pub mod evaluator;
 pub mod expr_eval;
 pub mod imports;
+pub mod json;
 mod kb_helpers;
+pub mod native;
+pub mod object_methods;
+pub mod place;
+pub mod session;
+pub mod stdlib;
 pub mod string_methods;
 pub mod types;
 
-pub(crate) use kb_helpers::parse_index_options;
+pub(crate) use kb_helpers::{parse_index_options, search_results_to_value};
 
-pub use array_methods::{call_array_method, call_array_method_with_callback, is_callback_method};
-pub use block_eval::{evaluate_block, evaluate_block_with_provider_factory, evaluate_expr_async, BlockEvalContext};
-pub use builtins::{call_builtin, is_builtin};
+pub use array_methods::{
+    call_array_method, call_array_method_with_callback, is_callback_call, is_callback_method,
+    ARRAY_METHODS,
+};
+pub use block_eval::{
+    evaluate_block, evaluate_block_in_scope, evaluate_block_with_provider_factory,
+    evaluate_expr_async, BlockEvalContext,
+};
+pub use builtins::{call_builtin, is_builtin, BUILTINS};
+pub use conversation_methods::{call_conversation_method, CONVERSATION, CONVERSATION_METHODS};
 pub use environment::Environment;
 pub use evaluator::*;
 pub use expr_eval::evaluate_expr;
 pub use imports::{collect_imports, load_import, resolve_import_path};
+pub use json::{json_to_enum, json_to_value, to_json_string, value_to_json, FROM_JSON, TO_JSON};
+pub use native::{
+    call_native_function, native_async_fn, native_fn, FromValue, IntoValue, NativeArgs,
+    NativeError, NativeFunction, NativeParam, Signature,
+};
+pub use object_methods::{call_object_method, OBJECT_METHODS};
+pub use place::{is_mutating_method, MUTATING_METHODS};
+pub use session::Session;
+pub use stdlib::{call_std_function, is_std_import, std_module, STD_MODULES};
+pub use string_methods::STRING_METHODS;
 pub use types::*;
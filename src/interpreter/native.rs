@@ -0,0 +1,465 @@
+//! Native functions: Rust functions callable from GENT code
+//!
+//! Programs that embed GENT register native functions on the
+//! `ToolRegistry` they evaluate with, and scripts call them by name like
+//! any `fn`:
+//!
+//! ```
+//! use gent::interpreter::native::{native_fn, NativeArgs, Signature};
+//! use gent::parser::ast::TypeName;
+//! use gent::runtime::ToolRegistry;
+//!
+//! let mut tools = ToolRegistry::new();
+//! tools.register_function(native_fn(
+//!     "double",
+//!     Signature::new()
+//!         .param("n", TypeName::Number)
+//!         .returns(TypeName::Number),
+//!     |args: NativeArgs| Ok(args.get::<f64>(0)? * 2.0),
+//! ));
+//! ```
+//!
+//! Arguments are checked against the signature before the function runs
+//! and the result against its return type afterwards; a failing function
+//! is reported at the GENT call site.
+
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::json::{json_to_value, value_to_json};
+use crate::interpreter::stdlib::{check_arguments, type_label, type_matches};
+use crate::interpreter::Value;
+use crate::parser::ast::TypeName;
+
+/// A function implemented in Rust and callable from GENT code
+#[async_trait]
+pub trait NativeFunction: Send + Sync {
+    /// Name GENT code calls the function by
+    fn name(&self) -> &str;
+
+    /// Parameter and return types
+    fn signature(&self) -> &Signature;
+
+    /// Run the function; `args` always match the signature, and the
+    /// result is checked against its return type
+    async fn call(&self, args: NativeArgs) -> Result<Value, NativeError>;
+}
+
+/// A parameter of a native function
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeParam {
+    pub name: String,
+    pub type_name: TypeName,
+    /// Optional parameters may only follow required ones
+    pub optional: bool,
+}
+
+/// Parameter and return types of a native function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub params: Vec<NativeParam>,
+    pub return_type: TypeName,
+}
+
+impl Signature {
+    /// A signature without parameters, returning any value
+    pub fn new() -> Self {
+        Self {
+            params: Vec::new(),
+            return_type: TypeName::Any,
+        }
+    }
+
+    /// Add a required parameter
+    pub fn param(mut self, name: impl Into<String>, type_name: TypeName) -> Self {
+        self.params.push(NativeParam {
+            name: name.into(),
+            type_name,
+            optional: false,
+        });
+        self
+    }
+
+    /// Add an optional parameter
+    pub fn optional(mut self, name: impl Into<String>, type_name: TypeName) -> Self {
+        self.params.push(NativeParam {
+            name: name.into(),
+            type_name,
+            optional: true,
+        });
+        self
+    }
+
+    /// Set the return type
+    pub fn returns(mut self, return_type: TypeName) -> Self {
+        self.return_type = return_type;
+        self
+    }
+
+    /// Check the returned value against the return type
+    fn check_return(&self, value: &Value) -> Result<(), NativeError> {
+        if type_matches(&self.return_type, value) {
+            return Ok(());
+        }
+        Err(NativeError::new(format!(
+            "returned {}, but its signature returns {}",
+            value.type_name(),
+            type_label(&self.return_type)
+        )))
+    }
+
+    /// Check argument count and types
+    fn check(&self, args: &[Value], span: &Span) -> GentResult<()> {
+        let params: Vec<(&str, &TypeName, bool)> = self
+            .params
+            .iter()
+            .map(|p| (p.name.as_str(), &p.type_name, p.optional))
+            .collect();
+        check_arguments(&params, args, span)
+    }
+}
+
+impl Default for Signature {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned by a native function
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeError {
+    pub message: String,
+}
+
+impl NativeError {
+    /// Create an error with a message
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for NativeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for NativeError {
+    fn from(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl From<&str> for NativeError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+/// Arguments passed to a native function
+#[derive(Debug, Clone)]
+pub struct NativeArgs {
+    values: Vec<Value>,
+}
+
+impl NativeArgs {
+    /// Wrap argument values
+    pub fn new(values: Vec<Value>) -> Self {
+        Self { values }
+    }
+
+    /// Convert the argument at `index` (missing optional arguments are null)
+    pub fn get<T: FromValue>(&self, index: usize) -> Result<T, NativeError> {
+        let value = self.values.get(index).cloned().unwrap_or(Value::Null);
+        T::from_value(value).map_err(|e| NativeError::new(format!("argument {}: {}", index + 1, e)))
+    }
+
+    /// Number of arguments given
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if no arguments were given
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The raw argument values
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+}
+
+/// Conversion from a GENT value to a Rust type
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Result<Self, NativeError>;
+}
+
+/// Conversion from a Rust type to a GENT value
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+fn mismatch(expected: &str, value: &Value) -> NativeError {
+    NativeError::new(format!("expected {}, got {}", expected, value.type_name()))
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        Ok(value)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::Number(n) => Ok(n),
+            other => Err(mismatch("Number", &other)),
+        }
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::Number(n) if n.fract() == 0.0 => Ok(n as i64),
+            other => Err(mismatch("integer", &other)),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::Boolean(b) => Ok(b),
+            other => Err(mismatch("Boolean", &other)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::String(s) => Ok(s),
+            other => Err(mismatch("String", &other)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::Array(items) => items.into_iter().map(T::from_value).collect(),
+            other => Err(mismatch("Array", &other)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for BTreeMap<String, T> {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        match value {
+            Value::Object(map) => map
+                .into_iter()
+                .map(|(k, v)| T::from_value(v).map(|v| (k, v)))
+                .collect(),
+            other => Err(mismatch("Object", &other)),
+        }
+    }
+}
+
+impl FromValue for serde_json::Value {
+    fn from_value(value: Value) -> Result<Self, NativeError> {
+        Ok(value_to_json(&value))
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Null
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Number(self)
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Value {
+        Value::Number(self as f64)
+    }
+}
+
+impl IntoValue for usize {
+    fn into_value(self) -> Value {
+        Value::Number(self as f64)
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Boolean(self)
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        self.map(IntoValue::into_value).unwrap_or(Value::Null)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::Array(self.into_iter().map(IntoValue::into_value).collect())
+    }
+}
+
+impl<T: IntoValue> IntoValue for BTreeMap<String, T> {
+    fn into_value(self) -> Value {
+        Value::Object(self.into_iter().map(|(k, v)| (k, v.into_value())).collect())
+    }
+}
+
+impl IntoValue for serde_json::Value {
+    fn into_value(self) -> Value {
+        json_to_value(&self)
+    }
+}
+
+/// Native function backed by a synchronous closure
+pub struct SyncFunction<F> {
+    name: String,
+    signature: Signature,
+    function: F,
+}
+
+#[async_trait]
+impl<F, R> NativeFunction for SyncFunction<F>
+where
+    F: Fn(NativeArgs) -> Result<R, NativeError> + Send + Sync,
+    R: IntoValue,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    async fn call(&self, args: NativeArgs) -> Result<Value, NativeError> {
+        (self.function)(args).map(IntoValue::into_value)
+    }
+}
+
+/// Native function backed by a closure returning a future
+pub struct AsyncFunction<F> {
+    name: String,
+    signature: Signature,
+    function: F,
+}
+
+#[async_trait]
+impl<F, Fut, R> NativeFunction for AsyncFunction<F>
+where
+    F: Fn(NativeArgs) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<R, NativeError>> + Send,
+    R: IntoValue,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    async fn call(&self, args: NativeArgs) -> Result<Value, NativeError> {
+        (self.function)(args).await.map(IntoValue::into_value)
+    }
+}
+
+/// Create a native function from a synchronous closure
+pub fn native_fn<F, R>(
+    name: impl Into<String>,
+    signature: Signature,
+    function: F,
+) -> Box<dyn NativeFunction>
+where
+    F: Fn(NativeArgs) -> Result<R, NativeError> + Send + Sync + 'static,
+    R: IntoValue + 'static,
+{
+    Box::new(SyncFunction {
+        name: name.into(),
+        signature,
+        function,
+    })
+}
+
+/// Create a native function from a closure returning a future
+pub fn native_async_fn<F, Fut, R>(
+    name: impl Into<String>,
+    signature: Signature,
+    function: F,
+) -> Box<dyn NativeFunction>
+where
+    F: Fn(NativeArgs) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, NativeError>> + Send + 'static,
+    R: IntoValue + 'static,
+{
+    Box::new(AsyncFunction {
+        name: name.into(),
+        signature,
+        function,
+    })
+}
+
+/// Call a native function from the call site at `span`
+pub async fn call_native_function(
+    function: &dyn NativeFunction,
+    args: Vec<Value>,
+    span: &Span,
+) -> GentResult<Value> {
+    function.signature().check(&args, span)?;
+    let result = function.call(NativeArgs::new(args)).await;
+    result
+        .and_then(|value| function.signature().check_return(&value).map(|()| value))
+        .map_err(|e| GentError::NativeFunctionError {
+            function: function.name().to_string(),
+            message: e.message,
+            span: span.clone(),
+        })
+}
@@ -0,0 +1,118 @@
+//! Object method implementations for GENT
+//!
+//! This module provides built-in methods for object values,
+//! including keys, values, entries, has, get, merge, remove and length.
+//! Objects keep their keys sorted, so every method that lists keys
+//! returns them in the same order.
+
+use crate::errors::{GentError, GentResult};
+use crate::interpreter::Value;
+use crate::Span;
+use std::collections::BTreeMap;
+
+/// Names of the methods available on objects
+pub const OBJECT_METHODS: &[&str] = &[
+    "keys", "values", "entries", "has", "get", "merge", "remove", "length",
+];
+
+/// Call a method on an object value
+///
+/// Objects are never mutated: `merge` and `remove` return a new object.
+///
+/// # Arguments
+/// * `map` - The object to call the method on
+/// * `method` - The method name
+/// * `args` - Arguments to the method
+///
+/// # Supported Methods
+/// * `keys()` - Returns the keys, in order
+/// * `values()` - Returns the values, in key order
+/// * `entries()` - Returns `[key, value]` pairs, in key order
+/// * `has(key)` - Checks if the key exists
+/// * `get(key, default)` - Returns the value for key, or default (null if omitted)
+/// * `merge(other)` - Returns a new object with the fields of other added (other wins)
+/// * `remove(key)` - Returns a new object without key
+/// * `length()` - Returns the number of keys
+pub fn call_object_method(
+    map: &BTreeMap<String, Value>,
+    method: &str,
+    args: &[Value],
+) -> GentResult<Value> {
+    match method {
+        "keys" => Ok(Value::Array(
+            map.keys().map(|k| Value::String(k.clone())).collect(),
+        )),
+
+        "values" => Ok(Value::Array(map.values().cloned().collect())),
+
+        "entries" => Ok(Value::Array(
+            map.iter()
+                .map(|(k, v)| Value::Array(vec![Value::String(k.clone()), v.clone()]))
+                .collect(),
+        )),
+
+        "has" => {
+            let key = get_string_arg(args, 0, "has")?;
+            Ok(Value::Boolean(map.contains_key(&key)))
+        }
+
+        "get" => {
+            let key = get_string_arg(args, 0, "get")?;
+            let default = args.get(1).cloned().unwrap_or(Value::Null);
+            Ok(map.get(&key).cloned().unwrap_or(default))
+        }
+
+        "merge" => {
+            let other = match args.first() {
+                Some(Value::Object(other)) => other,
+                other => {
+                    return Err(GentError::TypeError {
+                        expected: "Object argument for merge()".to_string(),
+                        got: other
+                            .map(|v| v.type_name())
+                            .unwrap_or_else(|| "missing argument".to_string()),
+                        span: Span::default(),
+                    })
+                }
+            };
+            let mut result = map.clone();
+            result.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
+            Ok(Value::Object(result))
+        }
+
+        "remove" => {
+            let key = get_string_arg(args, 0, "remove")?;
+            let mut result = map.clone();
+            result.remove(&key);
+            Ok(Value::Object(result))
+        }
+
+        "length" => Ok(Value::Number(map.len() as f64)),
+
+        _ => Err(GentError::UndefinedProperty {
+            property: method.to_string(),
+            type_name: "Object".to_string(),
+            span: Span::default(),
+        }),
+    }
+}
+
+/// Helper function to extract a string argument from the argument list
+fn get_string_arg(args: &[Value], index: usize, method: &str) -> GentResult<String> {
+    args.get(index)
+        .and_then(|v| match v {
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            let got = args
+                .get(index)
+                .map(|v| v.type_name())
+                .unwrap_or_else(|| "missing argument".to_string());
+            GentError::TypeError {
+                expected: format!("String argument for {}()", method),
+                got,
+                span: Span::default(),
+            }
+        })
+}
@@ -0,0 +1,165 @@
+//! In-place method calls on variables
+//!
+//! Arrays and objects are values: `let b = a`, function arguments and
+//! lambda captures all copy them. The mutating array methods (`push`,
+//! `pop`) are the exception: called on a variable, or on a field or
+//! element reached from one (`state.items.push(x)`, `rows[0].pop()`),
+//! they update that location in the `Environment` in place. Both async
+//! evaluators route such calls through `call_in_place`; the synchronous
+//! `expr_eval` path never calls methods.
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::array_methods::call_array_method;
+use crate::interpreter::object_methods::call_object_method;
+use crate::interpreter::string_methods::call_string_method;
+use crate::interpreter::{Environment, Value};
+use crate::parser::ast::Expression;
+
+/// Array methods that modify the array they are called on
+pub const MUTATING_METHODS: &[&str] = &["push", "pop"];
+
+/// Check if a method modifies the array it is called on
+pub fn is_mutating_method(method: &str) -> bool {
+    MUTATING_METHODS.contains(&method)
+}
+
+/// One step from a variable to the location a method is called on
+pub(crate) enum PathSegment<'a> {
+    Field(&'a str, &'a Span),
+    Index(&'a Expression, &'a Span),
+}
+
+/// An evaluated path step
+pub(crate) enum PathKey {
+    Field(String, Span),
+    Index(Value, Span),
+}
+
+/// Split a receiver like `state.rows[0]` into its root variable and the
+/// path from it, or None if the receiver is not such a location
+pub(crate) fn receiver_path(expr: &Expression) -> Option<(&str, Vec<PathSegment<'_>>)> {
+    match expr {
+        Expression::Identifier(name, _) => Some((name, Vec::new())),
+        Expression::Member(object, field, span) => {
+            let (root, mut path) = receiver_path(object)?;
+            path.push(PathSegment::Field(field, span));
+            Some((root, path))
+        }
+        Expression::Index(target, index, span) => {
+            let (root, mut path) = receiver_path(target)?;
+            path.push(PathSegment::Index(index, span));
+            Some((root, path))
+        }
+        _ => None,
+    }
+}
+
+/// Call a mutating array method on the value stored at `root` and `path`
+///
+/// The path and arguments are already evaluated, so this never falls back
+/// to evaluating the receiver again: a missing field or index reports the
+/// same error as reading it would, and a value that is not an array gets
+/// the method call it would have had as an ordinary receiver.
+pub(crate) fn call_in_place(
+    env: &mut Environment,
+    root: &str,
+    path: &[PathKey],
+    method: &str,
+    args: &[Value],
+    span: &Span,
+) -> GentResult<Value> {
+    let mut target = env.get_mut(root).ok_or_else(|| GentError::SyntaxError {
+        message: format!("Undefined variable: {}", root),
+        span: span.clone(),
+    })?;
+    for (i, key) in path.iter().enumerate() {
+        let reachable = match (&*target, key) {
+            (Value::Object(map), PathKey::Field(field, _))
+            | (Value::Object(map), PathKey::Index(Value::String(field), _)) => map.contains_key(field),
+            (Value::Array(arr), PathKey::Index(Value::Number(n), _)) => {
+                *n as i64 >= 0 && (*n as i64) < arr.len() as i64
+            }
+            _ => false,
+        };
+        if !reachable {
+            // Nothing reached from here is a location: finish on a copy
+            let mut value = follow(target, key)?;
+            for key in &path[i + 1..] {
+                value = follow(&value, key)?;
+            }
+            return call_on_value(&mut value, method, args, span);
+        }
+        target = match (target, key) {
+            (Value::Object(map), PathKey::Field(field, _))
+            | (Value::Object(map), PathKey::Index(Value::String(field), _)) => {
+                map.get_mut(field).expect("checked above")
+            }
+            (Value::Array(arr), PathKey::Index(Value::Number(n), _)) => &mut arr[*n as i64 as usize],
+            _ => unreachable!("checked above"),
+        };
+    }
+    call_on_value(target, method, args, span)
+}
+
+/// Read one path step from a value, with the errors of member and index access
+fn follow(value: &Value, key: &PathKey) -> GentResult<Value> {
+    match (value, key) {
+        (Value::Object(map), PathKey::Field(property, span))
+        | (Value::Object(map), PathKey::Index(Value::String(property), span)) => {
+            map.get(property).cloned().ok_or_else(|| GentError::UndefinedProperty {
+                property: property.clone(),
+                type_name: "Object".to_string(),
+                span: span.clone(),
+            })
+        }
+        (Value::Error(err), PathKey::Field(property, span)) => {
+            err.get_field(property).ok_or_else(|| GentError::UndefinedProperty {
+                property: property.clone(),
+                type_name: "Error".to_string(),
+                span: span.clone(),
+            })
+        }
+        (value, PathKey::Field(property, span)) => Err(GentError::UndefinedProperty {
+            property: property.clone(),
+            type_name: value.type_name().to_string(),
+            span: span.clone(),
+        }),
+        (Value::Array(items), PathKey::Index(Value::Number(n), span)) => {
+            let idx = *n as i64;
+            if idx < 0 || idx >= items.len() as i64 {
+                return Err(GentError::IndexOutOfBounds {
+                    index: idx,
+                    length: items.len(),
+                    span: span.clone(),
+                });
+            }
+            Ok(items[idx as usize].clone())
+        }
+        (Value::Array(_), PathKey::Index(index, span)) => Err(GentError::NotIndexable {
+            type_name: format!("Array with {} index", index.type_name()),
+            span: span.clone(),
+        }),
+        (Value::Object(_), PathKey::Index(index, span)) => Err(GentError::NotIndexable {
+            type_name: format!("Object with {} index", index.type_name()),
+            span: span.clone(),
+        }),
+        (value, PathKey::Index(_, span)) => Err(GentError::NotIndexable {
+            type_name: value.type_name().to_string(),
+            span: span.clone(),
+        }),
+    }
+}
+
+/// Call the method on the value the path led to
+fn call_on_value(target: &mut Value, method: &str, args: &[Value], span: &Span) -> GentResult<Value> {
+    match target {
+        Value::Array(arr) => call_array_method(arr, method, args),
+        Value::String(s) => call_string_method(s, method, args),
+        Value::Object(map) => call_object_method(map, method, args),
+        other => Err(GentError::TypeError {
+            expected: "Array".to_string(),
+            got: other.type_name().to_string(),
+            span: span.clone(),
+        }),
+    }
+}
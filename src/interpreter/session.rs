@@ -0,0 +1,208 @@
+//! Long-lived evaluation state for interactive use
+//!
+//! A `Session` keeps one `Environment` and `ToolRegistry` alive across
+//! evaluations, so declarations and `let` bindings made by one input are
+//! visible to the next. The REPL is built on it.
+
+use crate::errors::{GentResult, Span};
+use crate::interpreter::block_eval::evaluate_block_in_scope;
+use crate::interpreter::evaluator::{
+    declare_std_imports, declare_types, evaluate_statement, load_imports,
+};
+use crate::interpreter::native::NativeFunction;
+use crate::interpreter::{AgentValue, Environment, Value};
+use crate::logging::Logger;
+use crate::parser::{Block, BlockStmt, Expression, LetStmt, Program, StructField};
+use crate::runtime::{ProviderFactory, Tool, ToolRegistry};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Variable holding the value of a trailing expression while it is evaluated
+///
+/// Not a valid identifier, so it cannot clash with user variables.
+const RESULT_VAR: &str = "<result>";
+
+/// Evaluation state shared by successive inputs
+pub struct Session {
+    env: Environment,
+    tools: ToolRegistry,
+    structs: HashMap<String, Vec<StructField>>,
+    provider_factory: ProviderFactory,
+    logger: Arc<dyn Logger>,
+}
+
+impl Session {
+    /// Create a session with the built-in tools registered
+    pub fn new(provider_factory: ProviderFactory, logger: Arc<dyn Logger>) -> Self {
+        let mut env = Environment::new();
+
+        // Register built-in tools in environment so they can be referenced by name
+        for tool_name in ["web_fetch", "read_file", "write_file", "json_parse"] {
+            env.define(tool_name, Value::BuiltinTool(tool_name.to_string()));
+        }
+
+        Self {
+            env,
+            tools: ToolRegistry::with_builtins(),
+            structs: HashMap::new(),
+            provider_factory,
+            logger,
+        }
+    }
+
+    /// Get the session environment
+    pub fn env(&self) -> &Environment {
+        &self.env
+    }
+
+    /// Get the session tool registry
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tools
+    }
+
+    /// Register a native function that inputs evaluated in this session can call
+    pub fn register_function(&mut self, function: Box<dyn NativeFunction>) {
+        self.tools.register_function(function);
+    }
+
+    /// Register a tool that agents declared in this session can use
+    pub fn register_tool(&mut self, tool: Box<dyn Tool>) {
+        self.tools.register(tool);
+    }
+
+    /// Set the arguments returned by `args()` from `std:env`
+    pub fn set_script_args(&mut self, args: Vec<String>) {
+        self.env.set_script_args(args);
+    }
+
+    /// Get the provider factory agents are run with
+    pub fn provider_factory(&self) -> &ProviderFactory {
+        &self.provider_factory
+    }
+
+    /// Get the session logger
+    pub fn logger(&self) -> &dyn Logger {
+        self.logger.as_ref()
+    }
+
+    /// User-visible variables, sorted by name (built-in tools are left out)
+    pub fn variables(&self) -> Vec<(&str, &Value)> {
+        let mut names: Vec<&str> = self.env.names().collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|name| self.env.get(name).map(|value| (name, value)))
+            .filter(|(_, value)| !matches!(value, Value::BuiltinTool(_)))
+            .collect()
+    }
+
+    /// Declared agents, sorted by name
+    pub fn agents(&self) -> Vec<&AgentValue> {
+        self.variables()
+            .into_iter()
+            .filter_map(|(_, value)| match value {
+                Value::Agent(agent) => Some(agent.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Evaluate the statements of a program
+    ///
+    /// Imports are resolved relative to `file` when it is given.
+    pub async fn eval_program(&mut self, program: &Program, file: Option<&Path>) -> GentResult<()> {
+        if let Some(file) = file {
+            load_imports(
+                program,
+                file,
+                &mut self.env,
+                &mut self.structs,
+                &mut self.tools,
+            )?;
+        }
+
+        declare_types(program, &mut self.env, &mut self.structs);
+        declare_std_imports(program, &mut self.env)?;
+
+        for statement in &program.statements {
+            evaluate_statement(
+                statement,
+                &mut self.env,
+                &self.provider_factory,
+                &mut self.tools,
+                self.logger.as_ref(),
+                &self.structs,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Call a function or tool by name with already evaluated arguments
+    ///
+    /// The call is dispatched exactly as `name(args...)` written in GENT code.
+    pub async fn call(&mut self, name: &str, args: Vec<Value>) -> GentResult<Value> {
+        let span = Span::new(0, 0);
+        let arg_names: Vec<String> = (0..args.len()).map(|i| format!("<arg{}>", i)).collect();
+        self.env.push_scope();
+        for (arg_name, value) in arg_names.iter().zip(args) {
+            self.env.define(arg_name, value);
+        }
+        let call = Expression::Call(
+            Box::new(Expression::Identifier(name.to_string(), span.clone())),
+            arg_names
+                .iter()
+                .map(|arg_name| Expression::Identifier(arg_name.clone(), span.clone()))
+                .collect(),
+            span,
+        );
+        let result = self.eval_statements(vec![BlockStmt::Expr(call)]).await;
+        self.env.pop_scope();
+        result.map(|value| value.unwrap_or(Value::Null))
+    }
+
+    /// Evaluate block statements in the session scope
+    ///
+    /// Returns the value of the last statement when it is an expression.
+    pub async fn eval_statements(
+        &mut self,
+        mut statements: Vec<BlockStmt>,
+    ) -> GentResult<Option<Value>> {
+        let trailing_expr = match statements.pop() {
+            Some(BlockStmt::Expr(expr)) => {
+                let span = expr.span().clone();
+                statements.push(BlockStmt::Let(LetStmt {
+                    name: RESULT_VAR.to_string(),
+                    value: expr,
+                    span,
+                }));
+                true
+            }
+            Some(stmt) => {
+                statements.push(stmt);
+                false
+            }
+            None => false,
+        };
+
+        let block = Block {
+            statements,
+            span: Span::new(0, 0),
+        };
+        let result = evaluate_block_in_scope(
+            &block,
+            &mut self.env,
+            &self.tools,
+            &self.provider_factory,
+            self.logger.as_ref(),
+        )
+        .await;
+        let value = self.env.remove(RESULT_VAR);
+        result?;
+
+        Ok(if trailing_expr { value } else { None })
+    }
+}
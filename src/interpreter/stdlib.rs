@@ -0,0 +1,573 @@
+//! Standard library modules for GENT
+//!
+//! Modules are imported by name with a `std:` prefix instead of a file path:
+//!
+//! ```text
+//! import { now, sleep } from "std:time"
+//! ```
+//!
+//! Every function has a typed signature. Arguments are checked against it
+//! before the function runs, and the language server shows it on hover.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::json::{json_to_value, to_json_string};
+use crate::interpreter::Value;
+use crate::parser::ast::TypeName;
+
+/// Prefix marking an import path as a standard library module
+pub const STD_PREFIX: &str = "std:";
+
+/// A parameter of a standard library function
+#[derive(Debug, PartialEq)]
+pub struct StdParam {
+    pub name: &'static str,
+    pub type_name: TypeName,
+    /// Optional parameters may only follow required ones
+    pub optional: bool,
+}
+
+/// A standard library function signature
+#[derive(Debug, PartialEq)]
+pub struct StdFunction {
+    pub module: &'static str,
+    pub name: &'static str,
+    pub params: &'static [StdParam],
+    pub return_type: TypeName,
+    pub doc: &'static str,
+}
+
+/// A standard library module
+#[derive(Debug)]
+pub struct StdModule {
+    pub name: &'static str,
+    pub functions: &'static [StdFunction],
+}
+
+const fn param(name: &'static str, type_name: TypeName) -> StdParam {
+    StdParam {
+        name,
+        type_name,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, type_name: TypeName) -> StdParam {
+    StdParam {
+        name,
+        type_name,
+        optional: true,
+    }
+}
+
+/// All standard library modules
+pub const STD_MODULES: &[StdModule] = &[
+    StdModule {
+        name: "math",
+        functions: &[
+            StdFunction {
+                module: "math",
+                name: "floor",
+                params: &[param("x", TypeName::Number)],
+                return_type: TypeName::Number,
+                doc: "Largest integer less than or equal to x",
+            },
+            StdFunction {
+                module: "math",
+                name: "ceil",
+                params: &[param("x", TypeName::Number)],
+                return_type: TypeName::Number,
+                doc: "Smallest integer greater than or equal to x",
+            },
+            StdFunction {
+                module: "math",
+                name: "round",
+                params: &[param("x", TypeName::Number)],
+                return_type: TypeName::Number,
+                doc: "x rounded to the nearest integer (halves away from zero)",
+            },
+            StdFunction {
+                module: "math",
+                name: "abs",
+                params: &[param("x", TypeName::Number)],
+                return_type: TypeName::Number,
+                doc: "Absolute value of x",
+            },
+            StdFunction {
+                module: "math",
+                name: "pow",
+                params: &[
+                    param("base", TypeName::Number),
+                    param("exponent", TypeName::Number),
+                ],
+                return_type: TypeName::Number,
+                doc: "base raised to the power exponent",
+            },
+            StdFunction {
+                module: "math",
+                name: "sqrt",
+                params: &[param("x", TypeName::Number)],
+                return_type: TypeName::Number,
+                doc: "Square root of x (an error for negative x)",
+            },
+        ],
+    },
+    StdModule {
+        name: "time",
+        functions: &[
+            StdFunction {
+                module: "time",
+                name: "now",
+                params: &[],
+                return_type: TypeName::Number,
+                doc: "Milliseconds since the Unix epoch",
+            },
+            StdFunction {
+                module: "time",
+                name: "sleep",
+                params: &[param("duration", TypeName::Number)],
+                return_type: TypeName::Any,
+                doc: "Pause for a duration in milliseconds, such as `500ms` or `2s`",
+            },
+            StdFunction {
+                module: "time",
+                name: "formatTime",
+                params: &[
+                    param("timestamp", TypeName::Number),
+                    optional("format", TypeName::String),
+                ],
+                return_type: TypeName::String,
+                doc: "Format a timestamp in UTC with %Y %m %d %H %M %S (ISO 8601 by default)",
+            },
+        ],
+    },
+    StdModule {
+        name: "env",
+        functions: &[
+            StdFunction {
+                module: "env",
+                name: "getEnv",
+                params: &[
+                    param("name", TypeName::String),
+                    optional("default", TypeName::Any),
+                ],
+                return_type: TypeName::Any,
+                doc: "Environment variable, or default (null if omitted) when unset",
+            },
+            StdFunction {
+                module: "env",
+                name: "args",
+                params: &[],
+                return_type: TypeName::Array,
+                doc: "Arguments given after the script path",
+            },
+        ],
+    },
+    StdModule {
+        name: "json",
+        functions: &[
+            StdFunction {
+                module: "json",
+                name: "parse",
+                params: &[param("text", TypeName::String)],
+                return_type: TypeName::Any,
+                doc: "Parse JSON text into a value",
+            },
+            StdFunction {
+                module: "json",
+                name: "stringify",
+                params: &[
+                    param("value", TypeName::Any),
+                    optional("pretty", TypeName::Boolean),
+                ],
+                return_type: TypeName::String,
+                doc: "Serialize a value as JSON, indented when pretty is true",
+            },
+        ],
+    },
+    StdModule {
+        name: "random",
+        functions: &[
+            StdFunction {
+                module: "random",
+                name: "seed",
+                params: &[param("seed", TypeName::Number)],
+                return_type: TypeName::Any,
+                doc: "Seed the generator so later calls repeat the same sequence",
+            },
+            StdFunction {
+                module: "random",
+                name: "random",
+                params: &[],
+                return_type: TypeName::Number,
+                doc: "Number in [0, 1)",
+            },
+            StdFunction {
+                module: "random",
+                name: "randomInt",
+                params: &[
+                    param("min", TypeName::Number),
+                    param("max", TypeName::Number),
+                ],
+                return_type: TypeName::Number,
+                doc: "Integer between min and max, inclusive",
+            },
+            StdFunction {
+                module: "random",
+                name: "choice",
+                params: &[param("items", TypeName::Array)],
+                return_type: TypeName::Any,
+                doc: "Random element of items, or null when empty",
+            },
+            StdFunction {
+                module: "random",
+                name: "shuffle",
+                params: &[param("items", TypeName::Array)],
+                return_type: TypeName::Array,
+                doc: "New array with the elements of items in random order",
+            },
+        ],
+    },
+];
+
+/// Check if an import path names a standard library module rather than a file
+pub fn is_std_import(path: &str) -> bool {
+    path.starts_with(STD_PREFIX)
+}
+
+/// Find a module by import path (`std:time`)
+pub fn std_module(path: &str) -> Option<&'static StdModule> {
+    let name = path.strip_prefix(STD_PREFIX)?;
+    STD_MODULES.iter().find(|m| m.name == name)
+}
+
+impl StdModule {
+    /// Look up a function of this module
+    pub fn function(&self, name: &str) -> Option<&'static StdFunction> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+}
+
+impl StdFunction {
+    /// Signature as written in GENT, e.g. `pow(base: number, exponent: number) -> number`
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|p| {
+                format!(
+                    "{}{}: {}",
+                    p.name,
+                    if p.optional { "?" } else { "" },
+                    type_label(&p.type_name)
+                )
+            })
+            .collect();
+        format!(
+            "{}({}) -> {}",
+            self.name,
+            params.join(", "),
+            type_label(&self.return_type)
+        )
+    }
+
+    /// Check argument count and types against the signature
+    fn check_args(&self, args: &[Value], span: &Span) -> GentResult<()> {
+        let params: Vec<(&str, &TypeName, bool)> = self
+            .params
+            .iter()
+            .map(|p| (p.name, &p.type_name, p.optional))
+            .collect();
+        check_arguments(&params, args, span)
+    }
+}
+
+/// Check argument count and types against `(name, type, optional)`
+/// parameters, where optional parameters only follow required ones
+///
+/// Shared by standard library and native functions.
+pub(crate) fn check_arguments(
+    params: &[(&str, &TypeName, bool)],
+    args: &[Value],
+    span: &Span,
+) -> GentResult<()> {
+    let required = params.iter().filter(|(_, _, optional)| !optional).count();
+    if args.len() < required || args.len() > params.len() {
+        return Err(GentError::WrongArgumentCount {
+            expected: if args.len() < required {
+                required
+            } else {
+                params.len()
+            },
+            got: args.len(),
+            span: span.clone(),
+        });
+    }
+    for ((name, type_name, _), arg) in params.iter().zip(args) {
+        if !type_matches(type_name, arg) {
+            return Err(GentError::ArgumentTypeMismatch {
+                param: name.to_string(),
+                expected: type_label(type_name),
+                got: arg.type_name(),
+                span: span.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn type_label(type_name: &TypeName) -> String {
+    match type_name {
+        TypeName::String => "string".to_string(),
+        TypeName::Number => "number".to_string(),
+        TypeName::Boolean => "boolean".to_string(),
+        TypeName::Object => "object".to_string(),
+        TypeName::Array => "array".to_string(),
+        TypeName::Any => "any".to_string(),
+        TypeName::Named(name) => name.clone(),
+        TypeName::ArrayOf(inner) => format!("{}[]", type_label(inner)),
+        TypeName::Nullable(inner) => format!("{}?", type_label(inner)),
+    }
+}
+
+pub(crate) fn type_matches(type_name: &TypeName, value: &Value) -> bool {
+    match (type_name, value) {
+        (TypeName::Nullable(inner), _) => {
+            matches!(value, Value::Null) || type_matches(inner, value)
+        }
+        (TypeName::ArrayOf(inner), Value::Array(items)) => {
+            items.iter().all(|item| type_matches(inner, item))
+        }
+        (TypeName::Named(name), Value::Enum(e)) => e.enum_name == *name,
+        // Struct values are plain objects
+        (TypeName::Named(_), Value::Object(_)) => true,
+        _ => matches!(
+            (type_name, value),
+            (TypeName::Any, _)
+                | (TypeName::String, Value::String(_))
+                | (TypeName::Number, Value::Number(_))
+                | (TypeName::Boolean, Value::Boolean(_))
+                | (TypeName::Object, Value::Object(_))
+                | (TypeName::Array, Value::Array(_))
+        ),
+    }
+}
+
+/// Call a standard library function with the state of the calling environment
+///
+/// Arguments are checked against the signature first, so each
+/// implementation can rely on the types it declares.
+pub async fn call_std_function(
+    function: &StdFunction,
+    args: &[Value],
+    span: &Span,
+    state: &StdState,
+) -> GentResult<Value> {
+    function.check_args(args, span)?;
+
+    let number = |i: usize| match args.get(i) {
+        Some(Value::Number(n)) => *n,
+        _ => 0.0,
+    };
+    let string = |i: usize| match args.get(i) {
+        Some(Value::String(s)) => Some(s.as_str()),
+        _ => None,
+    };
+    let array = |i: usize| match args.get(i) {
+        Some(Value::Array(items)) => items.as_slice(),
+        _ => &[],
+    };
+    let value_error = |message: String| GentError::ValueError {
+        message,
+        span: span.clone(),
+    };
+    let json_error = |message: String| GentError::JsonError {
+        message,
+        span: span.clone(),
+    };
+
+    match (function.module, function.name) {
+        ("math", "floor") => Ok(Value::Number(number(0).floor())),
+        ("math", "ceil") => Ok(Value::Number(number(0).ceil())),
+        ("math", "round") => Ok(Value::Number(number(0).round())),
+        ("math", "abs") => Ok(Value::Number(number(0).abs())),
+        ("math", "pow") => Ok(Value::Number(number(0).powf(number(1)))),
+        ("math", "sqrt") => {
+            if number(0) < 0.0 {
+                return Err(value_error(format!("sqrt() of negative number {}", number(0))));
+            }
+            Ok(Value::Number(number(0).sqrt()))
+        }
+
+        ("time", "now") => Ok(Value::Number(now_millis() as f64)),
+        ("time", "sleep") => {
+            tokio::time::sleep(Duration::from_millis(number(0).max(0.0) as u64)).await;
+            Ok(Value::Null)
+        }
+        ("time", "formatTime") => Ok(Value::String(format_time(
+            number(0),
+            string(1).unwrap_or("%Y-%m-%dT%H:%M:%SZ"),
+        ))),
+
+        ("env", "getEnv") => Ok(string(0)
+            .and_then(|name| std::env::var(name).ok())
+            .map(Value::String)
+            .unwrap_or_else(|| args.get(1).cloned().unwrap_or(Value::Null))),
+        ("env", "args") => Ok(Value::Array(
+            state.args().iter().cloned().map(Value::String).collect(),
+        )),
+
+        ("json", "parse") => serde_json::from_str(string(0).unwrap_or_default())
+            .map(|json| json_to_value(&json))
+            .map_err(|e| json_error(format!("Invalid JSON: {}", e))),
+        ("json", "stringify") => {
+            to_json_string(&args[0], matches!(args.get(1), Some(Value::Boolean(true))))
+                .map(Value::String)
+                .map_err(json_error)
+        }
+
+        ("random", "seed") => {
+            state.seed(number(0) as u64);
+            Ok(Value::Null)
+        }
+        ("random", "random") => Ok(Value::Number(state.next_random())),
+        ("random", "randomInt") => {
+            let (min, max) = (number(0).ceil(), number(1).floor());
+            if min > max {
+                return Err(value_error(format!(
+                    "randomInt() range is empty: {}..{}",
+                    min, max
+                )));
+            }
+            Ok(Value::Number(
+                min + (state.next_random() * (max - min + 1.0)).floor(),
+            ))
+        }
+        ("random", "choice") => {
+            let items = array(0);
+            if items.is_empty() {
+                return Ok(Value::Null);
+            }
+            let index = (state.next_random() * items.len() as f64) as usize;
+            Ok(items[index.min(items.len() - 1)].clone())
+        }
+        ("random", "shuffle") => {
+            let mut items = array(0).to_vec();
+            // Fisher-Yates
+            for i in (1..items.len()).rev() {
+                let j = ((state.next_random() * (i + 1) as f64) as usize).min(i);
+                items.swap(i, j);
+            }
+            Ok(Value::Array(items))
+        }
+
+        // Every function in STD_MODULES is implemented above
+        _ => Err(GentError::UndefinedVariable {
+            name: format!("std:{}.{}", function.module, function.name),
+            span: span.clone(),
+        }),
+    }
+}
+
+// ============================================
+// time
+// ============================================
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+/// Format a millisecond timestamp in UTC
+fn format_time(timestamp: f64, format: &str) -> String {
+    let seconds = (timestamp / 1000.0).floor() as i64;
+    let (days, secs_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => out.push_str(&format!("{:04}", year)),
+            Some('m') => out.push_str(&format!("{:02}", month)),
+            Some('d') => out.push_str(&format!("{:02}", day)),
+            Some('H') => out.push_str(&format!("{:02}", secs_of_day / 3600)),
+            Some('M') => out.push_str(&format!("{:02}", secs_of_day % 3600 / 60)),
+            Some('S') => out.push_str(&format!("{:02}", secs_of_day % 60)),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// Year, month and day of a count of days since 1970-01-01
+///
+/// From Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// ============================================
+// env and random
+// ============================================
+
+/// Standard library state of one evaluation: the arguments returned by
+/// `args()` and the `std:random` generator
+///
+/// Clones share the generator, so calls into functions (which run in a
+/// clone of the environment) continue the same sequence.
+#[derive(Debug, Clone, Default)]
+pub struct StdState {
+    args: Arc<Vec<String>>,
+    /// Generator state, seeded from the clock on first use unless `seed()` ran
+    rng: Arc<Mutex<Option<u64>>>,
+}
+
+impl StdState {
+    /// Set the arguments returned by `args()` from `std:env`
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = Arc::new(args);
+    }
+
+    /// Arguments returned by `args()` from `std:env`
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    fn seed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = Some(seed);
+    }
+
+    /// Next number in [0, 1) from a SplitMix64 generator
+    fn next_random(&self) -> f64 {
+        let mut state = self.rng.lock().unwrap();
+        let s = state.get_or_insert_with(|| now_millis() as u64);
+        *s = s.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *s;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
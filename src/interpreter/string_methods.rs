@@ -1,11 +1,44 @@
 //! String method implementations for GENT
 //!
 //! This module provides built-in methods for string values,
-//! including length, trim, split, contains, and more.
+//! including length, trim, split, contains, and more, regular expression
+//! matching, and helpers for pulling JSON and fenced code blocks out of
+//! model replies.
 
 use crate::errors::{GentError, GentResult};
+use crate::interpreter::json::json_to_value;
 use crate::interpreter::Value;
 use crate::Span;
+use regex::Regex;
+
+/// Names of the methods available on strings
+pub const STRING_METHODS: &[&str] = &[
+    "length",
+    "trim",
+    "toLowerCase",
+    "toUpperCase",
+    "contains",
+    "startsWith",
+    "endsWith",
+    "split",
+    "replace",
+    "matches",
+    "match",
+    "replaceAll",
+    "splitRegex",
+    "substring",
+    "indexOf",
+    "lines",
+    "chars",
+    "padStart",
+    "padEnd",
+    "repeat",
+    "trimStart",
+    "trimEnd",
+    "toNumber",
+    "extractJson",
+    "extractCodeBlocks",
+];
 
 /// Call a method on a string value
 ///
@@ -24,6 +57,21 @@ use crate::Span;
 /// * `endsWith(suffix)` - Checks if string ends with suffix
 /// * `split(separator)` - Splits string by separator
 /// * `replace(old, new)` - Replaces first occurrence
+/// * `matches(pattern)` - Checks if the regular expression matches anywhere
+/// * `match(pattern)` - Returns `[match, group1, ...]` for the first match, or null
+/// * `replaceAll(pattern, new)` - Replaces every match of the regular expression
+///   (`$1` in `new` refers to a group)
+/// * `splitRegex(pattern)` - Splits string by a regular expression
+/// * `substring(start, end)` - Characters from start up to end (end defaults to the length)
+/// * `indexOf(substr)` - Character index of the first occurrence, or -1
+/// * `lines()` - Splits string into lines
+/// * `chars()` - Splits string into characters
+/// * `padStart(length, fill)` / `padEnd(length, fill)` - Pads to length (fill defaults to " ")
+/// * `repeat(count)` - Repeats the string
+/// * `trimStart()` / `trimEnd()` - Removes leading / trailing whitespace
+/// * `toNumber()` - Parses the trimmed string as a number, or returns null
+/// * `extractJson()` - Parses the first JSON object or array in the string, or returns null
+/// * `extractCodeBlocks(lang)` - Contents of the fenced code blocks, optionally only `lang` ones
 pub fn call_string_method(s: &str, method: &str, args: &[Value]) -> GentResult<Value> {
     match method {
         "length" => Ok(Value::Number(s.chars().count() as f64)),
@@ -64,6 +112,110 @@ pub fn call_string_method(s: &str, method: &str, args: &[Value]) -> GentResult<V
             Ok(Value::String(s.replacen(&old, &new, 1)))
         }
 
+        "matches" => {
+            let re = get_regex_arg(args, 0, "matches")?;
+            Ok(Value::Boolean(re.is_match(s)))
+        }
+
+        "match" => {
+            let re = get_regex_arg(args, 0, "match")?;
+            Ok(match re.captures(s) {
+                Some(caps) => Value::Array(
+                    caps.iter()
+                        .map(|group| {
+                            group
+                                .map(|m| Value::String(m.as_str().to_string()))
+                                .unwrap_or(Value::Null)
+                        })
+                        .collect(),
+                ),
+                None => Value::Null,
+            })
+        }
+
+        "replaceAll" => {
+            let re = get_regex_arg(args, 0, "replaceAll")?;
+            let new = get_string_arg(args, 1, "replaceAll")?;
+            Ok(Value::String(re.replace_all(s, new.as_str()).into_owned()))
+        }
+
+        "splitRegex" => {
+            let re = get_regex_arg(args, 0, "splitRegex")?;
+            Ok(strings(re.split(s)))
+        }
+
+        "substring" => {
+            let len = s.chars().count();
+            let start = (get_number_arg(args, 0, "substring")?.max(0.0) as usize).min(len);
+            let end = if args.len() > 1 {
+                (get_number_arg(args, 1, "substring")?.max(0.0) as usize).min(len)
+            } else {
+                len
+            };
+            Ok(Value::String(
+                s.chars()
+                    .skip(start)
+                    .take(end.saturating_sub(start))
+                    .collect(),
+            ))
+        }
+
+        "indexOf" => {
+            let substr = get_string_arg(args, 0, "indexOf")?;
+            let index = s.find(&substr).map(|i| s[..i].chars().count() as f64);
+            Ok(Value::Number(index.unwrap_or(-1.0)))
+        }
+
+        "lines" => Ok(strings(s.lines())),
+
+        "chars" => Ok(Value::Array(
+            s.chars().map(|c| Value::String(c.to_string())).collect(),
+        )),
+
+        "padStart" | "padEnd" => {
+            let width = get_number_arg(args, 0, method)?.max(0.0) as usize;
+            let fill = if args.len() > 1 {
+                get_string_arg(args, 1, method)?
+            } else {
+                " ".to_string()
+            };
+            let missing = width.saturating_sub(s.chars().count());
+            let padding: String = fill.chars().cycle().take(missing).collect();
+            Ok(Value::String(if method == "padStart" {
+                padding + s
+            } else {
+                s.to_string() + &padding
+            }))
+        }
+
+        "repeat" => {
+            let count = get_number_arg(args, 0, "repeat")?.max(0.0) as usize;
+            Ok(Value::String(s.repeat(count)))
+        }
+
+        "trimStart" => Ok(Value::String(s.trim_start().to_string())),
+
+        "trimEnd" => Ok(Value::String(s.trim_end().to_string())),
+
+        "toNumber" => Ok(s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(Value::Number)
+            .unwrap_or(Value::Null)),
+
+        "extractJson" => Ok(extract_json(s).unwrap_or(Value::Null)),
+
+        "extractCodeBlocks" => {
+            let lang = if args.is_empty() {
+                None
+            } else {
+                Some(get_string_arg(args, 0, "extractCodeBlocks")?)
+            };
+            Ok(strings(extract_code_blocks(s, lang.as_deref())))
+        }
+
         _ => Err(GentError::UndefinedProperty {
             property: method.to_string(),
             type_name: "String".to_string(),
@@ -72,6 +224,87 @@ pub fn call_string_method(s: &str, method: &str, args: &[Value]) -> GentResult<V
     }
 }
 
+/// Convert string pieces to an array value
+fn strings<'a>(parts: impl Iterator<Item = &'a str>) -> Value {
+    Value::Array(parts.map(|p| Value::String(p.to_string())).collect())
+}
+
+/// Parse the first JSON object or array in `s`
+///
+/// Models often wrap JSON in prose or a ```json fence, so every `{` or `[`
+/// is tried as a start until one begins a complete JSON value.
+fn extract_json(s: &str) -> Option<Value> {
+    s.match_indices(['{', '['].as_slice())
+        .find_map(|(start, _)| {
+            serde_json::Deserializer::from_str(&s[start..])
+                .into_iter::<serde_json::Value>()
+                .next()
+                .and_then(Result::ok)
+                .map(|json| json_to_value(&json))
+        })
+}
+
+/// Contents of the ``` fenced code blocks in `s`, optionally only those
+/// whose info string starts with `lang` (case-insensitive)
+fn extract_code_blocks<'a>(s: &'a str, lang: Option<&str>) -> impl Iterator<Item = &'a str> + 'a {
+    let lang = lang.map(str::to_lowercase);
+    let mut blocks = Vec::new();
+    let mut rest = s;
+    while let Some(open) = rest.find("```") {
+        let after_fence = &rest[open + 3..];
+        let Some(info_end) = after_fence.find('\n') else {
+            break;
+        };
+        let info = after_fence[..info_end].trim();
+        let body = &after_fence[info_end + 1..];
+        // The closing fence starts a line; an unclosed block runs to the end
+        let (content, next) = match body
+            .find("\n```")
+            .map(|i| i + 1)
+            .or_else(|| body.starts_with("```").then_some(0))
+        {
+            Some(close) => (&body[..close], &body[close + 3..]),
+            None => (body, ""),
+        };
+        let block_lang = info.split_whitespace().next().unwrap_or("").to_lowercase();
+        if lang.as_ref().map(|l| *l == block_lang).unwrap_or(true) {
+            blocks.push(content.strip_suffix('\n').unwrap_or(content));
+        }
+        rest = next;
+    }
+    blocks.into_iter()
+}
+
+/// Helper function to compile a regular expression argument
+fn get_regex_arg(args: &[Value], index: usize, method: &str) -> GentResult<Regex> {
+    let pattern = get_string_arg(args, index, method)?;
+    Regex::new(&pattern).map_err(|e| GentError::TypeError {
+        expected: format!("valid regular expression for {}()", method),
+        got: e.to_string(),
+        span: Span::default(),
+    })
+}
+
+/// Helper function to extract a number argument from the argument list
+fn get_number_arg(args: &[Value], index: usize, method: &str) -> GentResult<f64> {
+    args.get(index)
+        .and_then(|v| match v {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            let got = args
+                .get(index)
+                .map(|v| v.type_name())
+                .unwrap_or_else(|| "missing argument".to_string());
+            GentError::TypeError {
+                expected: format!("Number argument for {}()", method),
+                got,
+                span: Span::default(),
+            }
+        })
+}
+
 /// Helper function to extract a string argument from the argument list
 fn get_string_arg(args: &[Value], index: usize, method: &str) -> GentResult<String> {
     args.get(index)
@@ -1,12 +1,17 @@
 //! Value types for the GENT interpreter
 
+use crate::errors::{GentError, Span};
+use crate::interpreter::stdlib::StdFunction;
+use crate::runtime::context::ContextStrategy;
+use crate::runtime::ToolChoice;
 use crate::parser::ast::{
     Block, FieldType, OutputType, Param, StructField, TypeName as ParserTypeName,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 /// Runtime representation of an output schema
 #[derive(Debug, Clone, PartialEq)]
@@ -102,6 +107,10 @@ fn resolve_field_type(
             let resolved_fields = resolve_named_types(struct_fields, structs)?;
             Ok(FieldType::Object(resolved_fields))
         }
+        FieldType::Nullable(inner) => {
+            let resolved_inner = resolve_field_type(inner, structs)?;
+            Ok(FieldType::Nullable(Box::new(resolved_inner)))
+        }
     }
 }
 
@@ -128,6 +137,9 @@ fn field_type_to_json_schema(ft: &FieldType) -> serde_json::Value {
             })
         }
         FieldType::Named(name) => json!({"$ref": format!("#/definitions/{}", name)}),
+        FieldType::Nullable(inner) => json!({
+            "anyOf": [field_type_to_json_schema(inner), {"type": "null"}]
+        }),
     }
 }
 
@@ -135,6 +147,8 @@ fn field_type_to_json_schema(ft: &FieldType) -> serde_json::Value {
 #[derive(Debug, Clone, PartialEq)]
 pub struct UserToolValue {
     pub name: String,
+    /// Description from the tool's doc comment
+    pub description: Option<String>,
     pub params: Vec<Param>,
     pub return_type: Option<ParserTypeName>,
     pub body: Block,
@@ -147,6 +161,8 @@ pub struct FnValue {
     pub params: Vec<Param>,
     pub return_type: Option<ParserTypeName>,
     pub body: Block,
+    /// File the function was imported from (None = the main program)
+    pub source_file: Option<String>,
 }
 
 /// Represents a lambda/closure at runtime
@@ -201,6 +217,101 @@ pub struct EnumConstructor {
     pub expected_fields: usize,
 }
 
+/// Runtime value of a caught error, bound by `catch`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorValue {
+    /// Error kind (e.g. "ToolError", or the kind given to `throw`)
+    pub kind: String,
+    /// Human-readable error message
+    pub message: String,
+    /// Source location where the error was raised, if known
+    pub span: Option<Span>,
+    /// Name of the agent that was running when the error occurred
+    pub agent: Option<String>,
+    /// Name of the tool that failed
+    pub tool: Option<String>,
+}
+
+impl ErrorValue {
+    /// Create a new error value with the given kind and message
+    pub fn new(kind: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            message: message.into(),
+            span: None,
+            agent: None,
+            tool: None,
+        }
+    }
+
+    /// Build an error value from an interpreter error
+    pub fn from_error(error: &GentError) -> Self {
+        let mut value = match error {
+            GentError::Thrown { kind, message, .. } => ErrorValue::new(kind.clone(), message.clone()),
+            GentError::AgentContext { agent, source } => {
+                let mut inner = ErrorValue::from_error(source);
+                inner.agent.get_or_insert_with(|| agent.clone());
+                return inner;
+            }
+            GentError::WithHelp { source, .. } => return ErrorValue::from_error(source),
+            GentError::InFile { source, .. } => return ErrorValue::from_error(source),
+            GentError::Traced { source, frames } => {
+                // Errors without a span of their own point at the innermost call site
+                let mut inner = ErrorValue::from_error(source);
+                if inner.span.is_none() {
+                    inner.span = frames.first().map(|f| f.span.clone());
+                }
+                return inner;
+            }
+            other => ErrorValue::new(other.kind(), other.to_string()),
+        };
+        value.span = error.span().cloned();
+        value.tool = match error {
+            GentError::ToolError { tool, .. } => Some(tool.clone()),
+            GentError::UnknownTool { name, .. } => Some(name.clone()),
+            _ => None,
+        };
+        value
+    }
+
+    /// Get a field of the error by name (`kind`, `message`, `span`, `agent`, `tool`)
+    pub fn get_field(&self, name: &str) -> Option<Value> {
+        let optional = |v: &Option<String>| v.clone().map(Value::String).unwrap_or(Value::Null);
+        match name {
+            "kind" => Some(Value::String(self.kind.clone())),
+            "message" => Some(Value::String(self.message.clone())),
+            "span" => Some(match &self.span {
+                Some(span) => {
+                    let mut map = BTreeMap::new();
+                    map.insert("start".to_string(), Value::Number(span.start as f64));
+                    map.insert("end".to_string(), Value::Number(span.end as f64));
+                    Value::Object(map)
+                }
+                None => Value::Null,
+            }),
+            "agent" => Some(optional(&self.agent)),
+            "tool" => Some(optional(&self.tool)),
+            _ => None,
+        }
+    }
+
+    /// Convert back into an interpreter error (used when rethrowing)
+    pub fn to_error(&self, span: Span) -> GentError {
+        let error = GentError::Thrown {
+            kind: self.kind.clone(),
+            message: self.message.clone(),
+            span: self.span.clone().unwrap_or(span),
+        };
+        match &self.agent {
+            Some(agent) => GentError::AgentContext {
+                agent: agent.clone(),
+                source: Box::new(error),
+            },
+            None => error,
+        }
+    }
+}
+
 /// Definition of an interface type
 #[derive(Debug, Clone, PartialEq)]
 pub struct InterfaceDef {
@@ -234,11 +345,11 @@ pub enum Value {
     /// Null/none value
     Null,
     /// Agent value
-    Agent(AgentValue),
+    Agent(Box<AgentValue>),
     /// Array value
     Array(Vec<Value>),
-    /// Object value (key-value map)
-    Object(HashMap<String, Value>),
+    /// Object value (key-value map, ordered by key)
+    Object(BTreeMap<String, Value>),
     /// User-defined tool
     Tool(UserToolValue),
     /// User-defined function (pure, no agent access)
@@ -253,8 +364,14 @@ pub enum Value {
     Parallel(ParallelValue),
     /// Knowledge base for RAG
     KnowledgeBase(Arc<RwLock<crate::runtime::rag::KnowledgeBase>>),
+    /// Multi-turn conversation with an agent
+    Conversation(Arc<Mutex<crate::runtime::Conversation>>),
     /// Built-in tool reference (name only, actual tool in registry)
     BuiltinTool(String),
+    /// Standard library function imported from a `std:` module
+    StdFunction(&'static StdFunction),
+    /// Caught error value
+    Error(ErrorValue),
 }
 
 impl PartialEq for Value {
@@ -275,7 +392,10 @@ impl PartialEq for Value {
             (Value::Parallel(a), Value::Parallel(b)) => a == b,
             // KnowledgeBase uses Arc pointer equality
             (Value::KnowledgeBase(a), Value::KnowledgeBase(b)) => Arc::ptr_eq(a, b),
+            (Value::Conversation(a), Value::Conversation(b)) => Arc::ptr_eq(a, b),
             (Value::BuiltinTool(a), Value::BuiltinTool(b)) => a == b,
+            (Value::StdFunction(a), Value::StdFunction(b)) => a == b,
+            (Value::Error(a), Value::Error(b)) => a == b,
             _ => false,
         }
     }
@@ -312,16 +432,32 @@ pub struct AgentValue {
     pub user_prompt: Option<String>,
     /// Tools available to this agent
     pub tools: Vec<String>,
+    /// Agents this agent can delegate to, offered to it as tools
+    pub sub_agents: Vec<AgentValue>,
+    /// Names of the agents this agent can transfer the conversation to
+    pub handoffs: Vec<String>,
+    /// Maximum handoffs in one turn (None = default 5)
+    pub max_handoffs: Option<u32>,
     /// Knowledge base configuration for auto-RAG (optional)
     pub knowledge_config: Option<KnowledgeConfig>,
     /// Maximum steps before stopping (None = default 10)
     pub max_steps: Option<u32>,
+    /// Tool calls from one response that may run at once (None = default 4)
+    pub tool_concurrency: Option<usize>,
+    /// Time limit for each tool call in milliseconds (None = no limit)
+    pub tool_timeout_ms: Option<u64>,
+    /// Whether the model may, must or must not call tools
+    pub tool_choice: ToolChoice,
+    /// Tool whose call ends the run, with its arguments as the result
+    pub finish_tool: Option<String>,
     /// Model to use (None = default)
     pub model: Option<String>,
     /// Provider to use (openai, claude-code)
     pub provider: Option<String>,
     /// Output schema for structured responses
     pub output_schema: Option<OutputSchema>,
+    /// Input schema used when another agent calls this one as a tool
+    pub input_schema: Option<OutputSchema>,
     /// Number of retries for output validation
     pub output_retries: u32,
     /// Custom instructions for schema output (None = default)
@@ -330,6 +466,12 @@ pub struct AgentValue {
     pub retry_prompt: Option<String>,
     /// Skip permission prompts for claude-code provider (dangerous!)
     pub dangerously_skip_permissions: bool,
+    /// Directory of the agent's persistent memory (None = no memory)
+    pub memory: Option<PathBuf>,
+    /// How older messages are reduced when a request would not fit
+    pub context_strategy: ContextStrategy,
+    /// Context window in tokens (None = known limit of the model)
+    pub context_limit: Option<usize>,
 }
 
 impl AgentValue {
@@ -340,15 +482,26 @@ impl AgentValue {
             system_prompt: system_prompt.into(),
             user_prompt: None,
             tools: Vec::new(),
+            sub_agents: Vec::new(),
+            handoffs: Vec::new(),
+            max_handoffs: None,
             knowledge_config: None,
             max_steps: None,
+            tool_concurrency: None,
+            tool_timeout_ms: None,
+            tool_choice: ToolChoice::Auto,
+            finish_tool: None,
             model: None,
             provider: None,
             output_schema: None,
+            input_schema: None,
             output_retries: 1, // default: retry once
             output_instructions: None,
             retry_prompt: None,
             dangerously_skip_permissions: false,
+            memory: None,
+            context_strategy: ContextStrategy::default(),
+            context_limit: None,
         }
     }
 
@@ -358,12 +511,60 @@ impl AgentValue {
         self
     }
 
+    /// Add agents this agent can delegate to
+    pub fn with_sub_agents(mut self, agents: Vec<AgentValue>) -> Self {
+        self.sub_agents = agents;
+        self
+    }
+
+    /// Add the names of agents this agent can hand off to
+    pub fn with_handoffs(mut self, agents: Vec<String>) -> Self {
+        self.handoffs = agents;
+        self
+    }
+
+    /// Set max handoffs
+    pub fn with_max_handoffs(mut self, handoffs: u32) -> Self {
+        self.max_handoffs = Some(handoffs);
+        self
+    }
+
     /// Set max steps
     pub fn with_max_steps(mut self, steps: u32) -> Self {
         self.max_steps = Some(steps);
         self
     }
 
+    /// Set how many tool calls may run at once
+    pub fn with_tool_concurrency(mut self, limit: usize) -> Self {
+        self.tool_concurrency = Some(limit);
+        self
+    }
+
+    /// Set the time limit for each tool call
+    pub fn with_tool_timeout(mut self, timeout_ms: u64) -> Self {
+        self.tool_timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Set whether the model may, must or must not call tools
+    pub fn with_tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice = choice;
+        self
+    }
+
+    /// Set the tool whose call ends the run
+    pub fn with_finish_tool(mut self, name: impl Into<String>) -> Self {
+        self.finish_tool = Some(name.into());
+        self
+    }
+
+    /// Whether runs answer with JSON: structured output, or the arguments
+    /// of the finish tool
+    pub fn returns_json(&self) -> bool {
+        self.output_schema.is_some() || self.finish_tool.is_some()
+    }
+
     /// Set model
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
         self.model = Some(model.into());
@@ -382,6 +583,12 @@ impl AgentValue {
         self
     }
 
+    /// Set input schema
+    pub fn with_input_schema(mut self, schema: OutputSchema) -> Self {
+        self.input_schema = Some(schema);
+        self
+    }
+
     /// Set output retries
     pub fn with_output_retries(mut self, retries: u32) -> Self {
         self.output_retries = retries;
@@ -405,6 +612,24 @@ impl AgentValue {
         self.user_prompt = Some(prompt.into());
         self
     }
+
+    /// Set the persistent memory directory
+    pub fn with_memory(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.memory = Some(dir.into());
+        self
+    }
+
+    /// Set the context strategy
+    pub fn with_context_strategy(mut self, strategy: ContextStrategy) -> Self {
+        self.context_strategy = strategy;
+        self
+    }
+
+    /// Set the context window in tokens
+    pub fn with_context_limit(mut self, tokens: usize) -> Self {
+        self.context_limit = Some(tokens);
+        self
+    }
 }
 
 impl fmt::Display for Value {
@@ -446,7 +671,10 @@ impl fmt::Display for Value {
             }
             Value::Parallel(p) => write!(f, "<parallel {}>", p.name),
             Value::KnowledgeBase(_) => write!(f, "<KnowledgeBase>"),
+            Value::Conversation(_) => write!(f, "<Conversation>"),
             Value::BuiltinTool(name) => write!(f, "<builtin tool {}>", name),
+            Value::StdFunction(func) => write!(f, "<fn {}.{}>", func.module, func.name),
+            Value::Error(e) => write!(f, "{}", e.message),
         }
     }
 }
@@ -475,7 +703,10 @@ impl Value {
             Value::EnumConstructor(_) => true,
             Value::Parallel(_) => true,
             Value::KnowledgeBase(_) => true,
+            Value::Conversation(_) => true,
             Value::BuiltinTool(_) => true,
+            Value::StdFunction(_) => true,
+            Value::Error(_) => true,
         }
     }
 
@@ -496,7 +727,10 @@ impl Value {
             Value::EnumConstructor(c) => format!("EnumConstructor({}.{})", c.enum_name, c.variant),
             Value::Parallel(_) => "parallel".to_string(),
             Value::KnowledgeBase(_) => "KnowledgeBase".to_string(),
+            Value::Conversation(_) => "Conversation".to_string(),
             Value::BuiltinTool(_) => "BuiltinTool".to_string(),
+            Value::StdFunction(_) => "Function".to_string(),
+            Value::Error(_) => "Error".to_string(),
         }
     }
 
@@ -525,7 +759,7 @@ impl Value {
     }
 
     /// Try to get as object
-    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Value>> {
         match self {
             Value::Object(map) => Some(map),
             _ => None,
@@ -1,11 +1,16 @@
 //! GENT - A programming language for AI agents
 
 pub mod config;
+pub mod engine;
 pub mod errors;
+pub mod formatter;
 pub mod interpreter;
 pub mod lexer;
 pub mod logging;
+pub mod lsp;
 pub mod parser;
+pub mod repl;
 pub mod runtime;
 
+pub use engine::{CancelHandle, Engine, EngineBuilder};
 pub use errors::{GentError, GentResult, Span};
@@ -184,6 +184,48 @@ impl Logger for NullLogger {
     }
 }
 
+/// Logger that prefixes every message before passing it on
+///
+/// Runs of sub-agents log through one of these, so their messages can be
+/// told apart from those of the agent that delegated to them.
+pub struct PrefixLogger<'a> {
+    inner: &'a dyn Logger,
+    prefix: String,
+}
+
+impl<'a> PrefixLogger<'a> {
+    pub fn new(inner: &'a dyn Logger, prefix: impl Into<String>) -> Self {
+        Self {
+            inner,
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl Logger for PrefixLogger<'_> {
+    fn log(&self, level: LogLevel, target: &str, message: &str) {
+        self.inner
+            .log(level, target, &format!("{}{}", self.prefix, message));
+    }
+
+    fn log_with_duration(&self, level: LogLevel, target: &str, message: &str, duration_ms: u64) {
+        self.inner.log_with_duration(
+            level,
+            target,
+            &format!("{}{}", self.prefix, message),
+            duration_ms,
+        );
+    }
+
+    fn is_enabled(&self, level: LogLevel) -> bool {
+        self.inner.is_enabled(level)
+    }
+
+    fn level(&self) -> LogLevel {
+        self.inner.level()
+    }
+}
+
 /// Timer for measuring operation duration
 /// Logs when dropped
 pub struct Timer<'a> {
@@ -0,0 +1,819 @@
+//! Editor features computed from GENT source text
+//!
+//! Everything here works on a document's text (plus imported files read from
+//! disk), so it can be used and tested without a running server. Incomplete
+//! documents are handled through the parser's error recovery.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::errors::Span;
+use crate::interpreter::stdlib::StdFunction;
+use crate::interpreter::{
+    is_std_import, resolve_import_path, std_module, ARRAY_METHODS, BUILTINS, OBJECT_METHODS,
+    STRING_METHODS,
+};
+use crate::parser::{
+    check_agent_fields, check_nullable_access, enclosing_construct, parse_with_recovery, AgentDecl, Block, BlockStmt,
+    InterfaceMember, OutputType, Program, Statement, KNOWN_AGENT_FIELDS, TOP_LEVEL_KEYWORDS,
+};
+
+/// Methods callable on agent values
+pub const AGENT_METHODS: &[&str] = &["userPrompt", "systemPrompt", "run", "conversation"];
+
+/// Keywords usable inside function and tool bodies
+const BLOCK_KEYWORDS: &[&str] = &[
+    "let", "return", "throw", "if", "else", "for", "in", "while", "try", "catch", "finally",
+    "break", "continue", "match", "true", "false", "null",
+];
+
+/// LSP `SymbolKind` values used for document symbols
+pub mod symbol_kind {
+    pub const CLASS: u8 = 5;
+    pub const METHOD: u8 = 6;
+    pub const PROPERTY: u8 = 7;
+    pub const FIELD: u8 = 8;
+    pub const ENUM: u8 = 10;
+    pub const INTERFACE: u8 = 11;
+    pub const FUNCTION: u8 = 12;
+    pub const VARIABLE: u8 = 13;
+    pub const OBJECT: u8 = 19;
+    pub const ENUM_MEMBER: u8 = 22;
+    pub const STRUCT: u8 = 23;
+}
+
+/// LSP `CompletionItemKind` values used for completions
+pub mod completion_kind {
+    pub const METHOD: u8 = 2;
+    pub const FUNCTION: u8 = 3;
+    pub const VARIABLE: u8 = 6;
+    pub const CLASS: u8 = 7;
+    pub const INTERFACE: u8 = 8;
+    pub const PROPERTY: u8 = 10;
+    pub const ENUM: u8 = 13;
+    pub const KEYWORD: u8 = 14;
+    pub const STRUCT: u8 = 22;
+}
+
+/// Zero-based line and UTF-16 character offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A range between two positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A problem found in a document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub range: Range,
+    /// 1 = error, 2 = warning
+    pub severity: u8,
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// An entry in the document outline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbol {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub kind: u8,
+    pub range: Range,
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A completion suggestion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_text: Option<String>,
+}
+
+/// Where a name is defined
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// File containing the definition (None = the same document)
+    pub file: Option<PathBuf>,
+    pub range: Range,
+}
+
+/// Convert a byte offset into a position
+pub fn offset_to_position(source: &str, offset: usize) -> Position {
+    let offset = floor_char_boundary(source, offset);
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: source[..line_start].matches('\n').count() as u32,
+        character: source[line_start..offset].encode_utf16().count() as u32,
+    }
+}
+
+/// Convert a position into a byte offset, clamping to the end of its line
+pub fn position_to_offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match source[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return source.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in source[line_start..].char_indices() {
+        if c == '\n' || units >= position.character {
+            return line_start + i;
+        }
+        units += c.len_utf16() as u32;
+    }
+    source.len()
+}
+
+/// Convert a span into a range
+pub fn span_to_range(source: &str, span: &Span) -> Range {
+    Range {
+        start: offset_to_position(source, span.start),
+        end: offset_to_position(source, span.end),
+    }
+}
+
+/// Syntax errors in the document, with any "did you mean" hints appended,
+/// followed by warnings for misspelled agent fields and unchecked use of
+/// nullable parameters
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let (program, errors) = parse_with_recovery(source);
+    let fields = check_agent_fields(&program)
+        .into_iter()
+        .map(|field| (field.span.clone(), field.message()));
+    let accesses = check_nullable_access(&program)
+        .into_iter()
+        .map(|access| (access.span.clone(), access.message()));
+    let warnings = fields.chain(accesses).map(|(span, message)| Diagnostic {
+        range: span_to_range(source, &span),
+        severity: 2,
+        source: "gent",
+        message,
+    });
+    errors
+        .iter()
+        .map(|error| {
+            let span = error.span().cloned().unwrap_or_default();
+            let mut message = error.to_string();
+            if let Some(help) = error.help() {
+                message.push_str(&format!("\nhelp: {}", help));
+            }
+            Diagnostic {
+                range: span_to_range(source, &span),
+                severity: 1,
+                source: "gent",
+                message,
+            }
+        })
+        .chain(warnings)
+        .collect()
+}
+
+/// Outline of the document: declarations and their fields
+pub fn document_symbols(source: &str) -> Vec<DocumentSymbol> {
+    let (program, _) = parse_with_recovery(source);
+    declarations(&program, source)
+        .into_iter()
+        .map(|decl| DocumentSymbol {
+            name: decl.name.to_string(),
+            detail: signature(source, decl.statement),
+            kind: decl.kind,
+            range: span_to_range(source, &decl.span),
+            selection_range: span_to_range(source, &decl.name_span),
+            children: child_symbols(source, decl.statement),
+        })
+        .collect()
+}
+
+/// Find the definition of the name (or import path) at `offset`
+///
+/// `file` is the document's path, used to resolve imports.
+pub fn definition(source: &str, file: Option<&Path>, offset: usize) -> Option<Location> {
+    let (program, _) = parse_with_recovery(source);
+
+    // Import paths jump to the imported file
+    for statement in &program.statements {
+        if let Statement::Import(import) = statement {
+            if is_std_import(&import.path) {
+                continue;
+            }
+            let quoted = format!("\"{}\"", import.path);
+            let text = &source[import.span.start..import.span.end.min(source.len())];
+            if let Some(i) = text.rfind(&quoted) {
+                let start = import.span.start + i;
+                if (start..=start + quoted.len()).contains(&offset) {
+                    let path = resolve_import_path(file.unwrap_or(Path::new("")), &import.path);
+                    let origin = Position {
+                        line: 0,
+                        character: 0,
+                    };
+                    return Some(Location {
+                        file: Some(path),
+                        range: Range {
+                            start: origin,
+                            end: origin,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    let (name, _) = word_at_offset(source, offset)?;
+
+    if let Some(local) = find_local(&program, source, name, offset) {
+        return Some(Location {
+            file: None,
+            range: span_to_range(source, &local.name_span),
+        });
+    }
+
+    if let Some(decl) = declarations(&program, source)
+        .into_iter()
+        .find(|d| d.name == name)
+    {
+        return Some(Location {
+            file: None,
+            range: span_to_range(source, &decl.name_span),
+        });
+    }
+
+    let (path, imported_source) = find_import(&program, file, name)?;
+    let (imported, _) = parse_with_recovery(&imported_source);
+    let decl = declarations(&imported, &imported_source)
+        .into_iter()
+        .find(|d| d.name == name)?;
+    Some(Location {
+        file: Some(path),
+        range: span_to_range(&imported_source, &decl.name_span),
+    })
+}
+
+/// Markdown describing the name at `offset`: tool and function signatures,
+/// agent fields, type declarations and local variables
+pub fn hover(source: &str, file: Option<&Path>, offset: usize) -> Option<String> {
+    let (program, _) = parse_with_recovery(source);
+    let (name, _) = word_at_offset(source, offset)?;
+
+    if let Some(local) = find_local(&program, source, name, offset) {
+        return Some(code_block(&local.description));
+    }
+
+    if let Some(decl) = declarations(&program, source)
+        .into_iter()
+        .find(|d| d.name == name)
+    {
+        return Some(code_block(&describe(source, decl.statement)));
+    }
+
+    if let Some(function) = find_std_import(&program, name) {
+        return Some(format!("{}\n\n{}", code_block(&function.signature()), function.doc));
+    }
+
+    let (_, imported_source) = find_import(&program, file, name)?;
+    let (imported, _) = parse_with_recovery(&imported_source);
+    let decl = declarations(&imported, &imported_source)
+        .into_iter()
+        .find(|d| d.name == name)?;
+    Some(code_block(&describe(&imported_source, decl.statement)))
+}
+
+/// Completion suggestions at `offset`
+///
+/// After a `.`, string, array and agent methods are offered. At the start of
+/// a line inside an agent body, known agent fields are offered. Elsewhere,
+/// keywords, built-ins and names in scope are offered.
+pub fn completions(source: &str, offset: usize) -> Vec<CompletionItem> {
+    let offset = floor_char_boundary(source, offset);
+    let prefix_start = source[..offset]
+        .rfind(|c: char| !is_ident_char(c))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let (program, _) = parse_with_recovery(source);
+
+    if let Some(receiver) = source[..prefix_start].strip_suffix('.') {
+        return member_completions(&program, source, receiver);
+    }
+
+    let construct = enclosing_construct(source, offset);
+    let line_before = &source[source[..prefix_start]
+        .rfind('\n')
+        .map(|i| i + 1)
+        .unwrap_or(0)..prefix_start];
+    if construct == "agent body" && line_before.trim().is_empty() {
+        return KNOWN_AGENT_FIELDS
+            .iter()
+            .map(|field| CompletionItem {
+                label: field.to_string(),
+                kind: completion_kind::PROPERTY,
+                detail: Some("agent field".to_string()),
+                insert_text: Some(format!("{}: ", field)),
+            })
+            .collect();
+    }
+
+    let keywords = if construct == "statement" {
+        TOP_LEVEL_KEYWORDS
+    } else {
+        BLOCK_KEYWORDS
+    };
+    let mut items: Vec<CompletionItem> = keywords
+        .iter()
+        .map(|k| item(k, completion_kind::KEYWORD, None))
+        .collect();
+    items.extend(
+        BUILTINS
+            .iter()
+            .map(|b| item(b, completion_kind::FUNCTION, Some("built-in"))),
+    );
+    for decl in declarations(&program, source) {
+        let kind = match decl.kind {
+            symbol_kind::CLASS => completion_kind::CLASS,
+            symbol_kind::FUNCTION => completion_kind::FUNCTION,
+            symbol_kind::STRUCT => completion_kind::STRUCT,
+            symbol_kind::ENUM => completion_kind::ENUM,
+            symbol_kind::INTERFACE => completion_kind::INTERFACE,
+            _ => completion_kind::VARIABLE,
+        };
+        items.push(CompletionItem {
+            label: decl.name.to_string(),
+            kind,
+            detail: signature(source, decl.statement),
+            insert_text: None,
+        });
+    }
+    for statement in &program.statements {
+        if let Statement::Import(import) = statement {
+            for name in &import.names {
+                items.push(item(name, completion_kind::VARIABLE, Some(&import.path)));
+            }
+        }
+    }
+    for local in locals_at(&program, source, offset) {
+        items.push(item(local.name, completion_kind::VARIABLE, None));
+    }
+    items
+}
+
+fn item(label: &str, kind: u8, detail: Option<&str>) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind,
+        detail: detail.map(str::to_string),
+        insert_text: None,
+    }
+}
+
+/// Methods for the value before a `.`
+fn member_completions(program: &Program, source: &str, receiver: &str) -> Vec<CompletionItem> {
+    let receiver = receiver.trim_end();
+    let receiver_name = &receiver[receiver
+        .rfind(|c: char| !is_ident_char(c))
+        .map(|i| i + 1)
+        .unwrap_or(0)..];
+    let is_agent = declarations(program, source)
+        .iter()
+        .any(|d| d.name == receiver_name && d.kind == symbol_kind::CLASS);
+
+    let method = |name: &str, detail: &str| item(name, completion_kind::METHOD, Some(detail));
+    if is_agent {
+        return AGENT_METHODS
+            .iter()
+            .map(|m| method(m, "agent method"))
+            .collect();
+    }
+    if receiver.ends_with('"') {
+        return STRING_METHODS
+            .iter()
+            .map(|m| method(m, "string method"))
+            .collect();
+    }
+    if receiver.ends_with(']') {
+        return ARRAY_METHODS
+            .iter()
+            .map(|m| method(m, "array method"))
+            .collect();
+    }
+    if receiver.ends_with('}') {
+        return OBJECT_METHODS
+            .iter()
+            .map(|m| method(m, "object method"))
+            .collect();
+    }
+
+    let mut items: Vec<CompletionItem> = STRING_METHODS
+        .iter()
+        .map(|m| {
+            let detail = if ARRAY_METHODS.contains(m) {
+                "string/array method"
+            } else {
+                "string method"
+            };
+            method(m, detail)
+        })
+        .collect();
+    items.extend(
+        ARRAY_METHODS
+            .iter()
+            .filter(|m| !STRING_METHODS.contains(m))
+            .map(|m| method(m, "array method")),
+    );
+    items.extend(
+        OBJECT_METHODS
+            .iter()
+            .filter(|m| !STRING_METHODS.contains(m) && !ARRAY_METHODS.contains(m))
+            .map(|m| method(m, "object method")),
+    );
+    items
+}
+
+/// A top-level declaration
+struct Declaration<'a> {
+    name: &'a str,
+    kind: u8,
+    span: Span,
+    name_span: Span,
+    statement: &'a Statement,
+}
+
+fn declarations<'a>(program: &'a Program, source: &str) -> Vec<Declaration<'a>> {
+    program
+        .statements
+        .iter()
+        .filter_map(|statement| {
+            let (name, kind, span) = match statement {
+                Statement::AgentDecl(a) => (&a.name, symbol_kind::CLASS, &a.span),
+                Statement::ToolDecl(t) => (&t.name, symbol_kind::FUNCTION, &t.span),
+                Statement::FnDecl(f) => (&f.name, symbol_kind::FUNCTION, &f.span),
+                Statement::StructDecl(s) => (&s.name, symbol_kind::STRUCT, &s.span),
+                Statement::EnumDecl(e) => (&e.name, symbol_kind::ENUM, &e.span),
+                Statement::InterfaceDecl(i) => (&i.name, symbol_kind::INTERFACE, &i.span),
+                Statement::ParallelDecl(p) => (&p.name, symbol_kind::OBJECT, &p.span),
+                Statement::LetStmt(l) => (&l.name, symbol_kind::VARIABLE, &l.span),
+                Statement::Import(_) | Statement::TopLevelCall(_) => return None,
+            };
+            Some(Declaration {
+                name,
+                kind,
+                span: span.clone(),
+                name_span: find_word(source, span, name, true),
+                statement,
+            })
+        })
+        .collect()
+}
+
+/// Fields, variants and members shown under a declaration in the outline
+fn child_symbols(source: &str, statement: &Statement) -> Vec<DocumentSymbol> {
+    let symbol = |name: &str, kind: u8, span: &Span| DocumentSymbol {
+        name: name.to_string(),
+        detail: None,
+        kind,
+        range: span_to_range(source, span),
+        selection_range: span_to_range(source, &find_word(source, span, name, false)),
+        children: Vec::new(),
+    };
+
+    match statement {
+        Statement::AgentDecl(agent) => {
+            let mut children: Vec<DocumentSymbol> = agent
+                .fields
+                .iter()
+                .map(|f| symbol(&f.name, symbol_kind::PROPERTY, &f.span))
+                .collect();
+            if let Some(tools) = &agent.tools_expr {
+                children.push(symbol("tools", symbol_kind::PROPERTY, tools.span()));
+            }
+            if let Some(knowledge) = &agent.knowledge_expr {
+                children.push(symbol("knowledge", symbol_kind::PROPERTY, knowledge.span()));
+            }
+            children.sort_by_key(|c| (c.range.start.line, c.range.start.character));
+            children
+        }
+        Statement::StructDecl(s) => s
+            .fields
+            .iter()
+            .map(|f| symbol(&f.name, symbol_kind::FIELD, &f.span))
+            .collect(),
+        Statement::EnumDecl(e) => e
+            .variants
+            .iter()
+            .map(|v| symbol(&v.name, symbol_kind::ENUM_MEMBER, &v.span))
+            .collect(),
+        Statement::InterfaceDecl(i) => i
+            .members
+            .iter()
+            .map(|m| match m {
+                InterfaceMember::Field(f) => symbol(&f.name, symbol_kind::FIELD, &f.span),
+                InterfaceMember::Method(m) => symbol(&m.name, symbol_kind::METHOD, &m.span),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Signature of a tool or function, e.g. `tool search(query: string) -> string`
+fn signature(source: &str, statement: &Statement) -> Option<String> {
+    let (span, body) = match statement {
+        Statement::ToolDecl(t) => (&t.span, &t.body),
+        Statement::FnDecl(f) => (&f.span, &f.body),
+        _ => return None,
+    };
+    Some(one_line(&source[span.start..body.span.start]))
+}
+
+/// Text shown when hovering over a declaration
+fn describe(source: &str, statement: &Statement) -> String {
+    match statement {
+        Statement::ToolDecl(_) | Statement::FnDecl(_) => {
+            signature(source, statement).unwrap_or_default()
+        }
+        Statement::AgentDecl(agent) => describe_agent(source, agent),
+        Statement::LetStmt(l) => format!("let {} = {}", l.name, excerpt(source, l.value.span())),
+        Statement::StructDecl(s) => source[s.span.start..s.span.end].to_string(),
+        Statement::EnumDecl(e) => source[e.span.start..e.span.end].to_string(),
+        Statement::InterfaceDecl(i) => source[i.span.start..i.span.end].to_string(),
+        Statement::ParallelDecl(p) => source[p.span.start..p.span.end].to_string(),
+        Statement::Import(_) | Statement::TopLevelCall(_) => String::new(),
+    }
+}
+
+/// An agent with one line per field
+fn describe_agent(source: &str, agent: &AgentDecl) -> String {
+    let mut fields: Vec<(usize, String)> = agent
+        .fields
+        .iter()
+        .map(|f| {
+            (
+                f.span.start,
+                format!("{}: {}", f.name, excerpt(source, f.value.span())),
+            )
+        })
+        .collect();
+    if let Some(tools) = &agent.tools_expr {
+        fields.push((
+            tools.span().start,
+            format!("tools: {}", excerpt(source, tools.span())),
+        ));
+    }
+    if let Some(knowledge) = &agent.knowledge_expr {
+        fields.push((
+            knowledge.span().start,
+            format!("knowledge: {}", excerpt(source, knowledge.span())),
+        ));
+    }
+    fields.sort_by_key(|(start, _)| *start);
+    for (field, ty) in [("input", &agent.input), ("output", &agent.output)] {
+        if let Some(ty) = ty {
+            let ty = match ty {
+                OutputType::Named(name) => name.clone(),
+                OutputType::Inline(_) => "{ ... }".to_string(),
+            };
+            fields.push((usize::MAX, format!("{}: {}", field, ty)));
+        }
+    }
+
+    let mut text = format!("agent {} {{\n", agent.name);
+    for (_, field) in fields {
+        text.push_str(&format!("    {}\n", field));
+    }
+    text.push('}');
+    text
+}
+
+/// A variable, parameter or loop variable visible at some offset
+struct Local<'a> {
+    name: &'a str,
+    name_span: Span,
+    description: String,
+}
+
+fn find_local<'a>(
+    program: &'a Program,
+    source: &str,
+    name: &str,
+    offset: usize,
+) -> Option<Local<'a>> {
+    locals_at(program, source, offset)
+        .into_iter()
+        .rev()
+        .find(|local| local.name == name)
+}
+
+/// Locals in scope at `offset`, outermost first
+fn locals_at<'a>(program: &'a Program, source: &str, offset: usize) -> Vec<Local<'a>> {
+    let mut locals = Vec::new();
+    for statement in &program.statements {
+        let (params, body) = match statement {
+            Statement::ToolDecl(t) => (&t.params, &t.body),
+            Statement::FnDecl(f) => (&f.params, &f.body),
+            _ => continue,
+        };
+        if !contains(&body.span, offset) {
+            continue;
+        }
+        for param in params {
+            locals.push(Local {
+                name: &param.name,
+                name_span: find_word(source, &param.span, &param.name, false),
+                description: format!(
+                    "(parameter) {}",
+                    one_line(&source[param.span.start..param.span.end])
+                ),
+            });
+        }
+        collect_block_locals(body, source, offset, &mut locals);
+    }
+    locals
+}
+
+fn collect_block_locals<'a>(
+    block: &'a Block,
+    source: &str,
+    offset: usize,
+    locals: &mut Vec<Local<'a>>,
+) {
+    for statement in &block.statements {
+        match statement {
+            BlockStmt::Let(l) if l.span.start < offset => locals.push(Local {
+                name: &l.name,
+                name_span: find_word(source, &l.span, &l.name, true),
+                description: format!("let {} = {}", l.name, excerpt(source, l.value.span())),
+            }),
+            BlockStmt::If(i) => {
+                for block in std::iter::once(&i.then_block).chain(i.else_block.as_ref()) {
+                    if contains(&block.span, offset) {
+                        collect_block_locals(block, source, offset, locals);
+                    }
+                }
+            }
+            BlockStmt::For(f) if contains(&f.body.span, offset) => {
+                for name in std::iter::once(&f.variable).chain(f.value_variable.as_ref()) {
+                    locals.push(Local {
+                        name,
+                        name_span: find_word(source, &f.span, name, true),
+                        description: format!("(loop variable) {}", name),
+                    });
+                }
+                collect_block_locals(&f.body, source, offset, locals);
+            }
+            BlockStmt::While(w) if contains(&w.body.span, offset) => {
+                collect_block_locals(&w.body, source, offset, locals);
+            }
+            BlockStmt::Try(t) => {
+                if contains(&t.try_block.span, offset) {
+                    collect_block_locals(&t.try_block, source, offset, locals);
+                }
+                if contains(&t.catch_block.span, offset) {
+                    let catch_start = source[t.try_block.span.end..t.catch_block.span.start]
+                        .find("catch")
+                        .map(|i| t.try_block.span.end + i)
+                        .unwrap_or(t.catch_block.span.start);
+                    let catch_span = Span::new(catch_start, t.catch_block.span.start);
+                    locals.push(Local {
+                        name: &t.error_var,
+                        name_span: find_word(source, &catch_span, &t.error_var, true),
+                        description: format!(
+                            "(error) {}: {}",
+                            t.error_var,
+                            t.error_type.as_deref().unwrap_or("Error")
+                        ),
+                    });
+                    collect_block_locals(&t.catch_block, source, offset, locals);
+                }
+                if let Some(finally) = &t.finally_block {
+                    if contains(&finally.span, offset) {
+                        collect_block_locals(finally, source, offset, locals);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The standard library function an imported name refers to
+fn find_std_import(program: &Program, name: &str) -> Option<&'static StdFunction> {
+    program
+        .statements
+        .iter()
+        .find_map(|statement| match statement {
+            Statement::Import(import) if import.names.iter().any(|n| n == name) => {
+                std_module(&import.path)?.function(name)
+            }
+            _ => None,
+        })
+}
+
+/// Read the file an imported name comes from
+fn find_import(program: &Program, file: Option<&Path>, name: &str) -> Option<(PathBuf, String)> {
+    program
+        .statements
+        .iter()
+        .find_map(|statement| match statement {
+            Statement::Import(import)
+                if import.names.iter().any(|n| n == name) && !is_std_import(&import.path) =>
+            {
+                let path = resolve_import_path(file.unwrap_or(Path::new("")), &import.path);
+                let source = std::fs::read_to_string(&path).ok()?;
+                Some((path, source))
+            }
+            _ => None,
+        })
+}
+
+/// The identifier under (or just before) `offset`
+fn word_at_offset(source: &str, offset: usize) -> Option<(&str, Span)> {
+    let offset = floor_char_boundary(source, offset);
+    let start = source[..offset]
+        .rfind(|c: char| !is_ident_char(c))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let end = source[offset..]
+        .find(|c: char| !is_ident_char(c))
+        .map(|i| offset + i)
+        .unwrap_or(source.len());
+    let word = &source[start..end];
+    if word.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        Some((word, Span::new(start, end)))
+    } else {
+        None
+    }
+}
+
+/// Span of the first whole-word occurrence of `name` inside `span`
+///
+/// With `skip_keyword`, the leading keyword (`agent`, `fn`, `let`, ...) is skipped.
+fn find_word(source: &str, span: &Span, name: &str, skip_keyword: bool) -> Span {
+    let end = span.end.min(source.len());
+    let text = &source[span.start..end];
+    let mut search = if skip_keyword {
+        text.find(|c: char| !is_ident_char(c)).unwrap_or(0)
+    } else {
+        0
+    };
+    while let Some(i) = text[search..].find(name) {
+        let start = search + i;
+        let end = start + name.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        if !before.map(is_ident_char).unwrap_or(false) && !after.map(is_ident_char).unwrap_or(false)
+        {
+            return Span::new(span.start + start, span.start + end);
+        }
+        search = end;
+    }
+    Span::new(span.start, span.start)
+}
+
+fn contains(span: &Span, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Source of an expression on one line, shortened if long
+fn excerpt(source: &str, span: &Span) -> String {
+    let text = one_line(&source[span.start..span.end.min(source.len())]);
+    if text.chars().count() > 60 {
+        format!("{}...", text.chars().take(57).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// Collapse runs of whitespace into single spaces
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn code_block(text: &str) -> String {
+    format!("```gent\n{}\n```", text)
+}
+
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
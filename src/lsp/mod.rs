@@ -0,0 +1,278 @@
+//! Language server for GENT (`gent lsp`)
+//!
+//! Speaks the Language Server Protocol over stdin/stdout and provides live
+//! diagnostics, go-to-definition, hover, completion and document symbols.
+//! Documents are synchronised in full on every change.
+
+pub mod analysis;
+pub mod protocol;
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use analysis::Position;
+use protocol::{read_body, write_message};
+
+/// JSON-RPC error code for messages that are not valid JSON
+const PARSE_ERROR: i64 = -32700;
+
+/// JSON-RPC error code for unknown methods
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Language server state: the open documents, keyed by URI
+#[derive(Debug, Default)]
+pub struct Server {
+    documents: HashMap<String, String>,
+    shutdown_requested: bool,
+    exited: bool,
+}
+
+impl Server {
+    /// Create a server with no open documents
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the client has sent `exit`
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Whether the client asked the server to shut down before exiting
+    pub fn shutdown_requested(&self) -> bool {
+        self.shutdown_requested
+    }
+
+    /// Handle one incoming message, returning the messages to send back
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match (
+            message.get("method").and_then(Value::as_str),
+            message.get("id"),
+        ) {
+            (Some(method), Some(id)) => vec![self.handle_request(method, id.clone(), &params)],
+            (Some(method), None) => self.handle_notification(method, &params),
+            // Responses to requests we never send
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, id: Value, params: &Value) -> Value {
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "gent", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Value::Null
+            }
+            "textDocument/definition" => self.definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/completion" => self.completion(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            _ => {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": METHOD_NOT_FOUND,
+                        "message": format!("Method not found: {}", method),
+                    },
+                });
+            }
+        };
+        json!({ "jsonrpc": "2.0", "id": id, "result": result })
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.clone(), text.to_string());
+                vec![self.publish_diagnostics(&uri)]
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole document
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+                vec![self.publish_diagnostics(&uri)]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                vec![diagnostics_notification(&uri, json!([]))]
+            }
+            "exit" => {
+                self.exited = true;
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let diagnostics = self
+            .documents
+            .get(uri)
+            .map(|text| json!(analysis::diagnostics(text)))
+            .unwrap_or_else(|| json!([]));
+        diagnostics_notification(uri, diagnostics)
+    }
+
+    /// The document and byte offset a position request refers to
+    fn document_at<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a str, usize)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let text = self.documents.get(uri)?;
+        let position = Position {
+            line: params["position"]["line"].as_u64()? as u32,
+            character: params["position"]["character"].as_u64()? as u32,
+        };
+        Some((uri, text, analysis::position_to_offset(text, position)))
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let Some((uri, text, offset)) = self.document_at(params) else {
+            return Value::Null;
+        };
+        let path = uri_to_path(uri);
+        match analysis::definition(text, path.as_deref(), offset) {
+            Some(location) => {
+                let target = match &location.file {
+                    Some(file) => path_to_uri(file),
+                    None => uri.to_string(),
+                };
+                json!({ "uri": target, "range": location.range })
+            }
+            None => Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let Some((uri, text, offset)) = self.document_at(params) else {
+            return Value::Null;
+        };
+        let path = uri_to_path(uri);
+        match analysis::hover(text, path.as_deref(), offset) {
+            Some(markdown) => json!({ "contents": { "kind": "markdown", "value": markdown } }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        match self.document_at(params) {
+            Some((_, text, offset)) => json!(analysis::completions(text, offset)),
+            None => json!([]),
+        }
+    }
+
+    fn document_symbols(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match self.documents.get(uri) {
+            Some(text) => json!(analysis::document_symbols(text)),
+            None => json!([]),
+        }
+    }
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Serve requests from `reader` until the client exits or closes the stream
+///
+/// A message that is not valid JSON is logged to stderr and answered with
+/// a parse error, and the server keeps serving. Returns whether the client
+/// shut the server down cleanly (`shutdown` before `exit`).
+pub fn run<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<bool> {
+    let mut server = Server::new();
+    while let Some(body) = read_body(reader)? {
+        let message = match serde_json::from_slice::<Value>(&body) {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("gent lsp: ignoring malformed message: {}", e);
+                write_message(
+                    writer,
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "id": Value::Null,
+                        "error": { "code": PARSE_ERROR, "message": format!("Parse error: {}", e) },
+                    }),
+                )?;
+                continue;
+            }
+        };
+        for reply in server.handle(&message) {
+            write_message(writer, &reply)?;
+        }
+        if server.has_exited() {
+            break;
+        }
+    }
+    Ok(server.shutdown_requested())
+}
+
+/// Serve requests over stdin/stdout
+pub fn run_stdio() -> io::Result<bool> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    run(&mut stdin.lock(), &mut stdout.lock())
+}
+
+/// Convert a `file://` URI into a path
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    Some(PathBuf::from(
+        String::from_utf8_lossy(&decoded).into_owned(),
+    ))
+}
+
+/// Convert a path into a `file://` URI
+pub fn path_to_uri(path: &Path) -> String {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
@@ -0,0 +1,62 @@
+//! JSON-RPC message framing for the language server
+//!
+//! Messages are JSON bodies preceded by a `Content-Length` header, as
+//! described by the Language Server Protocol base protocol.
+
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Read one message, returning None at end of input
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Value>> {
+    let Some(body) = read_body(reader)? else {
+        return Ok(None);
+    };
+    let message =
+        serde_json::from_slice(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some(message))
+}
+
+/// Read the body of one message without parsing it, returning None at end
+/// of input
+///
+/// The whole body is consumed, so the next message can be read even if
+/// this one is not valid JSON.
+pub fn read_body<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse::<usize>().map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("bad Content-Length: {}", e),
+                    )
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Write one message with its `Content-Length` header
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
@@ -1,24 +1,36 @@
 //! GENT CLI - A programming language for AI agents
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::sync::Arc;
 
 use gent::config::Config;
 use gent::errors::{ErrorReporter, GentError};
-use gent::interpreter::evaluate_with_output;
+use gent::formatter::format_program;
+use gent::interpreter::{evaluate_with_args, Session};
 use gent::logging::{GentLogger, LogLevel, Logger};
-use gent::parser::parse;
-use gent::runtime::{ProviderFactory, ToolRegistry};
+use gent::parser::{check_agent_fields, check_nullable_access, parse_with_recovery, Program};
+use gent::runtime::memory::DEFAULT_MEMORY_DIR;
+use gent::runtime::rag::embeddings::MockEmbeddings;
+use gent::runtime::{MemoryStore, ProviderFactory, ToolRegistry};
 
 #[derive(Parser, Debug)]
 #[command(name = "gent")]
 #[command(author, version, about = "A programming language for AI agents", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the .gnt file to execute
-    file: PathBuf,
+    file: Option<PathBuf>,
+
+    /// Arguments for the script (read with `args()` from "std:env")
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
 
     /// Use mock LLM (for testing)
     #[arg(long)]
@@ -41,6 +53,48 @@ struct Cli {
     quiet: bool,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Start the language server on stdin/stdout
+    Lsp,
+    /// Format .gnt files in place (or stdin to stdout when no paths are given)
+    Fmt {
+        /// Files or directories to format
+        paths: Vec<PathBuf>,
+
+        /// Report unformatted files instead of rewriting them
+        #[arg(long)]
+        check: bool,
+    },
+    /// Start an interactive session
+    Repl {
+        /// Use mock LLM (for testing)
+        #[arg(long)]
+        mock: bool,
+
+        /// Custom mock response
+        #[arg(long)]
+        mock_response: Option<String>,
+    },
+    /// List the facts an agent remembers
+    Memory {
+        /// Name of the agent
+        agent: String,
+
+        /// Memory directory of the agent's `memory:` field
+        #[arg(long, default_value = DEFAULT_MEMORY_DIR)]
+        dir: PathBuf,
+
+        /// Forget the fact with this key
+        #[arg(long, conflicts_with = "clear")]
+        forget: Option<String>,
+
+        /// Forget every fact
+        #[arg(long)]
+        clear: bool,
+    },
+}
+
 impl Cli {
     fn effective_log_level(&self) -> LogLevel {
         if self.quiet {
@@ -61,9 +115,18 @@ async fn main() -> ExitCode {
     let log_level = cli.effective_log_level();
     let logger: Arc<dyn Logger> = Arc::new(GentLogger::new(log_level));
 
+    if let Some(command) = &cli.command {
+        return run_command(command, logger).await;
+    }
+
+    let Some(file) = &cli.file else {
+        eprintln!("error: No file given. Usage: gent <FILE> or gent <COMMAND> (see --help)");
+        return ExitCode::FAILURE;
+    };
+
     // Load source first so we can use it for error reporting
-    let filename = cli.file.display().to_string();
-    let source = match fs::read_to_string(&cli.file) {
+    let filename = file.display().to_string();
+    let source = match fs::read_to_string(file) {
         Ok(s) => s,
         Err(e) => {
             eprintln!("error: Could not read file '{}': {}", filename, e);
@@ -73,7 +136,30 @@ async fn main() -> ExitCode {
 
     let reporter = ErrorReporter::new(&source, &filename);
 
-    if let Err(e) = run(&cli, &source, logger.as_ref()).await {
+    // Report every syntax error before giving up
+    logger.log(
+        LogLevel::Debug,
+        "cli",
+        &format!("Parsing {} bytes", source.len()),
+    );
+    let (program, errors) = parse_with_recovery(&source);
+    if !errors.is_empty() {
+        eprint!("{}", reporter.format_all(&errors));
+        return ExitCode::FAILURE;
+    }
+
+    // Misspelled agent fields and unchecked use of nullable parameters are
+    // reported but do not stop the run
+    if !cli.quiet {
+        for field in check_agent_fields(&program) {
+            eprint!("{}", reporter.format_warning(&field.message(), &field.span));
+        }
+        for access in check_nullable_access(&program) {
+            eprint!("{}", reporter.format_warning(&access.message(), &access.span));
+        }
+    }
+
+    if let Err(e) = run(&cli, &program, logger.as_ref()).await {
         eprint!("{}", reporter.format(&e));
         return ExitCode::FAILURE;
     }
@@ -81,13 +167,188 @@ async fn main() -> ExitCode {
     ExitCode::SUCCESS
 }
 
-async fn run(cli: &Cli, source: &str, logger: &dyn Logger) -> Result<(), GentError> {
-    logger.log(
-        LogLevel::Debug,
-        "cli",
-        &format!("Parsing {} bytes", source.len()),
-    );
-    let program = parse(source)?;
+async fn run_command(command: &Command, logger: Arc<dyn Logger>) -> ExitCode {
+    match command {
+        Command::Lsp => match gent::lsp::run_stdio() {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(e) => {
+                eprintln!("error: Language server failed: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Fmt { paths, check } => fmt(paths, *check),
+        Command::Repl { mock, mock_response } => {
+            let provider_factory =
+                provider_factory(*mock, mock_response.as_deref(), logger.as_ref());
+            let mut session = Session::new(provider_factory, logger);
+            println!("GENT {} (type :help for commands)", env!("CARGO_PKG_VERSION"));
+            let stdin = io::stdin();
+            let result =
+                gent::repl::run(&mut session, stdin.lock(), &mut io::stdout(), &mut io::stderr())
+                    .await;
+            match result {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Memory {
+            agent,
+            dir,
+            forget,
+            clear,
+        } => memory(agent, dir, forget.as_deref(), *clear).await,
+    }
+}
+
+fn fmt(paths: &[PathBuf], check: bool) -> ExitCode {
+    if paths.is_empty() {
+        let mut source = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut source) {
+            eprintln!("error: Could not read stdin: {}", e);
+            return ExitCode::FAILURE;
+        }
+        let Some(formatted) = format_file(&source, "<stdin>") else {
+            return ExitCode::FAILURE;
+        };
+        if check {
+            return if formatted == source {
+                ExitCode::SUCCESS
+            } else {
+                println!("Would reformat: <stdin>");
+                ExitCode::FAILURE
+            };
+        }
+        print!("{}", formatted);
+        return ExitCode::SUCCESS;
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        collect_gnt_files(path, &mut files);
+    }
+
+    let mut ok = true;
+    for file in &files {
+        let filename = file.display().to_string();
+        let source = match fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("error: Could not read file '{}': {}", filename, e);
+                ok = false;
+                continue;
+            }
+        };
+        let Some(formatted) = format_file(&source, &filename) else {
+            ok = false;
+            continue;
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("Would reformat: {}", filename);
+            ok = false;
+        } else if let Err(e) = fs::write(file, formatted) {
+            eprintln!("error: Could not write file '{}': {}", filename, e);
+            ok = false;
+        }
+    }
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Format one file, reporting its syntax errors if it does not parse
+fn format_file(source: &str, filename: &str) -> Option<String> {
+    let (program, errors) = parse_with_recovery(source);
+    if !errors.is_empty() {
+        let reporter = ErrorReporter::new(source, filename);
+        eprint!("{}", reporter.format_all(&errors));
+        return None;
+    }
+    Some(format_program(source, &program))
+}
+
+/// Collect `path` if it is a file, or every .gnt file under it if it is a
+/// directory (skipping hidden directories such as `.gent_index`)
+fn collect_gnt_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        files.push(path.to_path_buf());
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    entries.sort();
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .map(|name| name.to_string_lossy().starts_with('.'))
+            .unwrap_or(false);
+        if entry.is_dir() {
+            if !hidden {
+                collect_gnt_files(&entry, files);
+            }
+        } else if entry.extension().map(|ext| ext == "gnt").unwrap_or(false) {
+            files.push(entry);
+        }
+    }
+}
+
+async fn memory(agent: &str, dir: &Path, forget: Option<&str>, clear: bool) -> ExitCode {
+    // Listing and forgetting facts never embeds text, so any provider will do
+    let mut store = match MemoryStore::open(dir, agent, Arc::new(MockEmbeddings::new())) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = if clear {
+        store
+            .clear()
+            .await
+            .map(|()| format!("Forgot everything {} remembered", agent))
+    } else if let Some(key) = forget {
+        match store.forget(key).await {
+            Ok(true) => Ok(format!("Forgot {}", key)),
+            Ok(false) => Err(format!("{} does not remember '{}'", agent, key)),
+            Err(e) => Err(e),
+        }
+    } else if store.facts().is_empty() {
+        Ok(format!("{} remembers nothing", agent))
+    } else {
+        let facts: Vec<String> = store
+            .facts()
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        Ok(facts.join("\n"))
+    };
+
+    match result {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: &Cli, program: &Program, logger: &dyn Logger) -> Result<(), GentError> {
     logger.log(
         LogLevel::Debug,
         "cli",
@@ -96,9 +357,25 @@ async fn run(cli: &Cli, source: &str, logger: &dyn Logger) -> Result<(), GentErr
 
     let mut tools = ToolRegistry::with_builtins();
 
-    let provider_factory = if cli.mock {
+    let provider_factory = provider_factory(cli.mock, cli.mock_response.as_deref(), logger);
+
+    let outputs =
+        evaluate_with_args(program, cli.args.clone(), &provider_factory, &mut tools, logger).await?;
+
+    // Print outputs
+    for output in outputs {
+        println!("{}", output);
+    }
+
+    Ok(())
+}
+
+/// Create the provider factory for `--mock` / `--mock-response`, or from the
+/// configured providers
+fn provider_factory(mock: bool, mock_response: Option<&str>, logger: &dyn Logger) -> ProviderFactory {
+    if mock {
         logger.log(LogLevel::Info, "cli", "Using mock LLM");
-        if let Some(response) = &cli.mock_response {
+        if let Some(response) = mock_response {
             ProviderFactory::mock_with_response(response)
         } else {
             ProviderFactory::mock()
@@ -107,14 +384,5 @@ async fn run(cli: &Cli, source: &str, logger: &dyn Logger) -> Result<(), GentErr
         let config = Config::load();
         logger.log(LogLevel::Debug, "cli", "Using configured providers");
         ProviderFactory::new(config)
-    };
-
-    let outputs = evaluate_with_output(&program, &provider_factory, &mut tools, logger).await?;
-
-    // Print outputs
-    for output in outputs {
-        println!("{}", output);
     }
-
-    Ok(())
 }
@@ -65,6 +65,12 @@ pub enum TypeName {
     Object,
     Array,
     Any,
+    /// A struct or enum, by name
+    Named(String),
+    /// `T[]`: an array of `T`
+    ArrayOf(Box<TypeName>),
+    /// `T?`: a `T` or `null`
+    Nullable(Box<TypeName>),
 }
 
 /// Field types for structured output schemas
@@ -76,6 +82,8 @@ pub enum FieldType {
     Array(Box<FieldType>),
     Object(Vec<StructField>),
     Named(String), // reference to a struct
+    /// `T?`: a `T` or `null`
+    Nullable(Box<FieldType>),
 }
 
 /// A field in a struct or inline object type
@@ -239,6 +247,7 @@ pub struct AgentDecl {
     pub tools_expr: Option<Expression>,     // From `tools:` field
     pub knowledge_expr: Option<Expression>, // From `knowledge:` field (auto-RAG)
     pub output: Option<OutputType>,
+    pub input: Option<OutputType>, // From `input:` field (used when called as a tool)
     pub span: Span,
 }
 
@@ -254,6 +263,8 @@ pub struct AgentField {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ToolDecl {
     pub name: String,
+    /// Text of the `///` doc comment, without `@param` lines
+    pub description: Option<String>,
     pub params: Vec<Param>,
     pub return_type: Option<TypeName>,
     pub body: Block,
@@ -275,6 +286,10 @@ pub struct FnDecl {
 pub struct Param {
     pub name: String,
     pub type_name: TypeName,
+    /// Value used when the argument is left out (tool parameters only)
+    pub default: Option<Expression>,
+    /// Description from an `@param` line in the tool's doc comment
+    pub description: Option<String>,
     pub span: Span,
 }
 
@@ -349,6 +364,7 @@ pub enum BlockStmt {
     Let(LetStmt),
     Assignment(AssignmentStmt),
     Return(ReturnStmt),
+    Throw(ThrowStmt),
     If(IfStmt),
     For(ForStmt),
     While(WhileStmt),
@@ -381,6 +397,13 @@ pub struct ReturnStmt {
     pub span: Span,
 }
 
+/// Throw statement: `throw expr`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrowStmt {
+    pub value: Expression,
+    pub span: Span,
+}
+
 /// If statement: `if cond { ... } else { ... }`
 #[derive(Debug, Clone, PartialEq)]
 pub struct IfStmt {
@@ -393,8 +416,10 @@ pub struct IfStmt {
 /// For loop statement
 #[derive(Debug, Clone, PartialEq)]
 pub struct ForStmt {
-    /// Loop variable name
+    /// Loop variable name (the key or index in `for (k, v) in ...`)
     pub variable: String,
+    /// Second loop variable in `for (k, v) in ...`, bound to the value
+    pub value_variable: Option<String>,
     /// Expression to iterate over
     pub iterable: Expression,
     /// Loop body
@@ -414,15 +439,19 @@ pub struct WhileStmt {
     pub span: Span,
 }
 
-/// Try/catch statement
+/// Try/catch statement: `try { ... } catch (e: Kind) { ... } finally { ... }`
 #[derive(Debug, Clone, PartialEq)]
 pub struct TryStmt {
     /// The try block
     pub try_block: Block,
     /// The error variable name in catch
     pub error_var: String,
+    /// Error kind the catch is restricted to (None = catch everything)
+    pub error_type: Option<String>,
     /// The catch block
     pub catch_block: Block,
+    /// Block that always runs after try/catch
+    pub finally_block: Option<Block>,
     /// Source location
     pub span: Span,
 }
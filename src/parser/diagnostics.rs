@@ -0,0 +1,601 @@
+//! Syntax error recovery and friendly diagnostics
+//!
+//! When a program fails to parse, parsing resumes at the next top-level
+//! declaration so that every syntax error in the file can be reported at once.
+//! Raw pest rule names are translated into messages such as
+//! "expected `}` to close agent body".
+
+use pest::error::{ErrorVariant, InputLocation};
+use pest::Parser;
+
+use super::{parse_block_stmt, parse_statement, BlockStmt, Program, Statement};
+use crate::errors::{did_you_mean, did_you_mean_help, GentError, GentResult, Span};
+use crate::lexer::{GentParser, Rule};
+
+/// Keywords that can start a top-level statement
+pub const TOP_LEVEL_KEYWORDS: &[&str] = &[
+    "import",
+    "interface",
+    "struct",
+    "enum",
+    "fn",
+    "tool",
+    "agent",
+    "parallel",
+    "let",
+];
+
+/// Agent fields understood by the interpreter
+///
+/// Unknown fields are ignored for forward compatibility, unless they look like
+/// a typo of one of these.
+pub const KNOWN_AGENT_FIELDS: &[&str] = &[
+    "prompt",
+    "systemPrompt",
+    "userPrompt",
+    "tools",
+    "knowledge",
+    "output",
+    "input",
+    "model",
+    "provider",
+    "max_steps",
+    "maxSteps",
+    "output_retries",
+    "outputRetries",
+    "output_instructions",
+    "outputInstructions",
+    "retry_prompt",
+    "retryPrompt",
+    "dangerouslySkipPermissions",
+    "memory",
+    "contextStrategy",
+    "contextLimit",
+    "handoffs",
+    "maxHandoffs",
+    "toolConcurrency",
+    "toolTimeout",
+    "toolChoice",
+    "finishTool",
+];
+
+/// Parse GENT source code, recovering from syntax errors
+///
+/// Returns every statement that could be parsed together with all syntax
+/// errors found. After an error, parsing resumes at the next line that starts
+/// a top-level declaration.
+pub fn parse_with_recovery(source: &str) -> (Program, Vec<GentError>) {
+    let mut statements = Vec::new();
+    let mut errors = Vec::new();
+
+    match GentParser::parse(Rule::program, source) {
+        Ok(pairs) => {
+            for pair in pairs.flat_map(|p| p.into_inner()) {
+                if pair.as_rule() == Rule::statement {
+                    push_statement(parse_statement(pair), &mut statements, &mut errors);
+                }
+            }
+        }
+        Err(_) => {
+            // Everything before the statement being parsed is blanked out so
+            // spans stay byte offsets into `source`. Positions only move
+            // forward, so each byte of the copy is blanked once.
+            let mut masked = source.to_string();
+            let mut blanked = 0;
+            let mut pos = skip_trivia(source, 0);
+            while pos < source.len() {
+                masked.replace_range(blanked..pos, &" ".repeat(pos - blanked));
+                blanked = pos;
+                match parse_statement_at(&masked) {
+                    Ok((result, end)) => {
+                        push_statement(result, &mut statements, &mut errors);
+                        pos = skip_trivia(source, end);
+                    }
+                    Err(error) => {
+                        let resume = recovery_point(source, pos);
+                        errors.push(syntax_error(source, pos, resume, &error, true));
+                        pos = skip_trivia(source, resume);
+                    }
+                }
+            }
+        }
+    }
+
+    let program = Program {
+        statements,
+        span: Span::new(0, source.len()),
+    };
+    (program, errors)
+}
+
+/// Parse block statements on their own, such as `x = x + 1` or `Bot.run()`,
+/// outside of any function body
+pub fn parse_block_statements(source: &str) -> GentResult<Vec<BlockStmt>> {
+    let mut pairs = GentParser::parse(Rule::block_input, source).map_err(|error| {
+        let start = skip_trivia(source, 0);
+        syntax_error(source, start, source.len(), &error, false)
+    })?;
+    let input = pairs.next().expect("block_input always matches the whole input");
+    input
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::block_stmt)
+        .map(parse_block_stmt)
+        .collect()
+}
+
+fn push_statement(
+    result: GentResult<Statement>,
+    statements: &mut Vec<Statement>,
+    errors: &mut Vec<GentError>,
+) {
+    match result {
+        Ok(statement) => statements.push(statement),
+        Err(e) => errors.push(e),
+    }
+}
+
+/// A parsed statement (or AST error) and the offset where it ends
+type ParsedStatement = (GentResult<Statement>, usize);
+
+/// Parse the first statement of `masked`, source whose earlier statements
+/// have been blanked out
+fn parse_statement_at(masked: &str) -> Result<ParsedStatement, Box<pest::error::Error<Rule>>> {
+    let mut pairs = GentParser::parse(Rule::statement_at, masked).map_err(Box::new)?;
+    let statement = pairs
+        .next()
+        .and_then(|p| p.into_inner().find(|p| p.as_rule() == Rule::statement))
+        .expect("statement_at always contains a statement");
+    let end = statement.as_span().end();
+    Ok((parse_statement(statement), end))
+}
+
+/// Agent field that looks like a misspelling of a known field
+///
+/// Agents may have fields the language does not know, so this is a warning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownAgentField {
+    /// Field name as written
+    pub name: String,
+    /// Known field it is probably meant to be
+    pub suggestion: String,
+    /// Where the field name is
+    pub span: Span,
+}
+
+impl UnknownAgentField {
+    /// Warning text for the field
+    pub fn message(&self) -> String {
+        format!(
+            "unknown agent field `{}`; {}",
+            self.name,
+            did_you_mean_help(&self.suggestion)
+        )
+    }
+}
+
+/// Find agent fields that look like misspellings of known fields
+pub fn check_agent_fields(program: &Program) -> Vec<UnknownAgentField> {
+    let mut found = Vec::new();
+    for statement in &program.statements {
+        let Statement::AgentDecl(agent) = statement else {
+            continue;
+        };
+        for field in &agent.fields {
+            if KNOWN_AGENT_FIELDS.contains(&field.name.as_str()) {
+                continue;
+            }
+            if let Some(suggestion) = did_you_mean(&field.name, KNOWN_AGENT_FIELDS.iter().copied())
+            {
+                found.push(UnknownAgentField {
+                    name: field.name.clone(),
+                    suggestion: suggestion.to_string(),
+                    span: Span::new(field.span.start, field.span.start + field.name.len()),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// Build a readable syntax error for a statement that failed to parse
+///
+/// `top_level` says whether the statement had to be a declaration.
+fn syntax_error(
+    source: &str,
+    stmt_start: usize,
+    resume: usize,
+    error: &pest::error::Error<Rule>,
+    top_level: bool,
+) -> GentError {
+    let err_pos = match error.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    }
+    .min(source.len());
+
+    // The statement does not start with anything we recognise
+    let leading = word_at(source, stmt_start);
+    if top_level && !TOP_LEVEL_KEYWORDS.contains(&leading) && !is_call_start(source, stmt_start) {
+        let span = Span::new(stmt_start, stmt_start + leading.len().max(1));
+        if let Some(suggestion) = did_you_mean(leading, TOP_LEVEL_KEYWORDS.iter().copied()) {
+            return GentError::SyntaxError {
+                message: format!("unknown statement `{}`", leading),
+                span,
+            }
+            .with_help(did_you_mean_help(suggestion));
+        }
+        return GentError::SyntaxError {
+            message: format!(
+                "expected a top-level declaration (`agent`, `tool`, `fn`, `struct`, `enum`, `interface`, `parallel`, `import` or `let`), found {}",
+                describe_token(source, stmt_start)
+            ),
+            span,
+        };
+    }
+
+    // A brace is still open when the next declaration (or the end of file) begins
+    let boundary = if resume < source.len() && resume <= err_pos {
+        Some(resume)
+    } else if skip_trivia(source, err_pos) >= source.len() {
+        Some(source.len())
+    } else {
+        None
+    };
+    if let Some(boundary) = boundary {
+        let open = open_delimiters(source, stmt_start, boundary);
+        if let Some(&(pos, delimiter)) = open.last() {
+            let label = delimiter_label(source, stmt_start, &open);
+            let line = source[..pos].matches('\n').count() + 1;
+            let span = token_span(source, boundary);
+            return GentError::SyntaxError {
+                message: format!(
+                    "expected `{}` to close {}, found {}",
+                    closing(delimiter),
+                    label,
+                    describe_token(source, boundary)
+                ),
+                span,
+            }
+            .with_help(format!("the {} starts on line {}", label, line));
+        }
+    }
+
+    let open = open_delimiters(source, stmt_start, err_pos);
+    let found = describe_token(source, err_pos);
+    let span = token_span(source, err_pos);
+
+    // A field name that is not followed by `:`
+    if let Some((name, end)) = field_name_near(source, err_pos) {
+        let has_fields = matches!(
+            delimiter_label(source, stmt_start, &open),
+            "agent body" | "struct body" | "interface body" | "parallel block" | "object literal"
+        );
+        if has_fields && !source[end..].trim_start().starts_with(':') {
+            let next = skip_trivia(source, end);
+            return GentError::SyntaxError {
+                message: format!(
+                    "expected `:` after `{}`, found {}",
+                    name,
+                    describe_token(source, next)
+                ),
+                span: token_span(source, next),
+            };
+        }
+    }
+
+    let positives = match &error.variant {
+        ErrorVariant::ParsingError { positives, .. } => positives.clone(),
+        ErrorVariant::CustomError { .. } => Vec::new(),
+    };
+    let mut expected: Vec<&str> = Vec::new();
+    for rule in positives {
+        if let Some(description) = describe_rule(rule) {
+            if !expected.contains(&description) {
+                expected.push(description);
+            }
+        }
+    }
+    let in_body = expected.iter().any(|e| {
+        matches!(
+            *e,
+            "an agent field"
+                | "a statement"
+                | "a struct field"
+                | "an enum variant"
+                | "a match arm"
+                | "an object field"
+                | "an interface member"
+                | "a parallel field"
+        )
+    });
+    if in_body && open.last().is_some_and(|&(_, d)| d == b'{') {
+        expected.push("`}`");
+    }
+
+    let message = match expected.as_slice() {
+        [] => format!("unexpected {}", found),
+        [only] => format!("expected {}, found {}", only, found),
+        [rest @ .., last] => format!("expected {} or {}, found {}", rest.join(", "), last, found),
+    };
+    GentError::SyntaxError { message, span }
+}
+
+/// Describe a grammar rule for users, or None for rules that are just noise
+fn describe_rule(rule: Rule) -> Option<&'static str> {
+    let description = match rule {
+        Rule::expression
+        | Rule::logical_or
+        | Rule::logical_and
+        | Rule::equality
+        | Rule::comparison
+        | Rule::additive
+        | Rule::multiplicative
+        | Rule::unary
+        | Rule::postfix
+        | Rule::primary
+        | Rule::lambda
+        | Rule::range_expr => "an expression",
+        Rule::identifier | Rule::for_binding => "an identifier",
+        Rule::block => "a block",
+        Rule::block_stmt
+        | Rule::let_stmt
+        | Rule::assignment_stmt
+        | Rule::return_stmt
+        | Rule::throw_stmt
+        | Rule::if_stmt
+        | Rule::for_stmt
+        | Rule::while_stmt
+        | Rule::try_stmt
+        | Rule::expr_stmt => "a statement",
+        Rule::type_name
+        | Rule::type_name_base
+        | Rule::return_type
+        | Rule::field_type
+        | Rule::field_type_array
+        | Rule::field_type_object
+        | Rule::field_type_named
+        | Rule::field_type_base
+        | Rule::output_type => "a type",
+        Rule::string_literal | Rule::multiline_string => "a string",
+        Rule::number_literal => "a number",
+        Rule::duration_literal => "a duration like `30s`",
+        Rule::struct_field => "a struct field",
+        Rule::enum_variant => "an enum variant",
+        Rule::match_arm | Rule::match_pattern => "a match arm",
+        Rule::agent_item
+        | Rule::agent_field
+        | Rule::tools_field
+        | Rule::knowledge_field
+        | Rule::output_field
+        | Rule::input_field => "an agent field",
+        Rule::parallel_field | Rule::agents_field | Rule::timeout_field => "a parallel field",
+        Rule::interface_member | Rule::interface_field | Rule::interface_method => {
+            "an interface member"
+        }
+        Rule::object_field => "an object field",
+        Rule::param | Rule::param_list | Rule::tool_param | Rule::tool_param_list => {
+            "a parameter"
+        }
+        Rule::arg_list => "an argument",
+        Rule::statement | Rule::program => "a top-level declaration",
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// Name of the innermost construct open at `offset`, e.g. "agent body"
+///
+/// Works on incomplete source, so editors can use it while the user types.
+/// Returns "statement" at the top level.
+pub fn enclosing_construct(source: &str, offset: usize) -> &'static str {
+    let offset = offset.min(source.len());
+    let mut stmt_start = 0;
+    let mut line = 0;
+    while line < offset {
+        if TOP_LEVEL_KEYWORDS.contains(&word_at(source, line)) {
+            stmt_start = line;
+        }
+        line = match source[line..].find('\n') {
+            Some(i) => line + i + 1,
+            None => break,
+        };
+    }
+    delimiter_label(
+        source,
+        stmt_start,
+        &open_delimiters(source, stmt_start, offset),
+    )
+}
+
+/// Name of the construct opened by the innermost delimiter in `open`
+fn delimiter_label(source: &str, stmt_start: usize, open: &[(usize, u8)]) -> &'static str {
+    let Some(&(pos, delimiter)) = open.last() else {
+        return "statement";
+    };
+    let before = source[..pos].trim_end();
+    let after_name = before.ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == ')');
+    let leading = word_at(source, stmt_start);
+    match delimiter {
+        b'[' if after_name => "index expression",
+        b'[' => "array literal",
+        b'(' if open.len() == 1 && matches!(leading, "fn" | "tool") => "parameter list",
+        b'(' if after_name => "argument list",
+        b'(' => "parentheses",
+        _ if before.ends_with([':', '=', '(', ',', '[']) => "object literal",
+        _ if open.iter().any(|&(_, d)| d == b'{') && open.len() > 1 => "block",
+        _ => match leading {
+            "agent" => "agent body",
+            "struct" => "struct body",
+            "enum" => "enum body",
+            "interface" => "interface body",
+            "parallel" => "parallel block",
+            "tool" => "tool body",
+            "fn" => "function body",
+            _ => "block",
+        },
+    }
+}
+
+/// The closing counterpart of an opening delimiter
+fn closing(delimiter: u8) -> char {
+    match delimiter {
+        b'(' => ')',
+        b'[' => ']',
+        _ => '}',
+    }
+}
+
+/// Delimiters still open between `start` and `end`, outermost first
+///
+/// Delimiters inside strings and comments are ignored.
+fn open_delimiters(source: &str, start: usize, end: usize) -> Vec<(usize, u8)> {
+    let bytes = source.as_bytes();
+    let end = end.min(bytes.len());
+    let mut open = Vec::new();
+    let mut i = start;
+    while i < end {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < end && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'"' if bytes[i..].starts_with(b"\"\"\"") => {
+                i += 3;
+                while i < end && !bytes[i..].starts_with(b"\"\"\"") {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'"' => {
+                i += 1;
+                while i < end && bytes[i] != b'"' && bytes[i] != b'\n' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b @ (b'{' | b'[' | b'(') => open.push((i, b)),
+            b'}' | b']' | b')' => {
+                open.pop();
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    open
+}
+
+/// Find where to resume parsing after a failed statement
+///
+/// This is the first later line that starts, at no deeper indentation than the
+/// failed statement, with a top-level keyword or a call.
+fn recovery_point(source: &str, stmt_start: usize) -> usize {
+    let stmt_indent = stmt_start - line_start(source, stmt_start);
+    let mut line = match source[stmt_start..].find('\n') {
+        Some(i) => stmt_start + i + 1,
+        None => return source.len(),
+    };
+    while line < source.len() {
+        let first = line
+            + source[line..]
+                .find(|c: char| c != ' ' && c != '\t')
+                .unwrap_or(source.len() - line);
+        let word = word_at(source, first);
+        if first - line <= stmt_indent
+            && (TOP_LEVEL_KEYWORDS.contains(&word) || is_call_start(source, first))
+        {
+            return first;
+        }
+        line = match source[line..].find('\n') {
+            Some(i) => line + i + 1,
+            None => source.len(),
+        };
+    }
+    source.len()
+}
+
+/// Skip whitespace and line comments
+fn skip_trivia(source: &str, mut pos: usize) -> usize {
+    loop {
+        let rest = &source[pos..];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else {
+            return pos;
+        }
+    }
+}
+
+/// The identifier-like word starting at `pos` (empty if there is none)
+fn word_at(source: &str, pos: usize) -> &str {
+    let rest = &source[pos.min(source.len())..];
+    let len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    &rest[..len]
+}
+
+/// Whether `pos` starts a top-level call like `main()`
+fn is_call_start(source: &str, pos: usize) -> bool {
+    let word = word_at(source, pos);
+    !word.is_empty()
+        && word.starts_with(|c: char| c.is_ascii_alphabetic())
+        && source[pos + word.len()..].trim_start().starts_with('(')
+}
+
+/// The identifier at or just before `pos`, if it is the first thing on its line
+///
+/// Returns the name and the offset just after it.
+fn field_name_near(source: &str, pos: usize) -> Option<(&str, usize)> {
+    let at = word_at(source, pos);
+    let (start, name) = if at.is_empty() {
+        let before = source[..pos].trim_end();
+        let start = before
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        (start, &before[start..])
+    } else {
+        (pos, at)
+    };
+    let starts_line = source[line_start(source, start)..start].trim().is_empty();
+    if starts_line && name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        Some((name, start + name.len()))
+    } else {
+        None
+    }
+}
+
+/// Span of the token at `pos`; at the end of file, the spot just after the last token
+fn token_span(source: &str, pos: usize) -> Span {
+    if skip_trivia(source, pos) >= source.len() {
+        let end = source.trim_end().len();
+        return Span::new(end, end + 1);
+    }
+    Span::new(pos, pos + word_at(source, pos).len().max(1))
+}
+
+/// Describe the token at `pos` for a "found ..." message
+fn describe_token(source: &str, pos: usize) -> String {
+    if pos >= source.len() {
+        return "end of file".to_string();
+    }
+    let word = word_at(source, pos);
+    if !word.is_empty() {
+        return format!("`{}`", word);
+    }
+    match source[pos..].chars().next() {
+        Some('\n') | Some('\r') => "end of line".to_string(),
+        Some('"') => "a string".to_string(),
+        Some(c) => format!("`{}`", c),
+        None => "end of file".to_string(),
+    }
+}
+
+fn line_start(source: &str, pos: usize) -> usize {
+    source[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
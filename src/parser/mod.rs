@@ -1,48 +1,37 @@
 //! Parser module for GENT - transforms pest CST to AST
 
 pub mod ast;
+mod diagnostics;
+mod nullability;
 
 pub use ast::{
     AgentDecl, AgentField, AssignmentStmt, BinaryOp, Block, BlockStmt, Duration, DurationUnit,
     EnumDecl, EnumField, EnumVariant, Expression, FieldType, FnDecl, ForStmt, IfStmt, ImportStmt,
     InterfaceDecl, InterfaceField, InterfaceMember, InterfaceMethod, Lambda, LambdaBody, LetStmt,
     MatchArm, MatchBody, MatchExpr, MatchPattern, OutputType, ParallelDecl, Param, Program,
-    ReturnStmt, Statement, StringPart, StructDecl, StructField, ToolDecl, TopLevelCall, TryStmt,
-    TypeName, UnaryOp, WhileStmt,
+    ReturnStmt, Statement, StringPart, StructDecl, StructField, ThrowStmt, ToolDecl, TopLevelCall,
+    TryStmt, TypeName, UnaryOp, WhileStmt,
 };
 
+pub use diagnostics::{
+    check_agent_fields, enclosing_construct, parse_block_statements, parse_with_recovery,
+    UnknownAgentField, KNOWN_AGENT_FIELDS, TOP_LEVEL_KEYWORDS,
+};
+pub use nullability::{check_nullable_access, NullableAccess};
+
 use crate::errors::{GentError, GentResult, Span};
-use crate::lexer::{GentParser, Rule};
-use pest::Parser;
+use crate::lexer::Rule;
 
 /// Parse GENT source code into an AST
+///
+/// Returns the first syntax error; use [`parse_with_recovery`] to collect all of them.
 pub fn parse(source: &str) -> GentResult<Program> {
-    let pairs = GentParser::parse(Rule::program, source).map_err(|e| GentError::SyntaxError {
-        message: e.to_string(),
-        span: Span::new(0, 0),
-    })?;
-
-    let mut statements = Vec::new();
-    let program_span = Span::new(0, source.len());
-
-    for pair in pairs {
-        if pair.as_rule() == Rule::program {
-            for inner in pair.into_inner() {
-                match inner.as_rule() {
-                    Rule::statement => {
-                        statements.push(parse_statement(inner)?);
-                    }
-                    Rule::EOI => {}
-                    _ => {}
-                }
-            }
-        }
+    let (program, mut errors) = parse_with_recovery(source);
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        Err(errors.remove(0))
     }
-
-    Ok(Program {
-        statements,
-        span: program_span,
-    })
 }
 
 fn parse_statement(pair: pest::iterators::Pair<Rule>) -> GentResult<Statement> {
@@ -104,10 +93,11 @@ fn parse_agent_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<AgentDecl>
     let mut tools_expr = None;
     let mut knowledge_expr = None;
     let mut output = None;
+    let mut input = None;
 
     if let Some(body) = inner.next() {
         for item_pair in body.into_inner() {
-            // item_pair is agent_item which contains tools_field, knowledge_field, output_field, or agent_field
+            // item_pair is agent_item which contains tools_field, knowledge_field, output_field, input_field, or agent_field
             let item_inner = item_pair.into_inner().next().unwrap();
             match item_inner.as_rule() {
                 Rule::tools_field => {
@@ -124,6 +114,10 @@ fn parse_agent_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<AgentDecl>
                     // Parse output field directly from grammar rule
                     output = Some(parse_output_field(item_inner)?);
                 }
+                Rule::input_field => {
+                    // Input types are written like output types
+                    input = Some(parse_output_field(item_inner)?);
+                }
                 Rule::agent_field => {
                     let field = parse_agent_field(item_inner)?;
                     // Legacy support: Check if this is the output field (shouldn't happen with new grammar)
@@ -144,6 +138,7 @@ fn parse_agent_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<AgentDecl>
         tools_expr,
         knowledge_expr,
         output,
+        input,
         span,
     })
 }
@@ -305,6 +300,10 @@ fn parse_expression(pair: pest::iterators::Pair<Rule>) -> GentResult<Expression>
             })?;
             Ok(Expression::Number(num, span))
         }
+        Rule::duration_literal => {
+            let duration = parse_duration(pair)?;
+            Ok(Expression::Number(duration.to_millis() as f64, span))
+        }
         Rule::boolean_literal => {
             let val = pair.as_str() == "true";
             Ok(Expression::Boolean(val, span))
@@ -514,18 +513,13 @@ fn parse_unary(pair: pest::iterators::Pair<Rule>) -> GentResult<Expression> {
     let inner = pair.into_inner();
     let pairs: Vec<_> = inner.collect();
 
-    // If there's only one child, no unary operators
-    if pairs.len() == 1 {
-        return parse_expression(pairs[0].clone());
-    }
+    // The operators are plain literals, so the only child is the operand
+    let operand_pair = &pairs[pairs.len() - 1];
+    let operand_start = operand_pair.as_span().start() - base_pos;
 
     // Collect unary operators from the source text
     let mut ops = Vec::new();
 
-    // The last pair is the operand, everything before are unary operators
-    let operand_pair = &pairs[pairs.len() - 1];
-    let operand_start = operand_pair.as_span().start() - base_pos;
-
     // Extract operators from the text before the operand
     let op_text = &source[..operand_start].trim();
     for ch in op_text.chars() {
@@ -637,17 +631,28 @@ fn parse_object_literal(pair: pest::iterators::Pair<Rule>) -> GentResult<Express
 }
 
 fn parse_tool_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<ToolDecl> {
-    let span = Span::new(pair.as_span().start(), pair.as_span().end());
-    let mut inner = pair.into_inner();
+    let mut doc_lines = Vec::new();
+    let mut inner = pair.clone().into_inner().peekable();
+    while let Some(doc) = inner.next_if(|p| p.as_rule() == Rule::doc_comment) {
+        doc_lines.push(doc.as_str());
+    }
 
-    let name = inner.next().unwrap().as_str().to_string();
+    // The span starts at `tool`, after the doc comment
+    let name_pair = inner.next().unwrap();
+    let start = pair.as_span().start();
+    let keyword = pair.as_str()[..name_pair.as_span().start() - start]
+        .rfind("tool")
+        .unwrap_or(0);
+    let span = Span::new(start + keyword, pair.as_span().end());
+
+    let name = name_pair.as_str().to_string();
     let mut params = Vec::new();
     let mut return_type = None;
     let mut body = None;
 
     for item in inner {
         match item.as_rule() {
-            Rule::param_list => {
+            Rule::tool_param_list => {
                 params = parse_param_list(item)?;
             }
             Rule::return_type => {
@@ -661,8 +666,27 @@ fn parse_tool_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<ToolDecl> {
         }
     }
 
+    // Parameters with defaults can be left out, so they must come last
+    let mut optional = false;
+    for param in &params {
+        if param.default.is_some() {
+            optional = true;
+        } else if optional {
+            return Err(GentError::SyntaxError {
+                message: format!(
+                    "Required parameter '{}' cannot follow parameters with default values",
+                    param.name
+                ),
+                span: param.span.clone(),
+            });
+        }
+    }
+
+    let description = parse_doc_comment(&doc_lines, &mut params);
+
     Ok(ToolDecl {
         name,
+        description,
         params,
         return_type,
         body: body.unwrap_or_else(|| Block {
@@ -673,6 +697,30 @@ fn parse_tool_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<ToolDecl> {
     })
 }
 
+/// Split `///` lines into a description and `@param name text` entries,
+/// which become the descriptions of the matching parameters
+fn parse_doc_comment(lines: &[&str], params: &mut [Param]) -> Option<String> {
+    let mut description = Vec::new();
+    for line in lines {
+        let text = line.trim_start_matches('/');
+        let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+        let Some(rest) = text.trim_start().strip_prefix("@param ") else {
+            description.push(text);
+            continue;
+        };
+        let rest = rest.trim_start();
+        let (name, doc) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if let Some(param) = params.iter_mut().find(|p| p.name == name) {
+            let doc = doc.trim();
+            if !doc.is_empty() {
+                param.description = Some(doc.to_string());
+            }
+        }
+    }
+    let description = description.join("\n").trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
 fn parse_fn_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<FnDecl> {
     let span = Span::new(pair.as_span().start(), pair.as_span().end());
     let mut inner = pair.into_inner();
@@ -729,7 +777,7 @@ fn parse_top_level_call(pair: pest::iterators::Pair<Rule>) -> GentResult<TopLeve
 fn parse_param_list(pair: pest::iterators::Pair<Rule>) -> GentResult<Vec<Param>> {
     let mut params = Vec::new();
     for param_pair in pair.into_inner() {
-        if param_pair.as_rule() == Rule::param {
+        if matches!(param_pair.as_rule(), Rule::param | Rule::tool_param) {
             params.push(parse_param(param_pair)?);
         }
     }
@@ -743,27 +791,37 @@ fn parse_param(pair: pest::iterators::Pair<Rule>) -> GentResult<Param> {
     let name = inner.next().unwrap().as_str().to_string();
     let type_pair = inner.next().unwrap();
     let type_name = parse_type_name(type_pair)?;
+    let default = inner.next().map(parse_expression).transpose()?;
 
     Ok(Param {
         name,
         type_name,
+        default,
+        description: None,
         span,
     })
 }
 
 fn parse_type_name(pair: pest::iterators::Pair<Rule>) -> GentResult<TypeName> {
-    match pair.as_str() {
-        "string" => Ok(TypeName::String),
-        "number" => Ok(TypeName::Number),
-        "boolean" => Ok(TypeName::Boolean),
-        "object" => Ok(TypeName::Object),
-        "array" => Ok(TypeName::Array),
-        "any" => Ok(TypeName::Any),
-        other => Err(GentError::SyntaxError {
-            message: format!("Unknown type: {}", other),
-            span: Span::new(pair.as_span().start(), pair.as_span().end()),
-        }),
+    let mut inner = pair.into_inner();
+    let mut base = inner.next().unwrap().into_inner();
+    let mut type_name = match base.next().unwrap().as_str() {
+        "string" => TypeName::String,
+        "number" => TypeName::Number,
+        "boolean" => TypeName::Boolean,
+        "object" => TypeName::Object,
+        "array" => TypeName::Array,
+        "any" => TypeName::Any,
+        // Structs and enums are resolved where the type is used
+        other => TypeName::Named(other.to_string()),
+    };
+    if base.next().is_some() {
+        type_name = TypeName::ArrayOf(Box::new(type_name));
     }
+    Ok(match inner.next() {
+        Some(_) => TypeName::Nullable(Box::new(type_name)),
+        None => type_name,
+    })
 }
 
 fn parse_block(pair: pest::iterators::Pair<Rule>) -> GentResult<Block> {
@@ -785,6 +843,7 @@ fn parse_block_stmt(pair: pest::iterators::Pair<Rule>) -> GentResult<BlockStmt>
         Rule::let_stmt => Ok(BlockStmt::Let(parse_let_stmt(inner)?)),
         Rule::assignment_stmt => Ok(BlockStmt::Assignment(parse_assignment_stmt(inner)?)),
         Rule::return_stmt => Ok(BlockStmt::Return(parse_return_stmt(inner)?)),
+        Rule::throw_stmt => Ok(BlockStmt::Throw(parse_throw_stmt(inner)?)),
         Rule::if_stmt => Ok(BlockStmt::If(parse_if_stmt(inner)?)),
         Rule::for_stmt => Ok(BlockStmt::For(parse_for_stmt(inner)?)),
         Rule::while_stmt => Ok(BlockStmt::While(parse_while_stmt(inner)?)),
@@ -839,6 +898,17 @@ fn parse_return_stmt(pair: pest::iterators::Pair<Rule>) -> GentResult<ReturnStmt
     Ok(ReturnStmt { value, span })
 }
 
+fn parse_throw_stmt(pair: pest::iterators::Pair<Rule>) -> GentResult<ThrowStmt> {
+    let span = Span::new(pair.as_span().start(), pair.as_span().end());
+    let expr_pair = pair
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::expression)
+        .unwrap();
+    let value = parse_expression(expr_pair)?;
+
+    Ok(ThrowStmt { value, span })
+}
+
 fn parse_if_stmt(pair: pest::iterators::Pair<Rule>) -> GentResult<IfStmt> {
     let span = Span::new(pair.as_span().start(), pair.as_span().end());
     let mut inner = pair.into_inner();
@@ -859,12 +929,16 @@ fn parse_for_stmt(pair: pest::iterators::Pair<Rule>) -> GentResult<ForStmt> {
     let span = Span::new(pair.as_span().start(), pair.as_span().end());
     let mut inner = pair.into_inner();
 
-    let variable = inner.next().unwrap().as_str().to_string();
+    // for_binding: `x` or `(k, v)`
+    let mut binding = inner.next().unwrap().into_inner();
+    let variable = binding.next().unwrap().as_str().to_string();
+    let value_variable = binding.next().map(|p| p.as_str().to_string());
     let iterable = parse_expression(inner.next().unwrap())?;
     let body = parse_block(inner.next().unwrap())?;
 
     Ok(ForStmt {
         variable,
+        value_variable,
         iterable,
         body,
         span,
@@ -890,13 +964,24 @@ fn parse_try_stmt(pair: pest::iterators::Pair<Rule>) -> GentResult<TryStmt> {
     let mut inner = pair.into_inner();
 
     let try_block = parse_block(inner.next().unwrap())?;
-    let error_var = inner.next().unwrap().as_str().to_string();
+
+    // catch_binding: `e`, `(e)` or `(e: Kind)`
+    let mut binding = inner.next().unwrap().into_inner();
+    let error_var = binding.next().unwrap().as_str().to_string();
+    let error_type = binding.next().map(|p| p.as_str().to_string());
+
     let catch_block = parse_block(inner.next().unwrap())?;
+    let finally_block = inner
+        .next()
+        .map(|p| parse_block(p.into_inner().next().unwrap()))
+        .transpose()?;
 
     Ok(TryStmt {
         try_block,
         error_var,
+        error_type,
         catch_block,
+        finally_block,
         span,
     })
 }
@@ -970,7 +1055,16 @@ fn parse_struct_field(pair: pest::iterators::Pair<Rule>) -> GentResult<StructFie
 }
 
 fn parse_field_type(pair: pest::iterators::Pair<Rule>) -> GentResult<FieldType> {
-    let inner = pair.into_inner().next().unwrap();
+    let mut pairs = pair.into_inner();
+    let inner = pairs.next().unwrap();
+    let field_type = parse_field_type_inner(inner)?;
+    Ok(match pairs.next() {
+        Some(_) => FieldType::Nullable(Box::new(field_type)),
+        None => field_type,
+    })
+}
+
+fn parse_field_type_inner(inner: pest::iterators::Pair<Rule>) -> GentResult<FieldType> {
     match inner.as_rule() {
         Rule::field_type_array => {
             let base = inner.into_inner().next().unwrap();
@@ -1231,30 +1325,26 @@ fn parse_parallel_decl(pair: pest::iterators::Pair<Rule>) -> GentResult<Parallel
 
     // Parse parallel_body -> parallel_field*
     for field_pair in inner {
-        match field_pair.as_rule() {
-            Rule::parallel_body => {
-                for item in field_pair.into_inner() {
-                    match item.as_rule() {
-                        Rule::parallel_field => {
-                            let field_inner = item.into_inner().next().unwrap();
-                            match field_inner.as_rule() {
-                                Rule::agents_field => {
-                                    for expr_pair in field_inner.into_inner() {
-                                        agents.push(parse_expression(expr_pair)?);
-                                    }
-                                }
-                                Rule::timeout_field => {
-                                    let duration_pair = field_inner.into_inner().next().unwrap();
-                                    timeout = Some(parse_duration(duration_pair)?);
-                                }
-                                _ => {}
-                            }
-                        }
-                        _ => {}
+        if field_pair.as_rule() != Rule::parallel_body {
+            continue;
+        }
+        for item in field_pair.into_inner() {
+            if item.as_rule() != Rule::parallel_field {
+                continue;
+            }
+            let field_inner = item.into_inner().next().unwrap();
+            match field_inner.as_rule() {
+                Rule::agents_field => {
+                    for expr_pair in field_inner.into_inner() {
+                        agents.push(parse_expression(expr_pair)?);
                     }
                 }
+                Rule::timeout_field => {
+                    let duration_pair = field_inner.into_inner().next().unwrap();
+                    timeout = Some(parse_duration(duration_pair)?);
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 
@@ -1283,12 +1373,12 @@ fn parse_duration(pair: pest::iterators::Pair<Rule>) -> GentResult<Duration> {
     let text = pair.as_str();
 
     // Parse "30s", "2m", "500ms"
-    let (value_str, unit) = if text.ends_with("ms") {
-        (&text[..text.len() - 2], DurationUnit::Milliseconds)
-    } else if text.ends_with('s') {
-        (&text[..text.len() - 1], DurationUnit::Seconds)
-    } else if text.ends_with('m') {
-        (&text[..text.len() - 1], DurationUnit::Minutes)
+    let (value_str, unit) = if let Some(v) = text.strip_suffix("ms") {
+        (v, DurationUnit::Milliseconds)
+    } else if let Some(v) = text.strip_suffix('s') {
+        (v, DurationUnit::Seconds)
+    } else if let Some(v) = text.strip_suffix('m') {
+        (v, DurationUnit::Minutes)
     } else {
         return Err(GentError::SyntaxError {
             message: format!("Invalid duration: {}", text),
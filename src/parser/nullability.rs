@@ -0,0 +1,314 @@
+//! Checks for unguarded use of nullable parameters
+//!
+//! A parameter declared `T?` may be `null`, so reading a member of it,
+//! indexing it or calling a method on it needs a check first. An access
+//! counts as checked:
+//!
+//! - inside `if p != null { ... }` or `if p { ... }`
+//! - after `p != null &&` in the same condition
+//! - in the `else` branch of `if p == null`
+//! - after a guard such as `if p == null { return ... }`
+//! - after `p` is assigned something other than `null`
+
+use std::collections::HashSet;
+
+use super::ast::{
+    BinaryOp, Block, BlockStmt, Expression, LambdaBody, MatchBody, MatchPattern, Param, Program,
+    Statement, StringPart, TypeName, UnaryOp,
+};
+use crate::errors::Span;
+
+/// Member access, indexing or method call on a nullable parameter that
+/// may still be `null`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullableAccess {
+    /// Parameter name
+    pub name: String,
+    /// Where the parameter is used
+    pub span: Span,
+}
+
+impl NullableAccess {
+    /// Warning text for the access
+    pub fn message(&self) -> String {
+        format!(
+            "'{}' may be null; check `{} != null` before using it",
+            self.name, self.name
+        )
+    }
+}
+
+/// Find unguarded accesses to nullable parameters in functions and tools
+pub fn check_nullable_access(program: &Program) -> Vec<NullableAccess> {
+    let mut checker = Checker::default();
+    for statement in &program.statements {
+        let (params, body) = match statement {
+            Statement::FnDecl(decl) => (&decl.params, &decl.body),
+            Statement::ToolDecl(decl) => (&decl.params, &decl.body),
+            _ => continue,
+        };
+        checker.nullable = nullable_params(params);
+        let mut unchecked = checker.nullable.clone();
+        checker.block(body, &mut unchecked);
+    }
+    checker.found
+}
+
+fn nullable_params(params: &[Param]) -> HashSet<String> {
+    params
+        .iter()
+        .filter(|p| matches!(p.type_name, TypeName::Nullable(_)))
+        .map(|p| p.name.clone())
+        .collect()
+}
+
+/// Names that are not null when a condition is true, and when it is false
+fn narrowing(condition: &Expression) -> (Vec<String>, Vec<String>) {
+    match condition {
+        Expression::Identifier(name, _) => (vec![name.clone()], vec![]),
+        Expression::Binary(op @ (BinaryOp::Eq | BinaryOp::Ne), left, right, _) => {
+            let name = match (left.as_ref(), right.as_ref()) {
+                (Expression::Identifier(name, _), Expression::Null(_))
+                | (Expression::Null(_), Expression::Identifier(name, _)) => name.clone(),
+                _ => return (vec![], vec![]),
+            };
+            if *op == BinaryOp::Ne {
+                (vec![name], vec![])
+            } else {
+                (vec![], vec![name])
+            }
+        }
+        Expression::Unary(UnaryOp::Not, inner, _) => {
+            let (when_true, when_false) = narrowing(inner);
+            (when_false, when_true)
+        }
+        Expression::Binary(BinaryOp::And, left, right, _) => {
+            let (mut when_true, _) = narrowing(left);
+            when_true.extend(narrowing(right).0);
+            (when_true, vec![])
+        }
+        Expression::Binary(BinaryOp::Or, left, right, _) => {
+            let (_, mut when_false) = narrowing(left);
+            when_false.extend(narrowing(right).1);
+            (vec![], when_false)
+        }
+        _ => (vec![], vec![]),
+    }
+}
+
+fn without(unchecked: &HashSet<String>, names: &[String]) -> HashSet<String> {
+    let mut unchecked = unchecked.clone();
+    for name in names {
+        unchecked.remove(name);
+    }
+    unchecked
+}
+
+#[derive(Default)]
+struct Checker {
+    /// Nullable parameters of the declaration being checked
+    nullable: HashSet<String>,
+    found: Vec<NullableAccess>,
+}
+
+impl Checker {
+    /// Check a block; returns false if it never completes normally
+    /// (it always returns, throws, breaks or continues)
+    fn block(&mut self, block: &Block, unchecked: &mut HashSet<String>) -> bool {
+        for statement in &block.statements {
+            if !self.statement(statement, unchecked) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn statement(&mut self, statement: &BlockStmt, unchecked: &mut HashSet<String>) -> bool {
+        match statement {
+            BlockStmt::Let(stmt) => {
+                self.expr(&stmt.value, unchecked);
+                // A local with the same name shadows the parameter
+                unchecked.remove(&stmt.name);
+            }
+            BlockStmt::Assignment(stmt) => {
+                self.expr(&stmt.value, unchecked);
+                if self.nullable.contains(&stmt.name) {
+                    if matches!(stmt.value, Expression::Null(_)) {
+                        unchecked.insert(stmt.name.clone());
+                    } else {
+                        unchecked.remove(&stmt.name);
+                    }
+                }
+            }
+            BlockStmt::Return(stmt) => {
+                if let Some(value) = &stmt.value {
+                    self.expr(value, unchecked);
+                }
+                return false;
+            }
+            BlockStmt::Throw(stmt) => {
+                self.expr(&stmt.value, unchecked);
+                return false;
+            }
+            BlockStmt::Break(_) | BlockStmt::Continue(_) => return false,
+            BlockStmt::Expr(expr) => self.expr(expr, unchecked),
+            BlockStmt::If(stmt) => {
+                self.expr(&stmt.condition, unchecked);
+                let (when_true, when_false) = narrowing(&stmt.condition);
+
+                let mut then_set = without(unchecked, &when_true);
+                let then_completes = self.block(&stmt.then_block, &mut then_set);
+                let mut else_set = without(unchecked, &when_false);
+                let else_completes = match &stmt.else_block {
+                    Some(block) => self.block(block, &mut else_set),
+                    None => true,
+                };
+
+                match (then_completes, else_completes) {
+                    (true, true) => *unchecked = &then_set | &else_set,
+                    (true, false) => *unchecked = then_set,
+                    (false, true) => *unchecked = else_set,
+                    (false, false) => return false,
+                }
+            }
+            BlockStmt::For(stmt) => {
+                self.expr(&stmt.iterable, unchecked);
+                let mut body_set = unchecked.clone();
+                body_set.remove(&stmt.variable);
+                if let Some(value_variable) = &stmt.value_variable {
+                    body_set.remove(value_variable);
+                }
+                self.block(&stmt.body, &mut body_set);
+                self.merge_loop(unchecked, &body_set);
+            }
+            BlockStmt::While(stmt) => {
+                self.expr(&stmt.condition, unchecked);
+                let mut body_set = without(unchecked, &narrowing(&stmt.condition).0);
+                self.block(&stmt.body, &mut body_set);
+                self.merge_loop(unchecked, &body_set);
+            }
+            BlockStmt::Try(stmt) => {
+                let before = unchecked.clone();
+                let mut try_set = before.clone();
+                let try_completes = self.block(&stmt.try_block, &mut try_set);
+                let mut catch_set = &before | &try_set;
+                catch_set.remove(&stmt.error_var);
+                let catch_completes = self.block(&stmt.catch_block, &mut catch_set);
+
+                *unchecked = match (try_completes, catch_completes) {
+                    (true, false) => try_set,
+                    (false, true) => catch_set,
+                    _ => &try_set | &catch_set,
+                };
+                if let Some(finally_block) = &stmt.finally_block {
+                    if !self.block(finally_block, unchecked) {
+                        return false;
+                    }
+                }
+                if !try_completes && !catch_completes {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// After a loop, parameters the body may have set to `null` are
+    /// unchecked again
+    fn merge_loop(&self, unchecked: &mut HashSet<String>, body_set: &HashSet<String>) {
+        for name in body_set {
+            if self.nullable.contains(name) {
+                unchecked.insert(name.clone());
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression, unchecked: &HashSet<String>) {
+        match expr {
+            Expression::String(parts, _) => {
+                for part in parts {
+                    if let StringPart::Expr(expr) = part {
+                        self.expr(expr, unchecked);
+                    }
+                }
+            }
+            Expression::Number(..)
+            | Expression::Boolean(..)
+            | Expression::Identifier(..)
+            | Expression::Null(_) => {}
+            Expression::Array(items, _) => {
+                for item in items {
+                    self.expr(item, unchecked);
+                }
+            }
+            Expression::Object(fields, _) => {
+                for (_, value) in fields {
+                    self.expr(value, unchecked);
+                }
+            }
+            Expression::Binary(BinaryOp::And, left, right, _) => {
+                self.expr(left, unchecked);
+                self.expr(right, &without(unchecked, &narrowing(left).0));
+            }
+            Expression::Binary(BinaryOp::Or, left, right, _) => {
+                self.expr(left, unchecked);
+                self.expr(right, &without(unchecked, &narrowing(left).1));
+            }
+            Expression::Binary(_, left, right, _) | Expression::Range(left, right, _) => {
+                self.expr(left, unchecked);
+                self.expr(right, unchecked);
+            }
+            Expression::Unary(_, inner, _) => self.expr(inner, unchecked),
+            Expression::Call(callee, args, _) => {
+                self.expr(callee, unchecked);
+                for arg in args {
+                    self.expr(arg, unchecked);
+                }
+            }
+            Expression::Member(object, _, _) => {
+                self.receiver(object, unchecked);
+            }
+            Expression::Index(object, index, _) => {
+                self.receiver(object, unchecked);
+                self.expr(index, unchecked);
+            }
+            Expression::Lambda(lambda) => {
+                let mut body_set = without(unchecked, &lambda.params);
+                match &lambda.body {
+                    LambdaBody::Expression(body) => self.expr(body, &body_set),
+                    LambdaBody::Block(block) => {
+                        self.block(block, &mut body_set);
+                    }
+                }
+            }
+            Expression::Match(m) => {
+                self.expr(&m.subject, unchecked);
+                for arm in &m.arms {
+                    let mut arm_set = match &arm.pattern {
+                        MatchPattern::EnumVariant { bindings, .. } => without(unchecked, bindings),
+                        MatchPattern::Wildcard => unchecked.clone(),
+                    };
+                    match &arm.body {
+                        MatchBody::Expression(body) => self.expr(body, &arm_set),
+                        MatchBody::Block(block) => {
+                            self.block(block, &mut arm_set);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check the object of a member access or index
+    fn receiver(&mut self, object: &Expression, unchecked: &HashSet<String>) {
+        match object {
+            Expression::Identifier(name, span) if unchecked.contains(name) => {
+                self.found.push(NullableAccess {
+                    name: name.clone(),
+                    span: span.clone(),
+                });
+            }
+            _ => self.expr(object, unchecked),
+        }
+    }
+}
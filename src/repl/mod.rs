@@ -0,0 +1,358 @@
+//! Interactive read-eval-print loop (`gent repl`)
+//!
+//! Every input is evaluated in the same `Session`, so agents, functions and
+//! variables declared earlier stay available. Input lines are gathered until
+//! brackets balance and multi-line strings are closed, which lets agent and
+//! struct bodies span several lines.
+
+use crate::errors::{ErrorReporter, GentError};
+use crate::interpreter::{Session, Value};
+use crate::parser::{parse_block_statements, parse_with_recovery, TOP_LEVEL_KEYWORDS};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Prompt shown for a new input
+pub const PROMPT: &str = "gent> ";
+
+/// Prompt shown while an input continues on the next line
+pub const CONTINUATION_PROMPT: &str = "...> ";
+
+/// Objects and arrays longer than this are printed one entry per line
+const MAX_INLINE_WIDTH: usize = 80;
+
+const HELP: &str = "\
+Enter declarations, statements or expressions to evaluate them.
+Commands:
+  :load <file>   Evaluate a .gnt file in this session
+  :env           List variables
+  :agents        List agents with their model and tools
+  :help          Show this help
+  :quit          Exit (also :exit or Ctrl-D)";
+
+/// Run the REPL until `:quit` or end of input
+///
+/// Prompts, results and command output go to `out`; errors go to `err`.
+pub async fn run<R: BufRead>(
+    session: &mut Session,
+    input: R,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> io::Result<()> {
+    let mut lines = input.lines();
+    let mut buffer = String::new();
+
+    loop {
+        let prompt = if buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        write!(out, "{}", prompt)?;
+        out.flush()?;
+
+        let (line, at_end) = match lines.next() {
+            Some(line) => (line?, false),
+            None if buffer.is_empty() => {
+                writeln!(out)?;
+                break;
+            }
+            // Evaluate an unfinished input so its errors are reported
+            None => (String::new(), true),
+        };
+
+        if buffer.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(command) = trimmed.strip_prefix(':') {
+                if !run_command(session, command, out, err).await? {
+                    break;
+                }
+                continue;
+            }
+        }
+
+        buffer.push_str(&line);
+        buffer.push('\n');
+        if is_incomplete(&buffer) && !at_end {
+            continue;
+        }
+
+        let source = std::mem::take(&mut buffer);
+        match eval_input(session, &source).await {
+            Ok(Some(value)) if value != Value::Null => writeln!(out, "{}", format_value(&value))?,
+            Ok(_) => {}
+            Err(errors) => {
+                let reporter = ErrorReporter::new(&source, "<repl>");
+                write!(err, "{}", reporter.format_all(&errors))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a `:command`, returning false when the REPL should exit
+async fn run_command(
+    session: &mut Session,
+    command: &str,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> io::Result<bool> {
+    let (name, arg) = match command.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (command, ""),
+    };
+
+    match name {
+        "quit" | "exit" | "q" => return Ok(false),
+        "help" | "h" => writeln!(out, "{}", HELP)?,
+        "env" => {
+            for (name, value) in session.variables() {
+                writeln!(out, "{} = {}", name, format_value(value))?;
+            }
+        }
+        "agents" => {
+            for agent in session.agents() {
+                let model = agent.model.as_deref().unwrap_or("default model");
+                if agent.tools.is_empty() {
+                    writeln!(out, "{} ({})", agent.name, model)?;
+                } else {
+                    writeln!(
+                        out,
+                        "{} ({}, tools: {})",
+                        agent.name,
+                        model,
+                        agent.tools.join(", ")
+                    )?;
+                }
+            }
+        }
+        "load" if arg.is_empty() => writeln!(err, "error: Usage: :load <file>")?,
+        "load" => load_file(session, Path::new(arg), out, err).await?,
+        _ => writeln!(
+            err,
+            "error: Unknown command ':{}' (type :help for a list)",
+            name
+        )?,
+    }
+
+    Ok(true)
+}
+
+/// Evaluate a file in the session
+async fn load_file(
+    session: &mut Session,
+    path: &Path,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> io::Result<()> {
+    let filename = path.display().to_string();
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => return writeln!(err, "error: Could not read file '{}': {}", filename, e),
+    };
+
+    let reporter = ErrorReporter::new(&source, &filename);
+    let (program, errors) = parse_with_recovery(&source);
+    if !errors.is_empty() {
+        return write!(err, "{}", reporter.format_all(&errors));
+    }
+
+    match session.eval_program(&program, Some(path)).await {
+        Ok(()) => writeln!(out, "Loaded {}", filename),
+        Err(e) => write!(err, "{}", reporter.format(&e)),
+    }
+}
+
+/// Evaluate one complete input in the session
+///
+/// Statements and expressions are evaluated in the session scope, and the
+/// value of a trailing expression is returned. Anything else is evaluated
+/// as a program, which is how agents, structs and functions are declared.
+pub async fn eval_input(
+    session: &mut Session,
+    source: &str,
+) -> Result<Option<Value>, Vec<GentError>> {
+    // A leading declaration keyword settles it: `struct P { x: number }`
+    // would otherwise also read as three expression statements
+    let leading: String = source
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    let declaration = leading != "let" && TOP_LEVEL_KEYWORDS.contains(&leading.as_str());
+
+    let block_error = if declaration {
+        None
+    } else {
+        match parse_block_statements(source) {
+            Ok(statements) => {
+                return session
+                    .eval_statements(statements)
+                    .await
+                    .map_err(|e| vec![e])
+            }
+            Err(e) => Some(e),
+        }
+    };
+
+    let (program, errors) = parse_with_recovery(source);
+    if !errors.is_empty() {
+        return Err(block_error.map(|e| vec![e]).unwrap_or(errors));
+    }
+
+    // Imports resolve relative to the working directory
+    session
+        .eval_program(&program, Some(Path::new("<repl>")))
+        .await
+        .map_err(|e| vec![e])?;
+    Ok(None)
+}
+
+/// Whether `input` needs more lines: a bracket or `"""` string is still open
+pub fn is_incomplete(input: &str) -> bool {
+    /// What the scanner is currently inside of
+    enum Mode {
+        /// Code, with the brackets opened inside it
+        Code(Vec<u8>),
+        String,
+        Multiline,
+    }
+
+    let bytes = input.as_bytes();
+    let mut stack = vec![Mode::Code(Vec::new())];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        match stack.last_mut() {
+            Some(Mode::Code(open)) => {
+                if rest.starts_with(b"//") {
+                    i += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+                    continue;
+                } else if rest.starts_with(b"\"\"\"") {
+                    stack.push(Mode::Multiline);
+                    i += 3;
+                    continue;
+                }
+                match bytes[i] {
+                    b'"' => stack.push(Mode::String),
+                    b'(' | b'[' | b'{' => open.push(bytes[i]),
+                    b')' | b']' | b'}' if open.is_empty() => {
+                        if stack.len() == 1 {
+                            // Unbalanced: let the parser report it
+                            return false;
+                        }
+                        // End of a string interpolation
+                        stack.pop();
+                    }
+                    b')' | b']' | b'}' => {
+                        open.pop();
+                    }
+                    _ => {}
+                }
+            }
+            Some(Mode::String) => match bytes[i] {
+                b'\\' => i += 1,
+                b'"' | b'\n' => {
+                    stack.pop();
+                }
+                b'{' => stack.push(Mode::Code(Vec::new())),
+                _ => {}
+            },
+            Some(Mode::Multiline) => {
+                if rest.starts_with(b"\"\"\"") {
+                    stack.pop();
+                    i += 3;
+                    continue;
+                } else if bytes[i] == b'{' {
+                    stack.push(Mode::Code(Vec::new()));
+                }
+            }
+            None => break,
+        }
+        i += 1;
+    }
+
+    match stack.as_slice() {
+        [Mode::Code(open)] => !open.is_empty(),
+        _ => true,
+    }
+}
+
+/// Pretty-print a value for display
+///
+/// Top-level strings are shown as-is; strings inside arrays and objects are
+/// quoted. Long arrays and objects are printed one entry per line.
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => format_nested(value, 0),
+    }
+}
+
+fn format_nested(value: &Value, indent: usize) -> String {
+    let entries: Vec<String> = match value {
+        Value::String(s) => return format!("{:?}", s),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| format_nested(item, indent + 1))
+            .collect(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}: {}",
+                    format_key(key),
+                    format_nested(value, indent + 1)
+                )
+            })
+            .collect(),
+        _ => return value.to_string(),
+    };
+
+    let (open, close) = if matches!(value, Value::Array(_)) {
+        ("[", "]")
+    } else {
+        ("{", "}")
+    };
+    if entries.is_empty() {
+        return format!("{}{}", open, close);
+    }
+
+    let inline = if open == "[" {
+        format!("[{}]", entries.join(", "))
+    } else {
+        format!("{{ {} }}", entries.join(", "))
+    };
+    if inline.len() + indent * 4 <= MAX_INLINE_WIDTH && !inline.contains('\n') {
+        return inline;
+    }
+
+    let pad = "    ".repeat(indent + 1);
+    let mut out = format!("{}\n", open);
+    for (i, entry) in entries.iter().enumerate() {
+        let separator = if i + 1 < entries.len() { "," } else { "" };
+        out.push_str(&format!("{}{}{}\n", pad, entry, separator));
+    }
+    out.push_str(&"    ".repeat(indent));
+    out.push_str(close);
+    out
+}
+
+/// Object keys are quoted unless they are plain identifiers
+fn format_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .map(|c| c.is_alphabetic() || c == '_')
+        .unwrap_or(false)
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if identifier {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
@@ -1,13 +1,27 @@
 //! Agent execution for GENT
 
 use crate::errors::{GentError, GentResult};
-use crate::interpreter::{AgentValue, OutputSchema};
+use crate::interpreter::{AgentValue, Environment, OutputSchema, Value};
 use crate::logging::{LogLevel, Logger, NullLogger};
+use crate::runtime::context::fit_context;
+use crate::runtime::handoff::{
+    handoff_targets, set_system_message, transfer_definition, transfer_target, TurnResult,
+    DEFAULT_MAX_HANDOFFS, TRANSFER_TOOL,
+};
+use crate::runtime::memory::{memory_tools, MemoryStore};
 use crate::runtime::validation::validate_output;
-use crate::runtime::{LLMClient, LLMResponse, Message, ProviderFactory, ToolDefinition, ToolRegistry, ToolResult};
+use crate::runtime::{AgentTool, LLMClient, LLMResponse, Message, ProviderFactory, Tool, ToolCall, ToolChoice, ToolContext, ToolDefinition, ToolRegistry, ToolResult};
+use futures::stream::{self, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
 
 const DEFAULT_MAX_STEPS: u32 = 10;
 
+/// Tool calls from one response that run at once, unless the agent sets
+/// `toolConcurrency`
+const DEFAULT_TOOL_CONCURRENCY: usize = 4;
+
 /// Run an agent with the given input (simple, no tools)
 pub async fn run_agent(
     agent: &AgentValue,
@@ -27,59 +41,538 @@ pub async fn run_agent_with_tools(
     tools: &ToolRegistry,
     logger: &dyn Logger,
 ) -> GentResult<String> {
-    // Create LLM client from factory using agent's provider setting
-    let llm = provider_factory.create_with_options(
-        agent.provider.as_deref(),
-        agent.dangerously_skip_permissions,
-    )?;
-    let llm = llm.as_ref();
-
-    let max_steps = agent.max_steps.unwrap_or(DEFAULT_MAX_STEPS);
-    let tool_defs = tools.definitions_for(&agent.tools);
-    let model = agent.model.as_deref();
-    let json_mode = agent.output_schema.is_some();
-
-    logger.log(
-        LogLevel::Debug,
-        "agent",
-        &format!(
-            "Agent '{}' - knowledge_config: {}, user_prompt: {:?}",
-            agent.name,
-            agent.knowledge_config.is_some(),
-            agent.user_prompt
-        ),
-    );
-    logger.log(
-        LogLevel::Debug,
-        "agent",
-        &format!("Agent '{}' requested tools: {:?}", agent.name, agent.tools),
-    );
-    logger.log(
-        LogLevel::Debug,
-        "agent",
-        &format!("Tool definitions provided to LLM: {}", tool_defs.len()),
-    );
-    for def in &tool_defs {
-        logger.log(
-            LogLevel::Trace,
-            "agent",
-            &format!("  - {} : {}", def.name, def.description),
-        );
-    }
+    let mut history = Vec::new();
+    run_agent_turn(agent, input, &mut history, provider_factory, tools, logger).await
+}
 
-    // Build messages based on which prompts are present
-    let mut messages = Vec::new();
+/// Run one turn of an agent, continuing from `history`
+///
+/// An empty history starts with the agent's system message. The user
+/// message, tool calls, tool results and the final reply are appended to
+/// `history`, so the next turn sees the whole exchange.
+pub async fn run_agent_turn(
+    agent: &AgentValue,
+    input: Option<String>,
+    history: &mut Vec<Message>,
+    provider_factory: &ProviderFactory,
+    tools: &ToolRegistry,
+    logger: &dyn Logger,
+) -> GentResult<String> {
+    run_agent_turn_with_handoffs(agent, input, history, provider_factory, tools, logger, None)
+        .await
+        .map(|result| result.output)
+}
 
-    // Determine user query for RAG (if applicable)
+/// Run one turn of an agent, following handoffs to other agents
+///
+/// Works like [`run_agent_turn`], but when the active agent calls the
+/// transfer tool, the agent it names takes over with the same history.
+/// The result reports which agent gave the final answer.
+///
+/// User-defined tools called during the turn see `env`, the environment of
+/// the program that started it.
+pub async fn run_agent_turn_with_handoffs(
+    agent: &AgentValue,
+    input: Option<String>,
+    history: &mut Vec<Message>,
+    provider_factory: &ProviderFactory,
+    tools: &ToolRegistry,
+    logger: &dyn Logger,
+    env: Option<&Environment>,
+) -> GentResult<TurnResult> {
+    // Determine user query for RAG and memory (if applicable)
     let user_query = agent
         .user_prompt
         .as_ref()
         .or(input.as_ref())
         .cloned();
 
-    // Perform RAG search if knowledge config is present
-    let rag_context = if let Some(ref knowledge_config) = agent.knowledge_config {
-        if let Some(ref query) = user_query {
+    let mut active = ActiveAgent::new(
+        agent.clone(),
+        &[],
+        user_query.as_deref(),
+        provider_factory,
+        tools,
+        logger,
+        env,
+    )
+    .await?;
+    let max_handoffs = agent.max_handoffs.unwrap_or(DEFAULT_MAX_HANDOFFS);
+    // Agents that handed off during this turn, in order
+    let mut previous: Vec<AgentValue> = Vec::new();
+    let mut handoffs: Vec<String> = Vec::new();
+
+    let context = active.context(user_query.as_deref(), logger).await;
+
+    // Later turns keep the first system message; context found for them
+    // goes with the user message instead
+    let mut user_context = None;
+
+    if !history.is_empty() {
+        user_context = context;
+    } else {
+        // Build system message from: system_prompt + output_schema + RAG and memory context
+        let system_prompt = system_message(agent, context, logger);
+        if !system_prompt.is_empty() {
+            history.push(Message::system(&system_prompt));
+        }
+    }
+
+    // Add user message from agent's user_prompt or from input parameter
+    if let Some(mut user_message) = agent.user_prompt.clone().or(input) {
+        if let Some(context) = user_context {
+            user_message.push_str(&context);
+        }
+        history.push(Message::user(user_message));
+    }
+
+    // If no messages at all, return empty result
+    if history.is_empty() {
+        logger.log(
+            LogLevel::Debug,
+            "agent",
+            "No prompts provided, returning empty result",
+        );
+        return Ok(TurnResult {
+            output: String::new(),
+            agent: agent.clone(),
+            handoffs,
+        });
+    }
+
+    let mut step = 0;
+    // Whether the active agent has called a tool in this turn
+    let mut called_tools = false;
+    loop {
+        let agent = &active.agent;
+        let max_steps = agent.max_steps.unwrap_or(DEFAULT_MAX_STEPS);
+        if step >= max_steps {
+            return Err(GentError::MaxStepsExceeded { limit: max_steps });
+        }
+        step += 1;
+        let llm = active.llm.as_ref();
+        let model = agent.model.as_deref();
+        let json_mode = agent.output_schema.is_some();
+
+        logger.log(
+            LogLevel::Debug,
+            "agent",
+            &format!("Step {}/{}", step, max_steps),
+        );
+        // A required or named tool holds until the model has called a tool,
+        // unless it leads to the finish tool, which ends the run
+        let tool_choice = match &agent.tool_choice {
+            ToolChoice::Required if called_tools && agent.finish_tool.is_none() => ToolChoice::Auto,
+            ToolChoice::Tool(name) if called_tools && agent.finish_tool.as_ref() != Some(name) => {
+                ToolChoice::Auto
+            }
+            choice => choice.clone(),
+        };
+
+        fit_context(history, &active.tool_defs, agent, llm, logger).await;
+        let response = llm
+            .chat_with_tool_choice(
+                history.clone(),
+                active.tool_defs.clone(),
+                model,
+                json_mode,
+                &tool_choice,
+            )
+            .await?;
+
+        // If no tool calls, validate and return the response content
+        if response.tool_calls.is_empty() {
+            logger.log(
+                LogLevel::Debug,
+                "agent",
+                "No tool calls, returning response",
+            );
+            let content = response.content.unwrap_or_default();
+
+            // Validate output if schema exists
+            let content = match &agent.output_schema {
+                Some(schema) => {
+                    validate_and_retry_output(
+                        &content, schema, agent, history, llm, &active.tool_defs, model, logger,
+                    )
+                    .await?
+                }
+                None => content,
+            };
+
+            history.push(Message::assistant(&content));
+            return Ok(TurnResult {
+                output: content,
+                agent: active.agent,
+                handoffs,
+            });
+        }
+
+        logger.log(
+            LogLevel::Debug,
+            "agent",
+            &format!("LLM made {} tool call(s)", response.tool_calls.len()),
+        );
+        for call in &response.tool_calls {
+            logger.log(
+                LogLevel::Trace,
+                "agent",
+                &format!("  - {}({})", call.name, call.arguments),
+            );
+        }
+
+        called_tools = true;
+
+        // Add assistant message with tool calls
+        history.push(Message::assistant_with_tool_calls(
+            response.tool_calls.clone(),
+        ));
+
+        // Execute each tool call
+        let ctx = ToolContext {
+            agent: Some(&agent.name),
+            provider_factory,
+            tools,
+            logger,
+            env,
+        };
+        // Transfers are settled first, in order; the other calls run
+        // concurrently and their results are added in the order of the calls
+        let mut handoff = None;
+        let mut transfers = Vec::new();
+        let mut calls = Vec::new();
+        for call in &response.tool_calls {
+            if call.name == TRANSFER_TOOL && !active.targets.is_empty() {
+                let target = match transfer_target(call, &active.targets) {
+                    Ok(_) if handoff.is_some() => {
+                        Err("Only one transfer is allowed per step".to_string())
+                    }
+                    Ok(_) if handoffs.len() >= max_handoffs as usize => Err(format!(
+                        "Handoff limit ({}) reached; answer the user yourself",
+                        max_handoffs
+                    )),
+                    other => other,
+                };
+                let result = match target {
+                    Ok(target) => {
+                        let content = format!("Transferred to {}", target.name);
+                        handoff = Some(target.clone());
+                        ToolResult {
+                            call_id: call.id.clone(),
+                            content,
+                            is_error: false,
+                        }
+                    }
+                    Err(error) => {
+                        logger.log(
+                            LogLevel::Warn,
+                            "agent",
+                            &format!("Agent '{}' could not hand off: {}", agent.name, error),
+                        );
+                        ToolResult {
+                            call_id: call.id.clone(),
+                            content: error,
+                            is_error: true,
+                        }
+                    }
+                };
+                transfers.push(result);
+                continue;
+            }
+
+            let tool = active
+                .agent_tools
+                .iter()
+                .find(|tool| tool.name() == call.name)
+                .cloned()
+                .or_else(|| tools.get(&call.name));
+            calls.push((call, tool));
+        }
+
+        let concurrency = agent.tool_concurrency.unwrap_or(DEFAULT_TOOL_CONCURRENCY);
+        if calls.len() > 1 {
+            logger.log(
+                LogLevel::Debug,
+                "agent",
+                &format!(
+                    "Running {} tool calls, up to {} at a time",
+                    calls.len(),
+                    concurrency
+                ),
+            );
+        }
+        let pending: Vec<_> = calls
+            .into_iter()
+            .map(|(call, tool)| execute_tool_call(call, tool, &ctx, agent.tool_timeout_ms))
+            .collect();
+        let mut results: Vec<ToolResult> = stream::iter(pending)
+            .buffered(concurrency)
+            .collect()
+            .await;
+        results.extend(transfers);
+
+        // A successful call of the finish tool ends the run, with its
+        // arguments as the result
+        let mut finished = None;
+        if let Some(finish) = &agent.finish_tool {
+            for call in response.tool_calls.iter().filter(|call| call.name == *finish) {
+                let Some(result) = results
+                    .iter_mut()
+                    .find(|r| r.call_id == call.id && !r.is_error)
+                else {
+                    continue;
+                };
+                if let Some(schema) = &agent.output_schema {
+                    if let Err(error) = validate_output(&call.arguments, schema) {
+                        result.content = format!("Invalid result: {}", error);
+                        result.is_error = true;
+                        continue;
+                    }
+                }
+                finished = Some(call.arguments.to_string());
+                break;
+            }
+        }
+
+        for call in &response.tool_calls {
+            if let Some(index) = results.iter().position(|r| r.call_id == call.id) {
+                history.push(Message::tool_result(results.swap_remove(index)));
+            }
+        }
+
+        if let Some(output) = finished {
+            logger.log(
+                LogLevel::Debug,
+                "agent",
+                &format!("Agent '{}' finished with '{}'", agent.name, output),
+            );
+            return Ok(TurnResult {
+                output,
+                agent: active.agent,
+                handoffs,
+            });
+        }
+
+        // Switch to the new agent; it continues from the same history
+        // with its own system prompt, tools and step budget
+        if let Some(target) = handoff {
+            logger.log(
+                LogLevel::Info,
+                "agent",
+                &format!("Agent '{}' handed off to '{}'", agent.name, target.name),
+            );
+            handoffs.push(target.name.clone());
+            previous.push(active.agent);
+            active = ActiveAgent::new(
+                target,
+                &previous,
+                user_query.as_deref(),
+                provider_factory,
+                tools,
+                logger,
+                env,
+            )
+            .await?;
+            let context = active.context(user_query.as_deref(), logger).await;
+            set_system_message(history, system_message(&active.agent, context, logger));
+            step = 0;
+            called_tools = false;
+        }
+    }
+}
+
+/// Run one tool call; failures, unknown tools and calls that take longer
+/// than `timeout_ms` become error results for the model
+async fn execute_tool_call(
+    call: &ToolCall,
+    tool: Option<Arc<dyn Tool + '_>>,
+    ctx: &ToolContext<'_>,
+    timeout_ms: Option<u64>,
+) -> ToolResult {
+    let logger = ctx.logger;
+    let Some(tool) = tool else {
+        logger.log(
+            LogLevel::Warn,
+            "agent",
+            &format!("Unknown tool: {}", call.name),
+        );
+        return ToolResult {
+            call_id: call.id.clone(),
+            content: format!("Unknown tool: {}", call.name),
+            is_error: true,
+        };
+    };
+
+    let execute = tool.execute_with_context(call.arguments.clone(), ctx);
+    let output = match timeout_ms {
+        Some(ms) => tokio::time::timeout(Duration::from_millis(ms), execute)
+            .await
+            .unwrap_or_else(|_| Err(format!("Tool '{}' timed out after {}ms", call.name, ms))),
+        None => execute.await,
+    };
+    match output {
+        Ok(output) => {
+            logger.log(
+                LogLevel::Debug,
+                "agent",
+                &format!("Tool '{}' returned: {}", call.name, output),
+            );
+            ToolResult {
+                call_id: call.id.clone(),
+                content: output,
+                is_error: false,
+            }
+        }
+        Err(error) => {
+            logger.log(
+                LogLevel::Warn,
+                "agent",
+                &format!("Tool '{}' error: {}", call.name, error),
+            );
+            ToolResult {
+                call_id: call.id.clone(),
+                content: error,
+                is_error: true,
+            }
+        }
+    }
+}
+
+/// An agent taking part in a turn, with its client and tools
+struct ActiveAgent<'a> {
+    agent: AgentValue,
+    llm: Box<dyn LLMClient>,
+    /// Memory and sub-agent tools, looked up before the registry
+    agent_tools: Vec<Arc<dyn Tool + 'a>>,
+    tool_defs: Vec<ToolDefinition>,
+    /// Agents this one can hand off to
+    targets: Vec<AgentValue>,
+    /// Remembered facts relevant to the query
+    memory_context: Option<String>,
+}
+
+impl<'a> ActiveAgent<'a> {
+    /// Prepare `agent` to answer; agents in `previous` handed off earlier in
+    /// the turn and can be handed back to, and other handoff targets are
+    /// looked up in `env`
+    async fn new(
+        agent: AgentValue,
+        previous: &[AgentValue],
+        user_query: Option<&str>,
+        provider_factory: &'a ProviderFactory,
+        tools: &'a ToolRegistry,
+        logger: &'a dyn Logger,
+        env: Option<&Environment>,
+    ) -> GentResult<Self> {
+        // Create LLM client from factory using agent's provider setting
+        let llm = provider_factory.create_with_options(
+            agent.provider.as_deref(),
+            agent.dangerously_skip_permissions,
+        )?;
+
+        // Open the agent's memory and recall facts relevant to the query
+        let mut memory_context = None;
+        let mut agent_tools: Vec<Arc<dyn Tool + 'a>> = Vec::new();
+        if let Some(ref dir) = agent.memory {
+            let store = MemoryStore::open(dir, &agent.name, provider_factory.embeddings()?)?;
+            if let Some(query) = user_query {
+                match store.context(query).await {
+                    Ok(context) => memory_context = context,
+                    Err(e) => logger.log(
+                        LogLevel::Warn,
+                        "agent",
+                        &format!("Memory recall failed: {}", e),
+                    ),
+                }
+            }
+            logger.log(
+                LogLevel::Debug,
+                "agent",
+                &format!(
+                    "Agent '{}' remembers {} fact(s)",
+                    agent.name,
+                    store.facts().len()
+                ),
+            );
+            agent_tools.extend(memory_tools(Arc::new(Mutex::new(store))));
+        }
+
+        // Offer each sub-agent as a tool
+        for sub_agent in &agent.sub_agents {
+            agent_tools.push(Arc::new(AgentTool::new(
+                sub_agent,
+                provider_factory,
+                tools,
+                logger,
+            )));
+        }
+
+        let targets = handoff_targets(&agent, previous, |name| match env?.get(name)? {
+            Value::Agent(target) => Some((**target).clone()),
+            _ => None,
+        })?;
+        let mut tool_defs = tools.definitions_for(&agent.tools);
+        tool_defs.extend(agent_tools.iter().map(|tool| tool.to_definition()));
+        if !targets.is_empty() {
+            tool_defs.push(transfer_definition(&targets));
+        }
+
+        logger.log(
+            LogLevel::Debug,
+            "agent",
+            &format!(
+                "Agent '{}' - knowledge_config: {}, user_prompt: {:?}",
+                agent.name,
+                agent.knowledge_config.is_some(),
+                agent.user_prompt
+            ),
+        );
+        logger.log(
+            LogLevel::Debug,
+            "agent",
+            &format!("Agent '{}' requested tools: {:?}", agent.name, agent.tools),
+        );
+        logger.log(
+            LogLevel::Debug,
+            "agent",
+            &format!("Tool definitions provided to LLM: {}", tool_defs.len()),
+        );
+        for def in &tool_defs {
+            logger.log(
+                LogLevel::Trace,
+                "agent",
+                &format!("  - {} : {}", def.name, def.description),
+            );
+        }
+
+        Ok(Self {
+            agent,
+            llm,
+            agent_tools,
+            tool_defs,
+            targets,
+            memory_context,
+        })
+    }
+
+    /// Knowledge base and memory context for the agent's system message
+    async fn context(&mut self, user_query: Option<&str>, logger: &dyn Logger) -> Option<String> {
+        let rag = knowledge_context(&self.agent, user_query, logger).await;
+        match (rag, self.memory_context.take()) {
+            (Some(rag), Some(memory)) => Some(rag + &memory),
+            (rag, memory) => rag.or(memory),
+        }
+    }
+}
+
+/// Context found in the agent's knowledge base for `user_query`
+async fn knowledge_context(
+    agent: &AgentValue,
+    user_query: Option<&str>,
+    logger: &dyn Logger,
+) -> Option<String> {
+    if let Some(ref knowledge_config) = agent.knowledge_config {
+        if let Some(query) = user_query {
             logger.log(
                 LogLevel::Debug,
                 "agent",
@@ -159,160 +652,43 @@ pub async fn run_agent_with_tools(
         }
     } else {
         None
-    };
-
-    // Build system message from: system_prompt + output_schema + RAG context
-    let has_system_content = !agent.system_prompt.is_empty()
-        || agent.output_schema.is_some()
-        || rag_context.is_some();
-
-    if has_system_content {
-        let mut system_prompt = agent.system_prompt.clone();
-
-        // Add output schema instructions if present
-        if let Some(schema) = &agent.output_schema {
-            logger.log(
-                LogLevel::Debug,
-                "agent",
-                "Agent has output schema, enabling JSON mode",
-            );
-            let default_instructions = "You must respond with JSON matching this schema:";
-            let instructions = agent
-                .output_instructions
-                .as_deref()
-                .unwrap_or(default_instructions);
-            if !system_prompt.is_empty() {
-                system_prompt.push_str("\n\n");
-            }
-            system_prompt.push_str(&format!(
-                "{}\n{}",
-                instructions,
-                serde_json::to_string_pretty(&schema.to_json_schema())
-                    .unwrap_or_else(|_| "<schema>".to_string())
-            ));
-        }
-
-        // Append RAG context if available
-        if let Some(context) = rag_context {
-            system_prompt.push_str(&context);
-        }
-
-        messages.push(Message::system(&system_prompt));
     }
+}
 
-    // Add user message from agent's user_prompt or from input parameter
-    if let Some(user_prompt) = &agent.user_prompt {
-        messages.push(Message::user(user_prompt.clone()));
-    } else if let Some(user_input) = input {
-        messages.push(Message::user(user_input));
-    }
-
-    // If no messages at all, return empty result
-    if messages.is_empty() {
-        logger.log(
-            LogLevel::Debug,
-            "agent",
-            "No prompts provided, returning empty result",
-        );
-        return Ok(String::new());
-    }
-
-    for step in 0..max_steps {
-        logger.log(
-            LogLevel::Debug,
-            "agent",
-            &format!("Step {}/{}", step + 1, max_steps),
-        );
-        let response = llm
-            .chat(messages.clone(), tool_defs.clone(), model, json_mode)
-            .await?;
-
-        // If no tool calls, validate and return the response content
-        if response.tool_calls.is_empty() {
-            logger.log(
-                LogLevel::Debug,
-                "agent",
-                "No tool calls, returning response",
-            );
-            let content = response.content.unwrap_or_default();
-
-            // Validate output if schema exists
-            if let Some(schema) = &agent.output_schema {
-                return validate_and_retry_output(
-                    &content, schema, agent, &messages, llm, &tool_defs, model, logger,
-                )
-                .await;
-            }
-
-            return Ok(content);
-        }
+/// Build the system message from the system prompt, output schema
+/// instructions and any RAG or memory context
+fn system_message(agent: &AgentValue, context: Option<String>, logger: &dyn Logger) -> String {
+    let mut system_prompt = agent.system_prompt.clone();
 
+    // Add output schema instructions if present
+    if let Some(schema) = &agent.output_schema {
         logger.log(
             LogLevel::Debug,
             "agent",
-            &format!("LLM made {} tool call(s)", response.tool_calls.len()),
+            "Agent has output schema, enabling JSON mode",
         );
-        for call in &response.tool_calls {
-            logger.log(
-                LogLevel::Trace,
-                "agent",
-                &format!("  - {}({})", call.name, call.arguments),
-            );
+        let default_instructions = "You must respond with JSON matching this schema:";
+        let instructions = agent
+            .output_instructions
+            .as_deref()
+            .unwrap_or(default_instructions);
+        if !system_prompt.is_empty() {
+            system_prompt.push_str("\n\n");
         }
-
-        // Add assistant message with tool calls
-        messages.push(Message::assistant_with_tool_calls(
-            response.tool_calls.clone(),
+        system_prompt.push_str(&format!(
+            "{}\n{}",
+            instructions,
+            serde_json::to_string_pretty(&schema.to_json_schema())
+                .unwrap_or_else(|_| "<schema>".to_string())
         ));
+    }
 
-        // Execute each tool call
-        for call in &response.tool_calls {
-            let result = match tools.get(&call.name) {
-                Some(tool) => match tool.execute(call.arguments.clone()).await {
-                    Ok(output) => {
-                        logger.log(
-                            LogLevel::Debug,
-                            "agent",
-                            &format!("Tool '{}' returned: {}", call.name, output),
-                        );
-                        ToolResult {
-                            call_id: call.id.clone(),
-                            content: output,
-                            is_error: false,
-                        }
-                    }
-                    Err(error) => {
-                        logger.log(
-                            LogLevel::Warn,
-                            "agent",
-                            &format!("Tool '{}' error: {}", call.name, error),
-                        );
-                        ToolResult {
-                            call_id: call.id.clone(),
-                            content: error,
-                            is_error: true,
-                        }
-                    }
-                },
-                None => {
-                    logger.log(
-                        LogLevel::Warn,
-                        "agent",
-                        &format!("Unknown tool: {}", call.name),
-                    );
-                    ToolResult {
-                        call_id: call.id.clone(),
-                        content: format!("Unknown tool: {}", call.name),
-                        is_error: true,
-                    }
-                }
-            };
-
-            messages.push(Message::tool_result(result));
-        }
+    // Append RAG and memory context if available
+    if let Some(context) = context {
+        system_prompt.push_str(&context);
     }
 
-    Err(GentError::MaxStepsExceeded { limit: max_steps })
+    system_prompt
 }
 
 /// Run an agent and return the full LLM response
@@ -392,6 +768,7 @@ async fn validate_and_retry_output(
                 let retry_msg = agent.retry_prompt.as_deref().unwrap_or(default_retry);
                 retry_messages.push(Message::assistant(&last_content));
                 retry_messages.push(Message::user(retry_msg));
+                fit_context(&mut retry_messages, tools, agent, llm, logger).await;
                 let response = llm
                     .chat(retry_messages.clone(), tools.to_vec(), model, true)
                     .await?;
@@ -430,6 +807,7 @@ async fn validate_and_retry_output(
                     .unwrap_or(default_retry);
                 retry_messages.push(Message::assistant(&last_content));
                 retry_messages.push(Message::user(retry_msg));
+                fit_context(&mut retry_messages, tools, agent, llm, logger).await;
                 let response = llm
                     .chat(retry_messages.clone(), tools.to_vec(), model, true)
                     .await?;
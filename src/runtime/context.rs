@@ -0,0 +1,373 @@
+//! Context window management for agent runs
+//!
+//! Every tool call and result adds a message, so a long tool loop can grow
+//! past what the model accepts. Before each request the messages are
+//! measured against the model's context window and, if they do not fit,
+//! older turns are reduced according to the agent's `contextStrategy`.
+
+use crate::interpreter::AgentValue;
+use crate::logging::{LogLevel, Logger};
+use crate::runtime::{LLMClient, Message, Role, ToolDefinition};
+use std::ops::Range;
+
+/// Context window used for models not in [`MODEL_CONTEXT_LIMITS`]
+pub const DEFAULT_CONTEXT_LIMIT: usize = 128_000;
+
+/// Context window in tokens, by model name prefix (first match wins)
+pub const MODEL_CONTEXT_LIMITS: &[(&str, usize)] = &[
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+];
+
+/// Tokens added per message for role and formatting
+const MESSAGE_OVERHEAD: usize = 4;
+
+/// Most tokens kept free for the model's reply
+const MAX_RESPONSE_RESERVE: usize = 4_096;
+
+/// Content of a tool result removed by `drop-tool-results`
+pub const DROPPED_TOOL_RESULT: &str = "[tool result removed to save context]";
+
+/// Start of the message that replaces summarized turns
+pub const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:\n";
+
+const SUMMARY_PROMPT: &str = "Summarize the following conversation between a user, an assistant and its tools. \
+Keep every fact, decision and tool result the assistant may still need. Reply with the summary only.";
+
+/// How older turns are reduced when the messages do not fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContextStrategy {
+    /// Drop the oldest turns
+    #[default]
+    Truncate,
+    /// Replace the oldest turns with a summary written by the model
+    Summarize,
+    /// Replace old tool results with a placeholder, then drop turns if needed
+    DropToolResults,
+}
+
+impl ContextStrategy {
+    /// Names accepted by the `contextStrategy` agent field
+    pub const NAMES: &'static [&'static str] = &["truncate", "summarize", "drop-tool-results"];
+
+    /// Parse a `contextStrategy` name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truncate" => Some(Self::Truncate),
+            "summarize" => Some(Self::Summarize),
+            "drop-tool-results" => Some(Self::DropToolResults),
+            _ => None,
+        }
+    }
+
+    /// Name of the strategy as written in GENT
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Truncate => "truncate",
+            Self::Summarize => "summarize",
+            Self::DropToolResults => "drop-tool-results",
+        }
+    }
+}
+
+/// Estimate the number of tokens a message takes
+///
+/// Uses the common approximation of four characters per token, which is
+/// close enough for English text and JSON to stay within the window.
+pub fn estimate_tokens(message: &Message) -> usize {
+    let mut chars = message.content.chars().count();
+    for call in message.tool_calls.iter().flatten() {
+        chars += call.id.len() + call.name.len() + call.arguments.to_string().len();
+    }
+    if let Some(id) = &message.tool_call_id {
+        chars += id.len();
+    }
+    chars_to_tokens(chars) + MESSAGE_OVERHEAD
+}
+
+fn chars_to_tokens(chars: usize) -> usize {
+    (chars + 3) / 4
+}
+
+/// Context window of `model` in tokens
+pub fn context_limit(model: Option<&str>) -> usize {
+    let Some(model) = model else {
+        return DEFAULT_CONTEXT_LIMIT;
+    };
+    MODEL_CONTEXT_LIMITS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, limit)| *limit)
+        .unwrap_or(DEFAULT_CONTEXT_LIMIT)
+}
+
+/// Reduce `messages` so they fit the agent's context window
+///
+/// The system message, the latest user message and the latest turn are
+/// always kept. Everything that is removed or summarized is logged.
+pub async fn fit_context(
+    messages: &mut Vec<Message>,
+    tools: &[ToolDefinition],
+    agent: &AgentValue,
+    llm: &dyn LLMClient,
+    logger: &dyn Logger,
+) {
+    let limit = agent
+        .context_limit
+        .unwrap_or_else(|| context_limit(agent.model.as_deref()));
+    let budget = limit - (limit / 4).min(MAX_RESPONSE_RESERVE);
+    let tool_tokens = serde_json::to_string(tools)
+        .map(|json| chars_to_tokens(json.len()))
+        .unwrap_or(0);
+    let total = tool_tokens + messages.iter().map(estimate_tokens).sum::<usize>();
+    if total <= budget {
+        return;
+    }
+
+    logger.log(
+        LogLevel::Info,
+        "context",
+        &format!(
+            "Agent '{}' request is about {} tokens, over its budget of {} (context window {}); applying '{}'",
+            agent.name,
+            total,
+            budget,
+            limit,
+            agent.context_strategy.name()
+        ),
+    );
+    let excess = total - budget;
+    let excess = match agent.context_strategy {
+        ContextStrategy::Truncate => truncate(messages, excess, logger),
+        ContextStrategy::DropToolResults => {
+            let excess = drop_tool_results(messages, excess, logger);
+            truncate(messages, excess, logger)
+        }
+        ContextStrategy::Summarize => {
+            let excess = summarize(messages, excess, agent, llm, logger).await;
+            truncate(messages, excess, logger)
+        }
+    };
+    if excess > 0 {
+        logger.log(
+            LogLevel::Warn,
+            "context",
+            &format!(
+                "Agent '{}' request is still about {} tokens over budget",
+                agent.name, excess
+            ),
+        );
+    }
+}
+
+/// Messages that can be removed together, oldest first
+///
+/// Before the latest user message, a turn is a whole exchange: a user
+/// message with the replies, tool calls and tool results up to the next
+/// one, so no reply is left without its question. After it, in the current
+/// tool loop, an assistant message is grouped with the tool results that
+/// follow it, so a tool call is never sent without its result or the other
+/// way round. The system message, the latest user message and the latest
+/// turn are left out.
+fn removable_turns(messages: &[Message]) -> Vec<Range<usize>> {
+    let start = match messages.first() {
+        Some(first) if first.role == Role::System => 1,
+        _ => 0,
+    };
+    let latest_user = messages.iter().rposition(|m| m.role == Role::User);
+
+    let mut turns = Vec::new();
+    let mut i = start;
+    if let Some(latest_user) = latest_user {
+        while i < latest_user {
+            let mut end = i + 1;
+            while end < latest_user && messages[end].role != Role::User {
+                end += 1;
+            }
+            turns.push(i..end);
+            i = end;
+        }
+        i = latest_user + 1;
+    }
+
+    let mut tool_loop = Vec::new();
+    while i < messages.len() {
+        let mut end = i + 1;
+        while end < messages.len() && messages[end].role == Role::Tool {
+            end += 1;
+        }
+        tool_loop.push(i..end);
+        i = end;
+    }
+    tool_loop.pop();
+    turns.extend(tool_loop);
+    turns
+}
+
+/// Drop the oldest turns until `excess` tokens are gone; returns what is left
+fn truncate(messages: &mut Vec<Message>, excess: usize, logger: &dyn Logger) -> usize {
+    if excess == 0 {
+        return 0;
+    }
+    let (turns, removed) = oldest_turns(messages, excess);
+    if turns.is_empty() {
+        return excess;
+    }
+
+    let count: usize = turns.iter().map(|turn| turn.len()).sum();
+    logger.log(
+        LogLevel::Info,
+        "context",
+        &format!(
+            "Dropped {} older message(s), about {} tokens",
+            count, removed
+        ),
+    );
+    for i in turns.iter().flat_map(|turn| turn.clone()) {
+        logger.log(
+            LogLevel::Debug,
+            "context",
+            &format!("  - {}", describe(i, &messages[i])),
+        );
+    }
+    for turn in turns.iter().rev() {
+        messages.drain(turn.clone());
+    }
+    excess.saturating_sub(removed)
+}
+
+/// The oldest removable turns that add up to at least `excess` tokens, and
+/// their size
+fn oldest_turns(messages: &[Message], excess: usize) -> (Vec<Range<usize>>, usize) {
+    let mut removed = 0;
+    let mut turns = Vec::new();
+    for turn in removable_turns(messages) {
+        if removed >= excess {
+            break;
+        }
+        removed += messages[turn.clone()]
+            .iter()
+            .map(estimate_tokens)
+            .sum::<usize>();
+        turns.push(turn);
+    }
+    (turns, removed)
+}
+
+/// Clear the oldest tool results until `excess` tokens are gone; returns
+/// what is left
+fn drop_tool_results(messages: &mut [Message], excess: usize, logger: &dyn Logger) -> usize {
+    let mut removed = 0;
+    let mut cleared = 0;
+    for turn in removable_turns(messages) {
+        for message in &mut messages[turn] {
+            if removed >= excess {
+                break;
+            }
+            if message.role != Role::Tool || message.content == DROPPED_TOOL_RESULT {
+                continue;
+            }
+            let before = estimate_tokens(message);
+            message.content = DROPPED_TOOL_RESULT.to_string();
+            removed += before.saturating_sub(estimate_tokens(message));
+            cleared += 1;
+        }
+    }
+    if cleared > 0 {
+        logger.log(
+            LogLevel::Info,
+            "context",
+            &format!(
+                "Removed {} older tool result(s), about {} tokens",
+                cleared, removed
+            ),
+        );
+    }
+    excess.saturating_sub(removed)
+}
+
+/// Replace the oldest turns with a summary until `excess` tokens are gone;
+/// returns what is left
+///
+/// If the model cannot write the summary, nothing changes and the turns are
+/// truncated instead.
+async fn summarize(
+    messages: &mut Vec<Message>,
+    excess: usize,
+    agent: &AgentValue,
+    llm: &dyn LLMClient,
+    logger: &dyn Logger,
+) -> usize {
+    let (turns, removed) = oldest_turns(messages, excess);
+    let Some(first) = turns.first().map(|turn| turn.start) else {
+        return excess;
+    };
+
+    let transcript: Vec<String> = turns
+        .iter()
+        .flat_map(|turn| &messages[turn.clone()])
+        .map(transcript_line)
+        .collect();
+    let request = vec![
+        Message::system(SUMMARY_PROMPT),
+        Message::user(transcript.join("\n")),
+    ];
+    let summary = match llm
+        .chat(request, vec![], agent.model.as_deref(), false)
+        .await
+    {
+        Ok(response) => response.content.filter(|s| !s.trim().is_empty()),
+        Err(e) => {
+            logger.log(
+                LogLevel::Warn,
+                "context",
+                &format!("Could not summarize older messages: {}", e),
+            );
+            None
+        }
+    };
+    let Some(summary) = summary else {
+        return excess;
+    };
+
+    let summary = Message::user(format!("{}{}", SUMMARY_PREFIX, summary.trim()));
+    let added = estimate_tokens(&summary);
+    let count: usize = turns.iter().map(|turn| turn.len()).sum();
+    logger.log(
+        LogLevel::Info,
+        "context",
+        &format!(
+            "Summarized {} older message(s), about {} tokens, in {} tokens",
+            count, removed, added
+        ),
+    );
+    for turn in turns.iter().rev() {
+        messages.drain(turn.clone());
+    }
+    messages.insert(first, summary);
+    (excess + added).saturating_sub(removed)
+}
+
+fn transcript_line(message: &Message) -> String {
+    let mut line = format!("{:?}: {}", message.role, message.content);
+    for call in message.tool_calls.iter().flatten() {
+        line.push_str(&format!("\n(called {} with {})", call.name, call.arguments));
+    }
+    line
+}
+
+fn describe(index: usize, message: &Message) -> String {
+    let calls = message.tool_calls.as_ref().map_or(0, Vec::len);
+    if calls > 0 {
+        return format!("#{} {:?} with {} tool call(s)", index, message.role, calls);
+    }
+    let preview: String = message.content.chars().take(60).collect();
+    format!("#{} {:?}: {}", index, message.role, preview)
+}
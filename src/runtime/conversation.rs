@@ -0,0 +1,188 @@
+//! Multi-turn conversations with an agent
+//!
+//! A conversation keeps the messages of every turn, including tool calls
+//! and their results, and sends them with each new message so the agent
+//! can answer follow-up questions. When the agent hands off, later
+//! messages go to the agent that answered.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::{AgentValue, Environment};
+use crate::logging::Logger;
+use crate::runtime::agent::run_agent_turn_with_handoffs;
+use crate::runtime::{Message, ProviderFactory, Role, ToolRegistry};
+
+/// An agent and the messages exchanged with it so far
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    agent: AgentValue,
+    messages: Vec<Message>,
+    handoffs: Vec<String>,
+}
+
+/// Conversation as stored in a file
+#[derive(Serialize, Deserialize)]
+struct SavedConversation {
+    agent: String,
+    messages: Vec<Message>,
+}
+
+impl Conversation {
+    /// Start an empty conversation with `agent`
+    ///
+    /// Messages come from `send`, so the agent's `userPrompt` is not used.
+    pub fn new(agent: AgentValue) -> Self {
+        let agent = AgentValue {
+            user_prompt: None,
+            ..agent
+        };
+        Self {
+            agent,
+            messages: Vec::new(),
+            handoffs: Vec::new(),
+        }
+    }
+
+    /// The agent being talked to, which is the last agent that answered
+    pub fn agent(&self) -> &AgentValue {
+        &self.agent
+    }
+
+    /// Names of the agents handed off to in the last turn, in order
+    pub fn handoffs(&self) -> &[String] {
+        &self.handoffs
+    }
+
+    /// Messages so far, oldest first
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Send a message and return the agent's reply
+    ///
+    /// If the turn fails, the history is left as it was before the message.
+    pub async fn send(
+        &mut self,
+        text: impl Into<String>,
+        provider_factory: &ProviderFactory,
+        tools: &ToolRegistry,
+        logger: &dyn Logger,
+    ) -> GentResult<String> {
+        self.send_with_env(text, provider_factory, tools, logger, None)
+            .await
+    }
+
+    /// Send a message; user-defined tools called by the agent see `env`
+    pub async fn send_with_env(
+        &mut self,
+        text: impl Into<String>,
+        provider_factory: &ProviderFactory,
+        tools: &ToolRegistry,
+        logger: &dyn Logger,
+        env: Option<&Environment>,
+    ) -> GentResult<String> {
+        // Trimming may rewrite earlier messages, so keep a copy to restore
+        let before = self.messages.clone();
+        let result = run_agent_turn_with_handoffs(
+            &self.agent,
+            Some(text.into()),
+            &mut self.messages,
+            provider_factory,
+            tools,
+            logger,
+            env,
+        )
+        .await;
+        match result {
+            Ok(result) => {
+                if result.agent.name != self.agent.name {
+                    self.agent = AgentValue {
+                        user_prompt: None,
+                        ..result.agent
+                    };
+                }
+                self.handoffs = result.handoffs;
+                Ok(result.output)
+            }
+            Err(e) => {
+                self.messages = before;
+                Err(e)
+            }
+        }
+    }
+
+    /// Write the conversation to a JSON file
+    pub fn save(&self, path: &Path) -> GentResult<()> {
+        let saved = SavedConversation {
+            agent: self.agent.name.clone(),
+            messages: self.messages.clone(),
+        };
+        let json = serde_json::to_string_pretty(&saved).map_err(std::io::Error::from);
+        json.and_then(|json| std::fs::write(path, json))
+            .map_err(|source| GentError::FileWriteError {
+                path: path.display().to_string(),
+                source,
+            })
+    }
+
+    /// Take over a turn that was sent on a copy of this conversation
+    ///
+    /// `before` is the history when the copy was made. If it has not
+    /// changed since, the copy's history replaces it; otherwise the turn's
+    /// messages, from its user message on, are added after the current ones.
+    pub(crate) fn finish_turn(&mut self, before: &[Message], turn: Conversation) {
+        let mut messages = turn.messages;
+        if self.messages == before {
+            self.messages = messages;
+        } else {
+            let start = messages
+                .iter()
+                .rposition(|m| m.role == Role::User)
+                .unwrap_or(messages.len());
+            self.messages.extend(messages.drain(start..));
+        }
+        self.agent = turn.agent;
+        self.handoffs = turn.handoffs;
+    }
+
+    /// Replace the history with the messages saved in a JSON file
+    ///
+    /// The file must have been saved by a conversation with the same agent.
+    pub fn load(&mut self, path: &Path) -> GentResult<()> {
+        self.load_with(path, |name| {
+            Err(GentError::UndefinedAgent {
+                name: name.to_string(),
+                span: Span::default(),
+            })
+        })
+    }
+
+    /// Replace the history and agent with those saved in a JSON file
+    ///
+    /// When the saved agent is not the current one (after a handoff, or a
+    /// file from another conversation), `agent` looks it up by name.
+    pub fn load_with(
+        &mut self,
+        path: &Path,
+        agent: impl FnOnce(&str) -> GentResult<AgentValue>,
+    ) -> GentResult<()> {
+        let read_error = |source| GentError::FileReadError {
+            path: path.display().to_string(),
+            source,
+        };
+        let text = std::fs::read_to_string(path).map_err(read_error)?;
+        let saved: SavedConversation = serde_json::from_str(&text)
+            .map_err(|e| read_error(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+        if saved.agent != self.agent.name {
+            self.agent = AgentValue {
+                user_prompt: None,
+                ..agent(&saved.agent)?
+            };
+        }
+        self.messages = saved.messages;
+        self.handoffs.clear();
+        Ok(())
+    }
+}
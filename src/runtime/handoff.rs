@@ -0,0 +1,135 @@
+//! Handoffs between agents
+//!
+//! An agent declared with `handoffs: [Billing, Support]` is offered a
+//! transfer tool. When the model calls it, the named agent takes over the
+//! turn: it gets its own system prompt and tools but keeps the messages
+//! exchanged so far, and may hand off again until the limit is reached.
+//! Targets are looked up by name when the agent runs, so they may be
+//! declared after it.
+
+use serde_json::json;
+
+use crate::errors::{GentError, GentResult, Span};
+use crate::interpreter::AgentValue;
+use crate::runtime::{Message, Role, ToolCall, ToolDefinition};
+
+/// Tool the model transfers the conversation with
+pub const TRANSFER_TOOL: &str = "transfer_to_agent";
+
+/// Handoffs allowed in one turn when `maxHandoffs` is not set
+pub const DEFAULT_MAX_HANDOFFS: u32 = 5;
+
+/// Outcome of a turn that may have been handed off
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnResult {
+    /// The final reply
+    pub output: String,
+    /// The agent that gave the final reply
+    pub agent: AgentValue,
+    /// Names of the agents handed off to, in order
+    pub handoffs: Vec<String>,
+}
+
+/// Agents `agent` can hand off to
+///
+/// These are its own `handoffs` followed by the agents that handed off
+/// earlier in the turn, so control can be passed back. The agent itself
+/// is never a target. Handoffs are named, so agents may hand off to each
+/// other; names not among `previous` are looked up with `lookup`.
+pub fn handoff_targets(
+    agent: &AgentValue,
+    previous: &[AgentValue],
+    lookup: impl Fn(&str) -> Option<AgentValue>,
+) -> GentResult<Vec<AgentValue>> {
+    let mut targets: Vec<AgentValue> = Vec::new();
+    for name in &agent.handoffs {
+        if *name == agent.name || targets.iter().any(|t| t.name == *name) {
+            continue;
+        }
+        let target = previous
+            .iter()
+            .find(|a| a.name == *name)
+            .cloned()
+            .or_else(|| lookup(name))
+            .ok_or_else(|| GentError::UndefinedAgent {
+                name: name.clone(),
+                span: Span::default(),
+            })?;
+        targets.push(target);
+    }
+    for target in previous.iter().rev() {
+        if target.name != agent.name && !targets.iter().any(|t| t.name == target.name) {
+            targets.push(target.clone());
+        }
+    }
+    Ok(targets)
+}
+
+/// Definition of the transfer tool for `targets`
+pub fn transfer_definition(targets: &[AgentValue]) -> ToolDefinition {
+    let mut description = String::from(
+        "Transfer the conversation to an agent better suited to answer. \
+The agent sees the whole conversation and replies in your place. Available agents:",
+    );
+    for target in targets {
+        description.push_str(&format!("\n- {}", target.name));
+        if let Some(line) = target.system_prompt.lines().find(|l| !l.trim().is_empty()) {
+            description.push_str(&format!(": {}", line.trim()));
+        }
+    }
+    let names: Vec<&str> = targets.iter().map(|t| t.name.as_str()).collect();
+    ToolDefinition {
+        name: TRANSFER_TOOL.to_string(),
+        description,
+        parameters: json!({
+            "type": "object",
+            "properties": {
+                "agent": {
+                    "type": "string",
+                    "enum": names,
+                    "description": "Name of the agent to transfer to"
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "Why the agent is better suited"
+                }
+            },
+            "required": ["agent"]
+        }),
+    }
+}
+
+/// The target a transfer call asks for
+pub fn transfer_target<'a>(
+    call: &ToolCall,
+    targets: &'a [AgentValue],
+) -> Result<&'a AgentValue, String> {
+    let name = call
+        .arguments
+        .get("agent")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required 'agent' parameter".to_string())?;
+    targets.iter().find(|t| t.name == name).ok_or_else(|| {
+        let names: Vec<&str> = targets.iter().map(|t| t.name.as_str()).collect();
+        format!(
+            "Unknown agent '{}'; you can transfer to: {}",
+            name,
+            names.join(", ")
+        )
+    })
+}
+
+/// Make `content` the system message, replacing the current one
+pub fn set_system_message(messages: &mut Vec<Message>, content: String) {
+    match messages.first_mut() {
+        Some(first) if first.role == Role::System => {
+            if content.is_empty() {
+                messages.remove(0);
+            } else {
+                first.content = content;
+            }
+        }
+        _ if content.is_empty() => {}
+        _ => messages.insert(0, Message::system(content)),
+    }
+}
@@ -51,6 +51,32 @@ pub struct ToolDefinition {
     pub parameters: JsonValue,
 }
 
+/// Whether the model may, must or must not call tools
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ToolChoice {
+    /// The model decides
+    #[default]
+    Auto,
+    /// The model must call at least one tool
+    Required,
+    /// The model must answer without calling tools
+    None,
+    /// The model must call the named tool
+    Tool(String),
+}
+
+impl ToolChoice {
+    /// Read an agent's `toolChoice`: "auto", "required", "none" or a tool name
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "auto" => ToolChoice::Auto,
+            "required" => ToolChoice::Required,
+            "none" => ToolChoice::None,
+            name => ToolChoice::Tool(name.to_string()),
+        }
+    }
+}
+
 /// A message in a chat conversation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
@@ -165,6 +191,21 @@ pub trait LLMClient: Send + Sync {
         model: Option<&str>,
         json_mode: bool,
     ) -> GentResult<LLMResponse>;
+
+    /// Send a chat request, telling the model whether it must call tools
+    ///
+    /// Clients whose API has no tool choice use `chat`, which this calls
+    /// by default.
+    async fn chat_with_tool_choice(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<ToolDefinition>,
+        model: Option<&str>,
+        json_mode: bool,
+        _tool_choice: &ToolChoice,
+    ) -> GentResult<LLMResponse> {
+        self.chat(messages, tools, model, json_mode).await
+    }
 }
 
 /// Mock LLM client for testing
@@ -0,0 +1,324 @@
+//! Persistent agent memory
+//!
+//! Agents declared with a `memory:` field keep facts between runs. Each
+//! agent has its own directory under the memory directory:
+//!
+//! - `<dir>/<agent>/facts.json`: the facts, by key
+//! - `<dir>/<agent>/vectors.json`: their embeddings, for semantic recall
+//! - `<dir>/<agent>/embeddings.json`: the model that made the embeddings;
+//!   a store is only searched and extended with embeddings from that model
+//!
+//! Before each run the facts closest to the input are added to the system
+//! prompt, and the model gets `remember` and `recall` tools to store and
+//! look up facts itself.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::errors::{GentError, GentResult};
+use crate::runtime::rag::store::LocalVectorStore;
+use crate::runtime::rag::{EmbeddingProvider, Metadata, VectorStore};
+use crate::runtime::tools::Tool;
+
+/// Memory directory used by `memory: true` and the CLI
+pub const DEFAULT_MEMORY_DIR: &str = ".gent_memory";
+
+/// Tool the model stores facts with
+pub const REMEMBER_TOOL: &str = "remember";
+
+/// Tool the model looks facts up with
+pub const RECALL_TOOL: &str = "recall";
+
+/// Number of facts added to the prompt before a run
+const CONTEXT_LIMIT: usize = 3;
+
+/// Number of facts returned by the `recall` tool
+const RECALL_LIMIT: usize = 5;
+
+/// Facts remembered by one agent
+#[derive(Debug)]
+pub struct MemoryStore {
+    facts_path: PathBuf,
+    facts: BTreeMap<String, String>,
+    vectors: LocalVectorStore,
+    embeddings: Arc<dyn EmbeddingProvider>,
+    model_path: PathBuf,
+    model: Option<EmbeddingModel>,
+}
+
+/// Embedding model the stored vectors were made with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct EmbeddingModel {
+    model: String,
+    dimensions: usize,
+}
+
+impl EmbeddingModel {
+    fn of(embeddings: &dyn EmbeddingProvider) -> Self {
+        Self {
+            model: embeddings.model().to_string(),
+            dimensions: embeddings.dimensions(),
+        }
+    }
+}
+
+impl MemoryStore {
+    /// Open the memory of `agent` in `dir`, creating it on first write
+    pub fn open(
+        dir: &Path,
+        agent: &str,
+        embeddings: Arc<dyn EmbeddingProvider>,
+    ) -> GentResult<Self> {
+        let agent_dir = dir.join(agent);
+        let facts_path = agent_dir.join("facts.json");
+        let model_path = agent_dir.join("embeddings.json");
+        Ok(Self {
+            facts: read_json(&facts_path)?.unwrap_or_default(),
+            model: read_json(&model_path)?,
+            facts_path,
+            vectors: LocalVectorStore::with_path(agent_dir.join("vectors.json")),
+            embeddings,
+            model_path,
+        })
+    }
+
+    /// All facts, by key
+    pub fn facts(&self) -> &BTreeMap<String, String> {
+        &self.facts
+    }
+
+    /// Look up a fact by key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.facts.get(key).map(String::as_str)
+    }
+
+    /// Store a fact, replacing any fact with the same key
+    pub async fn remember(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.check_model()?;
+        let text = format!("{}: {}", key, value);
+        let embedding = self.embeddings.embed(&text).await?;
+        let metadata = Metadata {
+            source: key.to_string(),
+            chunk_index: 0,
+            start_line: 0,
+            end_line: 0,
+            content: text,
+        };
+        self.vectors.add(key, embedding, metadata).await?;
+        self.facts.insert(key.to_string(), value.to_string());
+        if self.model.is_none() {
+            self.model = Some(EmbeddingModel::of(self.embeddings.as_ref()));
+            write_json(&self.model_path, &self.model)?;
+        }
+        self.save()
+    }
+
+    /// Remove a fact; returns false if there was none
+    pub async fn forget(&mut self, key: &str) -> Result<bool, String> {
+        if self.facts.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.vectors.delete(key).await?;
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Remove every fact
+    pub async fn clear(&mut self) -> Result<(), String> {
+        self.facts.clear();
+        self.vectors.clear().await?;
+        if self.model.take().is_some() {
+            std::fs::remove_file(&self.model_path)
+                .map_err(|e| format!("Failed to write memory: {}", e))?;
+        }
+        self.save()
+    }
+
+    /// Facts relevant to `query`, most relevant first
+    ///
+    /// A fact whose key is the query comes first, followed by the facts
+    /// whose embeddings are closest to it.
+    pub async fn recall(&self, query: &str, limit: usize) -> Result<Vec<(String, String)>, String> {
+        let mut found = Vec::new();
+        if let Some(value) = self.facts.get(query) {
+            found.push((query.to_string(), value.clone()));
+        }
+        if self.facts.is_empty() || found.len() >= limit {
+            return Ok(found);
+        }
+        self.check_model()?;
+        let embedding = self.embeddings.embed(query).await?;
+        for result in self.vectors.search(embedding, limit).await? {
+            if found.len() >= limit {
+                break;
+            }
+            let key = result.id;
+            if key == query {
+                continue;
+            }
+            if let Some(value) = self.facts.get(&key) {
+                found.push((key, value.clone()));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Facts relevant to `query`, formatted for the system prompt
+    pub async fn context(&self, query: &str) -> Result<Option<String>, String> {
+        let facts = self.recall(query, CONTEXT_LIMIT).await?;
+        if facts.is_empty() {
+            return Ok(None);
+        }
+        let mut context = String::from("\n---\nThings you remember:\n");
+        for (key, value) in facts {
+            context.push_str(&format!("- {}: {}\n", key, value));
+        }
+        context.push_str("---");
+        Ok(Some(context))
+    }
+
+    /// Fail if the stored vectors were made by another embedding model,
+    /// whose vectors cannot be compared with this one's
+    fn check_model(&self) -> Result<(), String> {
+        let current = EmbeddingModel::of(self.embeddings.as_ref());
+        match &self.model {
+            Some(stored) if *stored != current => Err(format!(
+                "Memory was stored with embedding model '{}' ({} dimensions) but this run uses '{}' ({} dimensions); clear it with `gent memory <agent> --clear`",
+                stored.model, stored.dimensions, current.model, current.dimensions
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        write_json(&self.facts_path, &self.facts)
+    }
+}
+
+/// Read a JSON file of the store, or None if it does not exist yet
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> GentResult<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let read_error = |source| GentError::FileReadError {
+        path: path.display().to_string(),
+        source,
+    };
+    let data = std::fs::read_to_string(path).map_err(read_error)?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| read_error(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create memory directory: {}", e))?;
+    }
+    let data = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize memory: {}", e))?;
+    std::fs::write(path, data).map_err(|e| format!("Failed to write memory: {}", e))
+}
+
+/// The `remember` and `recall` tools for a memory store
+pub fn memory_tools(store: Arc<Mutex<MemoryStore>>) -> Vec<Arc<dyn Tool>> {
+    vec![
+        Arc::new(RememberTool {
+            store: store.clone(),
+        }),
+        Arc::new(RecallTool { store }),
+    ]
+}
+
+/// Tool that stores a fact in the agent's memory
+struct RememberTool {
+    store: Arc<Mutex<MemoryStore>>,
+}
+
+#[async_trait]
+impl Tool for RememberTool {
+    fn name(&self) -> &str {
+        REMEMBER_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Remember a fact for later runs. Use a short key; remembering the same key again replaces the fact."
+    }
+
+    fn parameters_schema(&self) -> JsonValue {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Short name for the fact, e.g. \"user_name\""
+                },
+                "value": {
+                    "type": "string",
+                    "description": "The fact to remember"
+                }
+            },
+            "required": ["key", "value"]
+        })
+    }
+
+    async fn execute(&self, args: JsonValue) -> Result<String, String> {
+        let key = string_arg(&args, "key")?;
+        let value = string_arg(&args, "value")?;
+        self.store.lock().await.remember(key, value).await?;
+        Ok(format!("Remembered {}", key))
+    }
+}
+
+/// Tool that looks up facts in the agent's memory
+struct RecallTool {
+    store: Arc<Mutex<MemoryStore>>,
+}
+
+#[async_trait]
+impl Tool for RecallTool {
+    fn name(&self) -> &str {
+        RECALL_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Look up remembered facts by key or by topic"
+    }
+
+    fn parameters_schema(&self) -> JsonValue {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Key of a fact, or what the facts are about"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, args: JsonValue) -> Result<String, String> {
+        let query = string_arg(&args, "query")?;
+        let facts = self.store.lock().await.recall(query, RECALL_LIMIT).await?;
+        if facts.is_empty() {
+            return Ok("Nothing remembered about that.".to_string());
+        }
+        Ok(facts
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+fn string_arg<'a>(args: &'a JsonValue, name: &str) -> Result<&'a str, String> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Missing required '{}' parameter", name))
+}
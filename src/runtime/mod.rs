@@ -1,18 +1,28 @@
 //! Runtime components for GENT
 
 pub mod agent;
+pub mod context;
+mod conversation;
+pub mod handoff;
 pub mod llm;
+pub mod memory;
 mod provider_factory;
 pub mod providers;
 pub mod rag;
 pub mod tools;
 pub mod validation;
 
-pub use agent::{run_agent, run_agent_full, run_agent_with_tools};
+pub use agent::{
+    run_agent, run_agent_full, run_agent_turn, run_agent_turn_with_handoffs, run_agent_with_tools,
+};
+pub use conversation::Conversation;
 pub use llm::{
-    LLMClient, LLMResponse, Message, MockLLMClient, Role, ToolCall, ToolDefinition, ToolResult,
+    LLMClient, LLMResponse, Message, MockLLMClient, Role, ToolCall, ToolChoice, ToolDefinition,
+    ToolResult,
 };
+pub use handoff::TurnResult;
+pub use memory::MemoryStore;
 pub use provider_factory::ProviderFactory;
 pub use providers::{AnthropicClient, ClaudeCodeClient, OpenAIClient};
-pub use tools::{Tool, ToolRegistry, UserToolWrapper};
+pub use tools::{AgentTool, Tool, ToolContext, ToolRegistry, UserToolWrapper};
 pub use validation::validate_output;
@@ -2,7 +2,9 @@
 
 use crate::config::Config;
 use crate::errors::{GentError, GentResult};
+use crate::runtime::rag::embeddings::{EmbeddingProvider, MockEmbeddings, OpenAIEmbeddings};
 use crate::runtime::{AnthropicClient, ClaudeCodeClient, LLMClient, MockLLMClient, OpenAIClient, ToolCall};
+use std::sync::Arc;
 
 /// Factory for creating LLM clients based on provider name
 pub struct ProviderFactory {
@@ -58,6 +60,19 @@ impl ProviderFactory {
         self.create_with_options(provider, false)
     }
 
+    /// Create an embedding provider
+    ///
+    /// Uses OpenAI embeddings, or mock embeddings when the factory is a
+    /// mock. Without an OpenAI API key this is an error: mock vectors carry
+    /// no meaning, so recall would return arbitrary facts.
+    pub fn embeddings(&self) -> GentResult<Arc<dyn EmbeddingProvider>> {
+        if self.use_mock {
+            return Ok(Arc::new(MockEmbeddings::new()));
+        }
+        let api_key = self.config.require_openai_key()?;
+        Ok(Arc::new(OpenAIEmbeddings::new(api_key.to_string())))
+    }
+
     /// Create an LLM client with additional options
     pub fn create_with_options(
         &self,
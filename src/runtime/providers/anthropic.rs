@@ -2,10 +2,12 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
+use serde_json::{json, Value as JsonValue};
 
 use crate::errors::{GentError, GentResult};
-use crate::runtime::llm::{LLMClient, LLMResponse, Message, Role, ToolCall, ToolDefinition};
+use crate::runtime::llm::{
+    LLMClient, LLMResponse, Message, Role, ToolCall, ToolChoice, ToolDefinition,
+};
 
 /// Anthropic API client
 pub struct AnthropicClient {
@@ -132,6 +134,8 @@ struct AnthropicRequest {
     messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<JsonValue>,
 }
 
 #[derive(Debug, Serialize)]
@@ -150,6 +154,7 @@ enum AnthropicContent {
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
 enum AnthropicContentBlock {
+    #[allow(dead_code)]
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "tool_use")]
@@ -177,6 +182,7 @@ struct AnthropicTool {
 #[derive(Debug, Deserialize)]
 struct AnthropicResponse {
     content: Vec<AnthropicResponseBlock>,
+    #[allow(dead_code)]
     stop_reason: Option<String>,
 }
 
@@ -193,14 +199,36 @@ enum AnthropicResponseBlock {
     },
 }
 
+/// `tool_choice` for a request; `None` leaves the default ("auto")
+fn anthropic_tool_choice(tool_choice: &ToolChoice) -> Option<JsonValue> {
+    match tool_choice {
+        ToolChoice::Auto => None,
+        ToolChoice::Required => Some(json!({"type": "any"})),
+        ToolChoice::None => Some(json!({"type": "none"})),
+        ToolChoice::Tool(name) => Some(json!({"type": "tool", "name": name})),
+    }
+}
+
 #[async_trait]
 impl LLMClient for AnthropicClient {
     async fn chat(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<ToolDefinition>,
+        model: Option<&str>,
+        json_mode: bool,
+    ) -> GentResult<LLMResponse> {
+        self.chat_with_tool_choice(messages, tools, model, json_mode, &ToolChoice::Auto)
+            .await
+    }
+
+    async fn chat_with_tool_choice(
         &self,
         messages: Vec<Message>,
         tools: Vec<ToolDefinition>,
         model: Option<&str>,
         _json_mode: bool, // Anthropic doesn't have json_mode, handled via prompting
+        tool_choice: &ToolChoice,
     ) -> GentResult<LLMResponse> {
         let url = format!("{}/v1/messages", self.base_url);
         let model_to_use = model.unwrap_or(&self.model);
@@ -213,6 +241,11 @@ impl LLMClient for AnthropicClient {
             model: model_to_use.to_string(),
             max_tokens: 4096,
             system,
+            tool_choice: if anthropic_tools.is_empty() {
+                None
+            } else {
+                anthropic_tool_choice(tool_choice)
+            },
             messages: anthropic_messages,
             tools: anthropic_tools,
         };
@@ -128,25 +128,6 @@ impl Default for ClaudeCodeClient {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::errors::GentError;
-
-    #[tokio::test]
-    async fn test_check_cli_not_found() {
-        let client = ClaudeCodeClient {
-            model: None,
-            cli_path: "nonexistent-claude-binary".to_string(),
-            skip_permissions: false,
-        };
-        let result = client.ensure_available().await;
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(matches!(err, GentError::ProviderError { .. }));
-    }
-}
-
 #[async_trait]
 impl LLMClient for ClaudeCodeClient {
     async fn chat(
@@ -218,3 +199,22 @@ impl LLMClient for ClaudeCodeClient {
         self.parse_response(&stdout)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GentError;
+
+    #[tokio::test]
+    async fn test_check_cli_not_found() {
+        let client = ClaudeCodeClient {
+            model: None,
+            cli_path: "nonexistent-claude-binary".to_string(),
+            skip_permissions: false,
+        };
+        let result = client.ensure_available().await;
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(matches!(err, GentError::ProviderError { .. }));
+    }
+}
@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 
 use crate::errors::{GentError, GentResult};
-use crate::runtime::llm::{LLMClient, LLMResponse, Message, Role, ToolCall, ToolDefinition};
+use crate::runtime::llm::{
+    LLMClient, LLMResponse, Message, Role, ToolCall, ToolChoice, ToolDefinition,
+};
 
 /// OpenAI API client
 pub struct OpenAIClient {
@@ -87,6 +89,16 @@ impl OpenAIClient {
     }
 }
 
+/// `tool_choice` for a request; `None` leaves the default ("auto")
+fn openai_tool_choice(tool_choice: &ToolChoice) -> Option<JsonValue> {
+    match tool_choice {
+        ToolChoice::Auto => None,
+        ToolChoice::Required => Some(json!("required")),
+        ToolChoice::None => Some(json!("none")),
+        ToolChoice::Tool(name) => Some(json!({"type": "function", "function": {"name": name}})),
+    }
+}
+
 #[async_trait]
 impl LLMClient for OpenAIClient {
     async fn chat(
@@ -95,6 +107,18 @@ impl LLMClient for OpenAIClient {
         tools: Vec<ToolDefinition>,
         model: Option<&str>,
         json_mode: bool,
+    ) -> GentResult<LLMResponse> {
+        self.chat_with_tool_choice(messages, tools, model, json_mode, &ToolChoice::Auto)
+            .await
+    }
+
+    async fn chat_with_tool_choice(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<ToolDefinition>,
+        model: Option<&str>,
+        json_mode: bool,
+        tool_choice: &ToolChoice,
     ) -> GentResult<LLMResponse> {
         let url = format!("{}/v1/chat/completions", self.base_url);
 
@@ -108,6 +132,9 @@ impl LLMClient for OpenAIClient {
 
         if !tools.is_empty() {
             body["tools"] = json!(self.to_openai_tools(&tools));
+            if let Some(choice) = openai_tool_choice(tool_choice) {
+                body["tool_choice"] = choice;
+            }
         }
 
         if json_mode {
@@ -62,7 +62,7 @@ pub trait Chunker: Send + Sync {
 }
 
 /// Semantic chunker that adapts to file type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SemanticChunker {
     config: ChunkConfig,
 }
@@ -79,14 +79,6 @@ impl SemanticChunker {
     }
 }
 
-impl Default for SemanticChunker {
-    fn default() -> Self {
-        Self {
-            config: ChunkConfig::default(),
-        }
-    }
-}
-
 impl Chunker for SemanticChunker {
     fn chunk(&self, content: &str, file_path: &Path) -> Vec<Chunk> {
         let extension = file_path
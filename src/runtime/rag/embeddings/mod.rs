@@ -18,6 +18,9 @@ pub trait EmbeddingProvider: Send + Sync + std::fmt::Debug {
 
     /// Get the dimensionality of embeddings
     fn dimensions(&self) -> usize;
+
+    /// Name of the embedding model, stored with saved vectors
+    fn model(&self) -> &str;
 }
 
 /// Mock embedding provider for testing
@@ -87,4 +90,8 @@ impl EmbeddingProvider for MockEmbeddings {
     fn dimensions(&self) -> usize {
         self.dimensions
     }
+
+    fn model(&self) -> &str {
+        "mock"
+    }
 }
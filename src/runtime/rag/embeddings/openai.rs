@@ -90,4 +90,8 @@ impl EmbeddingProvider for OpenAIEmbeddings {
             _ => 1536,
         }
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
@@ -0,0 +1,131 @@
+//! Tool that delegates a task to another agent
+
+use async_trait::async_trait;
+use serde_json::{json, Value as JsonValue};
+
+use super::{Tool, ToolContext, ToolRegistry};
+use crate::interpreter::{AgentValue, Environment};
+use crate::logging::{LogLevel, Logger, PrefixLogger, Timer};
+use crate::runtime::agent::run_agent_turn_with_handoffs;
+use crate::runtime::validation::validate_output;
+use crate::runtime::ProviderFactory;
+
+/// An agent offered to another agent as a tool
+///
+/// Calling the tool runs the agent with its own provider, model and tools.
+/// Its log messages are prefixed with its name.
+pub struct AgentTool<'a> {
+    agent: AgentValue,
+    description: String,
+    provider_factory: &'a ProviderFactory,
+    tools: &'a ToolRegistry,
+    logger: &'a dyn Logger,
+}
+
+impl<'a> AgentTool<'a> {
+    pub fn new(
+        agent: &AgentValue,
+        provider_factory: &'a ProviderFactory,
+        tools: &'a ToolRegistry,
+        logger: &'a dyn Logger,
+    ) -> Self {
+        // The caller's input replaces the agent's own userPrompt
+        let agent = AgentValue {
+            user_prompt: None,
+            ..agent.clone()
+        };
+        let description = match agent.system_prompt.lines().next().map(str::trim) {
+            Some(line) if !line.is_empty() => {
+                format!("Delegate a task to the {} agent: {}", agent.name, line)
+            }
+            _ => format!("Delegate a task to the {} agent", agent.name),
+        };
+        Self {
+            agent,
+            description,
+            provider_factory,
+            tools,
+            logger,
+        }
+    }
+
+    /// Run the agent with the caller's arguments as its input
+    async fn run(&self, args: JsonValue, env: Option<&Environment>) -> Result<String, String> {
+        let name = &self.agent.name;
+        let input = match &self.agent.input_schema {
+            Some(schema) => {
+                validate_output(&args, schema)?;
+                serde_json::to_string_pretty(&args).map_err(|e| e.to_string())?
+            }
+            None => args
+                .get("prompt")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required 'prompt' parameter")?
+                .to_string(),
+        };
+
+        self.logger.log(
+            LogLevel::Info,
+            "agent",
+            &format!("Delegating to agent '{}'", name),
+        );
+        let _timer = Timer::new(
+            format!("Agent '{}'", name),
+            "agent",
+            LogLevel::Info,
+            self.logger,
+        );
+        let logger = PrefixLogger::new(self.logger, format!("[{}] ", name));
+        run_agent_turn_with_handoffs(
+            &self.agent,
+            Some(input),
+            &mut Vec::new(),
+            self.provider_factory,
+            self.tools,
+            &logger,
+            env,
+        )
+        .await
+        .map(|result| result.output)
+        .map_err(|e| format!("Agent '{}' failed: {}", name, e))
+    }
+}
+
+#[async_trait]
+impl Tool for AgentTool<'_> {
+    fn name(&self) -> &str {
+        &self.agent.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> JsonValue {
+        match &self.agent.input_schema {
+            Some(schema) => schema.to_json_schema(),
+            None => json!({
+                "type": "object",
+                "properties": {
+                    "prompt": {
+                        "type": "string",
+                        "description": "The task or question for the agent"
+                    }
+                },
+                "required": ["prompt"]
+            }),
+        }
+    }
+
+    async fn execute(&self, args: JsonValue) -> Result<String, String> {
+        self.run(args, None).await
+    }
+
+    async fn execute_with_context(
+        &self,
+        args: JsonValue,
+        ctx: &ToolContext<'_>,
+    ) -> Result<String, String> {
+        self.run(args, ctx.env).await
+    }
+}
@@ -5,20 +5,38 @@ use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::interpreter::native::NativeFunction;
+use crate::interpreter::Environment;
+use crate::logging::Logger;
 use crate::runtime::llm::ToolDefinition;
+use crate::runtime::ProviderFactory;
 
+mod agent_tool;
 mod json_parse;
 mod read_file;
 mod user_tool;
 mod web_fetch;
 mod write_file;
 
+pub use agent_tool::AgentTool;
 pub use json_parse::JsonParseTool;
 pub use read_file::ReadFileTool;
-pub use user_tool::UserToolWrapper;
+pub use user_tool::{type_schema, UserToolWrapper};
 pub use web_fetch::WebFetchTool;
 pub use write_file::WriteFileTool;
 
+/// What a tool can use while it runs, besides its arguments
+#[derive(Clone, Copy)]
+pub struct ToolContext<'a> {
+    /// Name of the agent that called the tool (None when called from code)
+    pub agent: Option<&'a str>,
+    pub provider_factory: &'a ProviderFactory,
+    pub tools: &'a ToolRegistry,
+    pub logger: &'a dyn Logger,
+    /// Environment of the program at the time of the call
+    pub env: Option<&'a Environment>,
+}
+
 /// Trait for executable tools
 #[async_trait]
 pub trait Tool: Send + Sync {
@@ -27,6 +45,18 @@ pub trait Tool: Send + Sync {
     fn parameters_schema(&self) -> JsonValue;
     async fn execute(&self, args: JsonValue) -> Result<String, String>;
 
+    /// Execute with access to the running program
+    ///
+    /// Tools that only need their arguments implement `execute`, which
+    /// this calls by default.
+    async fn execute_with_context(
+        &self,
+        args: JsonValue,
+        _ctx: &ToolContext<'_>,
+    ) -> Result<String, String> {
+        self.execute(args).await
+    }
+
     fn to_definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: self.name().to_string(),
@@ -36,15 +66,17 @@ pub trait Tool: Send + Sync {
     }
 }
 
-/// Registry of available tools
+/// Registry of available tools and host-provided native functions
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
+    functions: HashMap<String, Arc<dyn NativeFunction>>,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            functions: HashMap::new(),
         }
     }
 
@@ -65,6 +97,24 @@ impl ToolRegistry {
         self.tools.get(name).cloned()
     }
 
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tools.keys().map(String::as_str)
+    }
+
+    /// Register a native function callable from GENT code by name
+    pub fn register_function(&mut self, function: Box<dyn NativeFunction>) {
+        self.functions
+            .insert(function.name().to_string(), Arc::from(function));
+    }
+
+    pub fn function(&self, name: &str) -> Option<Arc<dyn NativeFunction>> {
+        self.functions.get(name).cloned()
+    }
+
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
     pub fn definitions_for(&self, names: &[String]) -> Vec<ToolDefinition> {
         names
             .iter()
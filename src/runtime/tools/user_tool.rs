@@ -1,13 +1,20 @@
 //! User-defined tool wrapper for the tool registry
 
-use super::Tool;
-use crate::interpreter::block_eval::evaluate_block;
-use crate::interpreter::{Environment, UserToolValue, Value};
-use crate::parser::ast::TypeName;
+use super::{Tool, ToolContext};
+use crate::interpreter::block_eval::{evaluate_block, evaluate_block_with_provider_factory};
+use crate::interpreter::expr_eval::evaluate_expr;
+use crate::interpreter::json::{json_to_value, value_to_json};
+use crate::interpreter::stdlib::type_label;
+use crate::interpreter::{EnumDef, EnumValue, Environment, OutputSchema, UserToolValue, Value};
+use crate::parser::ast::{Expression, FieldType, OutputType, Param, TypeName};
+use crate::runtime::validation::{json_type_name, validate_field_type};
 use async_trait::async_trait;
 use serde_json::{json, Value as JsonValue};
 use std::sync::Arc;
 
+/// Variable holding the name of the agent that called the tool
+pub const CALLER: &str = "caller";
+
 /// Wrapper that makes UserToolValue implement the Tool trait
 pub struct UserToolWrapper {
     tool: UserToolValue,
@@ -28,7 +35,10 @@ impl Tool for UserToolWrapper {
     }
 
     fn description(&self) -> &str {
-        "User-defined tool"
+        self.tool
+            .description
+            .as_deref()
+            .unwrap_or("User-defined tool")
     }
 
     fn parameters_schema(&self) -> JsonValue {
@@ -36,24 +46,27 @@ impl Tool for UserToolWrapper {
         let mut required = Vec::new();
 
         for param in &self.tool.params {
-            required.push(param.name.clone());
-
-            let type_str = match param.type_name {
-                TypeName::String => "string",
-                TypeName::Number => "number",
-                TypeName::Boolean => "boolean",
-                TypeName::Array => "array",
-                TypeName::Object => "object",
-                TypeName::Any => "string", // Default to string for Any
+            let description = match &param.description {
+                Some(description) => description.clone(),
+                None => format!("Parameter {}", param.name),
             };
+            // Types were checked when the tool was declared
+            let mut property =
+                type_schema(&param.type_name, &self.env).unwrap_or_else(|_| json!({}));
+            property["description"] = json!(description);
 
-            properties.insert(
-                param.name.clone(),
-                json!({
-                    "type": type_str,
-                    "description": format!("Parameter {}", param.name)
-                }),
-            );
+            // Parameters with a default are optional; constant defaults are
+            // shown to the model
+            match &param.default {
+                Some(default) => {
+                    if let Ok(value) = evaluate_expr(default, &Environment::new()) {
+                        property["default"] = value_to_json(&value);
+                    }
+                }
+                None => required.push(param.name.clone()),
+            }
+
+            properties.insert(param.name.clone(), property);
         }
 
         json!({
@@ -66,73 +79,263 @@ impl Tool for UserToolWrapper {
     async fn execute(&self, args: JsonValue) -> Result<String, String> {
         // Clone all the data we need to own it in the async block
         let tool_body = self.tool.body.clone();
-        let params = self.tool.params.clone();
-        let base_env = self.env.clone();
+        let mut exec_env = (*self.env).clone();
+        bind_params(&self.tool.params, &args, &mut exec_env, None)?;
 
         // Use spawn_blocking to run the non-Send future in a blocking context
         tokio::task::spawn_blocking(move || {
             // Create a new runtime for the blocking task
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async move {
-                // Clone the environment to create an isolated execution context
-                let mut exec_env = (*base_env).clone();
-
-                // Bind parameters from JSON args to the environment
-                for param in &params {
-                    let arg_value = args
-                        .get(&param.name)
-                        .ok_or_else(|| format!("Missing required parameter: {}", param.name))?;
-
-                    // Convert JSON value to GENT Value
-                    let gent_value = json_to_value(arg_value);
-
-                    // Define the parameter in the environment
-                    exec_env.define(&param.name, gent_value);
-                }
-
-                // Create an empty tool registry for executing the tool body
-                // User tools cannot call other tools during their execution
+                // Without a context there is no provider factory, so the
+                // body cannot run agents or call other tools
                 let tools = super::ToolRegistry::new();
-
-                // Execute the tool body
                 let result = evaluate_block(&tool_body, &mut exec_env, &tools)
                     .await
                     .map_err(|e| format!("Tool execution failed: {}", e))?;
-
-                // Convert the result to a string
-                Ok::<String, String>(result.to_string())
+                Ok::<String, String>(tool_result(&result))
             })
         })
         .await
         .map_err(|e| format!("Task panicked: {}", e))?
     }
+
+    async fn execute_with_context(
+        &self,
+        args: JsonValue,
+        ctx: &ToolContext<'_>,
+    ) -> Result<String, String> {
+        // Run in the environment of the caller, so the body sees what was
+        // declared after the tool; fall back to the declaration snapshot
+        let mut exec_env = ctx.env.unwrap_or(&self.env).clone();
+        bind_params(&self.tool.params, &args, &mut exec_env, ctx.agent)?;
+
+        // The evaluator's futures are not Send, so run the body on its own
+        // thread and runtime; a scoped thread can borrow the context
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let rt = tokio::runtime::Runtime::new()
+                        .map_err(|e| format!("Failed to start tool runtime: {}", e))?;
+                    rt.block_on(evaluate_block_with_provider_factory(
+                        &self.tool.body,
+                        &mut exec_env,
+                        ctx.tools,
+                        ctx.provider_factory,
+                        ctx.logger,
+                    ))
+                    .map(|result| tool_result(&result))
+                    .map_err(|e| format!("Tool execution failed: {}", e))
+                })
+                .join()
+                .map_err(|_| "Task panicked".to_string())?
+        })
+    }
 }
 
-/// Convert a JSON value to a GENT Value
-fn json_to_value(json: &JsonValue) -> Value {
-    match json {
-        JsonValue::Null => Value::Null,
-        JsonValue::Bool(b) => Value::Boolean(*b),
-        JsonValue::Number(n) => {
-            if let Some(f) = n.as_f64() {
-                Value::Number(f)
-            } else {
-                Value::Null
+/// Define the tool's parameters from the JSON arguments, using the defaults
+/// of those left out, and `caller` as the name of the calling agent (null
+/// when the tool is called from code)
+///
+/// Arguments are checked against the parameter types first, so the body
+/// never runs with arguments of the wrong shape.
+fn bind_params(
+    params: &[Param],
+    args: &JsonValue,
+    env: &mut Environment,
+    caller: Option<&str>,
+) -> Result<(), String> {
+    env.define(
+        CALLER,
+        caller.map_or(Value::Null, |name| Value::String(name.to_string())),
+    );
+    for param in params {
+        let value = match (args.get(&param.name), &param.default) {
+            // Models often send null for optional parameters they skip
+            (Some(JsonValue::Null), Some(default))
+                if !matches!(param.type_name, TypeName::Nullable(_)) =>
+            {
+                evaluate_default(param, default, env)?
             }
+            (Some(arg_value), _) => {
+                argument_to_value(arg_value, &param.type_name, env, &param.name)?
+            }
+            (None, Some(default)) => evaluate_default(param, default, env)?,
+            (None, None) => return Err(format!("Missing required parameter: {}", param.name)),
+        };
+        env.define(&param.name, value);
+    }
+    Ok(())
+}
+
+fn evaluate_default(
+    param: &Param,
+    default: &Expression,
+    env: &Environment,
+) -> Result<Value, String> {
+    evaluate_expr(default, env)
+        .map_err(|e| format!("Invalid default for parameter '{}': {}", param.name, e))
+}
+
+/// Check a JSON argument against a parameter type and convert it; enum
+/// arguments are variant names, or `{"variant", "data"}` objects for
+/// variants with fields
+fn argument_to_value(
+    json: &JsonValue,
+    type_name: &TypeName,
+    env: &Environment,
+    path: &str,
+) -> Result<Value, String> {
+    let mismatch = |expected: &str| {
+        format!(
+            "Invalid argument '{}': expected {}, got {}",
+            path,
+            expected,
+            json_type_name(json)
+        )
+    };
+    match type_name {
+        TypeName::Any => Ok(json_to_value(json)),
+        TypeName::Nullable(_) if json.is_null() => Ok(Value::Null),
+        TypeName::Nullable(inner) => argument_to_value(json, inner, env, path),
+        TypeName::String if json.is_string() => Ok(json_to_value(json)),
+        TypeName::Number if json.is_number() => Ok(json_to_value(json)),
+        TypeName::Boolean if json.is_boolean() => Ok(json_to_value(json)),
+        TypeName::Object if json.is_object() => Ok(json_to_value(json)),
+        TypeName::Array if json.is_array() => Ok(json_to_value(json)),
+        TypeName::ArrayOf(inner) => {
+            let items = json.as_array().ok_or_else(|| mismatch("array"))?;
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| argument_to_value(item, inner, env, &format!("{}[{}]", path, i)))
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
         }
-        JsonValue::String(s) => Value::String(s.clone()),
-        JsonValue::Array(arr) => {
-            let items = arr.iter().map(json_to_value).collect();
-            Value::Array(items)
-        }
-        JsonValue::Object(obj) => {
-            let mut map = std::collections::HashMap::new();
-            for (k, v) in obj {
-                map.insert(k.clone(), json_to_value(v));
+        TypeName::Named(name) => match env.get_enum(name) {
+            Some(def) => enum_argument(json, def, path).map(Value::Enum),
+            None => {
+                let schema = OutputSchema::from_output_type(
+                    &OutputType::Named(name.clone()),
+                    env.structs(),
+                )?;
+                validate_field_type(json, &FieldType::Object(schema.fields), path)
+                    .map_err(|e| format!("Invalid argument {}", e))?;
+                Ok(json_to_value(json))
             }
-            Value::Object(map)
+        },
+        other => Err(mismatch(&type_label(other))),
+    }
+}
+
+/// Read an enum argument: a variant name, `{"variant", "data"}`, or the
+/// tagged object that enum values are written as
+fn enum_argument(json: &JsonValue, def: &EnumDef, path: &str) -> Result<EnumValue, String> {
+    let (variant, data) = match json {
+        JsonValue::String(variant) => (variant.as_str(), &[][..]),
+        JsonValue::Object(map) => (
+            map.get("variant")
+                .and_then(JsonValue::as_str)
+                .unwrap_or_default(),
+            map.get("data")
+                .and_then(JsonValue::as_array)
+                .map_or(&[][..], Vec::as_slice),
+        ),
+        other => {
+            return Err(format!(
+                "Invalid argument '{}': expected {} variant, got {}",
+                path,
+                def.name,
+                json_type_name(other)
+            ))
         }
+    };
+    let known = def
+        .variants
+        .iter()
+        .find(|v| v.name == variant)
+        .ok_or_else(|| {
+            let names: Vec<&str> = def.variants.iter().map(|v| v.name.as_str()).collect();
+            format!(
+                "Invalid argument '{}': expected one of {}, got '{}'",
+                path,
+                names.join(", "),
+                variant
+            )
+        })?;
+    if known.fields.len() != data.len() {
+        return Err(format!(
+            "Invalid argument '{}': {}.{} takes {} values, got {}",
+            path,
+            def.name,
+            variant,
+            known.fields.len(),
+            data.len()
+        ));
+    }
+    Ok(EnumValue {
+        enum_name: def.name.clone(),
+        variant: variant.to_string(),
+        data: data.iter().map(json_to_value).collect(),
+    })
+}
+
+/// Text a tool returns to its caller; structured values are written as JSON
+fn tool_result(value: &Value) -> String {
+    match value {
+        Value::Object(_) | Value::Array(_) | Value::Enum(_) => value_to_json(value).to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// JSON schema for a parameter or return type, with structs and enums
+/// resolved from the environment
+pub fn type_schema(type_name: &TypeName, env: &Environment) -> Result<JsonValue, String> {
+    Ok(match type_name {
+        TypeName::String => json!({"type": "string"}),
+        TypeName::Number => json!({"type": "number"}),
+        TypeName::Boolean => json!({"type": "boolean"}),
+        TypeName::Array => json!({"type": "array"}),
+        TypeName::Object => json!({"type": "object"}),
+        TypeName::Any => json!({}),
+        TypeName::ArrayOf(inner) => json!({
+            "type": "array",
+            "items": type_schema(inner, env)?
+        }),
+        TypeName::Named(name) => match env.get_enum(name) {
+            Some(def) => enum_schema(def),
+            None => OutputSchema::from_output_type(&OutputType::Named(name.clone()), env.structs())
+                .map_err(|_| format!("Unknown type: {}", name))?
+                .to_json_schema(),
+        },
+        // Nullable types also allow `"null"`
+        TypeName::Nullable(inner) => match type_schema(inner, env)? {
+            schema if schema.as_object().is_some_and(|s| s.is_empty()) => schema,
+            schema
+                if schema.as_object().is_some_and(|s| s.len() == 1)
+                    && schema["type"].is_string() =>
+            {
+                json!({"type": [schema["type"], "null"]})
+            }
+            schema => json!({"anyOf": [schema, {"type": "null"}]}),
+        },
+    })
+}
+
+/// Enums without fields are strings; others are `{"variant", "data"}` objects
+fn enum_schema(def: &EnumDef) -> JsonValue {
+    let names: Vec<&str> = def.variants.iter().map(|v| v.name.as_str()).collect();
+    if def.variants.iter().all(|v| v.fields.is_empty()) {
+        return json!({"type": "string", "enum": names});
     }
+    json!({
+        "type": "object",
+        "properties": {
+            "variant": {"type": "string", "enum": names},
+            "data": {"type": "array"}
+        },
+        "required": ["variant"]
+    })
 }
 
 #[cfg(test)]
@@ -19,7 +19,11 @@ pub fn validate_output(json: &JsonValue, schema: &OutputSchema) -> Result<(), St
     Ok(())
 }
 
-fn validate_field_type(value: &JsonValue, expected: &FieldType, path: &str) -> Result<(), String> {
+pub(crate) fn validate_field_type(
+    value: &JsonValue,
+    expected: &FieldType,
+    path: &str,
+) -> Result<(), String> {
     match expected {
         FieldType::String => {
             if !value.is_string() {
@@ -71,6 +75,11 @@ fn validate_field_type(value: &JsonValue, expected: &FieldType, path: &str) -> R
                 )?;
             }
         }
+        FieldType::Nullable(inner) => {
+            if !value.is_null() {
+                validate_field_type(value, inner, path)?;
+            }
+        }
         FieldType::Named(_) => {
             // Named types should be resolved before validation
             // For now, accept any object
@@ -86,7 +95,7 @@ fn validate_field_type(value: &JsonValue, expected: &FieldType, path: &str) -> R
     Ok(())
 }
 
-fn json_type_name(value: &JsonValue) -> &'static str {
+pub(crate) fn json_type_name(value: &JsonValue) -> &'static str {
     match value {
         JsonValue::Null => "null",
         JsonValue::Bool(_) => "boolean",
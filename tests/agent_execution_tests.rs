@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::interpreter::AgentValue;
 use gent::runtime::{run_agent, run_agent_full, ProviderFactory};
 
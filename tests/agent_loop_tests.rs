@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::interpreter::types::AgentValue;
 use gent::logging::NullLogger;
 use gent::runtime::{run_agent_with_tools, ProviderFactory, ToolCall, ToolRegistry};
@@ -0,0 +1,161 @@
+//! Tests for agents used as tools by other agents (`tools: [Agent]`)
+
+use gent::formatter::format_program;
+use gent::interpreter::{AgentValue, Session, Value};
+use gent::logging::{LogLevel, Logger, NullLogger};
+use gent::parser::parse;
+use gent::repl::eval_input;
+use gent::runtime::{AgentTool, ProviderFactory, Tool, ToolCall, ToolRegistry};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+const RESEARCHER: &str =
+    "agent Researcher { systemPrompt: \"You research topics.\" model: \"gpt-4o-mini\" maxSteps: 1 }";
+
+/// Logger that keeps every message
+#[derive(Default)]
+struct RecordingLogger(Mutex<Vec<String>>);
+
+impl Logger for RecordingLogger {
+    fn log(&self, _level: LogLevel, _target: &str, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+
+    fn log_with_duration(&self, level: LogLevel, target: &str, message: &str, _duration_ms: u64) {
+        self.log(level, target, message);
+    }
+
+    fn is_enabled(&self, _level: LogLevel) -> bool {
+        true
+    }
+
+    fn level(&self) -> LogLevel {
+        LogLevel::Trace
+    }
+}
+
+/// Evaluate `inputs` in one session and return the agent named `name`
+async fn declare(inputs: &[&str], name: &str) -> Result<AgentValue, String> {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    for input in inputs {
+        eval_input(&mut session, input)
+            .await
+            .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))?;
+    }
+    match eval_input(&mut session, name).await {
+        Ok(Some(Value::Agent(agent))) => Ok(*agent),
+        other => Err(format!("not an agent: {:?}", other.map_err(|_| ()))),
+    }
+}
+
+#[tokio::test]
+async fn test_agents_in_tools_become_sub_agents() {
+    let coordinator = declare(
+        &[
+            RESEARCHER,
+            "agent Coordinator { model: \"gpt-4o-mini\" tools: [Researcher] }",
+        ],
+        "Coordinator",
+    )
+    .await
+    .unwrap();
+    assert!(coordinator.tools.is_empty());
+    assert_eq!(coordinator.sub_agents.len(), 1);
+    assert_eq!(coordinator.sub_agents[0].name, "Researcher");
+
+    let err = declare(
+        &["agent Coordinator { model: \"gpt-4o-mini\" tools: [42] }"],
+        "Coordinator",
+    )
+    .await
+    .unwrap_err();
+    assert!(err.contains("or Agent"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_agent_tool_takes_a_prompt() {
+    let factory = ProviderFactory::mock_with_response("Rust is a language.");
+    let registry = ToolRegistry::new();
+    let researcher = AgentValue::new("Researcher", "You research topics.\nBe brief.")
+        .with_user_prompt("ignored");
+    let tool = AgentTool::new(&researcher, &factory, &registry, &NullLogger);
+
+    assert_eq!(tool.name(), "Researcher");
+    assert_eq!(
+        tool.description(),
+        "Delegate a task to the Researcher agent: You research topics."
+    );
+    assert_eq!(tool.parameters_schema()["required"], json!(["prompt"]));
+    assert_eq!(
+        tool.execute(json!({"prompt": "What is Rust?"})).await,
+        Ok("Rust is a language.".to_string())
+    );
+    assert!(tool.execute(json!({})).await.is_err());
+}
+
+#[tokio::test]
+async fn test_agent_tool_uses_typed_input() {
+    let researcher = declare(
+        &["agent Researcher {\n    model: \"gpt-4o-mini\"\n    input: { topic: string, depth: number }\n}"],
+        "Researcher",
+    )
+    .await
+    .unwrap();
+    let factory = ProviderFactory::mock_with_response("Done.");
+    let registry = ToolRegistry::new();
+    let tool = AgentTool::new(&researcher, &factory, &registry, &NullLogger);
+
+    let schema = tool.parameters_schema();
+    assert_eq!(schema["properties"]["topic"]["type"], "string");
+    assert_eq!(schema["properties"]["depth"]["type"], "number");
+    assert_eq!(
+        tool.execute(json!({"topic": "Rust", "depth": 2})).await,
+        Ok("Done.".to_string())
+    );
+    assert!(tool.execute(json!({"topic": 3})).await.is_err());
+}
+
+#[tokio::test]
+async fn test_nested_runs_are_logged() {
+    let factory = ProviderFactory::mock_with_tool_calls(vec![ToolCall {
+        id: "1".to_string(),
+        name: "Researcher".to_string(),
+        arguments: json!({"prompt": "Find facts"}),
+    }]);
+    let logger = Arc::new(RecordingLogger::default());
+    let mut session = Session::new(factory, logger.clone());
+    eval_input(&mut session, RESEARCHER).await.unwrap();
+    eval_input(
+        &mut session,
+        "agent Coordinator { model: \"gpt-4o-mini\" maxSteps: 1 tools: [Researcher] }",
+    )
+    .await
+    .unwrap();
+
+    // The mock keeps calling tools, so both runs stop at maxSteps
+    let errors = eval_input(&mut session, "Coordinator.userPrompt(\"Plan\").run()")
+        .await
+        .unwrap_err();
+    assert_eq!(errors[0].kind(), "MaxStepsExceeded");
+
+    let messages = logger.0.lock().unwrap();
+    assert!(messages
+        .iter()
+        .any(|m| m == "Delegating to agent 'Researcher'"));
+    assert!(messages
+        .iter()
+        .any(|m| m.starts_with("[Researcher] Step 1/1")));
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("Agent 'Researcher' failed: Agent exceeded maximum steps (1)")));
+}
+
+#[test]
+fn test_input_field_is_formatted() {
+    let source = "agent Researcher {\n    model: \"gpt-4o-mini\"\n    input:   Query\n}\n";
+    let program = parse(source).unwrap();
+    assert_eq!(
+        format_program(source, &program),
+        "agent Researcher {\n    model: \"gpt-4o-mini\"\n    input: Query\n}\n"
+    );
+}
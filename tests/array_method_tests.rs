@@ -5,7 +5,6 @@
 //! Integration tests that test the methods through the interpreter
 //! will be added in Task 5.
 
-use gent::config::Config;
 use gent::interpreter::array_methods::call_array_method;
 use gent::interpreter::Value;
 
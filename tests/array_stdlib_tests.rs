@@ -0,0 +1,255 @@
+//! Tests for the expanded array standard library
+//!
+//! Plain methods are called through the array_methods module directly;
+//! methods taking callbacks run through the interpreter.
+
+use gent::interpreter::array_methods::call_array_method;
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use std::sync::Arc;
+
+fn numbers(ns: &[f64]) -> Vec<Value> {
+    ns.iter().map(|n| Value::Number(*n)).collect()
+}
+
+fn strings(ss: &[&str]) -> Vec<Value> {
+    ss.iter().map(|s| Value::String(s.to_string())).collect()
+}
+
+fn call(arr: Vec<Value>, method: &str, args: &[Value]) -> Value {
+    let mut arr = arr;
+    call_array_method(&mut arr, method, args).unwrap()
+}
+
+/// Evaluate `let xs = ...` followed by `expr`, returning the value of `expr`
+async fn eval(xs: &str, expr: &str) -> Value {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval_input(&mut session, &format!("let xs = {}", xs))
+        .await
+        .unwrap();
+    eval_input(&mut session, expr).await.unwrap().unwrap()
+}
+
+// ============================================
+// Plain methods
+// ============================================
+
+#[test]
+fn test_sort_numbers_and_strings() {
+    assert_eq!(
+        call(numbers(&[3.0, 1.0, 2.0]), "sort", &[]),
+        Value::Array(numbers(&[1.0, 2.0, 3.0]))
+    );
+    assert_eq!(
+        call(strings(&["b", "c", "a"]), "sort", &[]),
+        Value::Array(strings(&["a", "b", "c"]))
+    );
+    let mut mixed = vec![Value::Number(1.0), Value::String("a".to_string())];
+    assert!(call_array_method(&mut mixed, "sort", &[]).is_err());
+}
+
+#[test]
+fn test_reverse_includes_unique() {
+    assert_eq!(
+        call(numbers(&[1.0, 2.0, 3.0]), "reverse", &[]),
+        Value::Array(numbers(&[3.0, 2.0, 1.0]))
+    );
+    assert_eq!(
+        call(numbers(&[1.0, 2.0]), "includes", &[Value::Number(2.0)]),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        call(strings(&["a"]), "includes", &[Value::Number(2.0)]),
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        call(numbers(&[1.0, 2.0, 1.0, 3.0, 2.0]), "unique", &[]),
+        Value::Array(numbers(&[1.0, 2.0, 3.0]))
+    );
+}
+
+#[test]
+fn test_flatten_with_depth() {
+    let nested = vec![
+        Value::Number(1.0),
+        Value::Array(vec![Value::Number(2.0), Value::Array(numbers(&[3.0]))]),
+    ];
+    assert_eq!(
+        call(nested.clone(), "flatten", &[]),
+        Value::Array(vec![
+            Value::Number(1.0),
+            Value::Number(2.0),
+            Value::Array(numbers(&[3.0])),
+        ])
+    );
+    assert_eq!(
+        call(nested, "flatten", &[Value::Number(2.0)]),
+        Value::Array(numbers(&[1.0, 2.0, 3.0]))
+    );
+}
+
+#[test]
+fn test_zip_chunk_take_skip() {
+    assert_eq!(
+        call(
+            numbers(&[1.0, 2.0, 3.0]),
+            "zip",
+            &[Value::Array(strings(&["a", "b"]))]
+        ),
+        Value::Array(vec![
+            Value::Array(vec![Value::Number(1.0), Value::String("a".to_string())]),
+            Value::Array(vec![Value::Number(2.0), Value::String("b".to_string())]),
+        ])
+    );
+    assert_eq!(
+        call(
+            numbers(&[1.0, 2.0, 3.0, 4.0, 5.0]),
+            "chunk",
+            &[Value::Number(2.0)]
+        ),
+        Value::Array(vec![
+            Value::Array(numbers(&[1.0, 2.0])),
+            Value::Array(numbers(&[3.0, 4.0])),
+            Value::Array(numbers(&[5.0])),
+        ])
+    );
+    let mut arr = numbers(&[1.0]);
+    assert!(call_array_method(&mut arr, "chunk", &[Value::Number(0.0)]).is_err());
+    assert_eq!(
+        call(numbers(&[1.0, 2.0, 3.0]), "take", &[Value::Number(2.0)]),
+        Value::Array(numbers(&[1.0, 2.0]))
+    );
+    assert_eq!(
+        call(numbers(&[1.0, 2.0, 3.0]), "take", &[Value::Number(10.0)]),
+        Value::Array(numbers(&[1.0, 2.0, 3.0]))
+    );
+    assert_eq!(
+        call(numbers(&[1.0, 2.0, 3.0]), "skip", &[Value::Number(2.0)]),
+        Value::Array(numbers(&[3.0]))
+    );
+    assert_eq!(
+        call(numbers(&[1.0]), "skip", &[Value::Number(5.0)]),
+        Value::Array(vec![])
+    );
+}
+
+#[test]
+fn test_sum_min_max() {
+    assert_eq!(
+        call(numbers(&[1.5, 2.5, 3.0]), "sum", &[]),
+        Value::Number(7.0)
+    );
+    assert_eq!(call(vec![], "sum", &[]), Value::Number(0.0));
+    let mut arr = strings(&["a"]);
+    assert!(call_array_method(&mut arr, "sum", &[]).is_err());
+
+    assert_eq!(
+        call(numbers(&[4.0, -1.0, 9.0]), "min", &[]),
+        Value::Number(-1.0)
+    );
+    assert_eq!(
+        call(numbers(&[4.0, -1.0, 9.0]), "max", &[]),
+        Value::Number(9.0)
+    );
+    assert_eq!(
+        call(strings(&["pear", "apple"]), "min", &[]),
+        Value::String("apple".to_string())
+    );
+    assert_eq!(call(vec![], "max", &[]), Value::Null);
+}
+
+// ============================================
+// Callback methods
+// ============================================
+
+#[tokio::test]
+async fn test_sort_with_comparator() {
+    assert_eq!(
+        eval("[3, 1, 2]", "xs.sort((a, b) => b - a)").await,
+        Value::Array(numbers(&[3.0, 2.0, 1.0]))
+    );
+    // Stable: equal elements keep their order
+    assert_eq!(
+        eval(
+            "[{ n: 1, id: \"a\" }, { n: 0, id: \"b\" }, { n: 1, id: \"c\" }]",
+            "xs.sort((a, b) => a.n - b.n).map((x) => x.id).join(\"\")"
+        )
+        .await,
+        Value::String("bac".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_sort_by_key() {
+    assert_eq!(
+        eval("[\"ccc\", \"a\", \"bb\"]", "xs.sortBy((s) => s.length())").await,
+        Value::Array(strings(&["a", "bb", "ccc"]))
+    );
+}
+
+#[tokio::test]
+async fn test_some_every_find_index() {
+    assert_eq!(
+        eval("[1, 2, 3]", "xs.some((x) => x > 2)").await,
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        eval("[1, 2, 3]", "xs.every((x) => x > 2)").await,
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        eval("[]", "xs.every((x) => x > 2)").await,
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        eval("[5, 6, 7]", "xs.findIndex((x) => x == 6)").await,
+        Value::Number(1.0)
+    );
+    assert_eq!(
+        eval("[5, 6, 7]", "xs.findIndex((x) => x == 9)").await,
+        Value::Number(-1.0)
+    );
+}
+
+#[tokio::test]
+async fn test_flat_map_and_for_each() {
+    assert_eq!(
+        eval("[1, 2]", "xs.flatMap((x) => [x, x * 10])").await,
+        Value::Array(numbers(&[1.0, 10.0, 2.0, 20.0]))
+    );
+
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    assert_eq!(
+        eval_input(&mut session, "[1, 2].forEach((x) => x)")
+            .await
+            .unwrap(),
+        Some(Value::Null)
+    );
+}
+
+#[tokio::test]
+async fn test_group_by() {
+    let grouped = eval(
+        "[\"apple\", \"avocado\", \"banana\"]",
+        "xs.groupBy((s) => s.length())",
+    )
+    .await;
+    assert_eq!(
+        grouped.to_string(),
+        "{5: [apple], 6: [banana], 7: [avocado]}"
+    );
+    assert_eq!(
+        eval("[1, 2, 3, 4]", "xs.groupBy((x) => x % 2 == 0).keys()").await,
+        Value::Array(strings(&["false", "true"]))
+    );
+}
+
+#[tokio::test]
+async fn test_plain_methods_chain_through_interpreter() {
+    assert_eq!(
+        eval("[3, 1, 2, 3]", "xs.unique().sort().reverse().take(2).sum()").await,
+        Value::Number(5.0)
+    );
+}
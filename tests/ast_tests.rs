@@ -56,9 +56,9 @@ fn test_number_expression_integer() {
 
 #[test]
 fn test_number_expression_float() {
-    let expr = Expression::Number(3.14, Span::new(0, 4));
+    let expr = Expression::Number(2.75, Span::new(0, 4));
     match &expr {
-        Expression::Number(n, _) => assert!((n - 3.14).abs() < 0.001),
+        Expression::Number(n, _) => assert!((n - 2.75).abs() < 0.001),
         _ => panic!("Expected Number"),
     }
 }
@@ -173,6 +173,7 @@ fn test_agent_decl_creation() {
         tools_expr: None,
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 41),
     };
     assert_eq!(agent.name, "Hello");
@@ -198,6 +199,7 @@ fn test_agent_decl_multiple_fields() {
         tools_expr: None,
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 50),
     };
     assert_eq!(agent.fields.len(), 2);
@@ -211,6 +213,7 @@ fn test_agent_decl_empty_fields() {
         tools_expr: None,
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 10),
     };
     assert!(agent.fields.is_empty());
@@ -224,6 +227,7 @@ fn test_agent_decl_equality() {
         tools_expr: None,
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 10),
     };
     let a2 = a1.clone();
@@ -285,6 +289,7 @@ fn test_statement_agent_decl() {
         tools_expr: None,
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 10),
     });
     match stmt {
@@ -353,6 +358,7 @@ fn test_program_multiple_statements() {
                 tools_expr: None,
                 knowledge_expr: None,
                 output: None,
+                input: None,
                 span: Span::new(0, 10),
             }),
             Statement::LetStmt(LetStmt {
@@ -405,6 +411,7 @@ fn test_hello_world_ast() {
                 tools_expr: None,
                 knowledge_expr: None,
                 output: None,
+                input: None,
                 span: Span::new(0, 43),
             }),
             Statement::LetStmt(LetStmt {
@@ -473,7 +480,7 @@ fn test_binary_op_variants() {
 
 #[test]
 fn test_unary_op_variants() {
-    let ops = vec![UnaryOp::Not, UnaryOp::Neg];
+    let ops = [UnaryOp::Not, UnaryOp::Neg];
     assert_eq!(ops.len(), 2);
 }
 
@@ -485,7 +492,7 @@ use gent::parser::TypeName as AstTypeName;
 
 #[test]
 fn test_ast_type_name_variants() {
-    let types = vec![
+    let types = [
         AstTypeName::String,
         AstTypeName::Number,
         AstTypeName::Boolean,
@@ -560,9 +567,12 @@ use gent::parser::{Param, ToolDecl};
 fn test_tool_decl_creation() {
     let tool = ToolDecl {
         name: "greet".to_string(),
+        description: None,
         params: vec![Param {
             name: "name".to_string(),
             type_name: AstTypeName::String,
+            default: None,
+            description: None,
             span: Span::new(0, 12),
         }],
         return_type: Some(AstTypeName::String),
@@ -580,6 +590,7 @@ fn test_tool_decl_creation() {
 fn test_statement_tool_decl_variant() {
     let tool = ToolDecl {
         name: "test".to_string(),
+        description: None,
         params: vec![],
         return_type: None,
         body: Block {
@@ -597,6 +608,8 @@ fn test_param_creation() {
     let param = Param {
         name: "x".to_string(),
         type_name: AstTypeName::Number,
+        default: None,
+        description: None,
         span: Span::new(0, 10),
     };
     assert_eq!(param.name, "x");
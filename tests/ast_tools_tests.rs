@@ -18,6 +18,7 @@ fn test_agent_decl_with_tools_expr() {
         )),
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 10),
     };
     assert!(decl.tools_expr.is_some());
@@ -34,6 +35,7 @@ fn test_agent_decl_without_tools() {
         tools_expr: None,
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 10),
     };
     assert!(decl.tools_expr.is_none());
@@ -54,6 +56,7 @@ fn test_agent_decl_with_tools_and_fields() {
         )),
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 50),
     };
     assert_eq!(decl.fields.len(), 1);
@@ -69,6 +72,7 @@ fn test_agent_decl_with_identifier_tools_expr() {
         tools_expr: Some(Expression::Identifier("myTools".to_string(), Span::new(0, 7))),
         knowledge_expr: None,
         output: None,
+        input: None,
         span: Span::new(0, 50),
     };
     assert!(decl.tools_expr.is_some());
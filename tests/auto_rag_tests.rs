@@ -1,10 +1,10 @@
 //! Tests for Auto-RAG functionality (knowledge field on agents)
 
-use gent::config::Config;
 use gent::interpreter::evaluate;
 use gent::logging::NullLogger;
 use gent::parser::parse;
 use gent::runtime::{ProviderFactory, ToolRegistry};
+use std::path::Path;
 
 // Helper to run a program and check success
 async fn run_program(source: &str) -> Result<(), String> {
@@ -17,6 +17,30 @@ async fn run_program(source: &str) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
+// Helper to run a program that indexes a copy of ./examples, so the index
+// it writes does not end up in the repository
+async fn run_on_examples(source: &str) -> Result<(), String> {
+    let dir = tempfile::tempdir().unwrap();
+    copy_dir(Path::new("examples"), dir.path());
+    let path = dir.path().display().to_string().replace('\\', "/");
+    run_program(&source.replace("./examples", &path)).await
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        let target = to.join(path.file_name().unwrap());
+        if path.ends_with(".gent_index") {
+            continue;
+        } else if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            std::fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
 // Helper to run and expect failure with substring
 async fn expect_failure(source: &str, expected_substring: &str) {
     let result = run_program(source).await;
@@ -119,7 +143,7 @@ async fn test_evaluate_knowledge_config() {
         }
     "#;
 
-    let result = run_program(source).await;
+    let result = run_on_examples(source).await;
     assert!(result.is_ok(), "Evaluation failed: {:?}", result.err());
 }
 
@@ -135,7 +159,7 @@ async fn test_evaluate_knowledge_config_defaults() {
         }
     "#;
 
-    let result = run_program(source).await;
+    let result = run_on_examples(source).await;
     assert!(result.is_ok(), "Evaluation failed: {:?}", result.err());
 }
 
@@ -221,7 +245,7 @@ async fn test_knowledge_with_all_fields() {
         }
     "#;
 
-    let result = run_program(source).await;
+    let result = run_on_examples(source).await;
     assert!(result.is_ok(), "Evaluation failed: {:?}", result.err());
 }
 
@@ -245,7 +269,7 @@ async fn test_run_agent_with_knowledge() {
         let result = DocHelper.userPrompt("What examples are available?").run()
     "#;
 
-    let result = run_program(source).await;
+    let result = run_on_examples(source).await;
     assert!(result.is_ok(), "Run with knowledge failed: {:?}", result.err());
 }
 
@@ -267,6 +291,6 @@ async fn test_knowledge_and_tools_together() {
         }
     "#;
 
-    let result = run_program(source).await;
+    let result = run_on_examples(source).await;
     assert!(result.is_ok(), "Knowledge + tools failed: {:?}", result.err());
 }
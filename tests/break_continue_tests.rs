@@ -14,6 +14,7 @@ async fn test_eval_break_directly_in_for_loop() {
         statements: vec![
             BlockStmt::For(ForStmt {
                 variable: "i".to_string(),
+                value_variable: None,
                 iterable: Expression::Array(
                     vec![
                         Expression::Number(1.0, Span::new(0, 1)),
@@ -56,6 +57,7 @@ async fn test_eval_continue_directly_in_for_loop() {
         statements: vec![
             BlockStmt::For(ForStmt {
                 variable: "i".to_string(),
+                value_variable: None,
                 iterable: Expression::Array(
                     vec![
                         Expression::Number(1.0, Span::new(0, 1)),
@@ -100,6 +102,7 @@ async fn test_eval_break_in_if_in_for_loop() {
         statements: vec![
             BlockStmt::For(ForStmt {
                 variable: "i".to_string(),
+                value_variable: None,
                 iterable: Expression::Array(
                     vec![
                         Expression::Number(1.0, Span::new(0, 1)),
@@ -159,6 +162,7 @@ async fn test_eval_continue_in_if_in_for_loop() {
         statements: vec![
             BlockStmt::For(ForStmt {
                 variable: "i".to_string(),
+                value_variable: None,
                 iterable: Expression::Array(
                     vec![
                         Expression::Number(1.0, Span::new(0, 1)),
@@ -1,6 +1,5 @@
 //! Tests for built-in functions (print, println)
 
-use gent::config::Config;
 use gent::interpreter::builtins::{call_builtin, is_builtin};
 use gent::interpreter::Value;
 use gent::Span;
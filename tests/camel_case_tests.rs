@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::parser::parse;
 use gent::interpreter::evaluate;
 use gent::logging::NullLogger;
@@ -0,0 +1,157 @@
+//! Tests for in-place collection methods (`push`, `pop`)
+//!
+//! Arrays and objects are values; only the mutating methods update the
+//! variable, field or element they are called on.
+
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::parser::parse;
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use std::sync::Arc;
+
+fn numbers(ns: &[f64]) -> Value {
+    Value::Array(ns.iter().map(|n| Value::Number(*n)).collect())
+}
+
+fn session() -> Session {
+    Session::new(ProviderFactory::mock(), Arc::new(NullLogger))
+}
+
+/// Evaluate `inputs` in one session and return the value of the last one
+async fn eval(inputs: &[&str]) -> Value {
+    let mut session = session();
+    let mut last = None;
+    for input in inputs {
+        last = eval_input(&mut session, input).await.unwrap();
+    }
+    last.unwrap_or(Value::Null)
+}
+
+#[tokio::test]
+async fn test_push_and_pop_update_the_variable() {
+    assert_eq!(
+        eval(&[
+            "let xs = [1]",
+            "xs.push(2)",
+            "let last = xs.pop()",
+            "[last, xs]"
+        ])
+        .await,
+        Value::Array(vec![Value::Number(2.0), numbers(&[1.0])])
+    );
+}
+
+#[tokio::test]
+async fn test_pop_inside_larger_expressions() {
+    assert_eq!(
+        eval(&[
+            "let xs = [1, 2, 3]",
+            "let sum = xs.pop() + xs.pop()",
+            "[sum, xs]"
+        ])
+        .await,
+        Value::Array(vec![Value::Number(5.0), numbers(&[1.0])])
+    );
+    assert_eq!(
+        eval(&["let xs = [1, 2]", "let s = \"got {xs.pop()}\"", "xs"]).await,
+        numbers(&[1.0])
+    );
+}
+
+#[tokio::test]
+async fn test_push_on_object_field_and_array_element() {
+    assert_eq!(
+        eval(&[
+            "let state = { items: [], name: \"s\" }",
+            "state.items.push(1)",
+            "state[\"items\"].push(2)",
+            "state.items",
+        ])
+        .await,
+        numbers(&[1.0, 2.0])
+    );
+    assert_eq!(
+        eval(&[
+            "let rows = [[], [1]]",
+            "rows[1].push(2)",
+            "rows[0].push(0)",
+            "rows"
+        ])
+        .await,
+        Value::Array(vec![numbers(&[0.0]), numbers(&[1.0, 2.0])])
+    );
+}
+
+#[tokio::test]
+async fn test_assignment_and_arguments_copy() {
+    assert_eq!(
+        eval(&[
+            "let a = [1]",
+            "let b = a",
+            "b.push(2)",
+            "[a.length(), b.length()]"
+        ])
+        .await,
+        numbers(&[1.0, 2.0])
+    );
+    assert_eq!(
+        eval(&[
+            "fn grow(xs: array) -> number {\n    xs.push(0)\n    return xs.length()\n}",
+            "let a = [1]",
+            "[grow(a), a.length()]",
+        ])
+        .await,
+        numbers(&[2.0, 1.0])
+    );
+}
+
+#[tokio::test]
+async fn test_push_inside_loops_and_match_arms() {
+    assert_eq!(
+        eval(&[
+            "enum Shape { Circle(number), Square }",
+            "let radii = []",
+            "let shapes = [Shape.Circle(1), Shape.Square, Shape.Circle(2)]",
+            "for s in shapes {\n    match s {\n        Shape.Circle(r) => { radii.push(r) }\n        _ => { radii.push(0) }\n    }\n}",
+            "radii",
+        ])
+        .await,
+        numbers(&[1.0, 0.0, 2.0])
+    );
+}
+
+#[tokio::test]
+async fn test_push_at_top_level_of_a_program() {
+    let program = parse("let xs = [1, 2]\nlet last = xs.pop()\n").unwrap();
+    let mut session = session();
+    session.eval_program(&program, None).await.unwrap();
+    assert_eq!(session.env().get("xs"), Some(&numbers(&[1.0])));
+    assert_eq!(session.env().get("last"), Some(&Value::Number(2.0)));
+}
+
+#[tokio::test]
+async fn test_push_on_non_arrays_is_an_error() {
+    let mut session = session();
+    assert!(eval_input(&mut session, "let s = \"ab\"").await.is_ok());
+    assert!(eval_input(&mut session, "s.push(\"c\")").await.is_err());
+    assert!(eval_input(&mut session, "missing.push(1)").await.is_err());
+    assert!(eval_input(&mut session, "let o = { a: 1 }").await.is_ok());
+    assert!(eval_input(&mut session, "o.b.push(1)").await.is_err());
+}
+
+#[tokio::test]
+async fn test_push_on_non_arrays_evaluates_arguments_once() {
+    let mut session = session();
+    for input in ["let log = []", "let n = 5", "let s = \"ab\"", "let o = { a: 1 }"] {
+        assert!(eval_input(&mut session, input).await.is_ok());
+    }
+    assert!(eval_input(&mut session, "n.push(log.push(1))").await.is_err());
+    assert!(eval_input(&mut session, "s.push(log.push(2))").await.is_err());
+    assert!(eval_input(&mut session, "o.a.push(log.push(3))").await.is_err());
+    assert!(eval_input(&mut session, "o[\"a\"][log.push(4)].pop()").await.is_err());
+    assert_eq!(
+        session.env().get("log"),
+        Some(&numbers(&[1.0, 2.0, 3.0, 4.0]))
+    );
+}
@@ -0,0 +1,235 @@
+//! Tests for context window management (`contextStrategy` agent field)
+
+use gent::interpreter::{AgentValue, Session};
+use gent::logging::{LogLevel, Logger, NullLogger};
+use gent::repl::eval_input;
+use gent::runtime::context::{
+    context_limit, estimate_tokens, fit_context, ContextStrategy, DEFAULT_CONTEXT_LIMIT,
+    DROPPED_TOOL_RESULT, SUMMARY_PREFIX,
+};
+use gent::runtime::{Message, MockLLMClient, ProviderFactory, Role, ToolCall, ToolResult};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// Logger that keeps every message
+#[derive(Default)]
+struct RecordingLogger(Mutex<Vec<String>>);
+
+impl Logger for RecordingLogger {
+    fn log(&self, _level: LogLevel, _target: &str, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+
+    fn log_with_duration(&self, level: LogLevel, target: &str, message: &str, _duration_ms: u64) {
+        self.log(level, target, message);
+    }
+
+    fn is_enabled(&self, _level: LogLevel) -> bool {
+        true
+    }
+
+    fn level(&self) -> LogLevel {
+        LogLevel::Trace
+    }
+}
+
+/// A tool loop: system, question, then `steps` calls with long results
+fn tool_loop(steps: usize) -> Vec<Message> {
+    let mut messages = vec![Message::system("You help."), Message::user("Look it up")];
+    for step in 0..steps {
+        let id = step.to_string();
+        messages.push(Message::assistant_with_tool_calls(vec![ToolCall {
+            id: id.clone(),
+            name: "search".to_string(),
+            arguments: json!({"query": "x"}),
+        }]));
+        messages.push(Message::tool_result(ToolResult {
+            call_id: id,
+            content: format!("result {} {}", step, "x".repeat(400)),
+            is_error: false,
+        }));
+    }
+    messages
+}
+
+fn agent(strategy: ContextStrategy, limit: usize) -> AgentValue {
+    AgentValue::new("Helper", "You help.")
+        .with_model("gpt-4o-mini")
+        .with_context_strategy(strategy)
+        .with_context_limit(limit)
+}
+
+fn total(messages: &[Message]) -> usize {
+    messages.iter().map(estimate_tokens).sum()
+}
+
+#[test]
+fn test_estimate_tokens() {
+    assert_eq!(estimate_tokens(&Message::user("a".repeat(40))), 14);
+    let call = Message::assistant_with_tool_calls(vec![ToolCall {
+        id: "1".to_string(),
+        name: "search".to_string(),
+        arguments: json!({"query": "rust"}),
+    }]);
+    assert!(estimate_tokens(&call) > estimate_tokens(&Message::assistant("")));
+}
+
+#[test]
+fn test_context_limit_by_model() {
+    assert_eq!(context_limit(Some("gpt-4o-mini")), 128_000);
+    assert_eq!(context_limit(Some("gpt-4")), 8_192);
+    assert_eq!(context_limit(Some("claude-3-5-sonnet")), 200_000);
+    assert_eq!(context_limit(Some("llama3")), DEFAULT_CONTEXT_LIMIT);
+    assert_eq!(context_limit(None), DEFAULT_CONTEXT_LIMIT);
+}
+
+#[tokio::test]
+async fn test_messages_that_fit_are_unchanged() {
+    let mut messages = tool_loop(3);
+    let before = messages.clone();
+    let agent = AgentValue::new("Helper", "You help.").with_model("gpt-4o-mini");
+    fit_context(
+        &mut messages,
+        &[],
+        &agent,
+        &MockLLMClient::new(),
+        &NullLogger,
+    )
+    .await;
+    assert_eq!(messages, before);
+}
+
+#[tokio::test]
+async fn test_truncate_drops_oldest_turns() {
+    let mut messages = tool_loop(5);
+    let last = messages.last().cloned();
+    let logger = RecordingLogger::default();
+    let agent = agent(ContextStrategy::Truncate, 400);
+    fit_context(&mut messages, &[], &agent, &MockLLMClient::new(), &logger).await;
+
+    assert!(total(&messages) <= 300, "{}", total(&messages));
+    assert_eq!(messages[0].role, Role::System);
+    assert_eq!(messages[1].content, "Look it up");
+    assert_eq!(messages.last().cloned(), last);
+    // Tool calls and their results are removed together
+    assert_eq!(messages[2].role, Role::Assistant);
+    assert_eq!(messages[3].role, Role::Tool);
+    assert!(logger
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|m| m.starts_with("Dropped 6 older message(s)")));
+}
+
+#[tokio::test]
+async fn test_truncate_keeps_exchanges_whole() {
+    // Three earlier exchanges, each a question, a tool call, its result and
+    // an answer, then a new question
+    let mut messages = vec![Message::system("You help.")];
+    for turn in 0..3 {
+        let id = turn.to_string();
+        messages.push(Message::user(format!("Question {}", turn)));
+        messages.push(Message::assistant_with_tool_calls(vec![ToolCall {
+            id: id.clone(),
+            name: "search".to_string(),
+            arguments: json!({"query": "x"}),
+        }]));
+        messages.push(Message::tool_result(ToolResult {
+            call_id: id,
+            content: "x".repeat(400),
+            is_error: false,
+        }));
+        messages.push(Message::assistant(format!("Answer {}", turn)));
+    }
+    messages.push(Message::user("Question 3"));
+    let agent = agent(ContextStrategy::Truncate, 250);
+    fit_context(&mut messages, &[], &agent, &MockLLMClient::new(), &NullLogger).await;
+
+    let roles: Vec<Role> = messages.iter().map(|m| m.role.clone()).collect();
+    assert_eq!(
+        roles,
+        vec![
+            Role::System,
+            Role::User,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant,
+            Role::User
+        ]
+    );
+    assert_eq!(messages[1].content, "Question 2");
+    assert_eq!(messages[4].content, "Answer 2");
+}
+
+#[tokio::test]
+async fn test_drop_tool_results() {
+    let mut messages = tool_loop(5);
+    let agent = agent(ContextStrategy::DropToolResults, 400);
+    fit_context(
+        &mut messages,
+        &[],
+        &agent,
+        &MockLLMClient::new(),
+        &NullLogger,
+    )
+    .await;
+
+    assert_eq!(messages.len(), 12);
+    assert_eq!(messages[3].content, DROPPED_TOOL_RESULT);
+    assert!(messages[11].content.starts_with("result 4"));
+    assert!(total(&messages) <= 300);
+}
+
+#[tokio::test]
+async fn test_summarize_replaces_oldest_turns() {
+    let mut messages = tool_loop(5);
+    let agent = agent(ContextStrategy::Summarize, 400);
+    let llm = MockLLMClient::with_response("Searched for x four times.");
+    fit_context(&mut messages, &[], &agent, &llm, &NullLogger).await;
+
+    assert_eq!(messages[0].role, Role::System);
+    assert_eq!(messages[1].content, "Look it up");
+    assert_eq!(
+        messages[2].content,
+        format!("{}Searched for x four times.", SUMMARY_PREFIX)
+    );
+    assert!(total(&messages) <= 300);
+}
+
+#[tokio::test]
+async fn test_summarize_falls_back_to_truncate() {
+    let mut messages = tool_loop(5);
+    let agent = agent(ContextStrategy::Summarize, 400);
+    let llm = MockLLMClient::with_tool_calls(vec![ToolCall {
+        id: "1".to_string(),
+        name: "search".to_string(),
+        arguments: json!({}),
+    }]);
+    fit_context(&mut messages, &[], &agent, &llm, &NullLogger).await;
+
+    assert!(!messages
+        .iter()
+        .any(|m| m.content.starts_with(SUMMARY_PREFIX)));
+    assert!(total(&messages) <= 300);
+}
+
+#[tokio::test]
+async fn test_context_fields() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval_input(
+        &mut session,
+        "agent Helper { model: \"gpt-4o-mini\" contextStrategy: \"summarize\" contextLimit: 4000 }",
+    )
+    .await
+    .unwrap();
+
+    let errors = eval_input(
+        &mut session,
+        "agent Other { model: \"gpt-4o-mini\" contextStrategy: \"sumarize\" }",
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(errors[0].kind(), "TypeError");
+    assert_eq!(errors[0].help(), Some("did you mean `summarize`?"));
+}
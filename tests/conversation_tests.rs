@@ -0,0 +1,254 @@
+//! Tests for multi-turn conversations (`Agent.conversation()`)
+
+use gent::interpreter::{AgentValue, Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::{Conversation, ProviderFactory, Role, ToolCall, ToolRegistry};
+use serde_json::json;
+use std::sync::Arc;
+
+const HELPER: &str = "agent Helper { systemPrompt: \"You help.\" model: \"gpt-4o-mini\" }";
+
+/// Evaluate `inputs` in one session and return the value of the last one
+///
+/// The mock agent always replies "Sure."
+async fn eval(inputs: &[&str]) -> Result<Value, String> {
+    let factory = ProviderFactory::mock_with_response("Sure.");
+    let mut session = Session::new(factory, Arc::new(NullLogger));
+    let mut last = None;
+    for input in inputs {
+        last = eval_input(&mut session, input)
+            .await
+            .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))?;
+    }
+    Ok(last.unwrap_or(Value::Null))
+}
+
+fn helper() -> AgentValue {
+    AgentValue::new("Helper", "You help.")
+}
+
+fn roles(conversation: &Conversation) -> Vec<Role> {
+    conversation
+        .messages()
+        .iter()
+        .map(|m| m.role.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_send_keeps_history() {
+    let factory = ProviderFactory::mock_with_response("Sure.");
+    let mut chat = Conversation::new(helper());
+    let reply = chat
+        .send("Hi", &factory, &ToolRegistry::new(), &NullLogger)
+        .await
+        .unwrap();
+    assert_eq!(reply, "Sure.");
+    chat.send("And then?", &factory, &ToolRegistry::new(), &NullLogger)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        roles(&chat),
+        vec![
+            Role::System,
+            Role::User,
+            Role::Assistant,
+            Role::User,
+            Role::Assistant
+        ]
+    );
+    assert_eq!(chat.messages()[3].content, "And then?");
+}
+
+#[tokio::test]
+async fn test_failed_turn_leaves_history_unchanged() {
+    let factory = ProviderFactory::mock_with_tool_calls(vec![ToolCall {
+        id: "1".to_string(),
+        name: "missing".to_string(),
+        arguments: json!({}),
+    }]);
+    let mut chat = Conversation::new(helper());
+    let err = chat
+        .send("Hi", &factory, &ToolRegistry::new(), &NullLogger)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), "MaxStepsExceeded");
+    assert!(chat.messages().is_empty());
+}
+
+#[tokio::test]
+async fn test_conversation_in_scripts() {
+    assert_eq!(
+        eval(&[
+            HELPER,
+            "let chat = Helper.conversation()",
+            "chat.send(\"Hi\")",
+        ])
+        .await
+        .unwrap(),
+        Value::String("Sure.".to_string())
+    );
+    assert_eq!(
+        eval(&[
+            HELPER,
+            "let chat = Helper.conversation()",
+            "chat.send(\"Hi\")",
+            "chat.send(\"More\")",
+            "chat.history().map((m) => m.role).join(\",\")",
+        ])
+        .await
+        .unwrap(),
+        Value::String("System,User,Assistant,User,Assistant".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_conversation_in_functions() {
+    assert_eq!(
+        eval(&[
+            HELPER,
+            "fn ask(question: string) -> number {\n    let chat = Helper.conversation()\n    chat.send(question)\n    chat.send(\"Thanks\")\n    return chat.history().length()\n}",
+            "ask(\"Hi\")",
+        ])
+        .await
+        .unwrap(),
+        Value::Number(5.0)
+    );
+}
+
+#[tokio::test]
+async fn test_save_and_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("chat.json");
+    let path = path.display().to_string().replace('\\', "/");
+
+    eval(&[
+        HELPER,
+        "let chat = Helper.conversation()",
+        "chat.send(\"Remember 42\")",
+        &format!("chat.save(\"{}\")", path),
+    ])
+    .await
+    .unwrap();
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["agent"], "Helper");
+    assert_eq!(saved["messages"][1]["content"], "Remember 42");
+
+    assert_eq!(
+        eval(&[
+            HELPER,
+            "let chat = Helper.conversation()",
+            &format!("chat.load(\"{}\")", path),
+            "chat.send(\"What number?\")",
+            "chat.history()[1].content",
+        ])
+        .await
+        .unwrap(),
+        Value::String("Remember 42".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_conversation_errors() {
+    let err = eval(&[HELPER, "Helper.conversation().ask(\"Hi\")"])
+        .await
+        .unwrap_err();
+    assert!(err.starts_with("UndefinedProperty"), "{}", err);
+    assert!(err.contains("ask on Conversation"), "{}", err);
+
+    // Runtime errors from conversations can be caught by kind
+    assert_eq!(
+        eval(&[
+            HELPER,
+            "fn ask() -> string {\n    let chat = Helper.conversation()\n    try {\n        chat.ask(\"Hi\")\n    } catch (e: UndefinedProperty) {\n        return \"caught\"\n    }\n    return \"not caught\"\n}",
+            "ask()",
+        ])
+        .await
+        .unwrap(),
+        Value::String("caught".to_string())
+    );
+
+    let err = eval(&[HELPER, "Helper.conversation().load(\"/no/such/chat.json\")"])
+        .await
+        .unwrap_err();
+    assert!(err.starts_with("FileReadError"), "{}", err);
+
+    let err = eval(&[HELPER, "Helper.conversation().send()"])
+        .await
+        .unwrap_err();
+    assert!(err.starts_with("WrongArgumentCount"), "{}", err);
+}
+
+#[test]
+fn test_tools_can_use_the_conversation_during_send() {
+    // The agent's finish tool reads the conversation it was called from.
+    // A deadlock would also hang the runtime, so it runs on its own thread.
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let length = runtime.block_on(async {
+            let factory = ProviderFactory::mock_with_tool_calls(vec![ToolCall {
+                id: "1".to_string(),
+                name: "peek".to_string(),
+                arguments: json!({}),
+            }]);
+            let mut session = Session::new(factory, Arc::new(NullLogger));
+            for input in [
+                "tool peek() -> number {\n    return chat.history().length()\n}",
+                "agent Peeker { model: \"gpt-4o-mini\" finishTool: peek maxSteps: 2 }",
+                "let chat = Peeker.conversation()",
+                "chat.send(\"Hi\")",
+            ] {
+                eval_input(&mut session, input).await.unwrap();
+            }
+            eval_input(&mut session, "chat.history().length()")
+                .await
+                .unwrap()
+        });
+        sender.send(length).unwrap();
+    });
+    let length = receiver
+        .recv_timeout(std::time::Duration::from_secs(30))
+        .expect("send deadlocked");
+    assert_eq!(length, Some(Value::Number(3.0)));
+}
+
+#[tokio::test]
+async fn test_load_restores_the_saved_agent() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("chat.json");
+    let path = path.display().to_string().replace('\\', "/");
+    let other = "agent Other { systemPrompt: \"You also help.\" model: \"gpt-4o-mini\" }";
+
+    assert_eq!(
+        eval(&[
+            HELPER,
+            other,
+            "let chat = Helper.conversation()",
+            "chat.send(\"Hi\")",
+            &format!("chat.save(\"{}\")", path),
+            "let resumed = Other.conversation()",
+            &format!("resumed.load(\"{}\")", path),
+            "resumed.agent()",
+        ])
+        .await
+        .unwrap(),
+        Value::String("Helper".to_string())
+    );
+
+    let err = eval(&[other, "let chat = Other.conversation()", &format!("chat.load(\"{}\")", path)])
+        .await
+        .unwrap_err();
+    assert!(err.starts_with("UndefinedAgent"), "{}", err);
+
+    let mut chat = Conversation::new(AgentValue::new("Other", "You also help."));
+    let err = chat.load(std::path::Path::new(&path)).unwrap_err();
+    assert_eq!(err.kind(), "UndefinedAgent");
+    assert!(chat.messages().is_empty());
+    let mut chat = Conversation::new(helper());
+    chat.load(std::path::Path::new(&path)).unwrap();
+    assert_eq!(chat.messages().len(), 3);
+}
@@ -0,0 +1,204 @@
+//! Tests for the embedding API (`gent::Engine`)
+
+use gent::interpreter::native::{native_async_fn, native_fn, NativeArgs, Signature};
+use gent::parser::ast::TypeName;
+use gent::runtime::ProviderFactory;
+use gent::{Engine, GentError};
+use serde_json::json;
+use std::time::Duration;
+
+const SCRIPT: &str = r#"
+agent Researcher { systemPrompt: "You research things." model: "gpt-4o-mini" }
+agent Grader { systemPrompt: "Grade." model: "gpt-4o-mini" output: { grade: string, score: number } }
+
+fn summarize(text: string, words: number) -> string {
+    return text.split(" ").slice(0, words).join(" ")
+}
+
+fn stats(xs: array) -> object {
+    return { count: xs.length(), first: xs[0] }
+}
+
+tool shout(text: string) -> string {
+    return text.toUpperCase()
+}
+"#;
+
+async fn engine() -> Engine {
+    let mut engine = Engine::builder()
+        .provider_factory(ProviderFactory::mock())
+        .build();
+    engine.load_source(SCRIPT).await.unwrap();
+    engine
+}
+
+#[tokio::test]
+async fn test_lists_declarations() {
+    let engine = engine().await;
+    assert_eq!(engine.agents(), vec!["Grader", "Researcher"]);
+    assert_eq!(engine.functions(), vec!["stats", "summarize"]);
+    assert_eq!(engine.tools(), vec!["shout"]);
+}
+
+#[tokio::test]
+async fn test_call_function_with_json() {
+    let mut engine = engine().await;
+    assert_eq!(
+        engine
+            .call("summarize", vec![json!("one two three four"), json!(2)])
+            .await
+            .unwrap(),
+        json!("one two")
+    );
+    assert_eq!(
+        engine
+            .call("stats", vec![json!([{ "id": 1 }, { "id": 2 }])])
+            .await
+            .unwrap(),
+        json!({ "count": 2, "first": { "id": 1 } })
+    );
+    // Calls leave no bindings behind
+    assert_eq!(engine.functions(), vec!["stats", "summarize"]);
+}
+
+#[tokio::test]
+async fn test_call_tool() {
+    let mut engine = engine().await;
+    assert_eq!(
+        engine.call("shout", vec![json!("hi")]).await.unwrap(),
+        json!("HI")
+    );
+}
+
+#[tokio::test]
+async fn test_call_errors() {
+    let mut engine = engine().await;
+    let err = engine.call("sumarize", vec![]).await.unwrap_err();
+    assert_eq!(err.kind(), "UnknownTool");
+    assert_eq!(err.help(), Some("did you mean `summarize`?"));
+    assert!(engine.call("summarize", vec![json!("a")]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_run_agent() {
+    let engine = engine().await;
+    let reply = engine.run_agent("Researcher", "Find papers").await.unwrap();
+    assert!(reply.is_string());
+
+    let err = engine.run_agent("Reseacher", "x").await.unwrap_err();
+    assert_eq!(err.kind(), "UndefinedAgent");
+    assert_eq!(err.help(), Some("did you mean `Researcher`?"));
+}
+
+#[tokio::test]
+async fn test_run_agent_with_structured_output() {
+    let mut engine = Engine::builder()
+        .provider_factory(ProviderFactory::mock_with_response(
+            r#"{"grade": "A", "score": 9}"#,
+        ))
+        .build();
+    engine.load_source(SCRIPT).await.unwrap();
+    assert_eq!(
+        engine.run_agent("Grader", "Grade this").await.unwrap(),
+        json!({ "grade": "A", "score": 9 })
+    );
+}
+
+#[tokio::test]
+async fn test_registered_native_functions() {
+    let mut engine = Engine::builder()
+        .provider_factory(ProviderFactory::mock())
+        .function(native_fn(
+            "tax",
+            Signature::new().param("amount", TypeName::Number),
+            |args: NativeArgs| Ok(args.get::<f64>(0)? * 0.2),
+        ))
+        .build();
+    engine
+        .load_source("fn total(amount: number) -> number {\n    return amount + tax(amount)\n}")
+        .await
+        .unwrap();
+    assert_eq!(
+        engine.call("total", vec![json!(10)]).await.unwrap(),
+        json!(12)
+    );
+    assert_eq!(engine.call("tax", vec![json!(5)]).await.unwrap(), json!(1));
+}
+
+#[tokio::test]
+async fn test_script_args_are_per_engine() {
+    let source = "import { args } from \"std:env\"\nfn arguments() -> array {\n    return args()\n}";
+    let mut first = Engine::builder()
+        .provider_factory(ProviderFactory::mock())
+        .script_args(vec!["a".to_string()])
+        .build();
+    let mut second = Engine::builder()
+        .provider_factory(ProviderFactory::mock())
+        .build();
+    first.load_source(source).await.unwrap();
+    second.load_source(source).await.unwrap();
+    assert_eq!(first.call("arguments", vec![]).await.unwrap(), json!(["a"]));
+    assert_eq!(second.call("arguments", vec![]).await.unwrap(), json!([]));
+}
+
+#[tokio::test]
+async fn test_cancel_stops_a_running_call() {
+    let mut engine = Engine::builder()
+        .provider_factory(ProviderFactory::mock())
+        .function(native_async_fn(
+            "wait",
+            Signature::new(),
+            |_: NativeArgs| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            },
+        ))
+        .build();
+    engine
+        .load_source("fn slow() -> number {\n    wait()\n    return 1\n}")
+        .await
+        .unwrap();
+
+    let cancel = engine.cancel_handle();
+    tokio::spawn({
+        let cancel = cancel.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        }
+    });
+    let err = engine.call("slow", vec![]).await.unwrap_err();
+    assert!(matches!(err, GentError::Cancelled));
+
+    // Calls keep failing until the handle is reset
+    assert!(engine.call("tax", vec![]).await.is_err());
+    cancel.reset();
+    assert!(engine.load_source("let x = 1").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_load_file_resolves_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("util.gnt"),
+        "fn double(n: number) -> number {\n    return n * 2\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("main.gnt"),
+        "import { double } from \"./util.gnt\"\n\nfn quadruple(n: number) -> number {\n    return double(double(n))\n}\n",
+    )
+    .unwrap();
+
+    let mut engine = Engine::builder()
+        .provider_factory(ProviderFactory::mock())
+        .build();
+    engine.load_file(dir.path().join("main.gnt")).await.unwrap();
+    assert_eq!(
+        engine.call("quadruple", vec![json!(3)]).await.unwrap(),
+        json!(12)
+    );
+
+    let err = engine.load_file(dir.path().join("missing.gnt")).await;
+    assert_eq!(err.unwrap_err().kind(), "FileReadError");
+}
@@ -1,6 +1,5 @@
 //! Tests for enum types in GENT
 
-use gent::config::Config;
 
 // ============================================
 // Parsing Tests
@@ -52,7 +52,7 @@ fn test_define_and_get_null() {
 fn test_define_and_get_agent() {
     let mut env = Environment::new();
     let agent = AgentValue::new("Bot", "You help.");
-    env.define("myAgent", Value::Agent(agent.clone()));
+    env.define("myAgent", Value::Agent(Box::new(agent.clone())));
     match env.get("myAgent") {
         Some(Value::Agent(a)) => {
             assert_eq!(a.name, "Bot");
@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::interpreter::evaluate_with_output;
 use gent::logging::NullLogger;
 use gent::parser::parse;
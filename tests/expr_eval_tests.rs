@@ -3,7 +3,7 @@
 use gent::interpreter::{evaluate_expr, Environment, Value};
 use gent::parser::ast::{BinaryOp, Expression, StringPart, UnaryOp};
 use gent::Span;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Helper to create a simple string expression (single literal)
 fn string_expr(s: &str, span: Span) -> Expression {
@@ -427,7 +427,7 @@ fn test_eval_neg_number() {
 // Member access
 #[test]
 fn test_eval_member_access() {
-    let mut obj = HashMap::new();
+    let mut obj = BTreeMap::new();
     obj.insert("name".to_string(), Value::String("John".to_string()));
     obj.insert("age".to_string(), Value::Number(30.0));
 
@@ -448,7 +448,7 @@ fn test_eval_member_access() {
 
 #[test]
 fn test_eval_member_access_undefined_property() {
-    let obj = HashMap::new();
+    let obj = BTreeMap::new();
 
     let mut env = Environment::new();
     env.define("person", Value::Object(obj));
@@ -511,7 +511,7 @@ fn test_eval_index_access_array() {
 
 #[test]
 fn test_eval_index_access_object() {
-    let mut obj = HashMap::new();
+    let mut obj = BTreeMap::new();
     obj.insert("key".to_string(), Value::String("value".to_string()));
 
     let mut env = Environment::new();
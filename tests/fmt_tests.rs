@@ -0,0 +1,282 @@
+//! Tests for the source formatter (`gent fmt`)
+
+use gent::formatter::{collect_comments, format_source, is_formatted};
+use gent::parser::parse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::tempdir;
+
+fn fmt(source: &str) -> String {
+    format_source(source).unwrap()
+}
+
+/// Debug output of the AST with every span removed
+fn ast_without_spans(source: &str) -> String {
+    let debug = format!("{:?}", parse(source).unwrap());
+    let mut out = String::new();
+    let mut rest = debug.as_str();
+    while let Some(start) = rest.find("Span {") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('}').unwrap();
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn example_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            example_files(&path, files);
+        } else if path.extension().map(|ext| ext == "gnt").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+}
+
+// ============================================
+// Layout
+// ============================================
+
+#[test]
+fn test_fmt_normalizes_whitespace() {
+    assert_eq!(
+        fmt("let   x=1+2\nprintln( x )"),
+        "let x = 1 + 2\nprintln(x)\n"
+    );
+}
+
+#[test]
+fn test_fmt_agent_body_one_field_per_line() {
+    let source = "agent Bot { model: \"gpt-4o\" tools: [search, fetch] output: { answer: string, score: number } }";
+    assert_eq!(
+        fmt(source),
+        "agent Bot {\n    model: \"gpt-4o\"\n    tools: [search, fetch]\n    output: { answer: string, score: number }\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_keeps_agent_field_order() {
+    let source = "agent Bot {\n  output: Summary\n  tools: [search]\n  model: \"m\"\n  knowledge: { source: \"./docs\" }\n}\n";
+    assert_eq!(
+        fmt(source),
+        "agent Bot {\n    output: Summary\n    tools: [search]\n    model: \"m\"\n    knowledge: { source: \"./docs\" }\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_multiline_string_is_verbatim() {
+    let source =
+        "agent Bot {\n  systemPrompt: \"\"\"\n    Line one\n      indented {name}\n  \"\"\"\n}\n";
+    assert_eq!(
+        fmt(source),
+        "agent Bot {\n    systemPrompt: \"\"\"\n    Line one\n      indented {name}\n  \"\"\"\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_object_literal_inline_when_written_inline() {
+    assert_eq!(
+        fmt("let o = {a: 1,\"b c\": \"x\"}"),
+        "let o = { a: 1, \"b c\": \"x\" }\n"
+    );
+}
+
+#[test]
+fn test_fmt_object_literal_multiline_when_written_multiline() {
+    assert_eq!(
+        fmt("let o = {a: 1,\n b: {c: true}}"),
+        "let o = {\n    a: 1,\n    b: { c: true }\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_long_array_is_split() {
+    let items: Vec<String> = (0..30).map(|i| format!("item{}", i)).collect();
+    let source = format!("let a = [{}]", items.join(", "));
+    let formatted = fmt(&source);
+    assert!(formatted.starts_with("let a = [\n    item0,\n    item1,\n"));
+    assert!(formatted.ends_with("    item29\n]\n"));
+}
+
+#[test]
+fn test_fmt_lambdas() {
+    let source =
+        "fn f() {\nlet d = xs.map((x)=>x*2)\nlet e = xs.filter((x) => { return x > 1 })\n}";
+    assert_eq!(
+        fmt(source),
+        "fn f() {\n    let d = xs.map((x) => x * 2)\n    let e = xs.filter((x) => {\n        return x > 1\n    })\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_statements() {
+    let source = "fn f(a: number) -> number {\nif a > 1 { return 1 } else { return 2 }\nfor i in 0..3 { continue }\nwhile a < 3 { a = a + 1 }\ntry { throw \"x\" } catch (e: ValidationError) { println(e.message) } finally { println(\"done\") }\n}";
+    assert_eq!(
+        fmt(source),
+        "fn f(a: number) -> number {\n    if a > 1 {\n        return 1\n    } else {\n        return 2\n    }\n    for i in 0..3 {\n        continue\n    }\n    while a < 3 {\n        a = a + 1\n    }\n    try {\n        throw \"x\"\n    } catch (e: ValidationError) {\n        println(e.message)\n    } finally {\n        println(\"done\")\n    }\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_for_key_value() {
+    assert_eq!(
+        fmt("fn f(o: object) {\nfor ( k,v ) in o { println(k) }\n}"),
+        "fn f(o: object) {\n    for (k, v) in o {\n        println(k)\n    }\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_declarations() {
+    let source = "struct P implements Shape { x: number, tags: string[] }\nenum E { A, B(msg: string) }\ninterface Shape { area() -> number\nname: string }\nparallel Team { agents: [A, B] timeout: 30s }";
+    assert_eq!(
+        fmt(source),
+        "struct P implements Shape {\n    x: number\n    tags: string[]\n}\nenum E {\n    A\n    B(msg: string)\n}\ninterface Shape {\n    area() -> number\n    name: string\n}\nparallel Team {\n    agents: [A, B]\n    timeout: 30s\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_match_expression() {
+    let source = "let m = match s { E.A => \"a\", E.B(msg) => { return msg } _ => \"z\" }";
+    assert_eq!(
+        fmt(source),
+        "let m = match s {\n    E.A => \"a\"\n    E.B(msg) => {\n        return msg\n    }\n    _ => \"z\"\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_keeps_needed_parentheses_only() {
+    assert_eq!(
+        fmt("let x = ((a + 1)) * 2 - (b - c) - !(d && e) + (f * g)"),
+        "let x = (a + 1) * 2 - (b - c) - !(d && e) + f * g\n"
+    );
+    assert_eq!(fmt("let h = ((x) => x)(2)"), "let h = ((x) => x)(2)\n");
+    assert_eq!(fmt("let n = -(a + b)"), "let n = -(a + b)\n");
+}
+
+#[test]
+fn test_fmt_keeps_unary_operators() {
+    assert_eq!(
+        fmt("let a = !done\nlet b = -x\nlet c = -5"),
+        "let a = !done\nlet b = -x\nlet c = -5\n"
+    );
+}
+
+#[test]
+fn test_fmt_collapses_blank_lines() {
+    assert_eq!(
+        fmt("\n\nlet a = 1\n\n\n\nlet b = 2\n\n"),
+        "let a = 1\n\nlet b = 2\n"
+    );
+}
+
+// ============================================
+// Comments
+// ============================================
+
+#[test]
+fn test_fmt_preserves_comments() {
+    let source = "// header\n\nlet a = 1 // trailing\n\n// before agent\nagent Bot { // open\n  // inside\n  model: \"m\" // model\n  // last\n}\n// end\n";
+    assert_eq!(
+        fmt(source),
+        "// header\n\nlet a = 1 // trailing\n\n// before agent\nagent Bot { // open\n    // inside\n    model: \"m\" // model\n    // last\n}\n// end\n"
+    );
+}
+
+#[test]
+fn test_fmt_comments_split_lists() {
+    assert_eq!(
+        fmt("println(\"a\", // first\n\"b\")"),
+        "println(\n    \"a\", // first\n    \"b\"\n)\n"
+    );
+    assert_eq!(
+        fmt("let o = { a: 1, // one\n b: 2 }"),
+        "let o = {\n    a: 1, // one\n    b: 2\n}\n"
+    );
+}
+
+#[test]
+fn test_fmt_comment_markers_in_strings_are_not_comments() {
+    let source = "let url = \"http://example.com\" // real\nlet s = \"\"\"\n// not a comment {\"//\"}\n\"\"\"\n";
+    assert_eq!(collect_comments(source).len(), 1);
+    assert_eq!(fmt(source), source);
+}
+
+// ============================================
+// Stability
+// ============================================
+
+#[test]
+fn test_fmt_is_idempotent() {
+    let source = "// c\nstruct S { a: string, b: { c: number } }\nagent A { model: \"m\"\n tools: [x,\n y] }\nfn f() { let o = {a: 1, // note\n b: 2}\n return o }\n";
+    let once = fmt(source);
+    assert_eq!(fmt(&once), once);
+    assert!(is_formatted(&once).unwrap());
+    assert!(!is_formatted(source).unwrap());
+}
+
+#[test]
+fn test_fmt_examples_are_formatted_and_unchanged_in_meaning() {
+    let mut files = Vec::new();
+    example_files(Path::new("examples"), &mut files);
+    assert!(!files.is_empty());
+    for file in files {
+        let source = fs::read_to_string(&file).unwrap();
+        let formatted = fmt(&source);
+        assert_eq!(formatted, source, "{} is not formatted", file.display());
+        assert_eq!(
+            ast_without_spans(&formatted),
+            ast_without_spans(&source),
+            "{}",
+            file.display()
+        );
+    }
+}
+
+#[test]
+fn test_fmt_preserves_meaning() {
+    let source = "fn f(a: number) -> number { let x = (a + 1) * -(a - 2) / !(a > 3) % 2\nlet l = xs.map((x) => x.value).filter((v) => { return v != null })\nreturn x }";
+    assert_eq!(ast_without_spans(&fmt(source)), ast_without_spans(source));
+}
+
+#[test]
+fn test_fmt_rejects_syntax_errors() {
+    assert!(format_source("agent {").is_err());
+}
+
+// ============================================
+// CLI
+// ============================================
+
+#[test]
+fn test_fmt_cli_check_and_rewrite() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("main.gnt");
+    fs::write(&file, "let   x=1\n").unwrap();
+
+    let check = Command::new(env!("CARGO_BIN_EXE_gent"))
+        .args(["fmt", "--check"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(!check.status.success());
+    assert!(String::from_utf8_lossy(&check.stdout).contains("main.gnt"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "let   x=1\n");
+
+    let rewrite = Command::new(env!("CARGO_BIN_EXE_gent"))
+        .arg("fmt")
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(rewrite.status.success());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "let x = 1\n");
+
+    let check = Command::new(env!("CARGO_BIN_EXE_gent"))
+        .args(["fmt", "--check"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(check.status.success());
+}
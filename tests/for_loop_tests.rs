@@ -75,6 +75,7 @@ async fn execute_tool_with_for_loop(
 
     let tool_value = UserToolValue {
         name: "test_loop".to_string(),
+        description: None,
         params: vec![],
         return_type: Some(TypeName::String),
         body: Block {
@@ -95,6 +96,7 @@ async fn test_eval_for_loop_array() {
     // return "done"
     let for_stmt = ForStmt {
         variable: "item".to_string(),
+        value_variable: None,
         iterable: Expression::Array(
             vec![
                 Expression::Number(1.0, Span::default()),
@@ -136,6 +138,7 @@ async fn test_eval_for_loop_range() {
     // return "done"
     let for_stmt = ForStmt {
         variable: "i".to_string(),
+        value_variable: None,
         iterable: Expression::Range(
             Box::new(Expression::Number(0.0, Span::default())),
             Box::new(Expression::Number(3.0, Span::default())),
@@ -174,6 +177,7 @@ async fn test_eval_for_loop_string() {
     // return "done"
     let for_stmt = ForStmt {
         variable: "char".to_string(),
+        value_variable: None,
         iterable: Expression::String(
             vec![gent::parser::ast::StringPart::Literal("abc".to_string())],
             Span::default(),
@@ -211,6 +215,7 @@ async fn test_eval_for_loop_empty_array() {
     // return "done"
     let for_stmt = ForStmt {
         variable: "item".to_string(),
+        value_variable: None,
         iterable: Expression::Array(vec![], Span::default()),
         body: Block {
             statements: vec![BlockStmt::Let(gent::parser::ast::LetStmt {
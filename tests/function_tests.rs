@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::interpreter::evaluate_with_output;
 use gent::logging::NullLogger;
 use gent::parser::parse;
@@ -227,10 +226,11 @@ async fn test_eval_fn_wrong_arg_count() {
     let fn_val = FnValue {
         name: "add".to_string(),
         params: vec![
-            Param { name: "a".to_string(), type_name: TypeName::Number, span: Span::new(0, 0) },
-            Param { name: "b".to_string(), type_name: TypeName::Number, span: Span::new(0, 0) },
+            Param { name: "a".to_string(), type_name: TypeName::Number, default: None, description: None, span: Span::new(0, 0) },
+            Param { name: "b".to_string(), type_name: TypeName::Number, default: None, description: None, span: Span::new(0, 0) },
         ],
         return_type: Some(TypeName::Number),
+        source_file: None,
         body: Block {
             statements: vec![
                 BlockStmt::Return(ReturnStmt {
@@ -0,0 +1,325 @@
+//! Tests for handoffs between agents (`handoffs:` agent field)
+
+use gent::interpreter::{AgentValue, Session, Value};
+use gent::logging::{LogLevel, Logger, NullLogger};
+use gent::parser::parse;
+use gent::repl::eval_input;
+use gent::runtime::handoff::{
+    handoff_targets, set_system_message, transfer_definition, transfer_target, TRANSFER_TOOL,
+};
+use gent::runtime::rag::embeddings::MockEmbeddings;
+use gent::runtime::{MemoryStore, Message, ProviderFactory, Role, ToolCall};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+const BILLING: &str =
+    "agent Billing { systemPrompt: \"You handle invoices.\" model: \"gpt-4o-mini\" maxSteps: 1 }";
+
+/// Logger that keeps every message
+#[derive(Default)]
+struct RecordingLogger(Mutex<Vec<String>>);
+
+impl Logger for RecordingLogger {
+    fn log(&self, _level: LogLevel, _target: &str, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+
+    fn log_with_duration(&self, level: LogLevel, target: &str, message: &str, _duration_ms: u64) {
+        self.log(level, target, message);
+    }
+
+    fn is_enabled(&self, _level: LogLevel) -> bool {
+        true
+    }
+
+    fn level(&self) -> LogLevel {
+        LogLevel::Trace
+    }
+}
+
+fn transfer_call(agent: &str) -> ToolCall {
+    ToolCall {
+        id: "1".to_string(),
+        name: TRANSFER_TOOL.to_string(),
+        arguments: json!({"agent": agent}),
+    }
+}
+
+#[tokio::test]
+async fn test_handoff_fields() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval_input(&mut session, BILLING).await.unwrap();
+    eval_input(
+        &mut session,
+        "agent Triage { model: \"gpt-4o-mini\" handoffs: [Billing] maxHandoffs: 2 }",
+    )
+    .await
+    .unwrap();
+    let triage = match eval_input(&mut session, "Triage").await {
+        Ok(Some(Value::Agent(agent))) => *agent,
+        _ => panic!("Triage is not an agent"),
+    };
+    assert_eq!(triage.handoffs.len(), 1);
+    assert_eq!(triage.handoffs[0], "Billing");
+    assert_eq!(triage.max_handoffs, Some(2));
+
+    let errors = eval_input(
+        &mut session,
+        "agent Other { model: \"gpt-4o-mini\" handoffs: [\"Billing\"] }",
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(errors[0].kind(), "TypeError");
+}
+
+#[test]
+fn test_targets_include_agents_handed_off_from() {
+    let billing = AgentValue::new("Billing", "You handle invoices.");
+    let support = AgentValue::new("Support", "You fix problems.");
+    let triage = AgentValue::new("Triage", "You route questions.")
+        .with_handoffs(vec!["Billing".to_string()]);
+    let lookup = |name: &str| [&billing, &support].into_iter().find(|a| a.name == name).cloned();
+
+    let names =
+        |targets: Vec<AgentValue>| -> Vec<String> { targets.into_iter().map(|t| t.name).collect() };
+    assert_eq!(names(handoff_targets(&triage, &[], lookup).unwrap()), vec!["Billing"]);
+    // An agent can hand back, but never to itself
+    let billing = billing.clone().with_handoffs(vec!["Support".to_string()]);
+    assert_eq!(
+        names(handoff_targets(&billing, &[triage.clone(), billing.clone()], lookup).unwrap()),
+        vec!["Support", "Triage"]
+    );
+
+    let err = handoff_targets(&triage, &[], |_| None).unwrap_err();
+    assert_eq!(err.kind(), "UndefinedAgent");
+}
+
+#[test]
+fn test_transfer_tool() {
+    let targets = vec![
+        AgentValue::new("Billing", "You handle invoices.\nBe polite."),
+        AgentValue::new("Support", ""),
+    ];
+    let def = transfer_definition(&targets);
+    assert_eq!(def.name, TRANSFER_TOOL);
+    assert!(def.description.contains("- Billing: You handle invoices."));
+    assert!(!def.description.contains("Be polite."));
+    assert_eq!(
+        def.parameters["properties"]["agent"]["enum"],
+        json!(["Billing", "Support"])
+    );
+
+    assert_eq!(
+        transfer_target(&transfer_call("Support"), &targets).map(|t| t.name.as_str()),
+        Ok("Support")
+    );
+    let err = transfer_target(&transfer_call("Sales"), &targets).unwrap_err();
+    assert!(err.contains("Unknown agent 'Sales'"), "{}", err);
+    assert!(err.contains("Billing, Support"), "{}", err);
+}
+
+#[test]
+fn test_set_system_message() {
+    let mut messages = vec![Message::system("You route questions."), Message::user("Hi")];
+    set_system_message(&mut messages, "You handle invoices.".to_string());
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].content, "You handle invoices.");
+
+    set_system_message(&mut messages, String::new());
+    assert_eq!(messages[0].role, Role::User);
+
+    set_system_message(&mut messages, "You fix problems.".to_string());
+    assert_eq!(messages[0].role, Role::System);
+    assert_eq!(messages.len(), 2);
+}
+
+#[tokio::test]
+async fn test_handoffs_stop_at_the_limit() {
+    let logger = Arc::new(RecordingLogger::default());
+    let factory = ProviderFactory::mock_with_tool_calls(vec![
+        transfer_call("Billing"),
+        transfer_call("Triage"),
+    ]);
+    let mut session = Session::new(factory, logger.clone());
+    eval_input(&mut session, BILLING).await.unwrap();
+    eval_input(
+        &mut session,
+        "agent Triage { model: \"gpt-4o-mini\" maxSteps: 1 handoffs: [Billing] maxHandoffs: 1 }",
+    )
+    .await
+    .unwrap();
+
+    // The mock keeps transferring, so Billing's run stops at maxSteps
+    let errors = eval_input(
+        &mut session,
+        "Triage.userPrompt(\"My invoice is wrong\").run()",
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(errors[0].kind(), "MaxStepsExceeded");
+
+    let messages = logger.0.lock().unwrap();
+    let handed_off: Vec<_> = messages
+        .iter()
+        .filter(|m| m.contains("handed off"))
+        .collect();
+    assert_eq!(handed_off, vec!["Agent 'Triage' handed off to 'Billing'"]);
+    // Billing can hand back to Triage, but not to itself
+    assert!(messages.iter().any(|m| m
+        == "Agent 'Billing' could not hand off: Unknown agent 'Billing'; you can transfer to: Triage"));
+    assert!(messages.iter().any(|m| {
+        m
+        == "Agent 'Billing' could not hand off: Handoff limit (1) reached; answer the user yourself"
+    }));
+}
+
+#[tokio::test]
+async fn test_agents_without_handoffs_have_no_transfer_tool() {
+    let factory = ProviderFactory::mock_with_tool_calls(vec![transfer_call("Billing")]);
+    let logger = Arc::new(RecordingLogger::default());
+    let mut session = Session::new(factory, logger.clone());
+    eval_input(&mut session, BILLING).await.unwrap();
+
+    let errors = eval_input(&mut session, "Billing.userPrompt(\"Hi\").run()")
+        .await
+        .unwrap_err();
+    assert_eq!(errors[0].kind(), "MaxStepsExceeded");
+    assert!(logger
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|m| m == &format!("Unknown tool: {}", TRANSFER_TOOL)));
+}
+
+#[tokio::test]
+async fn test_run_returns_the_reply_and_conversations_report_handoffs() {
+    // Triage hands off; Billing then ends the turn with its finish tool
+    let factory = ProviderFactory::mock_with_tool_calls(vec![
+        transfer_call("Billing"),
+        ToolCall {
+            id: "2".to_string(),
+            name: "done".to_string(),
+            arguments: json!({"answer": "ok"}),
+        },
+    ]);
+    let program = parse(
+        r#"tool done(answer: string) -> string {
+    return answer
+}
+agent Triage { model: "gpt-4o-mini" handoffs: [Billing] }
+agent Billing { model: "gpt-4o-mini" finishTool: done }
+parallel both {
+    agents: [Triage.userPrompt("Hi")]
+    timeout: 5s
+}
+let chat = Triage.conversation()
+let before = chat.handoffs()
+let results = [Triage.userPrompt("Hi").run(), Triage("Hi"), both.run()[0]]
+let reply = chat.send("Hi")
+"#,
+    )
+    .unwrap();
+    let mut session = Session::new(factory, Arc::new(NullLogger));
+    session.eval_program(&program, None).await.unwrap();
+
+    // Every way of running the agent returns the reply: here the finish
+    // tool's arguments, from the agent handed off to
+    let reply = Value::String(r#"{"answer":"ok"}"#.to_string());
+    assert_eq!(session.env().get("results"), Some(&Value::Array(vec![reply; 3])));
+    assert_eq!(session.env().get("before"), Some(&Value::Array(vec![])));
+
+    // A conversation tells who answered and who was handed off to
+    let agent = eval_input(&mut session, "chat.agent()").await.unwrap();
+    assert_eq!(agent, Some(Value::String("Billing".to_string())));
+    let handoffs = eval_input(&mut session, "chat.handoffs()").await.unwrap();
+    assert_eq!(
+        handoffs,
+        Some(Value::Array(vec![Value::String("Billing".to_string())]))
+    );
+}
+
+#[tokio::test]
+async fn test_agents_can_hand_off_to_each_other() {
+    let logger = Arc::new(RecordingLogger::default());
+    let factory = ProviderFactory::mock_with_tool_calls(vec![transfer_call("Triage")]);
+    let mut session = Session::new(factory, logger.clone());
+    // Triage names Billing before it is declared
+    eval_input(
+        &mut session,
+        "agent Triage { model: \"gpt-4o-mini\" maxSteps: 1 handoffs: [Billing] }",
+    )
+    .await
+    .unwrap();
+    eval_input(
+        &mut session,
+        "agent Billing { model: \"gpt-4o-mini\" handoffs: [Triage] maxHandoffs: 1 }",
+    )
+    .await
+    .unwrap();
+
+    // The mock keeps transferring to Triage, so its run stops at maxSteps
+    let errors = eval_input(&mut session, "Billing.userPrompt(\"Hi\").run()")
+        .await
+        .unwrap_err();
+    assert_eq!(errors[0].kind(), "MaxStepsExceeded");
+
+    {
+        let messages = logger.0.lock().unwrap();
+        assert!(messages
+            .iter()
+            .any(|m| m == "Agent 'Billing' handed off to 'Triage'"));
+        // Triage's own handoff to Billing was found by name
+        assert!(messages.iter().any(|m| m
+            == "Agent 'Triage' could not hand off: Unknown agent 'Triage'; you can transfer to: Billing"));
+    }
+
+    eval_input(
+        &mut session,
+        "agent Sales { model: \"gpt-4o-mini\" handoffs: [Nobody] }",
+    )
+    .await
+    .unwrap();
+    let errors = eval_input(&mut session, "Sales.userPrompt(\"Hi\").run()")
+        .await
+        .unwrap_err();
+    assert_eq!(errors[0].kind(), "UndefinedAgent");
+}
+
+#[tokio::test]
+async fn test_handed_off_agent_gets_its_memory_context() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = MemoryStore::open(dir.path(), "Billing", Arc::new(MockEmbeddings::new())).unwrap();
+    store.remember("invoice", "INV-7 was paid twice").await.unwrap();
+
+    // Triage hands off; Billing then ends the turn with its finish tool
+    let factory = ProviderFactory::mock_with_tool_calls(vec![
+        transfer_call("Billing"),
+        ToolCall {
+            id: "2".to_string(),
+            name: "done".to_string(),
+            arguments: json!({}),
+        },
+    ]);
+    let mut session = Session::new(factory, Arc::new(NullLogger));
+    for input in [
+        "tool done() -> string {\n    return \"ok\"\n}".to_string(),
+        "agent Triage { systemPrompt: \"You route questions.\" model: \"gpt-4o-mini\" handoffs: [Billing] }"
+            .to_string(),
+        format!(
+            "agent Billing {{ systemPrompt: \"You handle invoices.\" model: \"gpt-4o-mini\" finishTool: done memory: \"{}\" }}",
+            dir.path().display().to_string().replace('\\', "/")
+        ),
+        "let chat = Triage.conversation()".to_string(),
+        "chat.send(\"invoice\")".to_string(),
+    ] {
+        eval_input(&mut session, &input).await.unwrap();
+    }
+
+    let system = match eval_input(&mut session, "chat.history()[0].content").await {
+        Ok(Some(Value::String(system))) => system,
+        other => panic!("no system message: {:?}", other),
+    };
+    assert!(system.starts_with("You handle invoices."), "{}", system);
+    assert!(system.contains("invoice: INV-7 was paid twice"), "{}", system);
+}
@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::parser::parse;
 use std::fs;
 use tempfile::tempdir;
@@ -1,6 +1,5 @@
 //! Tests for interface declaration parsing
 
-use gent::config::Config;
 use gent::parser::{InterfaceMember, Statement, TypeName};
 
 #[test]
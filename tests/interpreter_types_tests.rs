@@ -2,7 +2,7 @@ use gent::interpreter::types::UserToolValue;
 use gent::interpreter::{AgentValue, Value};
 use gent::parser::ast::{Block, Param, TypeName};
 use gent::Span;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 // ============================================
 // Value Creation Tests
@@ -28,9 +28,9 @@ fn test_value_number_integer() {
 
 #[test]
 fn test_value_number_float() {
-    let val = Value::Number(3.14);
+    let val = Value::Number(2.75);
     match val {
-        Value::Number(n) => assert!((n - 3.14).abs() < 0.001),
+        Value::Number(n) => assert!((n - 2.75).abs() < 0.001),
         _ => panic!("Expected Number"),
     }
 }
@@ -62,7 +62,7 @@ fn test_value_null() {
 #[test]
 fn test_value_agent() {
     let agent = AgentValue::new("Hello", "You are friendly.");
-    let val = Value::Agent(agent);
+    let val = Value::Agent(Box::new(agent));
     match val {
         Value::Agent(a) => {
             assert_eq!(a.name, "Hello");
@@ -124,8 +124,8 @@ fn test_display_number_integer() {
 
 #[test]
 fn test_display_number_float() {
-    let val = Value::Number(3.14);
-    assert_eq!(format!("{}", val), "3.14");
+    let val = Value::Number(2.75);
+    assert_eq!(format!("{}", val), "2.75");
 }
 
 #[test]
@@ -148,7 +148,7 @@ fn test_display_null() {
 
 #[test]
 fn test_display_agent() {
-    let val = Value::Agent(AgentValue::new("Hello", "prompt"));
+    let val = Value::Agent(Box::new(AgentValue::new("Hello", "prompt")));
     assert_eq!(format!("{}", val), "<agent Hello>");
 }
 
@@ -204,7 +204,7 @@ fn test_truthy_number_negative() {
 
 #[test]
 fn test_truthy_agent() {
-    assert!(Value::Agent(AgentValue::new("A", "p")).is_truthy());
+    assert!(Value::Agent(Box::new(AgentValue::new("A", "p"))).is_truthy());
 }
 
 // ============================================
@@ -233,7 +233,7 @@ fn test_type_name_null() {
 
 #[test]
 fn test_type_name_agent() {
-    assert_eq!(Value::Agent(AgentValue::new("A", "p")).type_name(), "Agent");
+    assert_eq!(Value::Agent(Box::new(AgentValue::new("A", "p"))).type_name(), "Agent");
 }
 
 // ============================================
@@ -255,7 +255,7 @@ fn test_as_string_failure() {
 #[test]
 fn test_as_agent_success() {
     let agent = AgentValue::new("Bot", "prompt");
-    let val = Value::Agent(agent.clone());
+    let val = Value::Agent(Box::new(agent.clone()));
     assert_eq!(val.as_agent(), Some(&agent));
 }
 
@@ -280,7 +280,7 @@ fn test_as_array_failure() {
 
 #[test]
 fn test_as_object_success() {
-    let mut map = HashMap::new();
+    let mut map = BTreeMap::new();
     map.insert("key".to_string(), Value::Number(42.0));
     let val = Value::Object(map.clone());
     assert_eq!(val.as_object(), Some(&map));
@@ -366,7 +366,7 @@ fn test_array_is_truthy() {
 
 #[test]
 fn test_object_value_creation() {
-    let mut map = HashMap::new();
+    let mut map = BTreeMap::new();
     map.insert("name".to_string(), Value::String("Tokyo".to_string()));
     map.insert("temp".to_string(), Value::Number(22.0));
     let obj = Value::Object(map);
@@ -375,7 +375,7 @@ fn test_object_value_creation() {
 
 #[test]
 fn test_object_display() {
-    let mut map = HashMap::new();
+    let mut map = BTreeMap::new();
     map.insert("a".to_string(), Value::Number(1.0));
     let obj = Value::Object(map);
     // Object display shows {key: value} format
@@ -385,20 +385,20 @@ fn test_object_display() {
 
 #[test]
 fn test_object_display_empty() {
-    let obj = Value::Object(HashMap::new());
+    let obj = Value::Object(BTreeMap::new());
     assert_eq!(format!("{}", obj), "{}");
 }
 
 #[test]
 fn test_object_type_name() {
-    let obj = Value::Object(HashMap::new());
+    let obj = Value::Object(BTreeMap::new());
     assert_eq!(obj.type_name(), "Object");
 }
 
 #[test]
 fn test_object_is_truthy() {
-    let empty = Value::Object(HashMap::new());
-    let mut map = HashMap::new();
+    let empty = Value::Object(BTreeMap::new());
+    let mut map = BTreeMap::new();
     map.insert("x".to_string(), Value::Null);
     let non_empty = Value::Object(map);
     assert!(!empty.is_truthy());
@@ -413,6 +413,7 @@ fn test_object_is_truthy() {
 fn test_tool_value_creation() {
     let tool = UserToolValue {
         name: "greet".to_string(),
+        description: None,
         params: vec![],
         return_type: None,
         body: Block {
@@ -428,6 +429,7 @@ fn test_tool_value_creation() {
 fn test_tool_display() {
     let tool = UserToolValue {
         name: "greet".to_string(),
+        description: None,
         params: vec![],
         return_type: None,
         body: Block {
@@ -443,6 +445,7 @@ fn test_tool_display() {
 fn test_tool_type_name() {
     let tool = UserToolValue {
         name: "greet".to_string(),
+        description: None,
         params: vec![],
         return_type: None,
         body: Block {
@@ -458,6 +461,7 @@ fn test_tool_type_name() {
 fn test_tool_is_truthy() {
     let tool = UserToolValue {
         name: "greet".to_string(),
+        description: None,
         params: vec![],
         return_type: None,
         body: Block {
@@ -473,15 +477,20 @@ fn test_tool_is_truthy() {
 fn test_tool_with_params() {
     let tool = UserToolValue {
         name: "add".to_string(),
+        description: None,
         params: vec![
             Param {
                 name: "a".to_string(),
                 type_name: TypeName::Number,
+                default: None,
+                description: None,
                 span: Span::new(0, 0),
             },
             Param {
                 name: "b".to_string(),
                 type_name: TypeName::Number,
+                default: None,
+                description: None,
                 span: Span::new(0, 0),
             },
         ],
@@ -502,9 +511,12 @@ fn test_tool_with_ast_block() {
     // Create a tool with a real AST block containing statements
     let tool = UserToolValue {
         name: "calculate".to_string(),
+        description: None,
         params: vec![Param {
             name: "x".to_string(),
             type_name: TypeName::Number,
+            default: None,
+            description: None,
             span: Span::new(0, 1),
         }],
         return_type: Some(TypeName::Number),
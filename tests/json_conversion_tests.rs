@@ -0,0 +1,282 @@
+//! Tests for conversion between values and JSON (`toJson`, `fromJson`)
+
+use gent::interpreter::types::{EnumDef, EnumFieldDef, EnumValue, EnumVariantDef};
+use gent::interpreter::{json_to_enum, json_to_value, value_to_json, Environment, Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn circle(r: f64) -> Value {
+    Value::Enum(EnumValue {
+        enum_name: "Shape".to_string(),
+        variant: "Circle".to_string(),
+        data: vec![Value::Number(r)],
+    })
+}
+
+fn shape() -> EnumDef {
+    let variant = |name: &str, fields: &[&str]| EnumVariantDef {
+        name: name.to_string(),
+        fields: fields
+            .iter()
+            .map(|type_name| EnumFieldDef {
+                name: None,
+                type_name: type_name.to_string(),
+            })
+            .collect(),
+    };
+    EnumDef {
+        name: "Shape".to_string(),
+        variants: vec![variant("Circle", &["number"]), variant("Square", &[])],
+    }
+}
+
+const TYPES: &str = "struct Person { name: string, age: number, tags: string[] }";
+
+/// Evaluate `inputs` in one session and return the value of the last one
+async fn eval(inputs: &[&str]) -> Result<Value, String> {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let mut last = None;
+    for input in inputs {
+        last = eval_input(&mut session, input)
+            .await
+            .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))?;
+    }
+    Ok(last.unwrap_or(Value::Null))
+}
+
+#[test]
+fn test_round_trip() {
+    let mut map = BTreeMap::new();
+    map.insert("n".to_string(), Value::Number(1.5));
+    map.insert("whole".to_string(), Value::Number(3.0));
+    map.insert("none".to_string(), Value::Null);
+    map.insert(
+        "list".to_string(),
+        Value::Array(vec![Value::Boolean(true), string("x")]),
+    );
+    map.insert("shape".to_string(), circle(2.0));
+    let value = Value::Object(map);
+
+    let json = value_to_json(&value);
+    assert_eq!(
+        json,
+        json!({
+            "n": 1.5,
+            "whole": 3,
+            "none": null,
+            "list": [true, "x"],
+            "shape": { "enum": "Shape", "variant": "Circle", "data": [2] }
+        })
+    );
+
+    // Without a type, the enum comes back as the object it was written as
+    let Value::Object(mut back) = json_to_value(&json) else {
+        panic!("expected an object");
+    };
+    assert!(matches!(back.remove("shape"), Some(Value::Object(_))));
+    let Value::Object(mut map) = value else {
+        unreachable!()
+    };
+    map.remove("shape");
+    assert_eq!(back, map);
+    assert_eq!(
+        json_to_enum(&json["shape"], &shape(), &Environment::new()).map(Value::Enum),
+        Ok(circle(2.0))
+    );
+}
+
+#[test]
+fn test_unit_enum_variants_have_no_data() {
+    let square = Value::Enum(EnumValue {
+        enum_name: "Shape".to_string(),
+        variant: "Square".to_string(),
+        data: vec![],
+    });
+    let json = value_to_json(&square);
+    assert_eq!(json, json!({ "enum": "Shape", "variant": "Square" }));
+    assert_eq!(
+        json_to_enum(&json, &shape(), &Environment::new()).map(Value::Enum),
+        Ok(square)
+    );
+}
+
+#[test]
+fn test_objects_are_only_read_as_enums_where_one_is_expected() {
+    let env = Environment::new();
+    let json = json!({ "enum": "Shape", "variant": "Circle", "data": [1] });
+    assert!(matches!(json_to_value(&json), Value::Object(_)));
+    assert!(json_to_enum(&json, &shape(), &env).is_ok());
+
+    for json in [
+        json!({ "enum": "Shape", "variant": "Circle", "data": [1], "extra": 1 }),
+        json!({ "enum": "Shape", "variant": 3 }),
+        json!({ "enum": "Color", "variant": "Circle", "data": [1] }),
+        json!({}),
+        json!({ "data": [1] }),
+        json!({ "variant": "Circle" }),
+        json!({ "enum": "Shape", "data": [] }),
+        json!("Circle"),
+    ] {
+        assert!(matches!(json_to_value(&json), Value::Object(_) | Value::String(_)), "{}", json);
+        let err = json_to_enum(&json, &shape(), &env).unwrap_err();
+        assert!(err.starts_with("expected a Shape value"), "{}", err);
+    }
+
+    let err = json_to_enum(&json!({ "enum": "Shape", "variant": "Oval" }), &shape(), &env).unwrap_err();
+    assert_eq!(err, "Shape has no variant 'Oval'");
+    let err = json_to_enum(&json!({ "enum": "Shape", "variant": "Circle" }), &shape(), &env).unwrap_err();
+    assert_eq!(err, "Shape.Circle takes 1 values, got 0");
+}
+
+#[tokio::test]
+async fn test_to_json_method() {
+    assert_eq!(
+        eval(&["{ b: [true, null], a: 1 }.toJson()"]).await.unwrap(),
+        string(r#"{"a":1,"b":[true,null]}"#)
+    );
+    assert_eq!(
+        eval(&["[1].toJson(true)"]).await.unwrap(),
+        string("[\n  1\n]")
+    );
+    assert_eq!(
+        eval(&["\"a\\\"b\".toJson()"]).await.unwrap(),
+        string(r#""a\"b""#)
+    );
+    assert_eq!(
+        eval(&[
+            "enum Shape { Circle(number), Square }",
+            "Shape.Square.toJson()"
+        ])
+        .await
+        .unwrap(),
+        string(r#"{"enum":"Shape","variant":"Square"}"#)
+    );
+}
+
+#[tokio::test]
+async fn test_from_json_round_trips_through_the_language() {
+    assert_eq!(
+        eval(&[
+            "enum Shape { Circle(number), Square }",
+            "let s = fromJson(Shape.Circle(2).toJson(), Shape)",
+            "match s {\n    Shape.Circle(r) => r\n    _ => 0\n}",
+        ])
+        .await
+        .unwrap(),
+        Value::Number(2.0)
+    );
+    // Without the enum, JSON that looks like an enum value stays an object
+    assert_eq!(
+        eval(&[
+            "enum Shape { Circle(number), Square }",
+            "fromJson(Shape.Square.toJson()).variant",
+        ])
+        .await
+        .unwrap(),
+        string("Square")
+    );
+    let err = eval(&[
+        "enum Shape { Circle(number), Square }",
+        r#"fromJson("\{\"enum\": \"Shape\", \"variant\": \"Oval\"}", Shape)"#,
+    ])
+    .await
+    .unwrap_err();
+    assert!(err.starts_with("OutputValidationError"), "{}", err);
+    assert!(err.contains("Shape has no variant 'Oval'"), "{}", err);
+    assert_eq!(
+        eval(&["fromJson(\"[1, null]\")"]).await.unwrap(),
+        Value::Array(vec![Value::Number(1.0), Value::Null])
+    );
+}
+
+#[tokio::test]
+async fn test_from_json_with_struct() {
+    assert_eq!(
+        eval(&[
+            TYPES,
+            r#"let p = fromJson("\{\"name\": \"Ann\", \"age\": 41, \"tags\": [\"x\"]}", Person)"#,
+            "p.name",
+        ])
+        .await
+        .unwrap(),
+        string("Ann")
+    );
+}
+
+#[tokio::test]
+async fn test_from_json_validates_against_struct() {
+    let err = eval(&[
+        TYPES,
+        r#"fromJson("\{\"name\": \"Ann\", \"age\": \"old\", \"tags\": []}", Person)"#,
+    ])
+    .await
+    .unwrap_err();
+    assert!(err.starts_with("OutputValidationError"), "{}", err);
+    assert!(
+        err.contains("'age': expected number, got string"),
+        "{}",
+        err
+    );
+
+    assert_eq!(
+        eval(&[
+            TYPES,
+            "let kind = \"\"",
+            "try {\n    fromJson(\"\\{}\", Person)\n} catch (e: OutputValidationError) {\n    kind = e.kind\n}",
+            "kind",
+        ])
+        .await
+        .unwrap(),
+        string("OutputValidationError")
+    );
+}
+
+#[tokio::test]
+async fn test_from_json_errors() {
+    let err = eval(&["fromJson(\"\\{oops\")"]).await.unwrap_err();
+    assert!(err.starts_with("JsonError"), "{}", err);
+    assert!(err.contains("Invalid JSON"), "{}", err);
+
+    let err = eval(&[TYPES, "fromJson(\"\\{}\", \"Persn\")"])
+        .await
+        .unwrap_err();
+    assert!(err.starts_with("JsonError"), "{}", err);
+    assert!(err.contains("Unknown struct: Persn"), "{}", err);
+
+    assert_eq!(
+        eval(&[
+            "let kind = \"\"",
+            "try {\n    fromJson(\"[1,\")\n} catch (e: JsonError) {\n    kind = e.kind\n}",
+            "kind",
+        ])
+        .await
+        .unwrap(),
+        string("JsonError")
+    );
+
+    let err = eval(&["fromJson(1)"]).await.unwrap_err();
+    assert!(err.starts_with("ArgumentTypeMismatch"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_std_json_uses_the_same_conversion() {
+    assert_eq!(
+        eval(&[
+            "import { stringify, parse } from \"std:json\"",
+            "enum Shape { Circle(number), Square }",
+            "let json = stringify(Shape.Circle(1))",
+            "[parse(json).variant, fromJson(json, Shape).is(Shape.Circle)]",
+        ])
+        .await
+        .unwrap(),
+        Value::Array(vec![string("Circle"), Value::Boolean(true)])
+    );
+}
@@ -1,6 +1,5 @@
 //! Tests for KnowledgeBase in GENT
 
-use gent::config::Config;
 
 #[tokio::test]
 async fn test_create_knowledge_base() {
@@ -1,6 +1,5 @@
 //! Tests for lambda parsing
 
-use gent::config::Config;
 use gent::parser::parse;
 
 #[test]
@@ -1,4 +1,3 @@
-use gent::config::Config;
 use gent::interpreter::evaluate;
 use gent::logging::NullLogger;
 use gent::parser::{parse, Statement};
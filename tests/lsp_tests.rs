@@ -0,0 +1,440 @@
+//! Tests for the language server and the analysis behind it
+
+use gent::lsp::analysis::{
+    completion_kind, completions, definition, diagnostics, document_symbols, hover,
+    offset_to_position, position_to_offset, symbol_kind, Position,
+};
+use gent::lsp::protocol::{read_message, write_message};
+use gent::lsp::{path_to_uri, run, uri_to_path, Server};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Cursor;
+use tempfile::tempdir;
+
+const SOURCE: &str = r#"struct Summary {
+    title: string
+}
+
+tool search(query: string) -> string {
+    return "results for {query}"
+}
+
+agent Researcher {
+    model: "gpt-4o"
+    tools: [search]
+    prompt: "You research topics"
+    output: Summary
+}
+
+fn research(topic: string) -> string {
+    let answer = Researcher.userPrompt(topic).run()
+    return answer
+}
+
+research("rust")
+"#;
+
+fn offset_of(source: &str, needle: &str) -> usize {
+    source.find(needle).unwrap()
+}
+
+#[test]
+fn test_positions_round_trip() {
+    let source = "let a = 1\nlet é = \"ü\"\n";
+    let offset = offset_of(source, "\"ü\"") + 1;
+    let position = offset_to_position(source, offset);
+    assert_eq!(
+        position,
+        Position {
+            line: 1,
+            character: 9
+        }
+    );
+    assert_eq!(position_to_offset(source, position), offset);
+    // Positions past the end of a line clamp to the line end
+    let clamped = position_to_offset(
+        source,
+        Position {
+            line: 0,
+            character: 99,
+        },
+    );
+    assert_eq!(clamped, 9);
+}
+
+#[test]
+fn test_diagnostics_for_valid_source() {
+    assert!(diagnostics(SOURCE).is_empty());
+}
+
+#[test]
+fn test_diagnostics_report_all_errors_with_help() {
+    let source = "agnet A {}\nlet x = \n";
+    let found = diagnostics(source);
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].severity, 1);
+    assert_eq!(
+        found[0].range.start,
+        Position {
+            line: 0,
+            character: 0
+        }
+    );
+    assert_eq!(
+        found[0].range.end,
+        Position {
+            line: 0,
+            character: 5
+        }
+    );
+    assert!(found[0].message.contains("unknown statement `agnet`"));
+    assert!(found[0].message.contains("help: did you mean `agent`?"));
+    assert_eq!(found[1].range.start.line, 1);
+}
+
+#[test]
+fn test_document_symbols() {
+    let symbols = document_symbols(SOURCE);
+    let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Summary", "search", "Researcher", "research"]);
+
+    let agent = &symbols[2];
+    assert_eq!(agent.kind, symbol_kind::CLASS);
+    assert_eq!(
+        agent.selection_range.start,
+        Position {
+            line: 8,
+            character: 6
+        }
+    );
+    let fields: Vec<&str> = agent.children.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(fields, vec!["model", "tools", "prompt"]);
+
+    let tool = &symbols[1];
+    assert_eq!(tool.kind, symbol_kind::FUNCTION);
+    assert_eq!(
+        tool.detail.as_deref(),
+        Some("tool search(query: string) -> string")
+    );
+
+    assert_eq!(symbols[0].kind, symbol_kind::STRUCT);
+    assert_eq!(symbols[0].children[0].name, "title");
+}
+
+#[test]
+fn test_definition_of_tool_and_agent() {
+    let tool_use = offset_of(SOURCE, "[search]") + 2;
+    let location = definition(SOURCE, None, tool_use).unwrap();
+    assert_eq!(location.file, None);
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 4,
+            character: 5
+        }
+    );
+
+    let agent_use = offset_of(SOURCE, "Researcher.userPrompt");
+    let location = definition(SOURCE, None, agent_use).unwrap();
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 8,
+            character: 6
+        }
+    );
+
+    let struct_use = offset_of(SOURCE, "output: Summary") + 10;
+    let location = definition(SOURCE, None, struct_use).unwrap();
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 0,
+            character: 7
+        }
+    );
+}
+
+#[test]
+fn test_definition_of_locals() {
+    let use_site = offset_of(SOURCE, "return answer") + 8;
+    let location = definition(SOURCE, None, use_site).unwrap();
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 16,
+            character: 8
+        }
+    );
+
+    let param_use = offset_of(SOURCE, "userPrompt(topic)") + 12;
+    let location = definition(SOURCE, None, param_use).unwrap();
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 15,
+            character: 12
+        }
+    );
+}
+
+#[test]
+fn test_definition_through_imports() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("helpers.gnt"),
+        "// helpers\nfn greet(name: string) -> string {\n    return \"Hi {name}\"\n}\n",
+    )
+    .unwrap();
+    let main = dir.path().join("main.gnt");
+    let source =
+        "import { greet } from \"./helpers.gnt\"\n\nfn main() {\n    println(greet(\"x\"))\n}\n";
+
+    let call = offset_of(source, "greet(\"x\")");
+    let location = definition(source, Some(&main), call).unwrap();
+    assert!(location.file.unwrap().ends_with("helpers.gnt"));
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 1,
+            character: 3
+        }
+    );
+
+    let path = offset_of(source, "./helpers") + 2;
+    let location = definition(source, Some(&main), path).unwrap();
+    assert!(location.file.unwrap().ends_with("helpers.gnt"));
+    assert_eq!(
+        location.range.start,
+        Position {
+            line: 0,
+            character: 0
+        }
+    );
+
+    let text = hover(source, Some(&main), call).unwrap();
+    assert!(text.contains("fn greet(name: string) -> string"));
+}
+
+#[test]
+fn test_hover_tool_signature() {
+    let text = hover(SOURCE, None, offset_of(SOURCE, "[search]") + 1).unwrap();
+    assert_eq!(text, "```gent\ntool search(query: string) -> string\n```");
+}
+
+#[test]
+fn test_hover_agent_fields() {
+    let text = hover(SOURCE, None, offset_of(SOURCE, "Researcher.userPrompt") + 3).unwrap();
+    assert!(text.contains("agent Researcher {"));
+    assert!(text.contains("    model: \"gpt-4o\"\n"));
+    assert!(text.contains("    tools: [search]\n"));
+    assert!(text.contains("    output: Summary\n"));
+}
+
+#[test]
+fn test_hover_std_function_signature() {
+    let source = "import { pow } from \"std:math\"\nlet x = pow(2, 3)\n";
+    let text = hover(source, None, offset_of(source, "pow(2") + 1).unwrap();
+    assert!(text.starts_with("```gent\npow(base: number, exponent: number) -> number\n```"));
+}
+
+#[test]
+fn test_hover_nothing_on_punctuation() {
+    assert_eq!(hover(SOURCE, None, offset_of(SOURCE, "{\n    title")), None);
+}
+
+#[test]
+fn test_completion_of_agent_fields() {
+    let source = "agent A {\n    model: \"gpt-4o\"\n    pr\n}\n";
+    let items = completions(source, offset_of(source, "pr\n") + 2);
+    assert!(items.iter().all(|i| i.kind == completion_kind::PROPERTY));
+    let prompt = items.iter().find(|i| i.label == "prompt").unwrap();
+    assert_eq!(prompt.insert_text.as_deref(), Some("prompt: "));
+}
+
+#[test]
+fn test_completion_of_methods() {
+    let source = "fn f(xs: array) {\n    let s = \"a\".\n}\n";
+    let items = completions(source, offset_of(source, ".\n") + 1);
+    let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+    assert!(labels.contains(&"toUpperCase"));
+    assert!(!labels.contains(&"push"));
+
+    let source = "fn f(xs: array) {\n    xs.ma\n}\n";
+    let items = completions(source, offset_of(source, "ma\n") + 2);
+    let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+    assert!(labels.contains(&"map"));
+    assert!(labels.contains(&"split"));
+    assert_eq!(labels.iter().filter(|l| **l == "length").count(), 1);
+}
+
+#[test]
+fn test_completion_of_agent_methods() {
+    let items = completions(SOURCE, offset_of(SOURCE, "userPrompt(topic)"));
+    let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+    assert_eq!(labels, vec!["userPrompt", "systemPrompt", "run", "conversation"]);
+}
+
+#[test]
+fn test_completion_of_names_in_scope() {
+    let offset = offset_of(SOURCE, "return answer") + 7;
+    let items = completions(SOURCE, offset);
+    let find = |label: &str| items.iter().find(|i| i.label == label);
+    assert_eq!(find("Researcher").unwrap().kind, completion_kind::CLASS);
+    assert_eq!(find("search").unwrap().kind, completion_kind::FUNCTION);
+    assert_eq!(find("answer").unwrap().kind, completion_kind::VARIABLE);
+    assert_eq!(find("topic").unwrap().kind, completion_kind::VARIABLE);
+    assert_eq!(find("return").unwrap().kind, completion_kind::KEYWORD);
+    assert!(find("println").is_some());
+    // Top-level keywords are not offered inside a function body
+    assert!(find("agent").is_none());
+}
+
+#[test]
+fn test_uri_conversion() {
+    assert_eq!(
+        uri_to_path("file:///tmp/my%20project/main.gnt").unwrap(),
+        std::path::PathBuf::from("/tmp/my project/main.gnt")
+    );
+    assert_eq!(uri_to_path("untitled:Untitled-1"), None);
+    assert_eq!(
+        path_to_uri(std::path::Path::new("/no/such dir/a.gnt")),
+        "file:///no/such%20dir/a.gnt"
+    );
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+#[test]
+fn test_server_publishes_diagnostics() {
+    let mut server = Server::new();
+    let uri = "file:///tmp/test.gnt";
+
+    let replies = server.handle(&notification(
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": uri, "languageId": "gent", "version": 1, "text": "agnet A {}\n" } }),
+    ));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["method"], "textDocument/publishDiagnostics");
+    assert_eq!(replies[0]["params"]["uri"], uri);
+    assert_eq!(
+        replies[0]["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let replies = server.handle(&notification(
+        "textDocument/didChange",
+        json!({ "textDocument": { "uri": uri, "version": 2 }, "contentChanges": [{ "text": "agent A {}\n" }] }),
+    ));
+    assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+}
+
+#[test]
+fn test_server_requests() {
+    let mut server = Server::new();
+    let uri = "file:///tmp/test.gnt";
+    server.handle(&notification(
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": uri, "languageId": "gent", "version": 1, "text": SOURCE } }),
+    ));
+
+    let document = json!({ "uri": uri });
+    let replies = server.handle(&request(
+        1,
+        "textDocument/definition",
+        json!({ "textDocument": document, "position": { "line": 10, "character": 13 } }),
+    ));
+    assert_eq!(replies[0]["id"], 1);
+    assert_eq!(replies[0]["result"]["uri"], uri);
+    assert_eq!(
+        replies[0]["result"]["range"]["start"],
+        json!({ "line": 4, "character": 5 })
+    );
+
+    let replies = server.handle(&request(
+        2,
+        "textDocument/hover",
+        json!({ "textDocument": document, "position": { "line": 10, "character": 13 } }),
+    ));
+    assert_eq!(replies[0]["result"]["contents"]["kind"], "markdown");
+
+    let replies = server.handle(&request(
+        3,
+        "textDocument/documentSymbol",
+        json!({ "textDocument": document }),
+    ));
+    let symbols = replies[0]["result"].as_array().unwrap();
+    assert_eq!(symbols.len(), 4);
+    assert!(symbols[0].get("selectionRange").is_some());
+
+    let replies = server.handle(&request(4, "workspace/unknown", json!({})));
+    assert_eq!(replies[0]["error"]["code"], -32601);
+}
+
+#[test]
+fn test_run_over_framed_stream() {
+    let mut input = Vec::new();
+    for message in [
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        request(2, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ] {
+        write_message(&mut input, &message).unwrap();
+    }
+
+    let mut output = Vec::new();
+    let clean = run(&mut Cursor::new(input), &mut output).unwrap();
+    assert!(clean);
+
+    let mut reader = Cursor::new(output);
+    let init = read_message(&mut reader).unwrap().unwrap();
+    assert_eq!(init["result"]["capabilities"]["hoverProvider"], true);
+    assert_eq!(
+        init["result"]["capabilities"]["completionProvider"]["triggerCharacters"],
+        json!(["."])
+    );
+    let shutdown = read_message(&mut reader).unwrap().unwrap();
+    assert_eq!(shutdown["id"], 2);
+    assert_eq!(shutdown["result"], Value::Null);
+    assert!(read_message(&mut reader).unwrap().is_none());
+}
+
+#[test]
+fn test_malformed_message_gets_a_parse_error() {
+    let mut input = Vec::new();
+    write_message(&mut input, &request(1, "initialize", json!({ "capabilities": {} }))).unwrap();
+    let body = "{\"jsonrpc\": \"2.0\", \"id\": 2,";
+    input.extend(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes());
+    write_message(&mut input, &request(3, "shutdown", Value::Null)).unwrap();
+    write_message(&mut input, &notification("exit", Value::Null)).unwrap();
+
+    let mut output = Vec::new();
+    assert!(run(&mut Cursor::new(input), &mut output).unwrap());
+
+    let mut reader = Cursor::new(output);
+    assert_eq!(read_message(&mut reader).unwrap().unwrap()["id"], 1);
+    let error = read_message(&mut reader).unwrap().unwrap();
+    assert_eq!(error["id"], Value::Null);
+    assert_eq!(error["error"]["code"], -32700);
+    // The server kept serving after the bad message
+    assert_eq!(read_message(&mut reader).unwrap().unwrap()["id"], 3);
+}
+
+#[test]
+fn test_misspelled_agent_field_is_a_warning() {
+    let found = diagnostics("agent A { modle: \"gpt-4o\" }\n");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].severity, 2);
+    assert_eq!(found[0].range.start.character, 10);
+    assert!(found[0].message.contains("did you mean `model`?"));
+}
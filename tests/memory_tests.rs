@@ -0,0 +1,159 @@
+//! Tests for persistent agent memory (`memory:` agent field)
+
+use gent::config::Config;
+use gent::interpreter::{AgentValue, Session};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::rag::embeddings::MockEmbeddings;
+use gent::runtime::{Conversation, MemoryStore, ProviderFactory, ToolCall, ToolRegistry};
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+fn open(dir: &Path) -> MemoryStore {
+    MemoryStore::open(dir, "Helper", Arc::new(MockEmbeddings::new())).unwrap()
+}
+
+fn helper_decl(dir: &Path) -> String {
+    format!(
+        "agent Helper {{ systemPrompt: \"You help.\" model: \"gpt-4o-mini\" maxSteps: 1 memory: \"{}\" }}",
+        dir.display().to_string().replace('\\', "/")
+    )
+}
+
+#[tokio::test]
+async fn test_facts_persist_between_opens() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = open(dir.path());
+    store.remember("user_name", "Ada").await.unwrap();
+    store.remember("color", "blue").await.unwrap();
+    store.remember("color", "green").await.unwrap();
+
+    let store = open(dir.path());
+    assert_eq!(store.get("user_name"), Some("Ada"));
+    assert_eq!(store.get("color"), Some("green"));
+    assert_eq!(store.facts().len(), 2);
+    assert!(dir.path().join("Helper").join("facts.json").exists());
+}
+
+#[tokio::test]
+async fn test_memory_is_scoped_by_agent() {
+    let dir = tempfile::tempdir().unwrap();
+    open(dir.path()).remember("color", "blue").await.unwrap();
+
+    let other = MemoryStore::open(dir.path(), "Other", Arc::new(MockEmbeddings::new())).unwrap();
+    assert!(other.facts().is_empty());
+}
+
+#[tokio::test]
+async fn test_forget_and_clear() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = open(dir.path());
+    store.remember("a", "1").await.unwrap();
+    store.remember("b", "2").await.unwrap();
+
+    assert!(store.forget("a").await.unwrap());
+    assert!(!store.forget("a").await.unwrap());
+    assert_eq!(open(dir.path()).get("a"), None);
+
+    store.clear().await.unwrap();
+    assert!(open(dir.path()).facts().is_empty());
+}
+
+#[tokio::test]
+async fn test_recall_by_key_and_by_similarity() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = open(dir.path());
+    store.remember("user_name", "Ada").await.unwrap();
+    store.remember("color", "blue").await.unwrap();
+
+    let found = store.recall("color", 1).await.unwrap();
+    assert_eq!(found, vec![("color".to_string(), "blue".to_string())]);
+
+    // Mock embeddings are identical for identical text
+    let found = store.recall("user_name: Ada", 1).await.unwrap();
+    assert_eq!(found, vec![("user_name".to_string(), "Ada".to_string())]);
+
+    assert!(open(tempfile::tempdir().unwrap().path())
+        .recall("anything", 3)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_embeddings_from_another_model_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    open(dir.path()).remember("color", "blue").await.unwrap();
+    assert!(dir.path().join("Helper").join("embeddings.json").exists());
+
+    let mut other =
+        MemoryStore::open(dir.path(), "Helper", Arc::new(MockEmbeddings::with_dimensions(8))).unwrap();
+    let err = other.remember("size", "large").await.unwrap_err();
+    assert!(err.contains("384 dimensions"), "{}", err);
+    assert!(other.recall("what color", 3).await.is_err());
+    assert_eq!(other.get("size"), None);
+
+    // Clearing the memory lets the new model start over
+    other.clear().await.unwrap();
+    other.remember("size", "large").await.unwrap();
+    assert_eq!(other.recall("size", 1).await.unwrap().len(), 1);
+}
+
+#[test]
+fn test_memory_needs_an_embedding_provider() {
+    let factory = ProviderFactory::new(Config::default());
+    assert_eq!(factory.embeddings().unwrap_err().kind(), "MissingApiKey");
+    assert!(ProviderFactory::mock().embeddings().is_ok());
+}
+
+#[tokio::test]
+async fn test_remembered_facts_are_added_to_the_prompt() {
+    let dir = tempfile::tempdir().unwrap();
+    open(dir.path()).remember("user_name", "Ada").await.unwrap();
+
+    let factory = ProviderFactory::mock_with_response("Hi Ada.");
+    let agent = AgentValue::new("Helper", "You help.").with_memory(dir.path());
+    let mut chat = Conversation::new(agent);
+    chat.send("user_name", &factory, &ToolRegistry::new(), &NullLogger)
+        .await
+        .unwrap();
+
+    let system = &chat.messages()[0].content;
+    assert!(system.contains("Things you remember"), "{}", system);
+    assert!(system.contains("user_name: Ada"), "{}", system);
+}
+
+#[tokio::test]
+async fn test_agent_uses_remember_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let factory = ProviderFactory::mock_with_tool_calls(vec![ToolCall {
+        id: "1".to_string(),
+        name: "remember".to_string(),
+        arguments: json!({"key": "user_name", "value": "Ada"}),
+    }]);
+    let mut session = Session::new(factory, Arc::new(NullLogger));
+    eval_input(&mut session, &helper_decl(dir.path()))
+        .await
+        .unwrap();
+
+    // The mock keeps calling tools, so the run stops at maxSteps
+    let errors = eval_input(&mut session, "Helper.userPrompt(\"I'm Ada\").run()")
+        .await
+        .unwrap_err();
+    assert_eq!(errors[0].kind(), "MaxStepsExceeded");
+
+    assert_eq!(open(dir.path()).get("user_name"), Some("Ada"));
+}
+
+#[tokio::test]
+async fn test_memory_field_type() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let errors = eval_input(
+        &mut session,
+        "agent Helper { model: \"gpt-4o-mini\" memory: 3 }",
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(errors[0].kind(), "TypeError");
+}
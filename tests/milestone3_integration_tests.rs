@@ -1,7 +1,6 @@
 //! Milestone 3 Integration Tests
 //! End-to-end tests for user-defined tools feature
 
-use gent::config::Config;
 use gent::interpreter::evaluate_with_output;
 use gent::logging::NullLogger;
 use gent::parser::parse;
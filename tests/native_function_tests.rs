@@ -0,0 +1,240 @@
+//! Tests for native functions registered by embedding programs
+
+use gent::errors::GentError;
+use gent::interpreter::native::{
+    native_async_fn, native_fn, FromValue, IntoValue, NativeArgs, NativeError, Signature,
+};
+use gent::interpreter::{evaluate, Session, Value};
+use gent::logging::NullLogger;
+use gent::parser::ast::TypeName;
+use gent::parser::parse;
+use gent::repl::eval_input;
+use gent::runtime::{ProviderFactory, ToolRegistry};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn session() -> Session {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    session.register_function(native_fn(
+        "double",
+        Signature::new()
+            .param("n", TypeName::Number)
+            .returns(TypeName::Number),
+        |args: NativeArgs| Ok(args.get::<f64>(0)? * 2.0),
+    ));
+    session.register_function(native_fn(
+        "greet",
+        Signature::new()
+            .param("name", TypeName::String)
+            .optional("greeting", TypeName::String)
+            .returns(TypeName::String),
+        |args: NativeArgs| {
+            let greeting = args.get::<Option<String>>(1)?;
+            Ok(format!(
+                "{}, {}!",
+                greeting.as_deref().unwrap_or("Hello"),
+                args.get::<String>(0)?
+            ))
+        },
+    ));
+    session.register_function(native_fn(
+        "checkout",
+        Signature::new().param("sku", TypeName::String),
+        |args: NativeArgs| -> Result<(), NativeError> {
+            Err(format!("{} is out of stock", args.get::<String>(0)?).into())
+        },
+    ));
+    session
+}
+
+async fn eval(session: &mut Session, input: &str) -> Value {
+    eval_input(session, input)
+        .await
+        .unwrap()
+        .unwrap_or(Value::Null)
+}
+
+async fn eval_err(session: &mut Session, input: &str) -> GentError {
+    eval_input(session, input).await.unwrap_err().remove(0)
+}
+
+#[tokio::test]
+async fn test_sync_native_function() {
+    let mut session = session();
+    assert_eq!(eval(&mut session, "double(21)").await, Value::Number(42.0));
+    assert_eq!(
+        eval(&mut session, "[1, 2].map((n) => double(n))").await,
+        Value::Array(vec![Value::Number(2.0), Value::Number(4.0)])
+    );
+    assert_eq!(
+        eval(&mut session, "greet(\"Ann\")").await,
+        string("Hello, Ann!")
+    );
+    assert_eq!(
+        eval(&mut session, "greet(\"Ann\", \"Hi\")").await,
+        string("Hi, Ann!")
+    );
+}
+
+#[tokio::test]
+async fn test_async_native_function() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    session.register_function(native_async_fn(
+        "lookup",
+        Signature::new().param("key", TypeName::String),
+        move |args: NativeArgs| {
+            let counter = counter.clone();
+            async move {
+                tokio::task::yield_now().await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut row = BTreeMap::new();
+                row.insert("key".to_string(), args.get::<String>(0)?);
+                Ok(row)
+            }
+        },
+    ));
+
+    assert_eq!(eval(&mut session, "lookup(\"a\").key").await, string("a"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_native_function_called_from_gent_function() {
+    let mut session = session();
+    eval(
+        &mut session,
+        "fn quadruple(n: number) -> number {\n    return double(double(n))\n}",
+    )
+    .await;
+    assert_eq!(
+        eval(&mut session, "quadruple(3)").await,
+        Value::Number(12.0)
+    );
+}
+
+#[tokio::test]
+async fn test_signature_is_checked_at_call_site() {
+    let mut session = session();
+    let err = eval_err(&mut session, "double(\"x\")").await;
+    assert_eq!(err.kind(), "ArgumentTypeMismatch");
+    assert_eq!(
+        err.to_string(),
+        "Type mismatch for parameter 'n': expected number, got String"
+    );
+    assert_eq!(err.span().map(|s| s.start), Some(0));
+
+    let err = eval_err(&mut session, "greet()").await;
+    assert_eq!(err.kind(), "WrongArgumentCount");
+    let err = eval_err(&mut session, "greet(\"a\", \"b\", \"c\")").await;
+    assert_eq!(err.to_string(), "Expected 2 arguments, got 3");
+}
+
+#[tokio::test]
+async fn test_return_value_is_checked_against_signature() {
+    let mut session = session();
+    session.register_function(native_fn(
+        "label",
+        Signature::new()
+            .param("n", TypeName::Number)
+            .returns(TypeName::Nullable(Box::new(TypeName::String))),
+        |args: NativeArgs| match args.get::<f64>(0)? {
+            n if n < 0.0 => Ok(Value::Null),
+            n if n < 10.0 => Ok(string("small")),
+            n => Ok(Value::Number(n)),
+        },
+    ));
+    assert_eq!(eval(&mut session, "label(1)").await, string("small"));
+    assert_eq!(eval(&mut session, "label(-1)").await, Value::Null);
+
+    let err = eval_err(&mut session, "label(42)").await;
+    assert_eq!(err.kind(), "NativeFunctionError");
+    assert_eq!(
+        err.to_string(),
+        "Native function 'label' failed: returned Number, but its signature returns string?"
+    );
+    assert_eq!(err.span().map(|s| s.start), Some(0));
+}
+
+#[tokio::test]
+async fn test_native_error_is_catchable() {
+    let mut session = session();
+    let err = eval_err(&mut session, "let x = 1\ncheckout(\"mug\")").await;
+    assert_eq!(err.kind(), "NativeFunctionError");
+    assert_eq!(
+        err.to_string(),
+        "Native function 'checkout' failed: mug is out of stock"
+    );
+    assert_eq!(err.span().map(|s| s.start), Some(10));
+
+    assert_eq!(
+        eval(
+            &mut session,
+            "let kind = \"\"\ntry {\n    checkout(\"mug\")\n} catch (e: NativeFunctionError) {\n    kind = e.kind\n}\nkind",
+        )
+        .await,
+        string("NativeFunctionError")
+    );
+}
+
+#[tokio::test]
+async fn test_native_function_in_top_level_program() {
+    let seen = Arc::new(AtomicUsize::new(0));
+    let counter = seen.clone();
+    let mut tools = ToolRegistry::with_builtins();
+    tools.register_function(native_fn(
+        "record",
+        Signature::new().param("n", TypeName::Number),
+        move |args: NativeArgs| {
+            counter.fetch_add(args.get::<i64>(0)? as usize, Ordering::SeqCst);
+            Ok(())
+        },
+    ));
+
+    let program = parse("record(2)\nlet x = record(3)\n").unwrap();
+    evaluate(&program, &ProviderFactory::mock(), &mut tools, &NullLogger)
+        .await
+        .unwrap();
+    assert_eq!(seen.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn test_unknown_function_suggests_native_functions() {
+    let mut session = session();
+    let err = eval_err(&mut session, "dubble(1)").await;
+    assert_eq!(err.help(), Some("did you mean `double`?"));
+}
+
+#[test]
+fn test_value_conversions() {
+    let nested = Value::Array(vec![
+        Value::Array(vec![Value::Number(1.0)]),
+        Value::Array(vec![]),
+    ]);
+    assert_eq!(
+        Vec::<Vec<i64>>::from_value(nested.clone()).unwrap(),
+        vec![vec![1], vec![]]
+    );
+    assert_eq!(vec![vec![1i64], vec![]].into_value(), nested);
+
+    let json = serde_json::json!({ "a": [true, null], "b": 1.5 });
+    let value = json.clone().into_value();
+    assert_eq!(value.to_string(), "{a: [true, null], b: 1.5}");
+    assert_eq!(serde_json::Value::from_value(value).unwrap(), json);
+
+    assert_eq!(Option::<String>::from_value(Value::Null).unwrap(), None);
+    assert!(i64::from_value(Value::Number(1.5)).is_err());
+    assert_eq!(
+        NativeArgs::new(vec![string("x")])
+            .get::<f64>(0)
+            .unwrap_err()
+            .message,
+        "argument 1: expected Number, got String"
+    );
+}
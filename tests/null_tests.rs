@@ -0,0 +1,195 @@
+//! Tests for `null` values and nullable types (`string?`)
+
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::lsp::analysis::diagnostics;
+use gent::parser::{check_nullable_access, parse, FieldType, Statement, TypeName};
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use std::sync::Arc;
+
+/// Evaluate `inputs` in one session and return the value of the last one
+async fn eval(inputs: &[&str]) -> Result<Value, String> {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let mut last = None;
+    for input in inputs {
+        last = eval_input(&mut session, input)
+            .await
+            .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))?;
+    }
+    Ok(last.unwrap_or(Value::Null))
+}
+
+/// Names of the parameters flagged by the nullable access check
+fn flagged(source: &str) -> Vec<String> {
+    check_nullable_access(&parse(source).unwrap())
+        .into_iter()
+        .map(|access| access.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_null_is_the_same_value_everywhere() {
+    // Top-level evaluation
+    assert_eq!(eval(&["let x = null", "x"]).await.unwrap(), Value::Null);
+    assert_eq!(
+        eval(&["let x = null", "x == null"]).await.unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        eval(&["let x = null", "x == \"null\""]).await.unwrap(),
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        eval(&["let o = { a: null }", "o.toJson()"]).await.unwrap(),
+        Value::String(r#"{"a":null}"#.to_string())
+    );
+
+    // Function bodies
+    assert_eq!(
+        eval(&[
+            "fn check() -> boolean {\n    let x = null\n    if x {\n        return false\n    }\n    return x == null\n}",
+            "check()",
+        ])
+        .await
+        .unwrap(),
+        Value::Boolean(true)
+    );
+}
+
+#[test]
+fn test_parse_nullable_types() {
+    let program = parse(
+        "struct Person { name: string, nick: string?, tags: string[]? }\nfn greet(name: string?) -> string? {\n    return name\n}",
+    )
+    .unwrap();
+    let Statement::StructDecl(person) = &program.statements[0] else {
+        panic!("expected struct");
+    };
+    assert_eq!(
+        person.fields[1].field_type,
+        FieldType::Nullable(Box::new(FieldType::String))
+    );
+    assert_eq!(
+        person.fields[2].field_type,
+        FieldType::Nullable(Box::new(FieldType::Array(Box::new(FieldType::String))))
+    );
+    let Statement::FnDecl(greet) = &program.statements[1] else {
+        panic!("expected fn");
+    };
+    assert_eq!(
+        greet.params[0].type_name,
+        TypeName::Nullable(Box::new(TypeName::String))
+    );
+    assert_eq!(
+        greet.return_type,
+        Some(TypeName::Nullable(Box::new(TypeName::String)))
+    );
+}
+
+#[tokio::test]
+async fn test_nullable_struct_fields_accept_null() {
+    let types = "struct Person { name: string, nick: string? }";
+    assert_eq!(
+        eval(&[
+            types,
+            r#"let p = fromJson("\{\"name\": \"Ann\", \"nick\": null}", Person)"#,
+            "p.nick == null",
+        ])
+        .await
+        .unwrap(),
+        Value::Boolean(true)
+    );
+    let err = eval(&[
+        types,
+        r#"fromJson("\{\"name\": \"Ann\", \"nick\": 3}", Person)"#,
+    ])
+    .await
+    .unwrap_err();
+    assert!(
+        err.contains("'nick': expected string, got number"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_unchecked_nullable_access_is_flagged() {
+    assert_eq!(
+        flagged("fn f(name: string?) -> number {\n    return name.length()\n}"),
+        vec!["name"]
+    );
+    assert_eq!(
+        flagged("fn f(items: array?) -> number {\n    return items[0]\n}"),
+        vec!["items"]
+    );
+    // Non-nullable parameters and plain uses are fine
+    assert!(flagged(
+        "fn f(name: string, other: string?) -> string {\n    return name.trim() + other\n}"
+    )
+    .is_empty());
+    // Null again after assignment
+    assert_eq!(
+        flagged("fn f(name: string?) {\n    name = \"x\"\n    name.trim()\n    name = null\n    name.trim()\n}"),
+        vec!["name"]
+    );
+}
+
+#[test]
+fn test_checked_nullable_access_is_not_flagged() {
+    let guarded = [
+        "fn f(name: string?) -> number {\n    if name != null {\n        return name.length()\n    }\n    return 0\n}",
+        "fn f(name: string?) -> number {\n    if name {\n        return name.length()\n    }\n    return 0\n}",
+        "fn f(name: string?) -> number {\n    if name == null {\n        return 0\n    }\n    return name.length()\n}",
+        "fn f(name: string?) -> number {\n    if name == null {\n        return 0\n    } else {\n        return name.length()\n    }\n}",
+        "fn f(name: string?) -> boolean {\n    return name != null && name.length() > 2\n}",
+        "fn f(name: string?) -> boolean {\n    return name == null || name.length() > 2\n}",
+        "fn f(name: string?) -> string {\n    if name == null {\n        name = \"anonymous\"\n    }\n    return name.toUpperCase()\n}",
+    ];
+    for source in guarded {
+        assert!(flagged(source).is_empty(), "{}", source);
+    }
+}
+
+#[test]
+fn test_nullable_access_warnings_in_diagnostics() {
+    let found =
+        diagnostics("tool shout(text: string?) -> string {\n    return text.toUpperCase()\n}\n");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].severity, 2);
+    assert_eq!(found[0].range.start.line, 1);
+    assert!(found[0].message.contains("'text' may be null"));
+}
+
+#[test]
+fn test_cli_prints_nullable_access_warnings() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("main.gnt");
+    std::fs::write(
+        &file,
+        "fn shout(text: string?) -> string {\n    return text.toUpperCase()\n}\nprintln(shout(\"hi\"))\n",
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gent"))
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "HI\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: 'text' may be null; check `text != null` before using it"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("main.gnt:2:12"), "{}", stderr);
+
+    let quiet = std::process::Command::new(env!("CARGO_BIN_EXE_gent"))
+        .arg("--quiet")
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(quiet.status.success());
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("warning"));
+}
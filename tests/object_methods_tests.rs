@@ -0,0 +1,217 @@
+//! Tests for object methods and `for (k, v)` iteration in GENT
+
+use gent::interpreter::object_methods::call_object_method;
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::parser::{parse, BlockStmt, Statement};
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+fn object(fields: &[(&str, Value)]) -> BTreeMap<String, Value> {
+    fields
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect()
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+/// Evaluate `inputs` in one session and return the value of the last one
+async fn eval(inputs: &[&str]) -> Value {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let mut last = None;
+    for input in inputs {
+        last = eval_input(&mut session, input).await.unwrap();
+    }
+    last.unwrap_or(Value::Null)
+}
+
+// ============================================
+// Methods
+// ============================================
+
+#[test]
+fn test_object_keys_values_entries_are_ordered() {
+    let map = object(&[("b", Value::Number(2.0)), ("a", Value::Number(1.0))]);
+    assert_eq!(
+        call_object_method(&map, "keys", &[]).unwrap(),
+        Value::Array(vec![string("a"), string("b")])
+    );
+    assert_eq!(
+        call_object_method(&map, "values", &[]).unwrap(),
+        Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])
+    );
+    assert_eq!(
+        call_object_method(&map, "entries", &[]).unwrap(),
+        Value::Array(vec![
+            Value::Array(vec![string("a"), Value::Number(1.0)]),
+            Value::Array(vec![string("b"), Value::Number(2.0)]),
+        ])
+    );
+}
+
+#[test]
+fn test_object_has_get_length() {
+    let map = object(&[("name", string("ann"))]);
+    assert_eq!(
+        call_object_method(&map, "has", &[string("name")]).unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        call_object_method(&map, "has", &[string("age")]).unwrap(),
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        call_object_method(&map, "get", &[string("name")]).unwrap(),
+        string("ann")
+    );
+    assert_eq!(
+        call_object_method(&map, "get", &[string("age")]).unwrap(),
+        Value::Null
+    );
+    assert_eq!(
+        call_object_method(&map, "get", &[string("age"), Value::Number(30.0)]).unwrap(),
+        Value::Number(30.0)
+    );
+    assert_eq!(
+        call_object_method(&map, "length", &[]).unwrap(),
+        Value::Number(1.0)
+    );
+}
+
+#[test]
+fn test_object_merge_and_remove_return_new_objects() {
+    let map = object(&[("a", Value::Number(1.0)), ("b", Value::Number(2.0))]);
+    let other = object(&[("b", Value::Number(3.0)), ("c", Value::Number(4.0))]);
+    assert_eq!(
+        call_object_method(&map, "merge", &[Value::Object(other)]).unwrap(),
+        Value::Object(object(&[
+            ("a", Value::Number(1.0)),
+            ("b", Value::Number(3.0)),
+            ("c", Value::Number(4.0)),
+        ]))
+    );
+    assert_eq!(
+        call_object_method(&map, "remove", &[string("a")]).unwrap(),
+        Value::Object(object(&[("b", Value::Number(2.0))]))
+    );
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_object_method_errors() {
+    let map = object(&[]);
+    assert!(call_object_method(&map, "has", &[]).is_err());
+    assert!(call_object_method(&map, "merge", &[Value::Number(1.0)]).is_err());
+    assert!(call_object_method(&map, "nope", &[]).is_err());
+}
+
+#[test]
+fn test_object_display_is_ordered() {
+    let map = object(&[
+        ("zeta", Value::Number(1.0)),
+        ("alpha", Value::Number(2.0)),
+        ("mid", Value::Number(3.0)),
+    ]);
+    assert_eq!(
+        Value::Object(map).to_string(),
+        "{alpha: 2, mid: 3, zeta: 1}"
+    );
+}
+
+// ============================================
+// Interpreter
+// ============================================
+
+#[tokio::test]
+async fn test_object_methods_in_programs() {
+    assert_eq!(
+        eval(&["let o = { b: 1, a: 2 }", "o.keys().join(\",\")"]).await,
+        string("a,b")
+    );
+    assert_eq!(
+        eval(&["let o = { a: 1 }", "o.merge({ b: 2 }).length()"]).await,
+        Value::Number(2.0)
+    );
+    assert_eq!(
+        eval(&["let o = { a: 1 }", "o.get(\"x\", \"none\")"]).await,
+        string("none")
+    );
+}
+
+#[tokio::test]
+async fn test_object_methods_at_top_level() {
+    // Top-level `let` goes through the program evaluator, not block evaluation
+    let source = "let o = { a: 1 }\nlet k = o.keys()\n";
+    let program = parse(source).unwrap();
+    let mut tools = gent::runtime::ToolRegistry::new();
+    gent::interpreter::evaluate(&program, &ProviderFactory::mock(), &mut tools, &NullLogger)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_parse_for_key_value() {
+    let program =
+        parse("fn f(o: object) {\n    for (k, v) in o {\n        println(k)\n    }\n}").unwrap();
+    let Statement::FnDecl(decl) = &program.statements[0] else {
+        panic!("expected a function");
+    };
+    let BlockStmt::For(stmt) = &decl.body.statements[0] else {
+        panic!("expected a for loop");
+    };
+    assert_eq!(stmt.variable, "k");
+    assert_eq!(stmt.value_variable.as_deref(), Some("v"));
+}
+
+#[tokio::test]
+async fn test_for_key_value_over_object() {
+    assert_eq!(
+        eval(&[
+            "let o = { b: 2, a: 1, c: 3 }",
+            "let out = []",
+            "for (k, v) in o {\n    out.push(\"{k}={v}\")\n}",
+            "out.join(\" \")",
+        ])
+        .await,
+        string("a=1 b=2 c=3")
+    );
+}
+
+#[tokio::test]
+async fn test_for_single_variable_over_object_yields_keys() {
+    assert_eq!(
+        eval(&[
+            "let out = []",
+            "for k in { y: 1, x: 2 } {\n    out.push(k)\n}",
+            "out",
+        ])
+        .await,
+        Value::Array(vec![string("x"), string("y")])
+    );
+}
+
+#[tokio::test]
+async fn test_for_index_value_over_array() {
+    assert_eq!(
+        eval(&[
+            "let total = 0",
+            "for (i, x) in [10, 20] {\n    total = total + i * x\n}",
+            "total",
+        ])
+        .await,
+        Value::Number(20.0)
+    );
+}
+
+#[tokio::test]
+async fn test_for_key_value_over_string_is_an_error() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    assert!(eval_input(&mut session, "for (i, c) in \"ab\" {\n}")
+        .await
+        .is_err());
+}
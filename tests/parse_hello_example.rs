@@ -16,5 +16,5 @@ fn test_parse_hello_gnt_example() {
 
     // Verify we got a valid parse tree
     let pairs = parse_result.unwrap();
-    assert!(pairs.len() > 0, "Parse tree should not be empty");
+    assert!(pairs.peek().is_some(), "Parse tree should not be empty");
 }
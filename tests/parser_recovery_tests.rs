@@ -0,0 +1,255 @@
+//! Tests for parser error recovery, friendly messages and suggestions
+
+use gent::errors::{did_you_mean, ErrorReporter, GentError};
+use gent::interpreter::evaluate;
+use gent::logging::NullLogger;
+use gent::parser::{check_agent_fields, parse, parse_with_recovery, Statement};
+use gent::runtime::{ProviderFactory, ToolRegistry};
+
+fn messages(errors: &[GentError]) -> Vec<String> {
+    errors.iter().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn test_valid_program_has_no_errors() {
+    let source = r#"
+agent Greeter { model: "gpt-4o-mini" prompt: "Hi" }
+fn main() { println("ok") }
+main()
+"#;
+    let (program, errors) = parse_with_recovery(source);
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    assert_eq!(program.statements.len(), 3);
+}
+
+#[test]
+fn test_collects_multiple_errors() {
+    let source = r#"let a = [1, 2
+let b = 2
+fn broken( { }
+let c = 3
+"#;
+    let (program, errors) = parse_with_recovery(source);
+    assert_eq!(errors.len(), 2, "{:?}", messages(&errors));
+    // Statements around the errors are still parsed
+    let names: Vec<&str> = program
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::LetStmt(l) => Some(l.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["b", "c"]);
+}
+
+#[test]
+fn test_parse_returns_first_error() {
+    let source = "let a = \nlet b = ";
+    let err = parse(source).unwrap_err();
+    let (_, errors) = parse_with_recovery(source);
+    assert_eq!(err.to_string(), errors[0].to_string());
+}
+
+#[test]
+fn test_unclosed_agent_body() {
+    let source = r#"agent A {
+    model: "gpt-4o-mini"
+
+agent B {
+    model: "gpt-4o"
+}
+"#;
+    let (program, errors) = parse_with_recovery(source);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: expected `}` to close agent body, found `agent`"
+    );
+    assert_eq!(errors[0].help(), Some("the agent body starts on line 1"));
+    // Span points at the next declaration, and that declaration is still parsed
+    let span = errors[0].span().unwrap();
+    assert_eq!(&source[span.start..span.end], "agent");
+    assert_eq!(program.statements.len(), 1);
+}
+
+#[test]
+fn test_unclosed_body_at_end_of_file() {
+    let (_, errors) = parse_with_recovery("struct Point {\n    x: number\n");
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: expected `}` to close struct body, found end of file"
+    );
+}
+
+#[test]
+fn test_unclosed_function_body() {
+    let source = "fn first() {\n    let x = 1\n\nfn second() {}\n";
+    let (_, errors) = parse_with_recovery(source);
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: expected `}` to close function body, found `fn`"
+    );
+}
+
+#[test]
+fn test_unclosed_array_literal() {
+    let source = "let xs = [1, 2\nlet y = 1\n";
+    let (program, errors) = parse_with_recovery(source);
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: expected `]` to close array literal, found `let`"
+    );
+    assert_eq!(program.statements.len(), 1);
+}
+
+#[test]
+fn test_missing_colon_after_agent_field() {
+    let source = "agent A {\n    model: \"gpt-4o\"\n    prompt \"Hi\"\n}\n";
+    let (_, errors) = parse_with_recovery(source);
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: expected `:` after `prompt`, found a string"
+    );
+}
+
+#[test]
+fn test_missing_expression_message() {
+    let (_, errors) = parse_with_recovery("let x = \n}\n");
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: expected an expression, found `}`"
+    );
+}
+
+#[test]
+fn test_misspelled_keyword_suggestion() {
+    let (_, errors) = parse_with_recovery("agnet Helper { model: \"gpt-4o\" }\n");
+    assert_eq!(
+        errors[0].to_string(),
+        "Syntax error: unknown statement `agnet`"
+    );
+    assert_eq!(errors[0].help(), Some("did you mean `agent`?"));
+}
+
+#[test]
+fn test_unknown_statement_without_suggestion() {
+    let (_, errors) = parse_with_recovery("}\nlet x = 1\n");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .to_string()
+        .starts_with("Syntax error: expected a top-level declaration"));
+    assert_eq!(errors[0].help(), None);
+}
+
+#[test]
+fn test_misspelled_agent_field_suggestion() {
+    let source = "agent Helper {\n    modle: \"gpt-4o\"\n    prompt: \"Hi\"\n}\n";
+    let (program, errors) = parse_with_recovery(source);
+    assert!(errors.is_empty(), "{:?}", messages(&errors));
+    let fields = check_agent_fields(&program);
+    assert_eq!(fields.len(), 1);
+    assert_eq!(
+        fields[0].message(),
+        "unknown agent field `modle`; did you mean `model`?"
+    );
+    assert_eq!(&source[fields[0].span.start..fields[0].span.end], "modle");
+}
+
+#[tokio::test]
+async fn test_misspelled_agent_field_does_not_stop_the_program() {
+    let source = "agent Helper { model: \"gpt-4o\" maxStep: 3 prompt: \"Hi\" }\nlet reply = Helper.userPrompt(\"Hello\").run()\n";
+    let program = parse(source).unwrap();
+    assert_eq!(check_agent_fields(&program)[0].suggestion, "maxSteps");
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    evaluate(&program, &factory, &mut tools, &NullLogger)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_unrelated_agent_fields_are_allowed() {
+    let (program, errors) = parse_with_recovery("agent Helper { customSetting: 1 }\n");
+    assert!(errors.is_empty());
+    assert!(check_agent_fields(&program).is_empty());
+}
+
+#[tokio::test]
+async fn test_undefined_variable_suggestion() {
+    let source = "fn main() {\n    let count = 1\n    return cout\n}\nmain()\n";
+    let program = parse(source).unwrap();
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    let err = evaluate(&program, &factory, &mut tools, &NullLogger)
+        .await
+        .unwrap_err();
+    assert_eq!(err.help(), Some("did you mean `count`?"));
+}
+
+#[tokio::test]
+async fn test_unknown_function_suggestion() {
+    let source = "fn main() {\n    prnt(\"hi\")\n}\nmain()\n";
+    let program = parse(source).unwrap();
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    let err = evaluate(&program, &factory, &mut tools, &NullLogger)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), "UnknownTool");
+    assert_eq!(err.help(), Some("did you mean `print`?"));
+}
+
+#[test]
+fn test_did_you_mean() {
+    let keywords = ["agent", "tool", "struct"];
+    assert_eq!(did_you_mean("agnet", keywords), Some("agent"));
+    assert_eq!(did_you_mean("Tool", keywords), Some("tool"));
+    assert_eq!(did_you_mean("agent", keywords), None);
+    assert_eq!(did_you_mean("banana", keywords), None);
+}
+
+#[test]
+fn test_reporter_formats_all_errors() {
+    let source = "agnet A {}\nlet x = \n";
+    let (_, errors) = parse_with_recovery(source);
+    let mut reporter = ErrorReporter::new(source, "test.gnt");
+    reporter.use_colors = false;
+
+    let output = reporter.format_all(&errors);
+    assert!(output.contains("unknown statement `agnet`"));
+    assert!(output.contains("= help: did you mean `agent`?"));
+    assert!(output.contains("test.gnt:1:1"));
+    assert!(output.contains("test.gnt:2:8"));
+    assert!(output.contains("error: aborting due to 2 errors"));
+}
+
+#[test]
+fn test_reporter_single_error_has_no_summary() {
+    let source = "let x = \n";
+    let (_, errors) = parse_with_recovery(source);
+    let mut reporter = ErrorReporter::new(source, "test.gnt");
+    reporter.use_colors = false;
+
+    let output = reporter.format_all(&errors);
+    assert!(!output.contains("aborting"));
+}
+
+#[test]
+fn test_recovery_with_non_ascii_multiline_string() {
+    let source = "agent A {\n    systemPrompt: \"\"\"héllo wörld\"\"\"\n    model: \"gpt-4o-mini\"\n\nlet greeting = \"grüß dich\"\nlet y = 2\n";
+    let (program, errors) = parse_with_recovery(source);
+    let messages = messages(&errors);
+    assert_eq!(errors.len(), 1, "{:?}", messages);
+    assert!(messages[0].contains("expected `}` to close agent body"), "{:?}", messages);
+
+    // Spans of later statements are still offsets into the original source
+    let last = program.statements.last().unwrap();
+    match last {
+        Statement::LetStmt(l) => {
+            assert_eq!(l.name, "y");
+            assert_eq!(source[l.span.start..l.span.end].trim_end(), "let y = 2");
+        }
+        other => panic!("Expected let, got {:?}", other),
+    }
+}
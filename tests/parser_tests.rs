@@ -1,4 +1,4 @@
-use gent::parser::{parse, Expression, Statement, StringPart, TypeName};
+use gent::parser::{parse, Expression, Statement, StringPart, TypeName, UnaryOp};
 
 /// Helper to extract string content from an Expression::String with a single Literal part
 fn get_string_content(expr: &Expression) -> Option<&str> {
@@ -203,11 +203,11 @@ fn test_parse_number_integer() {
 
 #[test]
 fn test_parse_number_float() {
-    let result = parse("agent A { x: 3.14 }");
+    let result = parse("agent A { x: 2.75 }");
     let program = result.unwrap();
     match &program.statements[0] {
         Statement::AgentDecl(agent) => match &agent.fields[0].value {
-            Expression::Number(n, _) => assert!((n - 3.14).abs() < 0.001),
+            Expression::Number(n, _) => assert!((n - 2.75).abs() < 0.001),
             _ => panic!("Expected Number"),
         },
         _ => panic!("Expected AgentDecl"),
@@ -227,6 +227,23 @@ fn test_parse_number_negative() {
     }
 }
 
+#[test]
+fn test_parse_unary_operators() {
+    let program = parse("let a = !done\nlet b = -x").unwrap();
+    match &program.statements[0] {
+        Statement::LetStmt(stmt) => {
+            assert!(matches!(stmt.value, Expression::Unary(UnaryOp::Not, _, _)))
+        }
+        _ => panic!("Expected LetStmt"),
+    }
+    match &program.statements[1] {
+        Statement::LetStmt(stmt) => {
+            assert!(matches!(stmt.value, Expression::Unary(UnaryOp::Neg, _, _)))
+        }
+        _ => panic!("Expected LetStmt"),
+    }
+}
+
 #[test]
 fn test_parse_boolean_true() {
     let result = parse("agent A { x: true }");
@@ -438,7 +455,7 @@ fn test_parse_member_expression() {
         }
     "#;
     let program = parse(source).unwrap();
-    assert!(program.statements.len() > 0);
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -449,7 +466,7 @@ fn test_parse_call_expression() {
         }
     "#;
     let program = parse(source).unwrap();
-    assert!(program.statements.len() > 0);
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -460,7 +477,7 @@ fn test_parse_array_literal_in_tool() {
         }
     "#;
     let program = parse(source).unwrap();
-    assert!(program.statements.len() > 0);
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -471,7 +488,7 @@ fn test_parse_object_literal_in_tool() {
         }
     "#;
     let program = parse(source).unwrap();
-    assert!(program.statements.len() > 0);
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -482,5 +499,5 @@ fn test_parse_index_expression() {
         }
     "#;
     let program = parse(source).unwrap();
-    assert!(program.statements.len() > 0);
+    assert!(!program.statements.is_empty());
 }
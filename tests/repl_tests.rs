@@ -0,0 +1,227 @@
+//! Tests for the interactive session (`gent repl`)
+
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::{eval_input, format_value, is_incomplete, run};
+use gent::runtime::ProviderFactory;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+fn session() -> Session {
+    Session::new(
+        ProviderFactory::mock_with_response("mock answer"),
+        Arc::new(NullLogger),
+    )
+}
+
+/// Feed `input` to the REPL, returning (stdout, stderr)
+async fn repl(session: &mut Session, input: &str) -> (String, String) {
+    let mut out = Vec::new();
+    let mut err = Vec::new();
+    run(session, Cursor::new(input.to_string()), &mut out, &mut err)
+        .await
+        .unwrap();
+    (
+        String::from_utf8(out).unwrap(),
+        String::from_utf8(err).unwrap(),
+    )
+}
+
+// ============================================
+// Session
+// ============================================
+
+#[tokio::test]
+async fn test_session_keeps_bindings_across_inputs() {
+    let mut session = session();
+    assert_eq!(eval_input(&mut session, "let x = 20").await.unwrap(), None);
+    assert_eq!(
+        eval_input(&mut session, "x + 1").await.unwrap(),
+        Some(Value::Number(21.0))
+    );
+    eval_input(&mut session, "x = x * 2").await.unwrap();
+    assert_eq!(session.env().get("x"), Some(&Value::Number(40.0)));
+}
+
+#[tokio::test]
+async fn test_session_declarations_then_calls() {
+    let mut session = session();
+    eval_input(
+        &mut session,
+        "fn double(n: number) -> number {\n    return n * 2\n}\n",
+    )
+    .await
+    .unwrap();
+    eval_input(&mut session, "struct Point { x: number }")
+        .await
+        .unwrap();
+    assert_eq!(
+        eval_input(&mut session, "double(4)").await.unwrap(),
+        Some(Value::Number(8.0))
+    );
+}
+
+#[tokio::test]
+async fn test_session_runs_agents_with_mock_provider() {
+    let mut session = session();
+    eval_input(
+        &mut session,
+        "agent Bot {\n    systemPrompt: \"hi\"\n    model: \"m\"\n}\n",
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        eval_input(&mut session, "Bot.userPrompt(\"hello\").run()")
+            .await
+            .unwrap(),
+        Some(Value::String("mock answer".to_string()))
+    );
+    eval_input(&mut session, "let answer = Bot.userPrompt(\"again\").run()")
+        .await
+        .unwrap();
+    assert_eq!(
+        session.env().get("answer"),
+        Some(&Value::String("mock answer".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_session_array_mutation_persists() {
+    let mut session = session();
+    eval_input(&mut session, "let xs = [1]").await.unwrap();
+    eval_input(&mut session, "xs.push(2)").await.unwrap();
+    assert_eq!(
+        eval_input(&mut session, "xs").await.unwrap(),
+        Some(Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]))
+    );
+}
+
+#[tokio::test]
+async fn test_session_error_leaves_state_usable() {
+    let mut session = session();
+    eval_input(&mut session, "let a = 1").await.unwrap();
+    assert!(eval_input(&mut session, "let b = missing").await.is_err());
+    assert!(eval_input(&mut session, "agent {").await.is_err());
+    assert_eq!(
+        eval_input(&mut session, "a").await.unwrap(),
+        Some(Value::Number(1.0))
+    );
+    assert!(session.variables().iter().all(|(name, _)| *name == "a"));
+}
+
+// ============================================
+// Input handling
+// ============================================
+
+#[test]
+fn test_incomplete_input_detection() {
+    assert!(is_incomplete("agent Bot {\n"));
+    assert!(is_incomplete("let xs = [1,\n"));
+    assert!(is_incomplete("let s = \"\"\"\nstill open\n"));
+    assert!(is_incomplete("println(\"a {f(\"b\")}\",\n"));
+    assert!(!is_incomplete("agent Bot { model: \"m\" }\n"));
+    assert!(!is_incomplete("let s = 1 // {\n"));
+    assert!(!is_incomplete("let s = \"\"\"\n{x}\n\"\"\"\n"));
+    assert!(!is_incomplete("}\n"));
+}
+
+#[test]
+fn test_format_value() {
+    assert_eq!(
+        format_value(&Value::String("raw text".to_string())),
+        "raw text"
+    );
+
+    let mut map = BTreeMap::new();
+    map.insert(
+        "b".to_string(),
+        Value::Array(vec![Value::Number(1.0), Value::String("two".to_string())]),
+    );
+    map.insert("a".to_string(), Value::Boolean(true));
+    map.insert("key with space".to_string(), Value::Null);
+    assert_eq!(
+        format_value(&Value::Object(map)),
+        "{ a: true, b: [1, \"two\"], \"key with space\": null }"
+    );
+
+    let long: Vec<Value> = (0..30)
+        .map(|i| Value::String(format!("item{}", i)))
+        .collect();
+    let formatted = format_value(&Value::Array(long));
+    assert!(formatted.starts_with("[\n    \"item0\",\n"));
+    assert!(formatted.ends_with("    \"item29\"\n]"));
+}
+
+// ============================================
+// Loop and commands
+// ============================================
+
+#[tokio::test]
+async fn test_repl_multiline_input_and_results() {
+    let mut session = session();
+    let (out, err) = repl(
+        &mut session,
+        "agent Bot {\n    model: \"gpt-4o\"\n    tools: [web_fetch]\n}\nlet o = { n: 1 }\no\n",
+    )
+    .await;
+    assert_eq!(err, "");
+    assert!(out.contains("gent> ...> ...> ...> gent> gent> { n: 1 }\n"));
+}
+
+#[tokio::test]
+async fn test_repl_env_and_agents_commands() {
+    let mut session = session();
+    let (out, _) = repl(
+        &mut session,
+        "let x = 1\nagent Bot { model: \"gpt-4o\" tools: [web_fetch] }\nagent Plain { model: \"small\" }\n:env\n:agents\n",
+    )
+    .await;
+    assert!(out.contains("Bot = <agent Bot>\nPlain = <agent Plain>\nx = 1\n"));
+    assert!(out.contains("Bot (gpt-4o, tools: web_fetch)\nPlain (small)\n"));
+    assert!(!out.contains("json_parse"));
+}
+
+#[tokio::test]
+async fn test_repl_load_command() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("lib.gnt");
+    fs::write(
+        &file,
+        "fn greet(name: string) -> string {\n    return \"hi {name}\"\n}\n",
+    )
+    .unwrap();
+
+    let mut session = session();
+    let (out, err) = repl(
+        &mut session,
+        &format!(
+            ":load {}\ngreet(\"ann\")\n:load missing.gnt\n",
+            file.display()
+        ),
+    )
+    .await;
+    assert!(out.contains("Loaded "));
+    assert!(out.contains("hi ann\n"));
+    assert!(err.contains("Could not read file 'missing.gnt'"));
+}
+
+#[tokio::test]
+async fn test_repl_reports_errors_and_continues() {
+    let mut session = session();
+    let (out, err) = repl(&mut session, "let y = nope\n:bogus\nfoo(\n").await;
+    assert!(err.contains("nope"));
+    assert!(err.contains("Unknown command ':bogus'"));
+    assert!(err.contains("expected `)`"));
+    assert!(out.ends_with("gent> \n"));
+}
+
+#[tokio::test]
+async fn test_repl_quit_stops_reading() {
+    let mut session = session();
+    let (_, _) = repl(&mut session, "let a = 1\n:quit\nlet b = 2\n").await;
+    assert!(session.env().get("a").is_some());
+    assert!(session.env().get("b").is_none());
+}
@@ -11,7 +11,6 @@
 //! 6. Try/catch error handling
 //! 7. User-defined functions (fn declarations)
 
-use gent::config::Config;
 use gent::interpreter::evaluate;
 use gent::interpreter::evaluate_with_output;
 use gent::logging::NullLogger;
@@ -0,0 +1,269 @@
+//! Tests for call stack frames attached to runtime errors
+
+use gent::errors::{ErrorReporter, GentError};
+use gent::interpreter::evaluate;
+use gent::logging::NullLogger;
+use gent::parser::parse;
+use gent::runtime::{ProviderFactory, ToolRegistry};
+use std::fs;
+use tempfile::tempdir;
+
+async fn run(source: &str) -> Result<(), GentError> {
+    let program = parse(source).unwrap();
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    let logger = NullLogger;
+    evaluate(&program, &factory, &mut tools, &logger).await
+}
+
+fn frame_names(err: &GentError) -> Vec<&str> {
+    err.frames().iter().map(|f| f.function.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_nested_function_frames() {
+    let source = r#"
+fn inner(x: number) -> number {
+    return x / 0
+}
+fn outer() -> number {
+    return inner(1)
+}
+outer()
+"#;
+    let err = run(source).await.unwrap_err();
+    assert!(matches!(
+        err,
+        GentError::Traced { ref source, .. } if matches!(**source, GentError::DivisionByZero { .. })
+    ));
+    assert_eq!(err.kind(), "DivisionByZero");
+    assert_eq!(frame_names(&err), vec!["inner", "outer"]);
+
+    // Frames point at the call sites, innermost first
+    let frames = err.frames();
+    assert_eq!(&source[frames[0].span.start..frames[0].span.end], "inner(1)");
+    assert_eq!(&source[frames[1].span.start..frames[1].span.end], "outer()");
+    assert!(frames.iter().all(|f| f.file.is_none()));
+}
+
+#[tokio::test]
+async fn test_lambda_frame() {
+    let source = r#"
+fn check(n: number) -> number {
+    if n > 1 {
+        throw "too big"
+    }
+    return n
+}
+fn process() {
+    let xs = [1, 2]
+    let checked = xs.map((x) => { return check(x) })
+}
+process()
+"#;
+    let err = run(source).await.unwrap_err();
+    assert_eq!(frame_names(&err), vec!["check", "<lambda>", "process"]);
+}
+
+#[tokio::test]
+async fn test_tool_frame() {
+    let source = r#"
+tool failing(x: number) -> number {
+    return x / 0
+}
+fn useTool() {
+    return failing(1)
+}
+useTool()
+"#;
+    let err = run(source).await.unwrap_err();
+    assert_eq!(err.kind(), "ToolError");
+    assert_eq!(frame_names(&err), vec!["failing", "useTool"]);
+}
+
+#[tokio::test]
+async fn test_agent_frame_for_spanless_error() {
+    let source = r#"
+agent Looper {
+    systemPrompt: "loop"
+    model: "gpt-4o-mini"
+    maxSteps: 0
+}
+fn ask() {
+    let answer = Looper.userPrompt("hi").run()
+}
+ask()
+"#;
+    let err = run(source).await.unwrap_err();
+    assert_eq!(err.kind(), "MaxStepsExceeded");
+    assert!(err.span().is_none());
+    assert_eq!(frame_names(&err), vec!["Looper.run", "ask"]);
+
+    let mut reporter = ErrorReporter::new(source, "agents.gnt");
+    reporter.use_colors = false;
+    let formatted = reporter.format(&err);
+    assert!(formatted.contains("Agent exceeded maximum steps"));
+    // Points at the agent call site even though the error has no span
+    assert!(formatted.contains("--> agents.gnt:8:18"), "{}", formatted);
+    assert!(formatted.contains("stack trace:"));
+    assert!(formatted.contains("  at Looper.run (agents.gnt:8:18)"));
+    assert!(formatted.contains("  at ask (agents.gnt:10:1)"));
+}
+
+#[tokio::test]
+async fn test_caught_agent_error_has_agent_and_call_site() {
+    let source = r#"
+agent Looper {
+    systemPrompt: "loop"
+    model: "gpt-4o-mini"
+    maxSteps: 0
+}
+fn ask() {
+    try {
+        let answer = Looper.userPrompt("hi").run()
+    } catch e {
+        println("{e.agent} {e.kind} {e.span.start}")
+        throw e
+    }
+}
+ask()
+"#;
+    let err = run(source).await.unwrap_err();
+    assert_eq!(err.kind(), "MaxStepsExceeded");
+    let start = source.find("Looper.userPrompt").unwrap();
+    assert_eq!(err.span().unwrap().start, start);
+}
+
+#[test]
+fn test_reporter_prints_trace_for_nested_calls() {
+    let source = "fn a() {\n    return 1 / 0\n}\na()";
+    let err = GentError::DivisionByZero {
+        span: gent::Span::new(20, 25),
+    }
+    .with_frame(gent::errors::StackFrame::new("a", gent::Span::new(28, 31), None));
+
+    let mut reporter = ErrorReporter::new(source, "main.gnt");
+    reporter.use_colors = false;
+    let formatted = reporter.format(&err);
+
+    assert!(formatted.contains("error: Division by zero"));
+    assert!(formatted.contains("--> main.gnt:2:12"));
+    assert!(formatted.contains("stack trace:\n  at a (main.gnt:4:1)\n"));
+}
+
+#[test]
+fn test_reporter_without_frames_has_no_trace() {
+    let err = GentError::DivisionByZero {
+        span: gent::Span::new(0, 1),
+    };
+    let reporter = ErrorReporter::new("1", "main.gnt");
+    assert!(!reporter.format(&err).contains("stack trace"));
+}
+
+#[tokio::test]
+async fn test_frames_in_imported_file() {
+    use gent::interpreter::evaluate_with_imports;
+
+    let dir = tempdir().unwrap();
+    let helper_path = dir.path().join("helper.gnt");
+    fs::write(
+        &helper_path,
+        "fn boom() {\n    throw \"boom\"\n}\nfn wrapper() {\n    boom()\n}\n",
+    )
+    .unwrap();
+
+    let main_path = dir.path().join("main.gnt");
+    let main_source = "import { boom, wrapper } from \"./helper.gnt\"\nwrapper()\n";
+    fs::write(&main_path, main_source).unwrap();
+
+    let program = parse(main_source).unwrap();
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    let logger = NullLogger;
+    let err = evaluate_with_imports(&program, Some(&main_path), &factory, &mut tools, &logger)
+        .await
+        .unwrap_err();
+
+    let frames = err.frames();
+    assert_eq!(frame_names(&err), vec!["boom", "wrapper"]);
+    let helper_file = frames[0].file.clone().expect("frame should record the imported file");
+    assert!(helper_file.ends_with("helper.gnt"));
+    assert_eq!(frames[1].file, None);
+
+    let mut reporter = ErrorReporter::new(main_source, "main.gnt");
+    reporter.use_colors = false;
+    let formatted = reporter.format(&err);
+    assert!(formatted.contains(&format!("  at boom ({}:5:5)", helper_file)));
+    assert!(formatted.contains("  at wrapper (main.gnt:2:1)"));
+}
+
+#[tokio::test]
+async fn test_reporter_shows_source_of_imported_file() {
+    use gent::interpreter::evaluate_with_imports;
+
+    let dir = tempdir().unwrap();
+    let helper_path = dir.path().join("helper.gnt");
+    fs::write(&helper_path, "fn half(x: number) -> number {\n    return x / 0\n}\n").unwrap();
+
+    let main_path = dir.path().join("main.gnt");
+    let main_source = "import { half } from \"./helper.gnt\"\nlet a = 1\nhalf(4)\n";
+    fs::write(&main_path, main_source).unwrap();
+
+    let program = parse(main_source).unwrap();
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    let logger = NullLogger;
+    let err = evaluate_with_imports(&program, Some(&main_path), &factory, &mut tools, &logger)
+        .await
+        .unwrap_err();
+    let helper_file = err.file().expect("error should record the imported file").to_string();
+    assert!(helper_file.ends_with("helper.gnt"));
+
+    let mut reporter = ErrorReporter::new(main_source, "main.gnt");
+    reporter.use_colors = false;
+    let formatted = reporter.format(&err);
+    assert!(
+        formatted.contains(&format!("--> {}:2:12", helper_file)),
+        "{}",
+        formatted
+    );
+    assert!(formatted.contains("2 |     return x / 0"), "{}", formatted);
+    assert!(!formatted.contains("--> main.gnt"), "{}", formatted);
+    assert!(formatted.contains("  at half (main.gnt:3:1)"), "{}", formatted);
+
+    // Without the imported file there is no snippet rather than a wrong one
+    fs::remove_file(&helper_path).unwrap();
+    let formatted = reporter.format(&err);
+    assert!(!formatted.contains("-->"), "{}", formatted);
+    assert!(formatted.contains("error: Division by zero"));
+}
+
+#[tokio::test]
+async fn test_main_function_called_from_imported_file_keeps_main_source() {
+    use gent::interpreter::evaluate_with_imports;
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("helper.gnt"),
+        "fn apply(f: function, x: number) -> number {\n    return f(x)\n}\n",
+    )
+    .unwrap();
+    let main_path = dir.path().join("main.gnt");
+    let main_source =
+        "import { apply } from \"./helper.gnt\"\nfn bad(x: number) -> number {\n    return x / 0\n}\napply(bad, 1)\n";
+    fs::write(&main_path, main_source).unwrap();
+
+    let program = parse(main_source).unwrap();
+    let factory = ProviderFactory::mock();
+    let mut tools = ToolRegistry::new();
+    let logger = NullLogger;
+    let err = evaluate_with_imports(&program, Some(&main_path), &factory, &mut tools, &logger)
+        .await
+        .unwrap_err();
+    assert_eq!(err.file(), None);
+
+    let mut reporter = ErrorReporter::new(main_source, "main.gnt");
+    reporter.use_colors = false;
+    let formatted = reporter.format(&err);
+    assert!(formatted.contains("--> main.gnt:3:12"), "{}", formatted);
+}
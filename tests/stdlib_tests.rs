@@ -0,0 +1,274 @@
+//! Tests for the standard library modules (`std:math`, `std:time`, ...)
+
+use gent::interpreter::stdlib::{std_module, STD_MODULES};
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::parser::parse;
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use std::sync::Arc;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+/// Evaluate `inputs` in one session and return the value of the last one
+async fn eval(inputs: &[&str]) -> Value {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let mut last = None;
+    for input in inputs {
+        last = eval_input(&mut session, input).await.unwrap();
+    }
+    last.unwrap_or(Value::Null)
+}
+
+async fn eval_err(inputs: &[&str]) -> String {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let (last, rest) = inputs.split_last().unwrap();
+    for input in rest {
+        eval_input(&mut session, input).await.unwrap();
+    }
+    let err = &eval_input(&mut session, last).await.unwrap_err()[0];
+    format!("{}: {}", err.kind(), err)
+}
+
+#[test]
+fn test_signatures() {
+    let math = std_module("std:math").unwrap();
+    assert_eq!(
+        math.function("pow").unwrap().signature(),
+        "pow(base: number, exponent: number) -> number"
+    );
+    let time = std_module("std:time").unwrap();
+    assert_eq!(
+        time.function("formatTime").unwrap().signature(),
+        "formatTime(timestamp: number, format?: string) -> string"
+    );
+    assert!(std_module("std:nope").is_none());
+    assert!(std_module("./math.gnt").is_none());
+    assert_eq!(STD_MODULES.len(), 5);
+}
+
+#[tokio::test]
+async fn test_math() {
+    assert_eq!(
+        eval(&[
+            "import { floor, ceil, round, abs, pow, sqrt } from \"std:math\"",
+            "[floor(2.7), ceil(2.1), round(2.5), abs(-3), pow(2, 10), sqrt(16)]",
+        ])
+        .await
+        .to_string(),
+        "[2, 3, 3, 3, 1024, 4]"
+    );
+}
+
+#[tokio::test]
+async fn test_signature_checks_arguments() {
+    let err = eval_err(&["import { floor } from \"std:math\"", "floor(\"2\")"]).await;
+    assert_eq!(
+        err,
+        "ArgumentTypeMismatch: Type mismatch for parameter 'x': expected number, got String"
+    );
+    let err = eval_err(&["import { pow } from \"std:math\"", "pow(2)"]).await;
+    assert_eq!(err, "WrongArgumentCount: Expected 2 arguments, got 1");
+    let err = eval_err(&["import { sqrt } from \"std:math\"", "sqrt(-1)"]).await;
+    assert_eq!(err, "ValueError: Value error: sqrt() of negative number -1");
+}
+
+#[tokio::test]
+async fn test_domain_errors_are_catchable_by_kind() {
+    assert_eq!(
+        eval(&[
+            "import { sqrt } from \"std:math\"\nimport { randomInt } from \"std:random\"",
+            "fn safe() -> string {\n    try {\n        randomInt(5, 1)\n    } catch (e: ValueError) {\n        return e.message\n    }\n    return \"no error\"\n}",
+            "safe()",
+        ])
+        .await,
+        string("Value error: randomInt() range is empty: 5..1")
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_module_or_name_is_an_error() {
+    let err = eval_err(&["import { floor } from \"std:maths\""]).await;
+    assert!(err.contains("Unknown standard library module"), "{}", err);
+    let err = eval_err(&["import { flor } from \"std:math\""]).await;
+    assert!(
+        err.contains("'flor' is not exported by std:math"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_time() {
+    assert_eq!(
+        eval(&[
+            "import { formatTime } from \"std:time\"",
+            "formatTime(1700000000000)",
+        ])
+        .await,
+        string("2023-11-14T22:13:20Z")
+    );
+    assert_eq!(
+        eval(&[
+            "import { formatTime } from \"std:time\"",
+            "formatTime(951782400000, \"%d/%m/%Y %H:%M\")",
+        ])
+        .await,
+        string("29/02/2000 00:00")
+    );
+    assert_eq!(
+        eval(&[
+            "import { now, sleep } from \"std:time\"",
+            "let start = now()",
+            "sleep(20ms)",
+            "now() - start >= 20",
+        ])
+        .await,
+        Value::Boolean(true)
+    );
+}
+
+#[test]
+fn test_duration_literals_are_milliseconds() {
+    let program = parse("let a = 1500ms\nlet b = 2s\nlet c = 1m\n").unwrap();
+    let values: Vec<String> = program
+        .statements
+        .iter()
+        .map(|s| match s {
+            gent::parser::Statement::LetStmt(l) => format!("{:?}", l.value),
+            _ => String::new(),
+        })
+        .collect();
+    assert!(values[0].starts_with("Number(1500.0"));
+    assert!(values[1].starts_with("Number(2000.0"));
+    assert!(values[2].starts_with("Number(60000.0"));
+}
+
+#[tokio::test]
+async fn test_env() {
+    std::env::set_var("GENT_STDLIB_TEST_VAR", "on");
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    session.set_script_args(vec!["--name".to_string(), "ann".to_string()]);
+    eval_input(&mut session, "import { getEnv, args } from \"std:env\"")
+        .await
+        .unwrap();
+    let value = eval_input(
+        &mut session,
+        "[getEnv(\"GENT_STDLIB_TEST_VAR\"), getEnv(\"GENT_STDLIB_UNSET\"), getEnv(\"GENT_STDLIB_UNSET\", \"x\"), args()]",
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(value.to_string(), "[on, null, x, [--name, ann]]");
+
+    // Other sessions have their own arguments
+    assert_eq!(
+        eval(&["import { args } from \"std:env\"", "args()"]).await,
+        Value::Array(vec![])
+    );
+}
+
+#[tokio::test]
+async fn test_json() {
+    assert_eq!(
+        eval(&[
+            "import { parse, stringify } from \"std:json\"",
+            "parse(\"\\{\\\"a\\\": [1, 2.5, null]\\}\").a",
+        ])
+        .await
+        .to_string(),
+        "[1, 2.5, null]"
+    );
+    assert_eq!(
+        eval(&[
+            "import { stringify } from \"std:json\"",
+            "stringify({ b: [1, true], a: \"x\" })",
+        ])
+        .await,
+        string("{\"a\":\"x\",\"b\":[1,true]}")
+    );
+    assert_eq!(
+        eval(&[
+            "import { stringify } from \"std:json\"",
+            "stringify({ a: 1 }, true)",
+        ])
+        .await,
+        string("{\n  \"a\": 1\n}")
+    );
+    let err = eval_err(&["import { parse } from \"std:json\"", "parse(\"[1,\")"]).await;
+    assert!(err.starts_with("JsonError: JSON error: Invalid JSON"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_seeded_random_repeats() {
+    let source = [
+        "import { seed, random, randomInt, choice, shuffle } from \"std:random\"",
+        "seed(42)",
+        "[random(), randomInt(1, 6), choice([\"a\", \"b\", \"c\"]), shuffle([1, 2, 3, 4])]",
+    ];
+    let first = eval(&source).await;
+    let second = eval(&source).await;
+    assert_eq!(first, second);
+
+    let Value::Array(values) = first else {
+        panic!("expected an array");
+    };
+    let Value::Number(r) = values[0] else {
+        panic!("expected a number");
+    };
+    assert!((0.0..1.0).contains(&r));
+    let Value::Number(n) = values[1] else {
+        panic!("expected a number");
+    };
+    assert!((1.0..=6.0).contains(&n) && n.fract() == 0.0);
+    let Value::Array(shuffled) = &values[3] else {
+        panic!("expected an array");
+    };
+    assert_eq!(shuffled.len(), 4);
+}
+
+#[tokio::test]
+async fn test_sessions_have_separate_random_generators() {
+    let mut a = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    let mut b = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    for session in [&mut a, &mut b] {
+        eval_input(session, "import { seed, random } from \"std:random\"\nfn next() -> number {\n    return random()\n}")
+            .await
+            .unwrap();
+        eval_input(session, "seed(7)").await.unwrap();
+    }
+
+    // Interleaved calls do not advance each other's sequence, and calls
+    // inside functions continue the session's sequence
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    for input in ["random()", "next()", "random()"] {
+        first.push(eval_input(&mut a, input).await.unwrap());
+        second.push(eval_input(&mut b, input).await.unwrap());
+    }
+    assert_eq!(first, second);
+    assert_ne!(first[0], first[1]);
+}
+
+#[tokio::test]
+async fn test_std_functions_as_callbacks_and_in_functions() {
+    assert_eq!(
+        eval(&[
+            "import { floor } from \"std:math\"",
+            "[1.5, 2.7].map(floor)",
+        ])
+        .await
+        .to_string(),
+        "[1, 2]"
+    );
+    assert_eq!(
+        eval(&[
+            "import { abs } from \"std:math\"\nfn dist(a: number, b: number) -> number {\n    return abs(a - b)\n}",
+            "dist(2, 5)",
+        ])
+        .await,
+        Value::Number(3.0)
+    );
+}
@@ -0,0 +1,195 @@
+//! Tests for the expanded string standard library
+//!
+//! Methods are called through the string_methods module directly; a few
+//! chains run through the interpreter.
+
+use gent::interpreter::string_methods::call_string_method;
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use std::sync::Arc;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn strings(ss: &[&str]) -> Value {
+    Value::Array(ss.iter().map(|s| string(s)).collect())
+}
+
+fn call(s: &str, method: &str, args: &[Value]) -> Value {
+    call_string_method(s, method, args).unwrap()
+}
+
+async fn eval(source: &str) -> Value {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval_input(&mut session, source).await.unwrap().unwrap()
+}
+
+// ============================================
+// Regular expressions
+// ============================================
+
+#[test]
+fn test_matches() {
+    assert_eq!(
+        call("order 42", "matches", &[string(r"\d+")]),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        call("no digits", "matches", &[string(r"^\d+$")]),
+        Value::Boolean(false)
+    );
+}
+
+#[test]
+fn test_match_returns_groups() {
+    assert_eq!(
+        call("v1.20", "match", &[string(r"(\d+)\.(\d+)(-rc)?")]),
+        Value::Array(vec![string("1.20"), string("1"), string("20"), Value::Null])
+    );
+    assert_eq!(call("abc", "match", &[string(r"\d")]), Value::Null);
+}
+
+#[test]
+fn test_replace_all_and_split_regex() {
+    assert_eq!(
+        call("a1b22c", "replaceAll", &[string(r"\d+"), string("-")]),
+        string("a-b-c")
+    );
+    assert_eq!(
+        call(
+            "John Smith",
+            "replaceAll",
+            &[string(r"(\w+) (\w+)"), string("$2, $1")]
+        ),
+        string("Smith, John")
+    );
+    assert_eq!(
+        call("a, b;c", "splitRegex", &[string(r"[,;]\s*")]),
+        strings(&["a", "b", "c"])
+    );
+}
+
+#[test]
+fn test_invalid_regex_is_an_error() {
+    let err = call_string_method("abc", "matches", &[string("(")]).unwrap_err();
+    assert!(err.to_string().contains("valid regular expression"));
+    assert!(call_string_method("abc", "matches", &[Value::Number(1.0)]).is_err());
+}
+
+// ============================================
+// Slicing and padding
+// ============================================
+
+#[test]
+fn test_substring_and_index_of() {
+    assert_eq!(
+        call(
+            "héllo",
+            "substring",
+            &[Value::Number(1.0), Value::Number(3.0)]
+        ),
+        string("él")
+    );
+    assert_eq!(
+        call("hello", "substring", &[Value::Number(2.0)]),
+        string("llo")
+    );
+    assert_eq!(
+        call(
+            "hello",
+            "substring",
+            &[Value::Number(4.0), Value::Number(1.0)]
+        ),
+        string("")
+    );
+    assert_eq!(call("héllo", "indexOf", &[string("l")]), Value::Number(2.0));
+    assert_eq!(
+        call("hello", "indexOf", &[string("z")]),
+        Value::Number(-1.0)
+    );
+}
+
+#[test]
+fn test_lines_and_chars() {
+    assert_eq!(call("a\nb\r\nc", "lines", &[]), strings(&["a", "b", "c"]));
+    assert_eq!(call("héy", "chars", &[]), strings(&["h", "é", "y"]));
+}
+
+#[test]
+fn test_pad_repeat_trim() {
+    assert_eq!(
+        call("7", "padStart", &[Value::Number(3.0), string("0")]),
+        string("007")
+    );
+    assert_eq!(call("ab", "padEnd", &[Value::Number(4.0)]), string("ab  "));
+    assert_eq!(
+        call("abcd", "padStart", &[Value::Number(2.0)]),
+        string("abcd")
+    );
+    assert_eq!(
+        call("ab", "repeat", &[Value::Number(3.0)]),
+        string("ababab")
+    );
+    assert_eq!(call("  x  ", "trimStart", &[]), string("x  "));
+    assert_eq!(call("  x  ", "trimEnd", &[]), string("  x"));
+    assert!(call_string_method("ab", "repeat", &[string("3")]).is_err());
+}
+
+#[test]
+fn test_to_number() {
+    assert_eq!(call(" 3.5 ", "toNumber", &[]), Value::Number(3.5));
+    assert_eq!(call("-2", "toNumber", &[]), Value::Number(-2.0));
+    assert_eq!(call("abc", "toNumber", &[]), Value::Null);
+}
+
+// ============================================
+// Extraction
+// ============================================
+
+#[test]
+fn test_extract_json() {
+    let reply = "Sure! Here it is:\n```json\n{\"name\": \"ann\", \"tags\": [\"a\"]}\n```";
+    assert_eq!(
+        call(reply, "extractJson", &[]).to_string(),
+        "{name: ann, tags: [a]}"
+    );
+    // Braces that don't start valid JSON are skipped
+    assert_eq!(
+        call("use {braces} then [1, 2]", "extractJson", &[]),
+        Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])
+    );
+    assert_eq!(call("no json here", "extractJson", &[]), Value::Null);
+}
+
+#[test]
+fn test_extract_code_blocks() {
+    let reply = "Two files:\n```python\nprint(1)\n```\nand\n```JS\nlet a = 1;\nlet b = 2;\n```\n```\nplain\n```";
+    assert_eq!(
+        call(reply, "extractCodeBlocks", &[]),
+        strings(&["print(1)", "let a = 1;\nlet b = 2;", "plain"])
+    );
+    assert_eq!(
+        call(reply, "extractCodeBlocks", &[string("js")]),
+        strings(&["let a = 1;\nlet b = 2;"])
+    );
+    assert_eq!(
+        call("```rust\nfn main() {}", "extractCodeBlocks", &[]),
+        strings(&["fn main() {}"])
+    );
+    assert_eq!(call("no code", "extractCodeBlocks", &[]), strings(&[]));
+}
+
+#[tokio::test]
+async fn test_string_methods_through_interpreter() {
+    assert_eq!(
+        eval("\"Total: 42 items\".match(\"(\\\\d+)\")[1].toNumber() + 1").await,
+        Value::Number(43.0)
+    );
+    assert_eq!(
+        eval("\"a\\nb\".lines().map((l) => l.padStart(2, \"-\")).join(\",\")").await,
+        string("-a,-b")
+    );
+}
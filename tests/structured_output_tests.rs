@@ -1,6 +1,5 @@
 //! Integration tests for structured output feature
 
-use gent::config::Config;
 use gent::interpreter::evaluate_with_output;
 use gent::logging::NullLogger;
 use gent::parser::parse;
@@ -0,0 +1,199 @@
+//! Tests for tool choice (`toolChoice:`) and finish tools (`finishTool:`)
+
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::llm::{LLMClient, Message, ToolChoice, ToolDefinition};
+use gent::runtime::providers::{AnthropicClient, OpenAIClient};
+use gent::runtime::{ProviderFactory, ToolCall};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SUBMIT: &str = r#"
+/// Submit the extracted person
+tool submit(name: string, age: number) -> string {
+    return "ok"
+}
+"#;
+
+async fn eval(session: &mut Session, input: &str) -> Result<Option<Value>, String> {
+    eval_input(session, input)
+        .await
+        .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))
+}
+
+fn submit_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "submit".to_string(),
+        description: "Submit the result".to_string(),
+        parameters: json!({"type": "object", "properties": {}}),
+    }
+}
+
+fn submit_call(arguments: serde_json::Value) -> ToolCall {
+    ToolCall {
+        id: "1".to_string(),
+        name: "submit".to_string(),
+        arguments,
+    }
+}
+
+#[test]
+fn test_parse_tool_choice() {
+    assert_eq!(ToolChoice::parse("auto"), ToolChoice::Auto);
+    assert_eq!(ToolChoice::parse("required"), ToolChoice::Required);
+    assert_eq!(ToolChoice::parse("none"), ToolChoice::None);
+    assert_eq!(
+        ToolChoice::parse("submit"),
+        ToolChoice::Tool("submit".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_openai_tool_choice() {
+    let mock_server = MockServer::start().await;
+    let reply = json!({
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": "Done"}}]
+    });
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(json!({"tool_choice": "required"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&reply))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_partial_json(json!({
+            "tool_choice": {"type": "function", "function": {"name": "submit"}}
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&reply))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = OpenAIClient::new("test-key".to_string()).with_base_url(&mock_server.uri());
+    for choice in [ToolChoice::Required, ToolChoice::Tool("submit".to_string())] {
+        let response = client
+            .chat_with_tool_choice(
+                vec![Message::user("Hello")],
+                vec![submit_definition()],
+                None,
+                false,
+                &choice,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.content, Some("Done".to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_anthropic_tool_choice() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(json!({"tool_choice": {"type": "tool", "name": "submit"}})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "content": [{"type": "tool_use", "id": "t1", "name": "submit", "input": {"ok": true}}],
+            "stop_reason": "tool_use"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = AnthropicClient::new("test-key".to_string()).with_base_url(&mock_server.uri());
+    let response = client
+        .chat_with_tool_choice(
+            vec![Message::user("Hello")],
+            vec![submit_definition()],
+            None,
+            false,
+            &ToolChoice::Tool("submit".to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.tool_calls[0].name, "submit");
+}
+
+#[tokio::test]
+async fn test_tool_choice_fields() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval(&mut session, SUBMIT).await.unwrap();
+    eval(
+        &mut session,
+        "agent Extractor { model: \"gpt-4o-mini\" toolChoice: submit finishTool: submit }",
+    )
+    .await
+    .unwrap();
+    let extractor = match eval(&mut session, "Extractor").await {
+        Ok(Some(Value::Agent(agent))) => *agent,
+        _ => panic!("Extractor is not an agent"),
+    };
+    assert_eq!(extractor.tool_choice, ToolChoice::Tool("submit".to_string()));
+    assert_eq!(extractor.finish_tool.as_deref(), Some("submit"));
+    assert_eq!(extractor.tools, ["submit"]);
+
+    let error = eval(
+        &mut session,
+        "agent Other { model: \"gpt-4o-mini\" toolChoice: \"search\" }",
+    )
+    .await
+    .unwrap_err();
+    assert!(error.contains("'search'"), "{}", error);
+
+    let error = eval(
+        &mut session,
+        "agent Other { model: \"gpt-4o-mini\" finishTool: \"submit\" }",
+    )
+    .await
+    .unwrap_err();
+    assert!(error.starts_with("TypeError"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_finish_tool_arguments_are_the_result() {
+    let factory =
+        ProviderFactory::mock_with_tool_calls(vec![submit_call(json!({"name": "Ada", "age": 36}))]);
+    let mut session = Session::new(factory, Arc::new(NullLogger));
+    eval(&mut session, SUBMIT).await.unwrap();
+    eval(
+        &mut session,
+        "agent Extractor { model: \"gpt-4o-mini\" toolChoice: \"required\" finishTool: submit maxSteps: 2 }",
+    )
+    .await
+    .unwrap();
+
+    let mut expected = BTreeMap::new();
+    expected.insert("name".to_string(), Value::String("Ada".to_string()));
+    expected.insert("age".to_string(), Value::Number(36.0));
+    assert_eq!(
+        eval(&mut session, "Extractor.userPrompt(\"Ada is 36\").run()")
+            .await
+            .unwrap(),
+        Some(Value::Object(expected))
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_finish_arguments_do_not_finish() {
+    // The tool rejects a string age, so the run goes on until maxSteps
+    let factory =
+        ProviderFactory::mock_with_tool_calls(vec![submit_call(json!({"name": "Ada", "age": "old"}))]);
+    let mut session = Session::new(factory, Arc::new(NullLogger));
+    eval(&mut session, SUBMIT).await.unwrap();
+    eval(
+        &mut session,
+        "agent Extractor { model: \"gpt-4o-mini\" finishTool: submit maxSteps: 2 }",
+    )
+    .await
+    .unwrap();
+
+    let error = eval(&mut session, "Extractor.userPrompt(\"Ada\").run()")
+        .await
+        .unwrap_err();
+    assert!(error.contains("exceeded"), "{}", error);
+}
@@ -0,0 +1,145 @@
+//! Tests for running the tool calls of one response concurrently
+
+use async_trait::async_trait;
+use gent::interpreter::{AgentValue, Session, Value};
+use gent::logging::NullLogger;
+use gent::repl::eval_input;
+use gent::runtime::tools::Tool;
+use gent::runtime::{run_agent_turn, Message, ProviderFactory, Role, ToolCall, ToolRegistry};
+use serde_json::{json, Value as JsonValue};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tool that sleeps for `ms` milliseconds and records how many calls
+/// were running at the same time
+struct SleepTool {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Tool for SleepTool {
+    fn name(&self) -> &str {
+        "sleep"
+    }
+
+    fn description(&self) -> &str {
+        "Sleep for a while"
+    }
+
+    fn parameters_schema(&self) -> JsonValue {
+        json!({"type": "object", "properties": {"ms": {"type": "number"}}})
+    }
+
+    async fn execute(&self, args: JsonValue) -> Result<String, String> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        let ms = args["ms"].as_u64().unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(format!("slept {}", ms))
+    }
+}
+
+fn sleep_call(id: &str, ms: u64) -> ToolCall {
+    ToolCall {
+        id: id.to_string(),
+        name: "sleep".to_string(),
+        arguments: json!({"ms": ms}),
+    }
+}
+
+/// Run one step of `agent` with the given tool calls, returning the tool
+/// results in history order and the highest number of calls running at once
+async fn run_step(agent: AgentValue, calls: Vec<ToolCall>) -> (Vec<Message>, usize) {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(SleepTool {
+        running: Arc::new(AtomicUsize::new(0)),
+        peak: peak.clone(),
+    }));
+    let factory = ProviderFactory::mock_with_tool_calls(calls);
+    let mut history = Vec::new();
+
+    // The mock keeps calling tools, so the turn stops at maxSteps
+    let agent = agent.with_tools(vec!["sleep".to_string()]).with_max_steps(1);
+    let result = run_agent_turn(
+        &agent,
+        Some("Go".to_string()),
+        &mut history,
+        &factory,
+        &registry,
+        &NullLogger,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let results = history
+        .into_iter()
+        .filter(|m| m.role == Role::Tool)
+        .collect();
+    (results, peak.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn test_tool_calls_run_concurrently_in_order() {
+    let calls = vec![sleep_call("a", 150), sleep_call("b", 10), sleep_call("c", 80)];
+    let (results, peak) = run_step(AgentValue::new("Bot", ""), calls).await;
+
+    assert_eq!(peak, 3);
+    let ids: Vec<_> = results.iter().map(|m| m.tool_call_id.as_deref().unwrap()).collect();
+    assert_eq!(ids, ["a", "b", "c"]);
+    assert_eq!(results[0].content, "slept 150");
+    assert_eq!(results[2].content, "slept 80");
+}
+
+#[tokio::test]
+async fn test_tool_concurrency_limit() {
+    let calls = vec![sleep_call("a", 20), sleep_call("b", 20), sleep_call("c", 20)];
+
+    let (results, peak) = run_step(AgentValue::new("Bot", "").with_tool_concurrency(1), calls.clone()).await;
+    assert_eq!(peak, 1);
+    assert_eq!(results.len(), 3);
+
+    let (_, peak) = run_step(AgentValue::new("Bot", "").with_tool_concurrency(2), calls).await;
+    assert_eq!(peak, 2);
+}
+
+#[tokio::test]
+async fn test_tool_timeout() {
+    let calls = vec![sleep_call("hung", 5_000), sleep_call("quick", 10)];
+    let started = std::time::Instant::now();
+    let (results, _) = run_step(AgentValue::new("Bot", "").with_tool_timeout(100), calls).await;
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(results[0].content, "Tool 'sleep' timed out after 100ms");
+    assert_eq!(results[1].content, "slept 10");
+}
+
+#[tokio::test]
+async fn test_tool_concurrency_fields() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval_input(
+        &mut session,
+        "agent Fetcher { model: \"gpt-4o-mini\" toolConcurrency: 2 toolTimeout: 5s }",
+    )
+    .await
+    .unwrap();
+    let fetcher = match eval_input(&mut session, "Fetcher").await {
+        Ok(Some(Value::Agent(agent))) => *agent,
+        _ => panic!("Fetcher is not an agent"),
+    };
+    assert_eq!(fetcher.tool_concurrency, Some(2));
+    assert_eq!(fetcher.tool_timeout_ms, Some(5000));
+
+    for field in ["toolConcurrency: 0", "toolTimeout: \"5s\""] {
+        let errors = eval_input(
+            &mut session,
+            &format!("agent Other {{ model: \"gpt-4o-mini\" {} }}", field),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(errors[0].kind(), "TypeError");
+    }
+}
@@ -0,0 +1,100 @@
+//! Tests for tool doc comments and optional tool parameters
+
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::parser::{parse, Statement};
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use serde_json::json;
+use std::sync::Arc;
+
+const SEARCH: &str = r#"
+/// Search the web and return the top results.
+/// Results are plain text, one per line.
+/// @param query What to search for
+/// @param limit How many results to return
+tool search(query: string, limit: number = 3) -> string {
+    return "{query}:{limit}"
+}
+"#;
+
+async fn eval(session: &mut Session, input: &str) -> Result<Option<Value>, String> {
+    eval_input(session, input)
+        .await
+        .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))
+}
+
+#[test]
+fn test_parse_tool_doc_comment() {
+    let program = parse(SEARCH).unwrap();
+    match &program.statements[0] {
+        Statement::ToolDecl(tool) => {
+            assert_eq!(
+                tool.description.as_deref(),
+                Some("Search the web and return the top results.\nResults are plain text, one per line.")
+            );
+            assert_eq!(tool.params[0].description.as_deref(), Some("What to search for"));
+            assert_eq!(tool.params[1].description.as_deref(), Some("How many results to return"));
+            assert!(tool.params[0].default.is_none());
+            assert!(tool.params[1].default.is_some());
+            assert!(SEARCH[tool.span.start..].starts_with("tool search"));
+        }
+        _ => panic!("Expected ToolDecl"),
+    }
+}
+
+#[test]
+fn test_parse_tool_without_doc_comment() {
+    let program = parse("// helper\ntool noop() { return null }").unwrap();
+    match &program.statements[0] {
+        Statement::ToolDecl(tool) => {
+            assert_eq!(tool.description, None);
+        }
+        _ => panic!("Expected ToolDecl"),
+    }
+}
+
+#[test]
+fn test_required_param_after_default_is_rejected() {
+    let result = parse("tool bad(a: number = 1, b: number) { return b }");
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_tool_definition_uses_doc_comment() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval(&mut session, SEARCH).await.unwrap();
+
+    let tool = session.tools().get("search").unwrap();
+    assert!(tool.description().starts_with("Search the web"));
+
+    let schema = tool.parameters_schema();
+    assert_eq!(schema["properties"]["query"]["description"], "What to search for");
+    assert_eq!(schema["properties"]["limit"]["description"], "How many results to return");
+    assert_eq!(schema["properties"]["limit"]["default"], json!(3));
+    assert_eq!(schema["required"], json!(["query"]));
+}
+
+#[tokio::test]
+async fn test_optional_param_uses_default() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval(&mut session, SEARCH).await.unwrap();
+
+    assert_eq!(
+        eval(&mut session, "search(\"rust\")").await.unwrap(),
+        Some(Value::String("rust:3".to_string()))
+    );
+    assert_eq!(
+        eval(&mut session, "search(\"rust\", 5)").await.unwrap(),
+        Some(Value::String("rust:5".to_string()))
+    );
+
+    let tool = session.tools().get("search").unwrap();
+    assert_eq!(tool.execute(json!({"query": "gent"})).await.unwrap(), "gent:3");
+    assert_eq!(
+        tool.execute(json!({"query": "gent", "limit": null}))
+            .await
+            .unwrap(),
+        "gent:3"
+    );
+}
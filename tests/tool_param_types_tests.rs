@@ -0,0 +1,209 @@
+//! Tests for struct, enum and typed array tool parameters and return values
+
+use gent::interpreter::{Session, Value};
+use gent::logging::NullLogger;
+use gent::parser::{parse, Statement, TypeName};
+use gent::repl::eval_input;
+use gent::runtime::ProviderFactory;
+use serde_json::json;
+use std::sync::Arc;
+
+const TYPES: &str = r#"
+struct Address {
+    city: string
+    zip: string
+}
+
+struct Item {
+    name: string
+    quantity: number
+    shipTo: Address
+}
+
+enum Priority {
+    Low
+    High
+}
+
+enum Discount {
+    None
+    Percent(number)
+}
+"#;
+
+async fn eval(session: &mut Session, input: &str) -> Result<Option<Value>, String> {
+    eval_input(session, input)
+        .await
+        .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))
+}
+
+async fn session_with_types() -> Session {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval(&mut session, TYPES).await.unwrap();
+    session
+}
+
+#[test]
+fn test_parse_named_and_array_types() {
+    let program = parse("tool order(items: Item[], priority: Priority?) -> Item { return items[0] }").unwrap();
+    match &program.statements[0] {
+        Statement::ToolDecl(tool) => {
+            assert_eq!(
+                tool.params[0].type_name,
+                TypeName::ArrayOf(Box::new(TypeName::Named("Item".to_string())))
+            );
+            assert_eq!(
+                tool.params[1].type_name,
+                TypeName::Nullable(Box::new(TypeName::Named("Priority".to_string())))
+            );
+            assert_eq!(tool.return_type, Some(TypeName::Named("Item".to_string())));
+        }
+        _ => panic!("Expected ToolDecl"),
+    }
+}
+
+#[tokio::test]
+async fn test_struct_param_schema_is_nested() {
+    let mut session = session_with_types().await;
+    eval(&mut session, "tool order(items: Item[], note: any) { return note }")
+        .await
+        .unwrap();
+
+    let schema = session.tools().get("order").unwrap().parameters_schema();
+    let items = &schema["properties"]["items"];
+    assert_eq!(items["type"], "array");
+    assert_eq!(items["items"]["type"], "object");
+    assert_eq!(items["items"]["required"], json!(["name", "quantity", "shipTo"]));
+    assert_eq!(
+        items["items"]["properties"]["shipTo"]["properties"]["city"]["type"],
+        "string"
+    );
+    assert_eq!(schema["properties"]["note"].get("type"), None);
+}
+
+#[tokio::test]
+async fn test_enum_param_schema() {
+    let mut session = session_with_types().await;
+    eval(
+        &mut session,
+        "tool triage(priority: Priority, discount: Discount?) { return priority }",
+    )
+    .await
+    .unwrap();
+
+    let schema = session.tools().get("triage").unwrap().parameters_schema();
+    assert_eq!(schema["properties"]["priority"]["type"], "string");
+    assert_eq!(schema["properties"]["priority"]["enum"], json!(["Low", "High"]));
+    let discount = &schema["properties"]["discount"]["anyOf"];
+    assert_eq!(discount[0]["properties"]["variant"]["enum"], json!(["None", "Percent"]));
+    assert_eq!(discount[1]["type"], "null");
+}
+
+#[tokio::test]
+async fn test_unknown_param_type_is_an_error() {
+    let mut session = session_with_types().await;
+    let error = eval(&mut session, "tool bad(order: Order) { return order }")
+        .await
+        .unwrap_err();
+    assert!(error.contains("Unknown type: Order"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_arguments_are_validated() {
+    let mut session = session_with_types().await;
+    eval(
+        &mut session,
+        "tool ship(item: Item, priority: Priority) -> string { return item.shipTo.city }",
+    )
+    .await
+    .unwrap();
+    let tool = session.tools().get("ship").unwrap();
+
+    let item = json!({"name": "Pen", "quantity": 2, "shipTo": {"city": "Oslo", "zip": "0150"}});
+    assert_eq!(
+        tool.execute(json!({"item": item, "priority": "High"}))
+            .await
+            .unwrap(),
+        "Oslo"
+    );
+
+    let missing = json!({"name": "Pen", "quantity": 2, "shipTo": {"city": "Oslo"}});
+    let error = tool
+        .execute(json!({"item": missing, "priority": "High"}))
+        .await
+        .unwrap_err();
+    assert!(error.contains("'item.shipTo.zip'"), "{}", error);
+
+    let error = tool
+        .execute(json!({"item": item, "priority": "Urgent"}))
+        .await
+        .unwrap_err();
+    assert!(error.contains("expected one of Low, High"), "{}", error);
+
+    let error = tool
+        .execute(json!({"item": "Pen", "priority": "Low"}))
+        .await
+        .unwrap_err();
+    assert!(error.contains("expected object, got string"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_enum_arguments_become_enum_values() {
+    let mut session = session_with_types().await;
+    eval(
+        &mut session,
+        r#"tool price(amount: number, discount: Discount) -> number {
+            return match discount {
+                Discount.None => amount
+                Discount.Percent(p) => amount - amount * p / 100
+            }
+        }"#,
+    )
+    .await
+    .unwrap();
+    let tool = session.tools().get("price").unwrap();
+
+    assert_eq!(
+        tool.execute(json!({"amount": 200, "discount": "None"}))
+            .await
+            .unwrap(),
+        "200"
+    );
+    assert_eq!(
+        tool.execute(json!({"amount": 200, "discount": {"variant": "Percent", "data": [25]}}))
+            .await
+            .unwrap(),
+        "150"
+    );
+    assert_eq!(
+        eval(&mut session, "price(200, Discount.Percent(10))")
+            .await
+            .unwrap(),
+        Some(Value::String("180".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_structured_results_are_json() {
+    let mut session = session_with_types().await;
+    eval(
+        &mut session,
+        r#"tool address(city: string) -> Address {
+            return { city: city, zip: "0000" }
+        }"#,
+    )
+    .await
+    .unwrap();
+
+    let tool = session.tools().get("address").unwrap();
+    let result = tool.execute(json!({"city": "Oslo"})).await.unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&result).unwrap(),
+        json!({"city": "Oslo", "zip": "0000"})
+    );
+
+    assert_eq!(
+        eval(&mut session, "address(\"Oslo\").city").await.unwrap(),
+        Some(Value::String("Oslo".to_string()))
+    );
+}
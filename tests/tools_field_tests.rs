@@ -1,6 +1,5 @@
 //! Tests for tools: field in agents
 
-use gent::config::Config;
 use gent::parser::ast::{Expression, Statement};
 
 #[test]
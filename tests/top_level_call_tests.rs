@@ -1,6 +1,5 @@
 //! Tests for top-level function calls
 
-use gent::config::Config;
 use gent::parser::parse;
 
 #[test]
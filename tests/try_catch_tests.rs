@@ -26,6 +26,8 @@ async fn test_eval_try_catch_no_error() {
                     span: Span::new(0, 15),
                 },
                 error_var: "error".to_string(),
+                error_type: None,
+                finally_block: None,
                 catch_block: Block {
                     statements: vec![BlockStmt::Return(ReturnStmt {
                         value: Some(Expression::String(
@@ -76,6 +78,8 @@ async fn test_eval_try_catch_with_return_in_try() {
                     span: Span::new(0, 22),
                 },
                 error_var: "error".to_string(),
+                error_type: None,
+                finally_block: None,
                 catch_block: Block {
                     statements: vec![BlockStmt::Return(ReturnStmt {
                         value: Some(Expression::String(
@@ -126,6 +130,8 @@ async fn test_eval_nested_try_catch() {
                             span: Span::new(0, 14),
                         },
                         error_var: "innerErr".to_string(),
+                        error_type: None,
+                        finally_block: None,
                         catch_block: Block {
                             statements: vec![BlockStmt::Let(LetStmt {
                                 name: "y".to_string(),
@@ -139,6 +145,8 @@ async fn test_eval_nested_try_catch() {
                     span: Span::new(0, 40),
                 },
                 error_var: "outerErr".to_string(),
+                error_type: None,
+                finally_block: None,
                 catch_block: Block {
                     statements: vec![BlockStmt::Let(LetStmt {
                         name: "z".to_string(),
@@ -185,6 +193,8 @@ async fn test_eval_try_catch_normal_completion() {
                     span: Span::new(0, 14),
                 },
                 error_var: "error".to_string(),
+                error_type: None,
+                finally_block: None,
                 catch_block: Block {
                     statements: vec![BlockStmt::Let(LetStmt {
                         name: "y".to_string(),
@@ -318,3 +328,376 @@ fn test_parse_try_catch_with_if() {
     let result = parse(source);
     assert!(result.is_ok(), "Failed: {:?}", result.err());
 }
+
+// ============================================
+// throw, typed catch and finally
+// ============================================
+
+/// Parse `tool test() { ... }` and evaluate its body
+async fn eval_tool_body(source: &str) -> gent::errors::GentResult<Value> {
+    let program = parse(source).expect("parse failed");
+    let body = match &program.statements[0] {
+        gent::parser::Statement::ToolDecl(tool) => tool.body.clone(),
+        other => panic!("Expected tool declaration, got {:?}", other),
+    };
+    let mut env = Environment::new();
+    let tools = ToolRegistry::new();
+    evaluate_block(&body, &mut env, &tools).await
+}
+
+#[test]
+fn test_parse_throw_and_finally() {
+    let source = r#"
+        tool test() {
+            try {
+                throw "boom"
+            } catch (e: ToolError) {
+                let x = e.message
+            } finally {
+                let y = 1
+            }
+        }
+    "#;
+    let program = parse(source).unwrap();
+    let body = match &program.statements[0] {
+        gent::parser::Statement::ToolDecl(tool) => &tool.body,
+        _ => panic!("Expected tool declaration"),
+    };
+    match &body.statements[0] {
+        BlockStmt::Try(try_stmt) => {
+            assert_eq!(try_stmt.error_var, "e");
+            assert_eq!(try_stmt.error_type.as_deref(), Some("ToolError"));
+            assert!(try_stmt.finally_block.is_some());
+            assert!(matches!(try_stmt.try_block.statements[0], BlockStmt::Throw(_)));
+        }
+        other => panic!("Expected try statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_identifier_starting_with_throw() {
+    let source = r#"
+        tool test() {
+            let thrower = 1
+            thrower
+            return thrower
+        }
+    "#;
+    let program = parse(source).unwrap();
+    let body = match &program.statements[0] {
+        gent::parser::Statement::ToolDecl(tool) => &tool.body,
+        _ => panic!("Expected tool declaration"),
+    };
+    assert!(matches!(body.statements[1], BlockStmt::Expr(_)));
+}
+
+#[tokio::test]
+async fn test_eval_throw_string_is_caught() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                throw "boom"
+            } catch e {
+                return "{e.kind}: {e.message}"
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(result.unwrap(), Value::String("Error: boom".to_string()));
+}
+
+#[tokio::test]
+async fn test_eval_throw_object_with_kind() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                throw { kind: "ValidationError", message: "bad input" }
+            } catch (e: ValidationError) {
+                return e.message
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(result.unwrap(), Value::String("bad input".to_string()));
+}
+
+#[tokio::test]
+async fn test_eval_uncaught_throw_propagates() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            throw "unhandled"
+        }
+    "#,
+    )
+    .await;
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), "Error");
+    assert!(err.to_string().contains("unhandled"));
+}
+
+#[tokio::test]
+async fn test_eval_caught_error_has_builtin_kind_and_span() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                let x = 1 / 0
+            } catch e {
+                return [e.kind, e.span.start > 0, e.agent, e.tool]
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap(),
+        Value::Array(vec![
+            Value::String("DivisionByZero".to_string()),
+            Value::Boolean(true),
+            Value::Null,
+            Value::Null,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_eval_caught_tool_error_has_tool_name() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                missingTool()
+            } catch (e: UnknownTool) {
+                return e.tool
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(result.unwrap(), Value::String("missingTool".to_string()));
+}
+
+#[tokio::test]
+async fn test_eval_typed_catch_skips_other_kinds() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                throw { kind: "NetworkError", message: "offline" }
+            } catch (e: ToolError) {
+                return "wrong handler"
+            }
+        }
+    "#,
+    )
+    .await;
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), "NetworkError");
+}
+
+#[tokio::test]
+async fn test_eval_catch_error_type_catches_everything() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                throw { kind: "NetworkError", message: "offline" }
+            } catch (e: Error) {
+                return e.kind
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(result.unwrap(), Value::String("NetworkError".to_string()));
+}
+
+#[tokio::test]
+async fn test_eval_rethrow_preserves_kind() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                try {
+                    throw { kind: "Inner", message: "deep" }
+                } catch e {
+                    throw e
+                }
+            } catch (outer: Inner) {
+                return outer.message
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(result.unwrap(), Value::String("deep".to_string()));
+}
+
+#[tokio::test]
+async fn test_eval_finally_runs_after_success_and_error() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            let log = []
+            try {
+                log.push("try")
+            } catch e {
+                log.push("catch")
+            } finally {
+                log.push("finally")
+            }
+            try {
+                throw "boom"
+            } catch e {
+                log.push("catch")
+            } finally {
+                log.push("finally")
+            }
+            return log
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap(),
+        Value::Array(vec![
+            Value::String("try".to_string()),
+            Value::String("finally".to_string()),
+            Value::String("catch".to_string()),
+            Value::String("finally".to_string()),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_eval_finally_runs_before_uncaught_error_propagates() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            let count = 0
+            try {
+                try {
+                    throw { kind: "Fatal", message: "stop" }
+                } catch (e: ToolError) {
+                    return "wrong handler"
+                } finally {
+                    count = count + 1
+                }
+            } catch e {
+                return "{e.kind} after {count} finally"
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap(),
+        Value::String("Fatal after 1 finally".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_eval_finally_runs_on_break() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            let log = []
+            for i in [1, 2] {
+                try {
+                    break
+                } catch e {
+                    log.push("catch")
+                } finally {
+                    log.push("finally")
+                }
+            }
+            return log
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap(),
+        Value::Array(vec![Value::String("finally".to_string())])
+    );
+}
+
+#[tokio::test]
+async fn test_eval_caught_error_interpolates_as_message() {
+    let result = eval_tool_body(
+        r#"
+        tool test() {
+            try {
+                throw "something broke"
+            } catch error {
+                return "Error occurred: {error}"
+            }
+        }
+    "#,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap(),
+        Value::String("Error occurred: something broke".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_eval_error_in_finally_unwinds_scopes() {
+    use gent::interpreter::block_eval::evaluate_block_in_scope;
+    use gent::logging::NullLogger;
+    use gent::runtime::ProviderFactory;
+
+    let program = parse(
+        r#"
+        tool test() {
+            try {
+                let a = 1
+            } catch e {
+            } finally {
+                let b = 2
+                throw "in finally"
+            }
+        }
+    "#,
+    )
+    .expect("parse failed");
+    let body = match &program.statements[0] {
+        gent::parser::Statement::ToolDecl(tool) => tool.body.clone(),
+        other => panic!("Expected tool declaration, got {:?}", other),
+    };
+    let mut env = Environment::new();
+    let depth = env.depth();
+    let factory = ProviderFactory::mock();
+    let result =
+        evaluate_block_in_scope(&body, &mut env, &ToolRegistry::new(), &factory, &NullLogger)
+            .await;
+
+    assert!(result.unwrap_err().to_string().contains("in finally"));
+    assert_eq!(env.depth(), depth);
+    assert!(env.get("b").is_none());
+}
+
+#[test]
+fn test_error_value_from_agent_error() {
+    use gent::errors::GentError;
+    use gent::interpreter::ErrorValue;
+
+    let err = GentError::AgentContext {
+        agent: "Researcher".to_string(),
+        source: Box::new(GentError::ToolError {
+            tool: "search".to_string(),
+            message: "timeout".to_string(),
+        }),
+    };
+    let value = ErrorValue::from_error(&err);
+    assert_eq!(value.kind, "ToolError");
+    assert_eq!(value.agent.as_deref(), Some("Researcher"));
+    assert_eq!(value.tool.as_deref(), Some("search"));
+    assert_eq!(value.message, "Tool 'search' failed: timeout");
+}
@@ -0,0 +1,135 @@
+//! Tests for user-defined tools that run agents and see the live environment
+
+use gent::interpreter::{Session, Value};
+use gent::logging::{LogLevel, Logger, NullLogger};
+use gent::repl::eval_input;
+use gent::runtime::{ProviderFactory, ToolCall};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+const HELPER: &str = "agent Helper { model: \"gpt-4o-mini\" maxSteps: 1 tools: [whoami] }";
+
+/// Logger that keeps every message
+#[derive(Default)]
+struct RecordingLogger(Mutex<Vec<String>>);
+
+impl Logger for RecordingLogger {
+    fn log(&self, _level: LogLevel, _target: &str, message: &str) {
+        self.0.lock().unwrap().push(message.to_string());
+    }
+
+    fn log_with_duration(&self, level: LogLevel, target: &str, message: &str, _duration_ms: u64) {
+        self.log(level, target, message);
+    }
+
+    fn is_enabled(&self, _level: LogLevel) -> bool {
+        true
+    }
+
+    fn level(&self) -> LogLevel {
+        LogLevel::Trace
+    }
+}
+
+async fn eval(session: &mut Session, input: &str) -> Result<Option<Value>, String> {
+    eval_input(session, input)
+        .await
+        .map_err(|errors| format!("{}: {}", errors[0].kind(), errors[0]))
+}
+
+#[tokio::test]
+async fn test_tool_runs_an_agent() {
+    let mut session = Session::new(
+        ProviderFactory::mock_with_response("Paris"),
+        Arc::new(NullLogger),
+    );
+    eval(
+        &mut session,
+        "agent Geo { systemPrompt: \"You know capitals.\" model: \"gpt-4o-mini\" }",
+    )
+    .await
+    .unwrap();
+    eval(
+        &mut session,
+        "tool capital(country: string) -> string { return Geo.userPrompt(country).run() }",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        eval(&mut session, "capital(\"France\")").await.unwrap(),
+        Some(Value::String("Paris".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_tool_sees_later_declarations() {
+    let mut session = Session::new(ProviderFactory::mock(), Arc::new(NullLogger));
+    eval(
+        &mut session,
+        "tool greet(name: string) -> string { return \"{greeting}, {name}\" }",
+    )
+    .await
+    .unwrap();
+    eval(&mut session, "let greeting = \"Hello\"")
+        .await
+        .unwrap();
+    eval(
+        &mut session,
+        "tool shout(text: string) -> string { return text.toUpperCase() }",
+    )
+    .await
+    .unwrap();
+    eval(
+        &mut session,
+        "tool loudGreet(name: string) -> string { return shout(greet(name)) }",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        eval(&mut session, "greet(\"Ada\")").await.unwrap(),
+        Some(Value::String("Hello, Ada".to_string()))
+    );
+    assert_eq!(
+        eval(&mut session, "loudGreet(\"Ada\")").await.unwrap(),
+        Some(Value::String("HELLO, ADA".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_tool_knows_the_calling_agent() {
+    let logger = Arc::new(RecordingLogger::default());
+    let factory = ProviderFactory::mock_with_tool_calls(vec![ToolCall {
+        id: "1".to_string(),
+        name: "whoami".to_string(),
+        arguments: json!({}),
+    }]);
+    let mut session = Session::new(factory, logger.clone());
+    eval(
+        &mut session,
+        "tool whoami() -> string { return \"{caller} in {place}\" }",
+    )
+    .await
+    .unwrap();
+    eval(&mut session, HELPER).await.unwrap();
+    eval(&mut session, "let place = \"Paris\"").await.unwrap();
+
+    // The mock keeps calling tools, so the run stops at maxSteps
+    let err = eval(&mut session, "Helper.userPrompt(\"Who are you?\").run()")
+        .await
+        .unwrap_err();
+    assert!(err.starts_with("MaxStepsExceeded"), "{}", err);
+    assert!(logger
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|m| m == "Tool 'whoami' returned: Helper in Paris"));
+
+    // Called from code, there is no calling agent
+    assert_eq!(
+        eval(&mut session, "whoami()").await.unwrap(),
+        Some(Value::String("null in Paris".to_string()))
+    );
+}
@@ -12,9 +12,12 @@ async fn test_user_tool_wrapper_execute() {
     // Create a simple tool: greet(name: String) -> String { return "Hello, " + name }
     let tool_value = UserToolValue {
         name: "greet".to_string(),
+        description: None,
         params: vec![Param {
             name: "name".to_string(),
             type_name: TypeName::String,
+            default: None,
+            description: None,
             span: Span::default(),
         }],
         return_type: Some(TypeName::String),
@@ -58,9 +61,12 @@ async fn test_user_tool_wrapper_with_number_param() {
     // Create a tool: double(x: Number) -> Number { return x * 2 }
     let tool_value = UserToolValue {
         name: "double".to_string(),
+        description: None,
         params: vec![Param {
             name: "x".to_string(),
             type_name: TypeName::Number,
+            default: None,
+            description: None,
             span: Span::default(),
         }],
         return_type: Some(TypeName::Number),
@@ -94,15 +100,20 @@ async fn test_user_tool_wrapper_with_multiple_params() {
     // Create a tool: add(a: Number, b: Number) -> Number { return a + b }
     let tool_value = UserToolValue {
         name: "add".to_string(),
+        description: None,
         params: vec![
             Param {
                 name: "a".to_string(),
                 type_name: TypeName::Number,
+                default: None,
+                description: None,
                 span: Span::default(),
             },
             Param {
                 name: "b".to_string(),
                 type_name: TypeName::Number,
+                default: None,
+                description: None,
                 span: Span::default(),
             },
         ],
@@ -137,9 +148,12 @@ async fn test_user_tool_wrapper_missing_parameter() {
     // Create a simple tool
     let tool_value = UserToolValue {
         name: "greet".to_string(),
+        description: None,
         params: vec![Param {
             name: "name".to_string(),
             type_name: TypeName::String,
+            default: None,
+            description: None,
             span: Span::default(),
         }],
         return_type: Some(TypeName::String),
@@ -164,6 +178,7 @@ async fn test_user_tool_wrapper_in_registry() {
     // Create a tool
     let tool_value = UserToolValue {
         name: "test_tool".to_string(),
+        description: None,
         params: vec![],
         return_type: None,
         body: Block {